@@ -0,0 +1,226 @@
+//! Adding files and directory trees from the filesystem to writers
+
+#[cfg(feature = "ignore")]
+use crate::gitignore::Rules;
+use crate::{ArcEntry, ArcResult, ArcWriter};
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+/// Adds `path` and everything under it to `writer`, named by their path
+/// relative to `base`
+pub(crate) fn add_path(writer: &mut ArcWriter, path: &Path, base: &Path) -> ArcResult<()> {
+    let relative = path.strip_prefix(base).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} isn't under {}", path.display(), base.display()),
+        )
+    })?;
+    let name = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    add_named(writer, path, base, name)
+}
+
+/// Adds `path` and everything under it to `writer`, named by their path
+/// relative to `path` under `prefix`
+pub(crate) fn add_path_as(writer: &mut ArcWriter, path: &Path, prefix: &str) -> ArcResult<()> {
+    let name = prefix
+        .split('/')
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    // files need a name of their own
+    let name = match name.is_empty() && !path.is_dir() {
+        true => path
+            .file_name()
+            .map_or(name, |name| name.to_string_lossy().into_owned()),
+        false => name,
+    };
+    add_named(writer, path, path, name)
+}
+
+/// Adds `path` as the entry `name` and everything under it, with ignore
+/// files and exclude globs applying from `root`
+fn add_named(writer: &mut ArcWriter, path: &Path, root: &Path, name: String) -> ArcResult<()> {
+    #[cfg(feature = "ignore")]
+    let rules = match writer.add_ignore_files || !writer.add_excludes.is_empty() {
+        true => Some(Rules::new(
+            root,
+            &writer.add_excludes,
+            writer.add_ignore_files,
+        )?),
+        false => None,
+    };
+    #[cfg(not(feature = "ignore"))]
+    let _ = root;
+    let mut walk = Walk {
+        writer,
+        ancestors: Vec::new(),
+        #[cfg(feature = "ignore")]
+        rules,
+    };
+    walk.add(path, name)
+}
+
+/// Filter kept in [`ArcWriter::add_filter`], given the path and metadata of
+/// what [`ArcWriter::add_path`] comes across and returning whether to add it
+pub type AddFilterFn = dyn Fn(&Path, &Metadata) -> bool + Send + Sync;
+
+/// What [`ArcWriter::add_path`] does with symlinks it comes across
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddSymlinkPolicy {
+    /// Adds them as symlinks
+    #[default]
+    Store,
+    /// Adds what they point to in their place, failing on broken ones
+    ///
+    /// Symlinks to a directory they're in, which would be walked forever,
+    /// are added as symlinks.
+    Follow,
+}
+
+/// Walk of a directory tree, adding what it finds to `writer`
+struct Walk<'w> {
+    writer: &'w mut ArcWriter,
+    /// Canonical paths of the directories being walked, to tell when a
+    /// followed symlink leads back into one of them
+    ancestors: Vec<PathBuf>,
+    /// Rules leaving out what ignore files and exclude globs match
+    #[cfg(feature = "ignore")]
+    rules: Option<Rules>,
+}
+
+impl Walk<'_> {
+    /// Adds `path` as the entry `name`, then the contents of directories
+    /// sorted by name, so archives don't depend on the order the filesystem
+    /// lists them in
+    fn add(&mut self, path: &Path, name: String) -> ArcResult<()> {
+        let mut meta = fs::symlink_metadata(path)?;
+        if meta.file_type().is_symlink() && self.writer.add_symlinks == AddSymlinkPolicy::Follow {
+            let target = fs::metadata(path)?;
+            let looping = target.is_dir() && self.ancestors.contains(&fs::canonicalize(path)?);
+            if !looping {
+                meta = target;
+            }
+        }
+        if self.ignores(path, meta.is_dir()) {
+            return Ok(());
+        }
+        let filter = self.writer.add_filter.as_ref();
+        if filter.is_some_and(|filter| !filter(path, &meta)) {
+            return Ok(());
+        }
+        if meta.is_dir() {
+            self.add_dir(path, name, &meta)?;
+        } else if meta.file_type().is_symlink() {
+            let target = fs::read_link(path)?;
+            let target = target.to_string_lossy().replace('\\', "/");
+            self.writer
+                .push(with_meta(ArcEntry::symlink(name, target), &meta));
+        } else if meta.is_file() {
+            let entry = with_meta(ArcEntry::file(name, Vec::new()), &meta);
+            match ArcWriter::needs_data(&entry.name) {
+                true => self.writer.push(ArcEntry {
+                    data: fs::read(path)?,
+                    ..entry
+                }),
+                false => self
+                    .writer
+                    .push_reader(entry, LazyFile::new(path), meta.len()),
+            }
+        }
+        // sockets, FIFOs and devices can't be read like files
+        Ok(())
+    }
+
+    /// Adds the directory `path` as the entry `name`, with `meta`, then its
+    /// contents
+    fn add_dir(&mut self, path: &Path, name: String, meta: &Metadata) -> ArcResult<()> {
+        // the base itself has no name and isn't an entry
+        let prefix = match name.is_empty() {
+            true => String::new(),
+            false => {
+                let name = format!("{name}/");
+                self.writer
+                    .push(with_meta(ArcEntry::directory(&name), meta));
+                name
+            }
+        };
+        let mut children = fs::read_dir(path)?
+            .map(|child| child.map(|child| child.file_name()))
+            .collect::<io::Result<Vec<_>>>()?;
+        children.sort();
+        self.ancestors.push(fs::canonicalize(path)?);
+        #[cfg(feature = "ignore")]
+        if let Some(rules) = &mut self.rules {
+            rules.enter(path);
+        }
+        for child in children {
+            let child_name = format!("{prefix}{}", child.to_string_lossy());
+            self.add(&path.join(child), child_name)?;
+        }
+        #[cfg(feature = "ignore")]
+        if let Some(rules) = &mut self.rules {
+            rules.leave();
+        }
+        self.ancestors.pop();
+        Ok(())
+    }
+
+    /// Returns whether `path`, a directory if `is_dir` is set, is left out
+    #[cfg(feature = "ignore")]
+    fn ignores(&self, path: &Path, is_dir: bool) -> bool {
+        let rules = self.rules.as_ref();
+        rules.is_some_and(|rules| rules.ignores(path, is_dir))
+    }
+
+    /// Leaves nothing out, as rules need the `ignore` feature
+    #[cfg(not(feature = "ignore"))]
+    fn ignores(&self, _path: &Path, _is_dir: bool) -> bool {
+        false
+    }
+}
+
+/// Returns `entry` with the permissions and modification time in `meta`
+fn with_meta(entry: ArcEntry, meta: &Metadata) -> ArcEntry {
+    #[cfg(unix)]
+    let entry = {
+        use std::os::unix::fs::PermissionsExt;
+        entry.with_mode(meta.permissions().mode() & 0o7777)
+    };
+    match meta.modified() {
+        Ok(mtime) => entry.with_mtime(mtime),
+        Err(_) => entry,
+    }
+}
+
+/// File only opened once it's read, so adding big trees doesn't hold a
+/// handle to every file until the archive is created
+struct LazyFile {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl LazyFile {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            file: None,
+        }
+    }
+}
+
+impl Read for LazyFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(File::open(&self.path)?),
+        };
+        file.read(buf)
+    }
+}
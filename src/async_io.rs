@@ -0,0 +1,63 @@
+//! What the wrappers over tokio's and the `futures` I/O traits share
+
+/// Implements everything but the async methods for `$reader`, wrapping an
+/// [`ArcReader`](crate::ArcReader), and `$writer`, wrapping an
+/// [`ArcWriter`](crate::ArcWriter)
+macro_rules! wrappers {
+    ($reader:ident, $writer:ident) => {
+        impl $reader {
+            /// Returns the inner [`ArcReader`]
+            pub fn into_inner(self) -> ArcReader {
+                self.0
+            }
+        }
+
+        impl std::ops::Deref for $reader {
+            type Target = ArcReader;
+
+            fn deref(&self) -> &ArcReader {
+                &self.0
+            }
+        }
+
+        impl std::ops::DerefMut for $reader {
+            fn deref_mut(&mut self) -> &mut ArcReader {
+                &mut self.0
+            }
+        }
+
+        impl $writer {
+            /// Returns a new writer for the specified archive format
+            pub fn new(format: ArcFormat) -> Self {
+                Self(ArcWriter::new(format))
+            }
+
+            /// Returns the inner [`ArcWriter`]
+            pub fn into_inner(self) -> ArcWriter {
+                self.0
+            }
+        }
+
+        impl From<ArcWriter> for $writer {
+            fn from(writer: ArcWriter) -> Self {
+                Self(writer)
+            }
+        }
+
+        impl std::ops::Deref for $writer {
+            type Target = ArcWriter;
+
+            fn deref(&self) -> &ArcWriter {
+                &self.0
+            }
+        }
+
+        impl std::ops::DerefMut for $writer {
+            fn deref_mut(&mut self) -> &mut ArcWriter {
+                &mut self.0
+            }
+        }
+    };
+}
+
+pub(crate) use wrappers;
@@ -0,0 +1,68 @@
+//! Checking the data of zip entries against the CRC-32 stored with them
+
+use crate::{
+    progress::{CountingReader, Tracker},
+    ArcError, ArcResult,
+};
+use flate2::Crc;
+use std::io::{self, Read};
+use zip::read::ZipFile;
+
+/// What readers do with zip entries whose data doesn't match their CRC-32
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChecksumPolicy {
+    /// Fails with [`ArcError::ChecksumMismatch`]
+    #[default]
+    Error,
+    /// Keeps the data as it was decoded, listing the entry in
+    /// [`ArcReader::checksum_mismatches`](crate::ArcReader::checksum_mismatches)
+    Warn,
+}
+
+impl ChecksumPolicy {
+    /// Handles a mismatch in the entry `name`, adding it to `mismatches` if
+    /// it's only a warning
+    pub(crate) fn mismatch(self, name: &str, mismatches: &mut Vec<String>) -> ArcResult<()> {
+        match self {
+            ChecksumPolicy::Error => Err(ArcError::ChecksumMismatch(name.to_owned())),
+            ChecksumPolicy::Warn => {
+                mismatches.push(name.to_owned());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Returns the CRC-32 of `data`, as zip computes it
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+/// Decodes the data of `file` into `data`, reporting it to `tracker` as it
+/// comes out and returning whether it matches the file's CRC-32
+///
+/// The zip crate checks it too, failing once the data is decoded, so its
+/// error is only passed on if the data turns out to match. AES entries
+/// usually store `0` instead, and are checked by their authentication code.
+pub(crate) fn read_zip(
+    file: &mut ZipFile,
+    data: &mut Vec<u8>,
+    tracker: Option<&Tracker>,
+) -> io::Result<bool> {
+    let start = data.len();
+    let name = tracker.map(|_| file.name().to_owned());
+    let read = CountingReader {
+        inner: &mut *file,
+        name: name.as_deref().unwrap_or_default(),
+        tracker,
+    }
+    .read_to_end(data);
+    let unchecked = file.encrypted() && file.crc32() == 0;
+    let matches = unchecked || crc32(&data[start..]) == file.crc32();
+    match read {
+        Err(err) if matches || err.kind() != io::ErrorKind::InvalidData => Err(err),
+        _ => Ok(matches),
+    }
+}
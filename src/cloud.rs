@@ -0,0 +1,92 @@
+//! Reading archives stored in object stores like S3, GCS or Azure
+//!
+//! [`ArcReader::from_object_store`] downloads the whole archive, while
+//! [`ObjectStoreZipReader`] reads zip archives through ranged gets.
+
+use crate::{
+    remote::{self, Eocd, RemoteEntry},
+    zip_records::{ZIP64_END_SIZE, ZIP64_LOCATOR_SIZE},
+    ArcReader, ArcResult,
+};
+use object_store::{path::Path, ObjectStore, ObjectStoreExt};
+use std::sync::Arc;
+
+impl ArcReader {
+    /// Downloads the archive at `location` in `store` and reads it like
+    /// [`ArcReader::new`]
+    ///
+    /// Every entry is decoded, so the whole archive is downloaded. Use
+    /// [`ObjectStoreZipReader`] to only fetch the files you need from a zip
+    /// archive.
+    pub async fn from_object_store(store: &dyn ObjectStore, location: &Path) -> ArcResult<Self> {
+        let data = store.get(location).await?.bytes().await?;
+        ArcReader::new(&data)
+    }
+}
+
+/// Reader for zip archives in an object store, fetching files with ranged gets
+///
+/// Works like [`RemoteZipReader`](crate::RemoteZipReader): opening the archive
+/// fetches its central directory, reading a file fetches its local header and
+/// data.
+pub struct ObjectStoreZipReader {
+    store: Arc<dyn ObjectStore>,
+    location: Path,
+    entries: Vec<RemoteEntry>,
+}
+
+impl ObjectStoreZipReader {
+    /// Opens the zip archive at `location` in `store`, fetching its central
+    /// directory
+    pub async fn new(store: Arc<dyn ObjectStore>, location: Path) -> ArcResult<Self> {
+        let mut reader = Self {
+            store,
+            location,
+            entries: Vec::new(),
+        };
+        let size = reader.store.head(&reader.location).await?.size;
+        let tail = remote::tail_range(size);
+        let directory = match remote::eocd(&reader.fetch(tail).await?, tail.0)? {
+            Eocd::Directory(directory) => directory,
+            Eocd::Zip64(offset) => {
+                let locator = reader.fetch((offset, ZIP64_LOCATOR_SIZE as u64)).await?;
+                let offset = remote::zip64_locator(&locator, offset)?;
+                let eocd64 = reader.fetch((offset, ZIP64_END_SIZE as u64)).await?;
+                remote::zip64_eocd(&eocd64, offset)?
+            }
+        };
+        reader.entries = directory.entries(&reader.fetch(directory.range()?).await?)?;
+        Ok(reader)
+    }
+
+    /// Fetches `len` bytes at `offset`, failing if fewer were returned
+    async fn fetch(&self, (offset, len): (u64, u64)) -> ArcResult<Vec<u8>> {
+        // stores reject empty ranges
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        let data = self
+            .store
+            .get_range(&self.location, offset..offset + len)
+            .await?;
+        Ok(remote::check_len(data.to_vec(), len)?)
+    }
+
+    /// Returns the names of all entries in the order of the central
+    /// directory, directories ending with `/`
+    pub fn names(&self) -> Vec<&str> {
+        remote::names(&self.entries)
+    }
+
+    /// Fetches and decodes the data of the file `name`
+    pub async fn read_entry(&self, name: &str) -> ArcResult<Vec<u8>> {
+        let entry = remote::find(&self.entries, name)?;
+        let data = entry.data_range(&self.fetch(entry.header_range()).await?)?;
+        entry.decode(self.fetch(data).await?)
+    }
+
+    /// Returns the store and location the archive is read from
+    pub fn into_inner(self) -> (Arc<dyn ObjectStore>, Path) {
+        (self.store, self.location)
+    }
+}
@@ -0,0 +1,152 @@
+//! Reader for CRX3 Chrome extensions, a zip prefixed with a signed header
+
+use crate::{ArcError, ArcResult};
+use std::io;
+
+pub(crate) const MAGIC: &[u8] = b"Cr24";
+
+const VERSION: u32 = 3;
+
+fn invalid(msg: &str) -> ArcError {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid CRX: {msg}")).into()
+}
+
+/// A public key and the signature made with it
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CrxProof {
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// Signatures stored in the header of a CRX3 extension
+///
+/// Each signature covers `b"CRX3 SignedData\0"`, the length of
+/// `signed_header_data` as a little-endian `u32`, `signed_header_data` itself
+/// and finally the zip archive.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CrxHeader {
+    pub sha256_with_rsa: Vec<CrxProof>,
+    pub sha256_with_ecdsa: Vec<CrxProof>,
+    pub signed_header_data: Vec<u8>,
+}
+
+impl CrxHeader {
+    fn parse(buf: &[u8]) -> ArcResult<Self> {
+        let mut header = CrxHeader::default();
+        for field in Fields(buf) {
+            let (number, value) = field?;
+            match number {
+                2 => header.sha256_with_rsa.push(CrxProof::parse(value)?),
+                3 => header.sha256_with_ecdsa.push(CrxProof::parse(value)?),
+                10000 => header.signed_header_data = value.to_vec(),
+                _ => {}
+            }
+        }
+        Ok(header)
+    }
+}
+
+impl CrxProof {
+    fn parse(buf: &[u8]) -> ArcResult<Self> {
+        let mut proof = CrxProof::default();
+        for field in Fields(buf) {
+            let (number, value) = field?;
+            match number {
+                1 => proof.public_key = value.to_vec(),
+                2 => proof.signature = value.to_vec(),
+                _ => {}
+            }
+        }
+        Ok(proof)
+    }
+}
+
+/// Iterator over the length-delimited fields of a protobuf message, yielding
+/// field numbers and contents
+///
+/// Fields of other wire types are skipped, a malformed message ends the
+/// iteration with an error.
+struct Fields<'a>(&'a [u8]);
+
+impl<'a> Fields<'a> {
+    fn varint(&mut self) -> ArcResult<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self
+                .0
+                .split_first()
+                .ok_or_else(|| invalid("truncated header"))?;
+            self.0 = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("bad varint"))
+    }
+
+    fn bytes(&mut self, len: u64) -> ArcResult<&'a [u8]> {
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|&len| len <= self.0.len())
+            .ok_or_else(|| invalid("truncated header"))?;
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn field(&mut self) -> ArcResult<Option<(u64, &'a [u8])>> {
+        while !self.0.is_empty() {
+            let key = self.varint()?;
+            match key & 0x7 {
+                0 => drop(self.varint()?),
+                1 => drop(self.bytes(8)?),
+                2 => {
+                    let len = self.varint()?;
+                    return Ok(Some((key >> 3, self.bytes(len)?)));
+                }
+                5 => drop(self.bytes(4)?),
+                _ => return Err(invalid("bad wire type")),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = ArcResult<(u64, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.field();
+        if field.is_err() {
+            self.0 = &[];
+        }
+        field.transpose()
+    }
+}
+
+/// Splits an extension into its header and zip archive
+pub(crate) fn split(buf: &[u8]) -> ArcResult<(&[u8], &[u8])> {
+    let fixed = buf
+        .get(..12)
+        .filter(|fixed| fixed.starts_with(MAGIC))
+        .ok_or_else(|| invalid("bad header"))?;
+    let version = u32::from_le_bytes(fixed[4..8].try_into().unwrap());
+    if version != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("unsupported CRX version {version}"),
+        )
+        .into());
+    }
+    let header_len = u32::from_le_bytes(fixed[8..12].try_into().unwrap()) as usize;
+    let header = buf
+        .get(12..12 + header_len)
+        .ok_or_else(|| invalid("truncated header"))?;
+    Ok((header, &buf[12 + header_len..]))
+}
+
+/// Parses the signed header of an extension
+pub(crate) fn header(buf: &[u8]) -> ArcResult<CrxHeader> {
+    CrxHeader::parse(split(buf)?.0)
+}
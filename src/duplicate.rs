@@ -0,0 +1,60 @@
+//! Handling entries that share a name
+//!
+//! Zip and tar both allow several entries with the same name, tar
+//! extractors usually letting the last one win.
+
+use crate::{ArcError, ArcResult, NamedEntry};
+use std::collections::{HashMap, HashSet};
+
+/// What readers do with entries whose name an earlier entry already has
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Keeps every entry, in archive order
+    #[default]
+    KeepAll,
+    /// Keeps only the first entry of each name
+    KeepFirst,
+    /// Keeps only the last entry of each name, where it is in the archive
+    KeepLast,
+    /// Fails with [`ArcError::DuplicateEntry`]
+    Reject,
+}
+
+impl DuplicatePolicy {
+    /// Applies the policy to `entries`
+    pub(crate) fn apply<E: NamedEntry>(self, mut entries: Vec<E>) -> ArcResult<Vec<E>> {
+        match self {
+            DuplicatePolicy::KeepAll => {}
+            DuplicatePolicy::KeepFirst => {
+                let mut seen = HashSet::new();
+                entries.retain(|entry| seen.insert(entry.name().clone()));
+            }
+            DuplicatePolicy::KeepLast => {
+                let last: HashMap<_, _> = entries
+                    .iter()
+                    .enumerate()
+                    .map(|(i, entry)| (entry.name().clone(), i))
+                    .collect();
+                let mut i = 0;
+                entries.retain(|entry| {
+                    i += 1;
+                    last[entry.name()] == i - 1
+                });
+            }
+            DuplicatePolicy::Reject => reject(entries.iter().map(|entry| entry.name().as_str()))?,
+        }
+        Ok(entries)
+    }
+}
+
+/// Fails with [`ArcError::DuplicateEntry`] on the first name of `names`
+/// that came up before
+pub(crate) fn reject<'a>(names: impl IntoIterator<Item = &'a str>) -> ArcResult<()> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(ArcError::DuplicateEntry(name.to_owned()));
+        }
+    }
+    Ok(())
+}
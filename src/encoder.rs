@@ -0,0 +1,100 @@
+//! Compressors of every compressed format behind a single writer
+
+use crate::{ArcFormat, ArcWriter};
+use brotli::{enc::BrotliEncoderParams, CompressorWriter as BrotliEncoder};
+use bzip2::write::BzEncoder;
+use flate2::{write::GzEncoder, Compression, GzBuilder};
+use lz4_flex::frame::FrameEncoder as Lz4Encoder;
+use snap::write::FrameEncoder as SnappyEncoder;
+use std::io::{self, Write};
+use xz2::write::XzEncoder;
+use zstd::Encoder as ZstdEncoder;
+
+/// Compressor of a target format, or the bare writer for uncompressed ones
+pub(crate) enum Encoder<W: Write> {
+    Plain(W),
+    Gzip(GzEncoder<W>),
+    Xz(XzEncoder<W>),
+    Bz2(BzEncoder<W>),
+    Zstd(ZstdEncoder<'static, W>),
+    Lz4(Lz4Encoder<W>),
+    Snappy(Box<SnappyEncoder<W>>),
+    Brotli(Box<BrotliEncoder<W>>),
+}
+
+impl<W: Write> Encoder<W> {
+    /// Returns the compressor of `options`' target format writing into
+    /// `writer`, storing `name` in gzip headers unless it's empty
+    pub(crate) fn new(options: &ArcWriter, writer: W, name: &str) -> io::Result<Self> {
+        Ok(match options.format {
+            ArcFormat::Gzip | ArcFormat::TarGz => {
+                let mut builder = GzBuilder::new();
+                if !name.is_empty() {
+                    builder = builder.filename(name);
+                }
+                Encoder::Gzip(builder.write(writer, Compression::default()))
+            }
+            ArcFormat::TarXz => Encoder::Xz(XzEncoder::new(writer, 6)),
+            ArcFormat::TarBz2 => {
+                Encoder::Bz2(BzEncoder::new(writer, bzip2::Compression::default()))
+            }
+            ArcFormat::Zstd | ArcFormat::TarZst => {
+                Encoder::Zstd(ZstdEncoder::new(writer, options.zstd_level)?)
+            }
+            ArcFormat::Lz4 | ArcFormat::TarLz4 => Encoder::Lz4(Lz4Encoder::new(writer)),
+            ArcFormat::Snappy | ArcFormat::TarSz => {
+                Encoder::Snappy(Box::new(SnappyEncoder::new(writer)))
+            }
+            ArcFormat::Brotli | ArcFormat::TarBr => {
+                let params = BrotliEncoderParams {
+                    quality: options.brotli_quality as i32,
+                    lgwin: options.brotli_window as i32,
+                    ..Default::default()
+                };
+                Encoder::Brotli(Box::new(BrotliEncoder::with_params(writer, 4096, &params)))
+            }
+            _ => Encoder::Plain(writer),
+        })
+    }
+
+    /// Ends the compressed stream, returning the underlying writer
+    pub(crate) fn finish(self) -> io::Result<W> {
+        match self {
+            Encoder::Plain(writer) => Ok(writer),
+            Encoder::Gzip(encoder) => encoder.finish(),
+            Encoder::Xz(encoder) => encoder.finish(),
+            Encoder::Bz2(encoder) => encoder.finish(),
+            Encoder::Zstd(encoder) => encoder.finish(),
+            Encoder::Lz4(encoder) => encoder.finish().map_err(io::Error::from),
+            Encoder::Snappy(encoder) => encoder.into_inner().map_err(|err| err.into_error()),
+            Encoder::Brotli(mut encoder) => {
+                // finishing the stream can't report errors, so flush first
+                encoder.flush()?;
+                Ok(encoder.into_inner())
+            }
+        }
+    }
+
+    fn inner(&mut self) -> &mut dyn Write {
+        match self {
+            Encoder::Plain(writer) => writer,
+            Encoder::Gzip(encoder) => encoder,
+            Encoder::Xz(encoder) => encoder,
+            Encoder::Bz2(encoder) => encoder,
+            Encoder::Zstd(encoder) => encoder,
+            Encoder::Lz4(encoder) => encoder,
+            Encoder::Snappy(encoder) => encoder,
+            Encoder::Brotli(encoder) => encoder,
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner().flush()
+    }
+}
@@ -0,0 +1,58 @@
+//! Validation of the members required in an EPUB
+
+use crate::{ArcEntry, ArcError, ArcResult};
+use roxmltree::Document;
+use std::io;
+
+/// Name of the entry that has to come first, uncompressed
+pub(crate) const MIMETYPE_NAME: &str = "mimetype";
+
+pub(crate) const MIMETYPE: &[u8] = b"application/epub+zip";
+
+pub(crate) const CONTAINER: &str = "META-INF/container.xml";
+
+/// Returns whether `entry` is the `mimetype` file
+pub(crate) fn is_mimetype(entry: &ArcEntry) -> bool {
+    entry.is_file() && entry.name == MIMETYPE_NAME
+}
+
+fn file<'a>(entries: &'a [ArcEntry], path: &str) -> ArcResult<&'a [u8]> {
+    entries
+        .iter()
+        .find(|entry| entry.is_file() && entry.name == path)
+        .map(|entry| &entry.data[..])
+        .ok_or_else(|| ArcError::EntryNotFound(path.to_owned()))
+}
+
+fn invalid(msg: String) -> ArcError {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid EPUB: {msg}")).into()
+}
+
+/// Checks that a pushed `mimetype` is correct, and that the container and
+/// every package document it points to are present
+pub(crate) fn validate(entries: &[ArcEntry]) -> ArcResult<()> {
+    if let Ok(mimetype) = file(entries, MIMETYPE_NAME) {
+        if mimetype != MIMETYPE {
+            return Err(invalid("wrong mimetype".to_owned()));
+        }
+    }
+
+    let container = std::str::from_utf8(file(entries, CONTAINER)?)
+        .map_err(|_| invalid(format!("{CONTAINER} isn't valid UTF-8")))?;
+    let container =
+        Document::parse(container).map_err(|err| invalid(format!("{CONTAINER}: {err}")))?;
+    let mut rootfiles = container
+        .descendants()
+        .filter(|node| node.has_tag_name("rootfile"))
+        .peekable();
+    if rootfiles.peek().is_none() {
+        return Err(invalid(format!("{CONTAINER} has no rootfile")));
+    }
+    for rootfile in rootfiles {
+        let path = rootfile
+            .attribute("full-path")
+            .ok_or_else(|| invalid("rootfile without full-path".to_owned()))?;
+        file(entries, path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,774 @@
+//! Writing the entries of archives to the filesystem
+
+use crate::{
+    crx, guard, path,
+    progress::{ProgressFn, Tracker},
+    visit::{self, Visited},
+    ArcEntry, ArcError, ArcFormat, ArcMeta, ArcReader, ArcResult, ArcStreamReader, EntryKind,
+    Pattern, ReadOptions, TarStream,
+};
+use std::{
+    fmt,
+    fs::{self, File, FileTimes},
+    io::{self, Cursor, Read, Write},
+    path::{Path, PathBuf},
+};
+use zip::{result::ZipError, ZipArchive};
+
+/// How [`crate::ArcReader::extract_with`] writes entries
+#[derive(Default)]
+pub struct ExtractOptions {
+    /// Restores the permissions and modification times of files and
+    /// directories, and their owners when running as root, like `tar -xp`
+    ///
+    /// Permissions, owners and the metadata of directories are only restored
+    /// on Unix.
+    pub preserve_metadata: bool,
+    /// What happens when something is already at the path of a file or link
+    pub overwrite: OverwritePolicy,
+    /// Called as file data is written and after each entry, with the totals
+    /// of the archive
+    pub progress: Option<Box<ProgressFn>>,
+    /// Writes entries into a temporary directory next to the destination
+    /// and renames it into place once they're all there, so a crash or an
+    /// error never leaves a half-extracted tree behind
+    ///
+    /// The destination can't exist yet, failing with an
+    /// [`io::ErrorKind::AlreadyExists`] error before anything is written.
+    pub atomic: bool,
+    /// Called with the name, size and kind of each entry before it's
+    /// written, deciding whether it's extracted, left out or stops the
+    /// extraction with [`ArcError::Aborted`]
+    pub veto: Option<Box<VetoFn>>,
+}
+
+/// Hook kept in [`ExtractOptions::veto`], given the name, data size and kind
+/// of entries about to be written
+pub type VetoFn = dyn Fn(&str, u64, &EntryKind) -> EntryDecision + Send + Sync;
+
+/// What [`ExtractOptions::veto`] decides to do with an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntryDecision {
+    /// Writes it like any other
+    #[default]
+    Extract,
+    /// Leaves it out and carries on with the next one
+    Skip,
+    /// Stops the extraction with [`ArcError::Aborted`], leaving the entries
+    /// before it extracted unless it's atomic
+    Abort,
+}
+
+impl fmt::Debug for ExtractOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractOptions")
+            .field("preserve_metadata", &self.preserve_metadata)
+            .field("overwrite", &self.overwrite)
+            .field("progress", &self.progress.as_ref().map(|_| ".."))
+            .field("atomic", &self.atomic)
+            .field("veto", &self.veto.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl ExtractOptions {
+    /// Returns whether [`ExtractOptions::veto`] lets the entry `name` of
+    /// `kind` with `size` bytes of data be written, failing with
+    /// [`ArcError::Aborted`] if it stops the extraction
+    pub(crate) fn allows(&self, name: &str, size: u64, kind: &EntryKind) -> ArcResult<bool> {
+        let decision =
+            (self.veto.as_ref()).map_or(EntryDecision::Extract, |veto| veto(name, size, kind));
+        match decision {
+            EntryDecision::Extract => Ok(true),
+            EntryDecision::Skip => Ok(false),
+            EntryDecision::Abort => Err(ArcError::Aborted(name.to_owned())),
+        }
+    }
+}
+
+/// What extraction does with files and links whose path is already taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// Replaces what's there, like `tar` does
+    #[default]
+    Overwrite,
+    /// Leaves what's there and the entry out
+    Skip,
+    /// Fails with an [`io::ErrorKind::AlreadyExists`] error, leaving the
+    /// entries before it extracted
+    Error,
+    /// Writes the entry next to what's there, with `.1`, `.2`, ... appended
+    /// to its name
+    Rename,
+}
+
+impl OverwritePolicy {
+    /// Returns where an entry going to `path` is written and what happens to
+    /// it, failing if that's an error
+    pub(crate) fn apply(self, path: PathBuf) -> io::Result<(PathBuf, ExtractOutcome)> {
+        self.apply_with(path, exists)
+    }
+
+    /// Returns where an entry going to `path` is written and what happens to
+    /// it, with `taken` telling whether something is at a path
+    pub(crate) fn apply_with(
+        self,
+        path: PathBuf,
+        taken: impl Fn(&Path) -> io::Result<bool>,
+    ) -> io::Result<(PathBuf, ExtractOutcome)> {
+        if !taken(&path)? {
+            return Ok((path.clone(), ExtractOutcome::Created(path)));
+        }
+        match self {
+            OverwritePolicy::Overwrite => Ok((path.clone(), ExtractOutcome::Overwritten(path))),
+            OverwritePolicy::Skip => Ok((path.clone(), ExtractOutcome::Skipped(path))),
+            OverwritePolicy::Error => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            )),
+            OverwritePolicy::Rename => {
+                for suffix in 1.. {
+                    let mut renamed = path.clone().into_os_string();
+                    renamed.push(format!(".{suffix}"));
+                    let renamed = PathBuf::from(renamed);
+                    if !taken(&renamed)? {
+                        return Ok((renamed.clone(), ExtractOutcome::Renamed(renamed)));
+                    }
+                }
+                unreachable!("ran out of suffixes")
+            }
+        }
+    }
+}
+
+/// What happened to an entry during extraction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractOutcome {
+    /// Written to a path nothing was at
+    Created(PathBuf),
+    /// Written over what was at its path, with [`OverwritePolicy::Overwrite`]
+    Overwritten(PathBuf),
+    /// Written next to what was at its path, at this one, with
+    /// [`OverwritePolicy::Rename`]
+    Renamed(PathBuf),
+    /// Directory that was already there, kept with its contents
+    Existing(PathBuf),
+    /// Left out, as something was at its path, with [`OverwritePolicy::Skip`]
+    Skipped(PathBuf),
+    /// Left out, as it can't be created here, like devices, FIFOs, unnamed
+    /// entries and symlinks outside Unix
+    Unsupported,
+    /// Left out by [`ExtractOptions::veto`]
+    Vetoed,
+    /// Would make extraction fail, as something is in the way at this path,
+    /// only found in an [`crate::ExtractPlan`]
+    Conflict(PathBuf),
+}
+
+impl ExtractOutcome {
+    /// Returns the path the entry was written to or left out because of
+    fn path_mut(&mut self) -> Option<&mut PathBuf> {
+        match self {
+            ExtractOutcome::Created(path)
+            | ExtractOutcome::Overwritten(path)
+            | ExtractOutcome::Renamed(path)
+            | ExtractOutcome::Existing(path)
+            | ExtractOutcome::Skipped(path)
+            | ExtractOutcome::Conflict(path) => Some(path),
+            ExtractOutcome::Unsupported | ExtractOutcome::Vetoed => None,
+        }
+    }
+}
+
+/// Outcome of [`crate::ArcReader::extract_to`] and the other ways of
+/// extracting archives
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExtractSummary {
+    /// Paths of the files written, in archive order
+    pub files: Vec<PathBuf>,
+    /// Paths of the directories created for directory entries
+    pub directories: Vec<PathBuf>,
+    /// Paths of the symbolic and hard links created
+    pub links: Vec<PathBuf>,
+    /// Names of entries left out, because of their kind, what was at their
+    /// path or [`ExtractOptions::veto`]
+    pub skipped: Vec<String>,
+    /// Bytes of file data written
+    pub bytes: u64,
+    /// What happened to each entry extracted or left out, by name, in
+    /// archive order
+    pub entries: Vec<(String, ExtractOutcome)>,
+}
+
+impl ExtractSummary {
+    /// Records what happened to the entry `name`
+    fn record(&mut self, name: &str, outcome: ExtractOutcome) {
+        if matches!(
+            outcome,
+            ExtractOutcome::Skipped(_) | ExtractOutcome::Unsupported | ExtractOutcome::Vetoed
+        ) {
+            self.skipped.push(name.to_owned());
+        }
+        self.entries.push((name.to_owned(), outcome));
+    }
+
+    /// Moves the paths recorded under `from` to the same place under `to`
+    fn relocate(&mut self, from: &Path, to: &Path) {
+        let outcomes = self.entries.iter_mut().map(|(_, outcome)| outcome);
+        let paths = (self.files.iter_mut())
+            .chain(&mut self.directories)
+            .chain(&mut self.links)
+            .chain(outcomes.filter_map(ExtractOutcome::path_mut));
+        for path in paths {
+            if let Ok(relative) = path.strip_prefix(from) {
+                *path = to.join(relative);
+            }
+        }
+    }
+}
+
+/// Size of the pieces file data is written in, each reported to progress
+/// callbacks
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Writes `entries` under `dir` as `options` say, after checking that none
+/// of them would end up outside of it
+pub(crate) fn extract(
+    entries: &[ArcEntry],
+    dir: &Path,
+    options: &ExtractOptions,
+) -> ArcResult<ExtractSummary> {
+    let named = || entries.iter().map(|entry| (&entry.name, &entry.meta.kind));
+    path::reject_unsafe(named())?;
+    path::reject_symlink_escapes(named())?;
+    match options.atomic {
+        true => staged(dir, |staging| write_entries(entries, staging, options)),
+        false => write_entries(entries, dir, options),
+    }
+}
+
+/// Writes `entries`, already checked to stay inside it, under `dir`
+fn write_entries(
+    entries: &[ArcEntry],
+    dir: &Path,
+    options: &ExtractOptions,
+) -> ArcResult<ExtractSummary> {
+    let total_bytes = entries.iter().map(|entry| entry.data.len() as u64).sum();
+    let mut extraction = Extraction::new(dir, options, Some(entries.len()), Some(total_bytes))?;
+    for entry in entries {
+        let size = entry.data.len() as u64;
+        extraction.extract(&entry.name, size, &entry.meta, &mut &entry.data[..])?;
+    }
+    extraction.finish()
+}
+
+/// Runs `extract` on a temporary directory next to `dir`, then renames it to
+/// `dir`, so it only shows up once everything's been written
+///
+/// The temporary directory is removed if `extract` fails.
+fn staged(
+    dir: &Path,
+    extract: impl FnOnce(&Path) -> ArcResult<ExtractSummary>,
+) -> ArcResult<ExtractSummary> {
+    if exists(dir)? {
+        let message = format!("{} already exists", dir.display());
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, message).into());
+    }
+    let parent = match dir.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(parent)?;
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".cra-");
+    // temporary directories are private, but the umask should decide
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o777));
+    }
+    let staging = builder.tempdir_in(parent)?;
+    let mut summary = extract(staging.path())?;
+    fs::rename(staging.path(), dir)?;
+    let staging = staging.keep();
+    summary.relocate(&staging, dir);
+    Ok(summary)
+}
+
+/// Extraction of entries under `dir` in progress
+struct Extraction<'a> {
+    dir: &'a Path,
+    options: &'a ExtractOptions,
+    summary: ExtractSummary,
+    /// Directories extracted, getting their metadata last, as writing into
+    /// them changes their modification time and their permissions may not
+    /// allow it
+    directories: Vec<(PathBuf, ArcMeta)>,
+    tracker: Option<Tracker<'a>>,
+}
+
+impl<'a> Extraction<'a> {
+    /// Starts extracting under `dir`, creating it, with the totals of the
+    /// archive reported to progress callbacks when they're known
+    fn new(
+        dir: &'a Path,
+        options: &'a ExtractOptions,
+        total_entries: Option<usize>,
+        total_bytes: Option<u64>,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir,
+            options,
+            summary: ExtractSummary::default(),
+            directories: Vec::new(),
+            tracker: (options.progress.as_deref())
+                .map(|report| Tracker::new(report, total_entries, total_bytes)),
+        })
+    }
+
+    /// Writes the entry `name` with `meta` and `size` bytes of data read
+    /// from `data`, unless [`ExtractOptions::veto`] leaves it out, and
+    /// records what happened to it
+    fn extract(
+        &mut self,
+        name: &str,
+        size: u64,
+        meta: &ArcMeta,
+        data: &mut dyn Read,
+    ) -> ArcResult<()> {
+        let outcome = match self.options.allows(name, size, &meta.kind)? {
+            true => self.entry(name, meta, data)?,
+            false => ExtractOutcome::Vetoed,
+        };
+        if let Some(tracker) = &self.tracker {
+            // the data of files left out counts as done too
+            let written = meta.kind == EntryKind::File
+                && matches!(
+                    outcome,
+                    ExtractOutcome::Created(_)
+                        | ExtractOutcome::Overwritten(_)
+                        | ExtractOutcome::Renamed(_)
+                );
+            let left = if written { 0 } else { size };
+            tracker.finish(name, left);
+        }
+        self.summary.record(name, outcome);
+        Ok(())
+    }
+
+    /// Writes the entry `name` and returns what happened to it
+    fn entry(
+        &mut self,
+        name: &str,
+        meta: &ArcMeta,
+        data: &mut dyn Read,
+    ) -> ArcResult<ExtractOutcome> {
+        let Some(path) = destination(self.dir, name) else {
+            return Ok(ExtractOutcome::Unsupported);
+        };
+        check_symlinks(self.dir, &path, name, &meta.kind)?;
+        if let EntryKind::Directory = meta.kind {
+            let outcome = directory(path.clone())?;
+            // directories can't be opened like files elsewhere, and symlinks
+            // to them are left as they are
+            if self.options.preserve_metadata
+                && cfg!(unix)
+                && !fs::symlink_metadata(&path)?.file_type().is_symlink()
+            {
+                self.directories.push((path.clone(), meta.clone()));
+            }
+            self.summary.directories.push(path);
+            return Ok(outcome);
+        }
+        let supported = match &meta.kind {
+            EntryKind::Symlink(_) => cfg!(unix),
+            EntryKind::Special(_) => false,
+            _ => true,
+        };
+        if !supported {
+            return Ok(ExtractOutcome::Unsupported);
+        }
+        let (path, outcome) = self.options.overwrite.apply(path)?;
+        if let ExtractOutcome::Skipped(_) = outcome {
+            return Ok(outcome);
+        }
+        make_room(&path)?;
+        match &meta.kind {
+            EntryKind::File => {
+                let mut file = File::create(&path)?;
+                let mut chunk = vec![0; CHUNK_SIZE];
+                loop {
+                    let len = match data.read(&mut chunk) {
+                        Ok(0) => break,
+                        Ok(len) => len,
+                        Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(err) => return Err(err.into()),
+                    };
+                    file.write_all(&chunk[..len])?;
+                    if let Some(tracker) = &self.tracker {
+                        tracker.advance(name, len as u64);
+                    }
+                    self.summary.bytes += len as u64;
+                }
+                if self.options.preserve_metadata {
+                    restore(&file, meta)?;
+                }
+                self.summary.files.push(path);
+            }
+            EntryKind::Symlink(target) => {
+                symlink(target, &path)?;
+                self.summary.links.push(path);
+            }
+            EntryKind::HardLink(target) => {
+                let target = destination(self.dir, target)
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+                check_symlinks(self.dir, &target, name, &EntryKind::File)?;
+                fs::hard_link(target, &path)?;
+                self.summary.links.push(path);
+            }
+            EntryKind::Directory | EntryKind::Special(_) => unreachable!(),
+        }
+        Ok(outcome)
+    }
+
+    /// Gives directories their metadata and returns what was written
+    fn finish(self) -> ArcResult<ExtractSummary> {
+        for (path, meta) in self.directories.into_iter().rev() {
+            restore(&open_directory(&path)?, &meta)?;
+        }
+        Ok(self.summary)
+    }
+}
+
+/// Opens the directory `path` to restore its metadata, failing if it's been
+/// replaced with a symlink since it was extracted
+#[cfg(unix)]
+fn open_directory(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NOFOLLOW | libc::O_DIRECTORY)
+        .open(path)
+}
+
+/// Directories only get their metadata restored on Unix
+#[cfg(not(unix))]
+fn open_directory(path: &Path) -> io::Result<File> {
+    File::open(path)
+}
+
+/// Gives `file` the owner, permissions and modification time in
+/// `meta`, in that order since changing owners drops setuid bits
+fn restore(file: &File, meta: &ArcMeta) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Some(owner) = meta
+            .owner
+            .as_ref()
+            .filter(|_| uzers::get_current_uid() == 0)
+        {
+            let (uid, gid) = owner_ids(owner);
+            std::os::unix::fs::fchown(file, Some(uid), Some(gid))?;
+        }
+        if let Some(mode) = meta.mode {
+            file.set_permissions(fs::Permissions::from_mode(mode & 0o7777))?;
+        }
+    }
+    if let Some(mtime) = meta.mtime {
+        file.set_times(FileTimes::new().set_modified(mtime))?;
+    }
+    Ok(())
+}
+
+/// Returns the ids of the user and group owning an entry, looked up by name
+/// if they exist here like `tar` does, or the ids stored in the archive
+#[cfg(unix)]
+fn owner_ids(owner: &crate::Owner) -> (u32, u32) {
+    let uid = owner
+        .user
+        .as_deref()
+        .and_then(uzers::get_user_by_name)
+        .map_or(owner.uid as u32, |user| user.uid());
+    let gid = owner
+        .group
+        .as_deref()
+        .and_then(uzers::get_group_by_name)
+        .map_or(owner.gid as u32, |group| group.gid());
+    (uid, gid)
+}
+
+/// Writes the files and directories of the archive in `buf` matching any of
+/// `patterns` under `dir` as `options` say, checking their names as they're
+/// reached
+pub(crate) fn extract_matching(
+    buf: &[u8],
+    dir: &Path,
+    patterns: &[Pattern],
+    options: &ExtractOptions,
+) -> ArcResult<ExtractSummary> {
+    match options.atomic {
+        true => staged(dir, |staging| {
+            write_matching(buf, staging, patterns, options)
+        }),
+        false => write_matching(buf, dir, patterns, options),
+    }
+}
+
+/// Writes the files and directories of the archive in `buf` matching any of
+/// `patterns` under `dir`
+fn write_matching(
+    buf: &[u8],
+    dir: &Path,
+    patterns: &[Pattern],
+    options: &ExtractOptions,
+) -> ArcResult<ExtractSummary> {
+    // totals aren't known without decoding the archive twice
+    let mut extraction = Extraction::new(dir, options, None, None)?;
+    visit::visit(buf, &mut |entry: Visited| {
+        if !patterns.iter().any(|pattern| pattern.matches(&entry.name)) {
+            return Ok(());
+        }
+        if !path::is_safe_path(&entry.name) {
+            return Err(ArcError::UnsafePath(entry.name));
+        }
+        let mut empty = io::empty();
+        let data = entry.data.unwrap_or(&mut empty);
+        extraction.extract(&entry.name, entry.size, &entry.meta, data)
+    })?;
+    extraction.finish()
+}
+
+/// Writes the file `name` of the archive in `buf` read with `options` to
+/// `dest`, which is only created once the file is found and removed if
+/// writing it fails
+pub(crate) fn extract_entry(
+    buf: &[u8],
+    name: &str,
+    dest: &Path,
+    options: &ReadOptions,
+) -> ArcResult<u64> {
+    let mut created = false;
+    let result = guard(|| {
+        copy_entry(buf, name, options, || {
+            created = true;
+            File::create(dest)
+        })
+    });
+    if result.is_err() && created {
+        // the error matters more than a leftover file
+        let _ = fs::remove_file(dest);
+    }
+    result
+}
+
+/// Copies the data of the file `name` of the archive in `buf` read with
+/// `options` to what `create` returns once it's found, returning its size
+fn copy_entry<W: Write>(
+    buf: &[u8],
+    name: &str,
+    options: &ReadOptions,
+    create: impl FnOnce() -> io::Result<W>,
+) -> ArcResult<u64> {
+    let not_found = || ArcError::EntryNotFound(name.to_owned());
+    let indexed = options.plain_decoding() && options.plain_policies();
+    let password = options.password;
+    let format = match options.format {
+        Some(format) => format,
+        None if !indexed => ArcReader::detect(buf),
+        None => match ArcReader::tar_stream(buf)? {
+            TarStream::Tar(format, decoder) => {
+                return copy_tar_entry(format, decoder, name, create)
+            }
+            TarStream::Other(..) => ArcReader::detect(buf),
+        },
+    };
+    match format {
+        ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub if indexed => {
+            copy_zip_entry(buf, name, password, create)
+        }
+        ArcFormat::Crx if indexed => copy_zip_entry(crx::split(buf)?.1, name, password, create),
+        ArcFormat::Sevenz if indexed => ArcReader::copy_7z_entry(buf, name, password, create),
+        _ => {
+            let entry = (ArcReader::with_options(buf, options)?.entries.into_iter())
+                .find(|entry| entry.is_file() && entry.name == name)
+                .ok_or_else(not_found)?;
+            create()?.write_all(&entry.data)?;
+            Ok(entry.data.len() as u64)
+        }
+    }
+}
+
+/// Copies the data of the file `name` of the tarball decoded by `decoder` to
+/// what `create` returns once it's found, returning its size
+fn copy_tar_entry<W: Write>(
+    format: ArcFormat,
+    decoder: Box<dyn Read + '_>,
+    name: &str,
+    create: impl FnOnce() -> io::Result<W>,
+) -> ArcResult<u64> {
+    let mut reader = ArcStreamReader::from_decoder(format, decoder);
+    while let Some(mut entry) = reader.next_entry()? {
+        let file = !entry.is_dir()
+            && entry.symlink_target().is_none()
+            && entry.hard_link_target().is_none()
+            && entry.special().is_none();
+        if file && entry.name() == name {
+            return Ok(io::copy(&mut entry, &mut create()?)?);
+        }
+    }
+    Err(ArcError::EntryNotFound(name.to_owned()))
+}
+
+/// Copies the data of the file `name` of the zip archive in `buf` to what
+/// `create` returns once it's found, returning its size
+///
+/// The zip crate checks the CRC-32 as the data is read, failing with an
+/// [`io::ErrorKind::InvalidData`] error if it doesn't match.
+fn copy_zip_entry<W: Write>(
+    buf: &[u8],
+    name: &str,
+    password: Option<&[u8]>,
+    create: impl FnOnce() -> io::Result<W>,
+) -> ArcResult<u64> {
+    let mut archive = ZipArchive::new(Cursor::new(buf))?;
+    let file = match password {
+        Some(password) => archive.by_name_decrypt(name, password),
+        None => archive.by_name(name),
+    };
+    let mut file = match file {
+        Ok(file) if file.is_file() => file,
+        Ok(_) | Err(ZipError::FileNotFound) => {
+            return Err(ArcError::EntryNotFound(name.to_owned()))
+        }
+        Err(err) => return Err(err.into()),
+    };
+    Ok(io::copy(&mut file, &mut create()?)?)
+}
+
+/// Returns where the entry `name`, a safe path, goes under `dir`, or `None`
+/// if it has no name of its own
+pub(crate) fn destination(dir: &Path, name: &str) -> Option<PathBuf> {
+    let mut components = name
+        .split('/')
+        .filter(|component| !matches!(*component, "" | "."))
+        .peekable();
+    components.peek()?;
+    Some(components.fold(dir.to_owned(), |path, component| path.join(component)))
+}
+
+/// Fails with [`ArcError::SymlinkEscape`] for the entry `name` of `kind` if
+/// a symlink already there that leads outside of `dir` would be written
+/// through: one of the parents of `path` under `dir`, or `path` itself for
+/// directories, as anything else at `path` gets replaced
+pub(crate) fn check_symlinks(
+    dir: &Path,
+    path: &Path,
+    name: &str,
+    kind: &EntryKind,
+) -> ArcResult<()> {
+    let mut root = None;
+    if check_parents(dir, path, name, &mut root)? && *kind == EntryKind::Directory {
+        check_link(dir, path, name, &mut root)?;
+    }
+    Ok(())
+}
+
+/// Fails with [`ArcError::SymlinkEscape`] for the entry `name` if one of the
+/// parents of `path` under `dir` is a symlink leading outside of `dir`, and
+/// returns whether they're all there
+fn check_parents(
+    dir: &Path,
+    path: &Path,
+    name: &str,
+    root: &mut Option<PathBuf>,
+) -> ArcResult<bool> {
+    let Some(parents) = path
+        .parent()
+        .and_then(|parent| parent.strip_prefix(dir).ok())
+    else {
+        return Ok(true);
+    };
+    let mut parent = dir.to_owned();
+    for component in parents.components() {
+        parent.push(component);
+        if !check_link(dir, &parent, name, root)? {
+            // the rest is created as plain directories
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Fails with [`ArcError::SymlinkEscape`] for the entry `name` if `path` is
+/// a symlink leading outside of `dir`, whose canonical path is cached in
+/// `root`, and returns whether anything is at `path`
+fn check_link(dir: &Path, path: &Path, name: &str, root: &mut Option<PathBuf>) -> ArcResult<bool> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => {
+            let root = match root {
+                Some(root) => root,
+                None => root.insert(fs::canonicalize(dir)?),
+            };
+            match fs::canonicalize(path)?.starts_with(root) {
+                true => Ok(true),
+                false => Err(ArcError::SymlinkEscape(name.to_owned())),
+            }
+        }
+        Ok(_) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Creates the missing parents of `path` and removes anything but a
+/// directory at `path`, like `tar` does, so nothing is written through
+/// symlinks or hard links already there
+///
+/// Symlinks among the parents are followed, so they have to be checked with
+/// [`check_symlinks`] first.
+fn make_room(path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match fs::symlink_metadata(path) {
+        Ok(meta) if !meta.is_dir() => fs::remove_file(path),
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Returns whether anything is at `path`, without following symlinks
+pub(crate) fn exists(path: &Path) -> io::Result<bool> {
+    match fs::symlink_metadata(path) {
+        Ok(_) => Ok(true),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Creates the directory `path` and its parents unless it's already there
+///
+/// A symlink at `path` is followed, so it has to be checked with
+/// [`check_symlinks`] first.
+fn directory(path: PathBuf) -> io::Result<ExtractOutcome> {
+    if path.is_dir() {
+        return Ok(ExtractOutcome::Existing(path));
+    }
+    fs::create_dir_all(&path)?;
+    Ok(ExtractOutcome::Created(path))
+}
+
+/// Creates a symlink at `path` pointing to `target`
+#[cfg(unix)]
+fn symlink(target: &str, path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+/// Symlinks need extra privileges to be created on Windows, so they're left
+/// out before getting here
+#[cfg(not(unix))]
+fn symlink(_target: &str, _path: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,44 @@
+//! Async wrappers over the `futures` I/O traits, for any runtime
+
+use crate::{async_io::wrappers, ArcFormat, ArcReader, ArcResult, ArcWriter};
+use futures_util::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// [`ArcReader`] for archives read from a `futures` [`AsyncRead`]
+///
+/// The archive is read asynchronously and decoded once it's complete. The
+/// reader dereferences to the [`ArcReader`] holding the entries.
+pub struct FuturesArcReader(ArcReader);
+
+impl FuturesArcReader {
+    /// Reads the archive from `reader` to its end
+    ///
+    /// Decoding runs on the calling task once the archive is read, blocking
+    /// its thread until it's done since there's no runtime to hand it to.
+    /// Big archives are better decoded on a blocking thread pool, like the
+    /// `blocking` crate's.
+    pub async fn new(mut reader: impl AsyncRead + Unpin) -> ArcResult<Self> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await?;
+        Ok(Self(ArcReader::new(&buf)?))
+    }
+}
+
+/// [`ArcWriter`] writing archives to a `futures` [`AsyncWrite`]
+///
+/// Entries and options are set through the [`ArcWriter`] it dereferences to.
+pub struct FuturesArcWriter(ArcWriter);
+
+impl FuturesArcWriter {
+    /// Creates the finished archive and writes it to `writer`, failing like
+    /// [`ArcWriter::archive`]
+    ///
+    /// Encoding runs on the calling task before anything is written,
+    /// blocking its thread like [`FuturesArcReader::new`] decoding does.
+    pub async fn archive_to(&self, mut writer: impl AsyncWrite + Unpin) -> ArcResult<()> {
+        writer.write_all(&self.0.archive()?).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+wrappers!(FuturesArcReader, FuturesArcWriter);
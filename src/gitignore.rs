@@ -0,0 +1,83 @@
+//! Leaving out what `.gitignore` and `.ignore` files and exclude globs match
+//! when adding directory trees
+
+use crate::{ArcError, ArcResult};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+/// Names of the files whose rules apply to the directory they're in
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Rules in effect at some point of a walk
+pub(crate) struct Rules {
+    /// Exclude globs, applying everywhere and checked first
+    excludes: Gitignore,
+    /// Whether ignore files are read
+    read_files: bool,
+    /// Rules of the ignore files of the directories being walked, innermost
+    /// last
+    files: Vec<Gitignore>,
+}
+
+impl Rules {
+    /// Returns the rules of a walk of `root`, with `excludes` as gitignore
+    /// lines relative to it and ignore files read if `read_files` is set
+    ///
+    /// Fails with [`ArcError::InvalidPattern`] on invalid globs.
+    pub(crate) fn new(root: &Path, excludes: &[String], read_files: bool) -> ArcResult<Self> {
+        let mut builder = GitignoreBuilder::new(root);
+        builder.allow_unclosed_class(false);
+        for exclude in excludes {
+            builder
+                .add_line(None, exclude)
+                .map_err(|_| ArcError::InvalidPattern(exclude.clone()))?;
+        }
+        Ok(Self {
+            excludes: builder
+                .build()
+                .map_err(|err| ArcError::InvalidPattern(err.to_string()))?,
+            read_files,
+            files: Vec::new(),
+        })
+    }
+
+    /// Reads the ignore files of the directory `dir` about to be walked
+    ///
+    /// Lines that aren't valid are skipped, like git does.
+    pub(crate) fn enter(&mut self, dir: &Path) {
+        if !self.read_files {
+            return;
+        }
+        let mut builder = GitignoreBuilder::new(dir);
+        for name in IGNORE_FILES {
+            let path = dir.join(name);
+            if path.is_file() {
+                builder.add(path);
+            }
+        }
+        self.files
+            .push(builder.build().unwrap_or_else(|_| Gitignore::empty()));
+    }
+
+    /// Forgets the ignore files of the directory walked last
+    pub(crate) fn leave(&mut self) {
+        self.files.pop();
+    }
+
+    /// Returns whether `path`, a directory if `is_dir` is set, is left out
+    ///
+    /// The innermost rule matching it decides, so `!` lines in deeper ignore
+    /// files bring back what outer ones leave out.
+    pub(crate) fn ignores(&self, path: &Path, is_dir: bool) -> bool {
+        if self.excludes.matched(path, is_dir).is_ignore() {
+            return true;
+        }
+        for rules in self.files.iter().rev() {
+            let matched = rules.matched(path, is_dir);
+            if !matched.is_none() {
+                return matched.is_ignore();
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,53 @@
+//! Digests of file data, computed as it's decoded or archived
+
+use digest::{Digest, DynDigest};
+use std::{
+    collections::HashMap,
+    io::{self, Read},
+    sync::Mutex,
+};
+
+/// Feeds everything left in `reader` to a new `D` and returns its digest
+pub(crate) fn digest<D: Digest>(reader: &mut dyn Read) -> io::Result<Vec<u8>> {
+    let mut hasher = D::new();
+    let mut buf = [0; 8 * 1024];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(hasher.finalize().to_vec()),
+            Ok(read) => hasher.update(&buf[..read]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Reader hashing the data of the file `name` as it's read, storing the
+/// digest in `digests` once it reaches the end
+pub(crate) struct DigestReader<'a> {
+    pub(crate) inner: Box<dyn Read + 'a>,
+    pub(crate) name: &'a str,
+    /// Taken when the digest is stored
+    pub(crate) hasher: Option<Box<dyn DynDigest>>,
+    pub(crate) digests: &'a Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl Read for DigestReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        match read {
+            0 if !buf.is_empty() => {
+                if let Some(hasher) = self.hasher.take() {
+                    let digest = hasher.finalize().into_vec();
+                    let mut digests = self.digests.lock().unwrap();
+                    digests.insert(self.name.to_owned(), digest);
+                }
+            }
+            _ => {
+                if let Some(hasher) = &mut self.hasher {
+                    hasher.update(&buf[..read]);
+                }
+            }
+        }
+        Ok(read)
+    }
+}
@@ -0,0 +1,139 @@
+//! Parsing and validation of JAR manifests
+
+use crate::{ArcEntry, ArcError, ArcResult};
+
+/// Path of the manifest inside a JAR
+pub(crate) const MANIFEST: &str = "META-INF/MANIFEST.MF";
+
+/// Manifest written when none was pushed
+pub(crate) const DEFAULT_MANIFEST: &[u8] = b"Manifest-Version: 1.0\r\nCreated-By: cra\r\n\r\n";
+
+/// Longest allowed line in bytes, not counting the line break
+const MAX_LINE_LEN: usize = 72;
+
+/// Longest allowed attribute name
+const MAX_NAME_LEN: usize = 70;
+
+/// Returns whether `entry` is the manifest file
+pub(crate) fn is_manifest(entry: &ArcEntry) -> bool {
+    entry.is_file() && entry.name == MANIFEST
+}
+
+type Attributes = Vec<(String, String)>;
+
+/// Parsed contents of a JAR manifest
+///
+/// Attribute names are compared case-insensitively, as in Java.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Manifest {
+    main: Attributes,
+    sections: Vec<(String, Attributes)>,
+}
+
+impl Manifest {
+    /// Parses and validates a manifest
+    pub fn parse(buf: &[u8]) -> ArcResult<Self> {
+        let invalid =
+            |line: usize, msg: &str| ArcError::InvalidManifest(format!("line {}: {msg}", line + 1));
+        let text = std::str::from_utf8(buf)
+            .map_err(|_| ArcError::InvalidManifest("not valid UTF-8".to_owned()))?;
+
+        let mut lines = Vec::new();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let Some(end) = rest.find(['\r', '\n']) else {
+                return Err(invalid(lines.len(), "missing line break"));
+            };
+            let len = match &rest[end..] {
+                tail if tail.starts_with("\r\n") => 2,
+                _ => 1,
+            };
+            lines.push(&rest[..end]);
+            rest = &rest[end + len..];
+        }
+
+        // the main section comes first, then sections separated by blank lines
+        let mut sections: Vec<Vec<(usize, String, String)>> = vec![Vec::new()];
+        for (i, line) in lines.iter().enumerate() {
+            if line.len() > MAX_LINE_LEN {
+                return Err(invalid(i, "line longer than 72 bytes"));
+            }
+            let current = sections.last_mut().unwrap();
+            if line.is_empty() {
+                if !current.is_empty() || sections.len() == 1 {
+                    sections.push(Vec::new());
+                }
+            } else if let Some(continuation) = line.strip_prefix(' ') {
+                current
+                    .last_mut()
+                    .ok_or_else(|| invalid(i, "continuation without an attribute"))?
+                    .2
+                    .push_str(continuation);
+            } else {
+                let (name, value) = line
+                    .split_once(": ")
+                    .ok_or_else(|| invalid(i, "expected `name: value`"))?;
+                let valid_name = name.len() <= MAX_NAME_LEN
+                    && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+                if !valid_name {
+                    return Err(invalid(i, "invalid attribute name"));
+                }
+                current.push((i, name.to_owned(), value.to_owned()));
+            }
+        }
+
+        let strip = |attributes: Vec<(usize, String, String)>| {
+            attributes
+                .into_iter()
+                .map(|(_, name, value)| (name, value))
+                .collect::<Attributes>()
+        };
+        let mut sections = sections.into_iter().filter(|section| !section.is_empty());
+        let mut manifest = Manifest::default();
+        if lines.first().is_some_and(|line| !line.is_empty()) {
+            manifest.main = strip(sections.next().unwrap_or_default());
+        }
+        for mut attributes in sections {
+            let (line, name, value) = attributes.remove(0);
+            if !name.eq_ignore_ascii_case("Name") {
+                return Err(invalid(line, "section doesn't start with `Name`"));
+            }
+            manifest.sections.push((value, strip(attributes)));
+        }
+        Ok(manifest)
+    }
+
+    /// Returns the value of an attribute in the main section
+    pub fn get(&self, name: &str) -> Option<&str> {
+        Manifest::find(&self.main, name)
+    }
+
+    /// Returns the value of an attribute in the section for the entry `entry`
+    pub fn get_for(&self, entry: &str, name: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .find(|(section, _)| section == entry)
+            .and_then(|(_, attributes)| Manifest::find(attributes, name))
+    }
+
+    /// Returns all attributes of the main section in order
+    pub fn main(&self) -> &[(String, String)] {
+        &self.main
+    }
+
+    /// Returns the per-entry sections in order, as entry names and their
+    /// attributes
+    pub fn sections(&self) -> &[(String, Vec<(String, String)>)] {
+        &self.sections
+    }
+
+    fn find<'a>(attributes: &'a Attributes, name: &str) -> Option<&'a str> {
+        attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
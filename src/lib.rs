@@ -1,20 +1,292 @@
 //! Simple abstraction over archive formats.
 //!
-//! You can read and write archives in zip, 7z, and tar formats.
+//! You can read and write archives in zip, 7z, tar, cpio, and ar formats,
+//! including gzip-, xz-, bzip2-, zstd-, lz4-, brotli- and snappy-compressed
+//! tarballs, as well as single gzip-, zstd-, lz4-, brotli- or snappy-compressed
+//! files. Debian and RPM
+//! packages, Microsoft cabinets, WIM images, xar archives and WARC web archives
+//! can be read, and so can RAR archives with the `rar` feature. The `async`
+//! feature adds readers and writers for tokio's async I/O traits, and the
+//! `futures` feature does the same for the runtime-agnostic `futures` ones,
+//! and the `object_store` feature reads archives from S3, GCS or Azure.
 
-use infer::get;
-use sevenz_rust::{nt_time::FileTime, Password, SevenZArchiveEntry, SevenZReader, SevenZWriter};
-use std::io::{self, Cursor, Read, Write};
-use tar::{Archive as TarArchive, Entry as TarEntry};
+use ar::{Archive as ArArchive, Builder as ArBuilder, Header as ArHeader};
+use brotli::{
+    BrotliDecompressStream, BrotliResult, BrotliState, Decompressor as BrotliDecoder, HeapAlloc,
+    HuffmanCode,
+};
+use bzip2::read::BzDecoder;
+use cab::Cabinet;
+use cpio::{newc::ModeFileType, NewcBuilder, NewcReader};
+use digest::{Digest, DynDigest};
+use flate2::{read::GzDecoder, read::MultiGzDecoder};
+use infer::{
+    archive::{is_gz, is_tar},
+    Infer,
+};
+use lz4_flex::frame::FrameDecoder as Lz4Decoder;
+use memmap2::{Mmap, MmapMut};
+use sevenz_rust::{
+    lzma::{LZMA2Options, DICT_SIZE_MAX, DICT_SIZE_MIN},
+    nt_time::FileTime,
+    AesEncoderOptions, Archive, BlockDecoder, Password, SeqReader, SevenZArchiveEntry,
+    SevenZMethod, SevenZMethodConfiguration, SevenZReader, SevenZWriter, SourceReader,
+};
+use snap::read::FrameDecoder as SnappyDecoder;
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
+    fs::File,
+    io::{self, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    sync::Mutex,
+    time::SystemTime,
+};
+use tar::Entry as TarEntry;
+use tempfile::NamedTempFile;
 use thiserror::Error;
-use zip::{read::ZipFile, write::SimpleFileOptions, ZipArchive, ZipWriter};
+use xz2::read::XzDecoder;
+use zeroize::Zeroizing;
+use zip::{
+    read::ZipFile, result::ZipError, write::FullFileOptions, AesMode, CompressionMethod,
+    ZipArchive, ZipWriter,
+};
+use zstd::Decoder as ZstdDecoder;
+
+mod add;
+#[cfg(any(feature = "async", feature = "futures"))]
+mod async_io;
+mod checksum;
+#[cfg(feature = "object_store")]
+mod cloud;
+mod crx;
+mod duplicate;
+mod encoder;
+mod epub;
+mod extract;
+#[cfg(feature = "futures")]
+mod futures_io;
+#[cfg(feature = "ignore")]
+mod gitignore;
+mod hash;
+mod jar;
+mod meta;
+mod mmap;
+mod name;
+mod path;
+mod pattern;
+mod plan;
+mod progress;
+mod recover;
+mod remote;
+mod secret;
+mod sparse;
+mod spill;
+mod split_zip;
+mod stream;
+#[cfg(feature = "async")]
+mod tokio_io;
+mod validate;
+mod visit;
+mod warc;
+mod wim;
+mod xar;
+mod zip_comments;
+mod zip_records;
+
+use encoder::Encoder;
+use mmap::MmapWriter;
+use progress::{CountingReader, Tracker};
+use sparse::SparseMap;
+use visit::Visited;
+use zip_comments::{CommentWriter, Ending};
+
+pub use add::{AddFilterFn, AddSymlinkPolicy};
+pub use checksum::ChecksumPolicy;
+#[cfg(feature = "object_store")]
+pub use cloud::ObjectStoreZipReader;
+pub use crx::{CrxHeader, CrxProof};
+pub use digest;
+pub use duplicate::DuplicatePolicy;
+pub use extract::{
+    EntryDecision, ExtractOptions, ExtractOutcome, ExtractSummary, OverwritePolicy, VetoFn,
+};
+#[cfg(feature = "futures")]
+pub use futures_io::{FuturesArcReader, FuturesArcWriter};
+pub use jar::Manifest;
+pub use name::NonUtf8Policy;
+pub use path::{is_safe_path, sanitize_path, PathPolicy, SymlinkPolicy};
+pub use pattern::Pattern;
+pub use plan::ExtractPlan;
+pub use progress::{Progress, ProgressFn};
+pub use remote::{RangeFetcher, RemoteZipReader};
+pub use secret::Secret;
+pub use sha2::Sha256;
+pub use spill::{SpilledData, SpilledEntry};
+pub use stream::{ArcStreamEntry, ArcStreamReader, ArcStreamWriter};
+#[cfg(feature = "async")]
+pub use tokio_io::{AsyncArcReader, AsyncArcWriter};
+pub use validate::{Problem, ValidationReport};
 
 /// Enum representing supported archive formats
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ArcFormat {
     Zip,
+    /// A Java archive, a zip whose `META-INF/MANIFEST.MF` is stored first
+    ///
+    /// Zip archives are read as this format if they contain a manifest, use
+    /// [`ArcReader::jar_manifest`] to parse it. When writing, the manifest is
+    /// validated, and a minimal one is added if none was pushed.
+    Jar,
+    /// An EPUB book, a zip whose uncompressed `mimetype` file comes first
+    ///
+    /// When writing, `mimetype` is added if it wasn't pushed, and
+    /// `META-INF/container.xml` and the package documents it lists have to be
+    /// present.
+    Epub,
+    /// A CRX3 Chrome extension, read-only
+    ///
+    /// The entries are those of the zip archive following the header, use
+    /// [`ArcReader::crx_header`] to read its signatures.
+    Crx,
     Tar,
     Sevenz,
+    /// A single gzip-compressed file, read and written as one [`ArcEntry::file`]
+    Gzip,
+    /// A gzip-compressed tar archive
+    TarGz,
+    /// An xz-compressed tar archive
+    TarXz,
+    /// A bzip2-compressed tar archive
+    TarBz2,
+    /// A zstd-compressed tar archive
+    TarZst,
+    /// A single zstd-compressed file, read and written as one [`ArcEntry::file`]
+    ///
+    /// zstd doesn't store file names, so the entry name is always empty when
+    /// reading and ignored when writing.
+    Zstd,
+    /// An lz4-compressed tar archive
+    TarLz4,
+    /// A single file compressed in the lz4 frame format, read and written as
+    /// one [`ArcEntry::file`]
+    ///
+    /// lz4 doesn't store file names, so the entry name is always empty when
+    /// reading and ignored when writing.
+    Lz4,
+    /// A brotli-compressed tar archive
+    TarBr,
+    /// A single brotli-compressed file, read and written as one
+    /// [`ArcEntry::file`]
+    ///
+    /// brotli doesn't store file names, so the entry name is always empty when
+    /// reading and ignored when writing. It also has no magic bytes, so it's
+    /// only tried when no other format is recognized.
+    Brotli,
+    /// A snappy-compressed tar archive, in the snappy framing format
+    TarSz,
+    /// A single file compressed in the snappy framing format, read and written
+    /// as one [`ArcEntry::file`]
+    ///
+    /// snappy doesn't store file names, so the entry name is always empty when
+    /// reading and ignored when writing.
+    Snappy,
+    /// A cpio archive in the SVR4 `newc` format
+    Cpio,
+    /// A Unix `ar` archive, which can only hold files
+    Ar,
+    /// A Debian package, read-only
+    ///
+    /// The package is read as the underlying `ar` archive, use
+    /// [`ArcReader::deb_control`] and [`ArcReader::deb_data`] to read the
+    /// nested tarballs.
+    Deb,
+    /// An RPM package, read-only
+    ///
+    /// The entries are those of the package's cpio payload.
+    Rpm,
+    /// A Microsoft cabinet, read-only
+    ///
+    /// Backslashes in entry names are converted to forward slashes.
+    Cab,
+    /// A Windows Imaging Format file, read-only
+    ///
+    /// Only the first image is read by [`ArcReader::new`], use
+    /// [`ArcReader::wim_image`] to read the others. Uncompressed and XPRESS
+    /// images are supported.
+    Wim,
+    /// A xar archive, such as a macOS `.pkg` installer, read-only
+    ///
+    /// Symlinks and hardlinks are skipped.
+    Xar,
+    /// A WARC web archive, plain or gzip-compressed, read-only
+    ///
+    /// Each record is read as a file named after its `WARC-Target-URI`,
+    /// or its `WARC-Record-ID` if it has none, holding the record's content
+    /// block. Use [`ArcReader::warc_headers`] to read the record headers.
+    Warc,
+    /// A RAR archive, read-only and only available with the `rar` feature
+    #[cfg(feature = "rar")]
+    Rar,
+}
+
+impl ArcFormat {
+    /// Returns the format usually named by the extension of `path`, like
+    /// `TarGz` for `.tar.gz` and `.tgz`
+    /// Fails with [`ArcError::UnrecognizedFormat`] for unknown extensions
+    pub fn from_path(path: impl AsRef<Path>) -> ArcResult<Self> {
+        const EXTENSIONS: &[(&str, ArcFormat)] = &[
+            (".tar.gz", ArcFormat::TarGz),
+            (".tgz", ArcFormat::TarGz),
+            (".tar.xz", ArcFormat::TarXz),
+            (".txz", ArcFormat::TarXz),
+            (".tar.bz2", ArcFormat::TarBz2),
+            (".tbz2", ArcFormat::TarBz2),
+            (".tar.zst", ArcFormat::TarZst),
+            (".tzst", ArcFormat::TarZst),
+            (".tar.lz4", ArcFormat::TarLz4),
+            (".tar.br", ArcFormat::TarBr),
+            (".tar.sz", ArcFormat::TarSz),
+            (".warc.gz", ArcFormat::Warc),
+            (".zip", ArcFormat::Zip),
+            (".jar", ArcFormat::Jar),
+            (".epub", ArcFormat::Epub),
+            (".crx", ArcFormat::Crx),
+            (".tar", ArcFormat::Tar),
+            (".7z", ArcFormat::Sevenz),
+            (".gz", ArcFormat::Gzip),
+            (".zst", ArcFormat::Zstd),
+            (".lz4", ArcFormat::Lz4),
+            (".br", ArcFormat::Brotli),
+            (".sz", ArcFormat::Snappy),
+            (".cpio", ArcFormat::Cpio),
+            (".a", ArcFormat::Ar),
+            (".ar", ArcFormat::Ar),
+            (".deb", ArcFormat::Deb),
+            (".rpm", ArcFormat::Rpm),
+            (".cab", ArcFormat::Cab),
+            (".wim", ArcFormat::Wim),
+            (".xar", ArcFormat::Xar),
+            (".pkg", ArcFormat::Xar),
+            (".warc", ArcFormat::Warc),
+            #[cfg(feature = "rar")]
+            (".rar", ArcFormat::Rar),
+        ];
+
+        let name = path
+            .as_ref()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        EXTENSIONS
+            .iter()
+            .find(|(extension, _)| name.ends_with(extension))
+            .map(|&(_, format)| format)
+            .ok_or(ArcError::UnrecognizedFormat)
+    }
 }
 
 impl TryFrom<infer::Type> for ArcFormat {
@@ -23,265 +295,3742 @@ impl TryFrom<infer::Type> for ArcFormat {
     fn try_from(value: infer::Type) -> Result<Self, Self::Error> {
         Ok(match value.extension() {
             "zip" => ArcFormat::Zip,
+            "epub" => ArcFormat::Epub,
+            "crx" => ArcFormat::Crx,
             "7z" => ArcFormat::Sevenz,
             "tar" => ArcFormat::Tar,
+            "gz" => ArcFormat::Gzip,
+            "xz" => ArcFormat::TarXz,
+            "bz2" => ArcFormat::TarBz2,
+            "zst" => ArcFormat::Zstd,
+            "lz4" => ArcFormat::Lz4,
+            "sz" => ArcFormat::Snappy,
+            "cpio" => ArcFormat::Cpio,
+            "ar" => ArcFormat::Ar,
+            "deb" => ArcFormat::Deb,
+            "rpm" => ArcFormat::Rpm,
+            "cab" => ArcFormat::Cab,
+            "wim" => ArcFormat::Wim,
+            "xar" => ArcFormat::Xar,
+            "warc" => ArcFormat::Warc,
+            #[cfg(feature = "rar")]
+            "rar" => ArcFormat::Rar,
             _ => return Err(ArcError::UnrecognizedFormat),
         })
     }
 }
 
-/// Enum representing an archive entry
+/// Header format used when writing tar-based archives
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TarFormat {
+    /// GNU headers, storing long paths with GNU extensions
+    #[default]
+    Gnu,
+    /// POSIX.1-2001 ustar headers, storing long paths and large sizes in PAX
+    /// extended headers
+    Pax,
+}
+
+/// Owner of a tar entry
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Owner {
+    pub uid: u64,
+    pub gid: u64,
+    /// User name, looked up from `uid` by extracting tools that prefer names
+    pub user: Option<String>,
+    /// Group name, looked up from `gid` by extracting tools that prefer names
+    pub group: Option<String>,
+}
+
+impl Owner {
+    /// Returns `root:root`, with ids `0`
+    pub fn root() -> Self {
+        Self {
+            uid: 0,
+            gid: 0,
+            user: Some("root".into()),
+            group: Some("root".into()),
+        }
+    }
+
+    /// Returns the user and group running the process
+    #[cfg(unix)]
+    pub fn current() -> Self {
+        use uzers::{
+            get_current_gid, get_current_groupname, get_current_uid, get_current_username,
+        };
+
+        Self {
+            uid: get_current_uid().into(),
+            gid: get_current_gid().into(),
+            user: get_current_username().and_then(|name| name.into_string().ok()),
+            group: get_current_groupname().and_then(|name| name.into_string().ok()),
+        }
+    }
+
+    /// Returns ids `0` without names, as there are no Unix users to take
+    /// them from
+    #[cfg(not(unix))]
+    pub fn current() -> Self {
+        Self::default()
+    }
+}
+
+/// Extra field of a zip entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipExtraField {
+    /// Header id, registered in the zip specification or by a third party
+    pub id: u16,
+    pub data: Vec<u8>,
+}
+
+/// MS-DOS attributes of a zip entry, kept in the low byte of its external
+/// attributes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DosAttributes {
+    pub read_only: bool,
+    pub hidden: bool,
+    pub system: bool,
+    /// Set by Windows on files changed since they were last backed up
+    pub archive: bool,
+}
+
+impl DosAttributes {
+    const READ_ONLY: u8 = 0x01;
+    const HIDDEN: u8 = 0x02;
+    const SYSTEM: u8 = 0x04;
+    const ARCHIVE: u8 = 0x20;
+    /// Bits of the attributes above, the directory bit is left to the writer
+    pub(crate) const MASK: u8 = Self::READ_ONLY | Self::HIDDEN | Self::SYSTEM | Self::ARCHIVE;
+
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        Self {
+            read_only: bits & Self::READ_ONLY != 0,
+            hidden: bits & Self::HIDDEN != 0,
+            system: bits & Self::SYSTEM != 0,
+            archive: bits & Self::ARCHIVE != 0,
+        }
+    }
+
+    pub(crate) fn bits(self) -> u8 {
+        [
+            (self.read_only, Self::READ_ONLY),
+            (self.hidden, Self::HIDDEN),
+            (self.system, Self::SYSTEM),
+            (self.archive, Self::ARCHIVE),
+        ]
+        .into_iter()
+        .filter(|&(set, _)| set)
+        .fold(0, |bits, (_, bit)| bits | bit)
+    }
+}
+
+/// Entry of an archive listing, read from headers without decoding any data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryInfo {
+    pub name: String,
+    pub directory: bool,
+    /// Size of the data once extracted
+    pub size: u64,
+    /// Size of the data as stored, unknown for 7z files sharing a solid block
+    /// and for formats compressed as a whole
+    pub compressed_size: Option<u64>,
+    /// Compression method, like [`Method::Deflated`] in zip or
+    /// [`Method::Lzma2`] in 7z
+    pub method: Option<Method>,
+}
+
+impl EntryInfo {
+    /// Returns the compressed size as a fraction of the size, if both are
+    /// known and the entry isn't empty
+    pub fn ratio(&self) -> Option<f64> {
+        (self.size > 0).then_some(self.compressed_size? as f64 / self.size as f64)
+    }
+}
+
+/// How names of zip entries without the UTF-8 flag are decoded
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ZipNameEncoding {
+    /// IBM code page 437, as the zip specification prescribes
+    #[default]
+    Cp437,
+    /// UTF-8 if the name is valid UTF-8, CP437 otherwise, for archivers that
+    /// write UTF-8 names without setting the flag
+    Utf8OrCp437,
+    /// Decoded by a function, for legacy code pages such as Shift JIS
+    Custom(fn(&[u8]) -> String),
+}
+
+impl ZipNameEncoding {
+    /// Decodes `raw`, or returns `None` if it's left decoded as CP437
+    fn decode(self, raw: &[u8]) -> Option<String> {
+        match self {
+            ZipNameEncoding::Cp437 => None,
+            ZipNameEncoding::Utf8OrCp437 => std::str::from_utf8(raw).ok().map(str::to_owned),
+            ZipNameEncoding::Custom(decode) => Some(decode(raw)),
+        }
+    }
+}
+
+/// Kind of a special file, which has no data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialFile {
+    Fifo,
+    /// Character device with its major and minor numbers
+    CharDevice(u32, u32),
+    /// Block device with its major and minor numbers
+    BlockDevice(u32, u32),
+}
+
+/// Compression method used when writing 7z archives
+///
+/// The BCJ and BCJ2 filters for executables can be read but not written, as
+/// the 7z backend can't encode them, so big binaries compress no better than
+/// other data.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SevenzCodec {
+    Lzma,
+    #[default]
+    Lzma2,
+}
+
+/// Compression method of an entry of a zip-based or 7z archive
+///
+/// Zip archives can be written with `Stored`, `Deflated`, `Bzip2`, `Zstd` and
+/// `Xz`, 7z archives with `Lzma` and `Lzma2`. Other methods are only read.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Method {
+    Stored,
+    Deflated,
+    Bzip2,
+    Zstd,
+    Xz,
+    Lzma,
+    Lzma2,
+    /// Any other method or chain of them, like `Deflate64` in zip or
+    /// `BCJ_X86 LZMA` in 7z, named by the format
+    Other(String),
+}
+
+impl Method {
+    /// Returns the zip method writing entries with this method, if zip has
+    /// one that can be written
+    fn zip(&self) -> Option<CompressionMethod> {
+        Some(match self {
+            Method::Stored => CompressionMethod::Stored,
+            Method::Deflated => CompressionMethod::Deflated,
+            Method::Bzip2 => CompressionMethod::Bzip2,
+            Method::Zstd => CompressionMethod::Zstd,
+            Method::Xz => CompressionMethod::Xz,
+            _ => return None,
+        })
+    }
+
+    /// Returns the 7z codec writing entries with this method, if there's one
+    fn sevenz(&self) -> Option<SevenzCodec> {
+        match self {
+            Method::Lzma => Some(SevenzCodec::Lzma),
+            Method::Lzma2 => Some(SevenzCodec::Lzma2),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Method::Stored => "Stored",
+            Method::Deflated => "Deflated",
+            Method::Bzip2 => "Bzip2",
+            Method::Zstd => "Zstd",
+            Method::Xz => "Xz",
+            Method::Lzma => "LZMA",
+            Method::Lzma2 => "LZMA2",
+            Method::Other(name) => name,
+        })
+    }
+}
+
+/// Kind of an archive entry, along with what only that kind holds
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum EntryKind {
+    #[default]
+    File,
+    Directory,
+    /// Symbolic link with the path it points to
+    Symlink(String),
+    /// Hard link with the name of the earlier entry it shares its data with
+    HardLink(String),
+    /// FIFO or device node, which only tar and cpio archives can hold
+    Special(SpecialFile),
+}
+
+/// Metadata of an archive entry
+///
+/// Readers fill in what the format stores and leave the rest `None` or empty.
+/// Writers store what the format can hold.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ArcMeta {
+    pub kind: EntryKind,
+    /// Unix permission bits
+    pub mode: Option<u32>,
+    pub mtime: Option<SystemTime>,
+    /// Access time, stored by tarballs, zip-based and 7z archives
+    pub atime: Option<SystemTime>,
+    /// Creation time, stored by zip-based and 7z archives, and by tarballs as
+    /// what tar tools take for the status change time
+    pub ctime: Option<SystemTime>,
+    /// Only stored by tarballs
+    pub owner: Option<Owner>,
+    /// Extended attributes, only stored by tarballs
+    pub xattrs: BTreeMap<String, Vec<u8>>,
+    /// Compression method of files, only stored by zip-based and 7z archives
+    ///
+    /// Writers of those formats use it over their own settings if they can
+    /// write it, entries read from them keep theirs unless it's cleared.
+    pub method: Option<Method>,
+    /// Comment, only stored by zip-based archives, at most 65535 bytes
+    pub comment: Option<String>,
+    /// MS-DOS attributes, only stored by zip-based archives, and only read if
+    /// any is set
+    pub dos_attributes: Option<DosAttributes>,
+    /// Extra fields, only stored by zip-based archives, written to both the
+    /// local and central headers
+    ///
+    /// Fields held elsewhere are left out when reading: the times of the NTFS
+    /// and extended timestamp fields, and the ZIP64 and AES fields describing
+    /// how the entry is stored, which can't be written here.
+    pub extra_fields: Vec<ZipExtraField>,
+    /// Bytes of the name as stored, if they aren't valid UTF-8 and the name
+    /// holds them decoded lossily, see [`NonUtf8Policy`]
+    pub raw_name: Option<Vec<u8>>,
+}
+
+/// Archive entry, with its name, data and metadata
+///
+/// Only files have data. Entries are built with [`ArcEntry::file`] and the
+/// other constructors, and metadata added with the `with_` methods:
 ///
-/// Can be a directory with a name or a file with a name and data.
-#[derive(Debug, Clone, PartialEq)]
-pub enum ArcEntry {
-    File(String, Vec<u8>),
-    Directory(String),
+/// ```
+/// use cra::ArcEntry;
+///
+/// let entry = ArcEntry::file("uwu/owo", "twoja stara\n").with_mode(0o755);
+/// assert!(entry.is_file());
+/// assert_eq!(entry.meta.mode, Some(0o755));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ArcEntry {
+    pub name: String,
+    pub data: Vec<u8>,
+    pub meta: ArcMeta,
+}
+
+impl ArcEntry {
+    /// Returns a file holding `data`
+    pub fn file(name: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        ArcEntry::new(name, data.into(), EntryKind::File)
+    }
+
+    /// Returns a directory
+    pub fn directory(name: impl Into<String>) -> Self {
+        ArcEntry::new(name, Vec::new(), EntryKind::Directory)
+    }
+
+    /// Returns a symbolic link pointing to `target`
+    pub fn symlink(name: impl Into<String>, target: impl Into<String>) -> Self {
+        ArcEntry::new(name, Vec::new(), EntryKind::Symlink(target.into()))
+    }
+
+    /// Returns a hard link to the earlier entry `target`
+    pub fn hard_link(name: impl Into<String>, target: impl Into<String>) -> Self {
+        ArcEntry::new(name, Vec::new(), EntryKind::HardLink(target.into()))
+    }
+
+    /// Returns a FIFO or device node
+    pub fn special(name: impl Into<String>, special: SpecialFile) -> Self {
+        ArcEntry::new(name, Vec::new(), EntryKind::Special(special))
+    }
+
+    fn new(name: impl Into<String>, data: Vec<u8>, kind: EntryKind) -> Self {
+        Self {
+            name: name.into(),
+            data,
+            meta: ArcMeta {
+                kind,
+                ..ArcMeta::default()
+            },
+        }
+    }
+
+    /// Sets the Unix permission bits
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.meta.mode = Some(mode);
+        self
+    }
+
+    /// Sets the modification time
+    pub fn with_mtime(mut self, mtime: SystemTime) -> Self {
+        self.meta.mtime = Some(mtime);
+        self
+    }
+
+    /// Sets the access time
+    pub fn with_atime(mut self, atime: SystemTime) -> Self {
+        self.meta.atime = Some(atime);
+        self
+    }
+
+    /// Sets the creation time
+    pub fn with_ctime(mut self, ctime: SystemTime) -> Self {
+        self.meta.ctime = Some(ctime);
+        self
+    }
+
+    /// Sets the owner
+    pub fn with_owner(mut self, owner: Owner) -> Self {
+        self.meta.owner = Some(owner);
+        self
+    }
+
+    /// Adds the extended attribute `key`
+    pub fn with_xattr(mut self, key: impl Into<String>, value: impl Into<Vec<u8>>) -> Self {
+        self.meta.xattrs.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the compression method of the entry
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.meta.method = Some(method);
+        self
+    }
+
+    /// Sets the comment
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.meta.comment = Some(comment.into());
+        self
+    }
+
+    /// Sets the MS-DOS attributes
+    pub fn with_dos_attributes(mut self, attributes: DosAttributes) -> Self {
+        self.meta.dos_attributes = Some(attributes);
+        self
+    }
+
+    /// Adds the extra field `id`
+    pub fn with_extra_field(mut self, id: u16, data: impl Into<Vec<u8>>) -> Self {
+        let data = data.into();
+        self.meta.extra_fields.push(ZipExtraField { id, data });
+        self
+    }
+
+    /// Returns the entry with only its name, data and kind, for comparing
+    /// entries or archiving them reproducibly
+    pub fn stripped(self) -> Self {
+        ArcEntry::new(self.name, self.data, self.meta.kind)
+    }
+
+    /// Returns whether the entry is a file
+    pub fn is_file(&self) -> bool {
+        self.meta.kind == EntryKind::File
+    }
+
+    /// Returns whether the entry is a directory
+    pub fn is_dir(&self) -> bool {
+        self.meta.kind == EntryKind::Directory
+    }
+
+    /// Detects the type of the file's data from its magic number, `None` for
+    /// other kinds of entries and unknown data
+    ///
+    /// The matcher type tells apart executables
+    /// ([`infer::MatcherType::App`]) and nested archives
+    /// ([`infer::MatcherType::Archive`]).
+    pub fn content_type(&self) -> Option<infer::Type> {
+        match self.is_file() {
+            true => ArcReader::infer(&self.data),
+            false => None,
+        }
+    }
+
+    /// Returns the MIME type of the file's data, like `application/zip`,
+    /// detected like [`ArcEntry::content_type`]
+    pub fn mime_type(&self) -> Option<&'static str> {
+        self.content_type().map(|kind| kind.mime_type())
+    }
+
+    /// Returns the `D` digest of the entry's data
+    pub fn digest<D: Digest>(&self) -> Vec<u8> {
+        D::digest(&self.data).to_vec()
+    }
+
+    /// Returns the SHA-256 digest of the entry's data
+    pub fn sha256(&self) -> Vec<u8> {
+        self.digest::<Sha256>()
+    }
+}
+
+impl TryFrom<ZipFile<'_>> for ArcEntry {
+    type Error = ArcError;
+
+    fn try_from(entry: ZipFile) -> ArcResult<Self> {
+        ArcEntry::from_zip(entry, ChecksumPolicy::Error, &mut Vec::new(), None)
+    }
 }
 
-impl From<ZipFile<'_>> for ArcEntry {
-    fn from(mut entry: ZipFile) -> Self {
-        if entry.is_dir() {
-            ArcEntry::Directory(entry.name().to_owned())
+impl ArcEntry {
+    /// Reads a zip entry, handling a CRC-32 mismatch with `checksums`
+    fn from_zip(
+        mut entry: ZipFile,
+        checksums: ChecksumPolicy,
+        mismatches: &mut Vec<String>,
+        progress: Option<&Tracker>,
+    ) -> ArcResult<Self> {
+        let meta = meta::zip_meta(&entry);
+        let mut data = Vec::new();
+        let kind = if entry.is_dir() {
+            EntryKind::Directory
         } else {
-            let mut data = Vec::with_capacity(entry.size() as usize);
-            entry.read_to_end(&mut data).unwrap();
-            ArcEntry::File(entry.name().to_owned(), data)
+            let size = entry.size();
+            // link targets aren't counted as data
+            let tracker = progress.filter(|_| !entry.is_symlink());
+            let read = |data: &mut _| checksum::read_zip(&mut entry, data, tracker);
+            if !read_sized(&mut data, size, read)? {
+                checksums.mismatch(entry.name(), mismatches)?;
+            }
+            // zip stores the target as the link's data
+            match entry.is_symlink() {
+                true => EntryKind::Symlink(String::from_utf8_lossy(&data).into_owned()),
+                false => EntryKind::File,
+            }
+        };
+        if kind != EntryKind::File {
+            data.clear();
         }
+        Ok(Self {
+            name: entry.name().to_owned(),
+            data,
+            meta: ArcMeta { kind, ..meta },
+        })
     }
 }
 
-impl From<TarEntry<'_, &[u8]>> for ArcEntry {
-    fn from(mut entry: TarEntry<'_, &[u8]>) -> Self {
-        let name = entry.path().unwrap().to_str().unwrap().to_owned();
-        if entry.header().entry_type().is_dir() {
-            ArcEntry::Directory(name)
+impl<R: Read> TryFrom<TarEntry<'_, R>> for ArcEntry {
+    type Error = ArcError;
+
+    fn try_from(mut entry: TarEntry<'_, R>) -> ArcResult<Self> {
+        let (name, raw_name) = name::decode(entry.path_bytes().into_owned());
+        let (mut atime, mut ctime) = (None, None);
+        if let Some(extensions) = entry.pax_extensions()? {
+            for extension in extensions.flatten() {
+                match extension.key_bytes() {
+                    b"atime" => atime = meta::from_pax_time(extension.value_bytes()),
+                    b"ctime" => ctime = meta::from_pax_time(extension.value_bytes()),
+                    _ => {}
+                }
+            }
+        }
+        let header = entry.header();
+        let link = || {
+            let target = entry.link_name_bytes().unwrap_or_default();
+            String::from_utf8_lossy(&target).into_owned()
+        };
+        let kind = if header.entry_type().is_dir() {
+            EntryKind::Directory
+        } else if header.entry_type().is_symlink() {
+            EntryKind::Symlink(link())
+        } else if header.entry_type().is_hard_link() {
+            EntryKind::HardLink(link())
+        } else if let Some(special) = meta::tar_special(header) {
+            EntryKind::Special(special)
         } else {
-            let mut data = Vec::with_capacity(entry.size() as usize);
-            entry.read_to_end(&mut data).unwrap();
-            ArcEntry::File(name, data)
+            EntryKind::File
+        };
+        let meta = meta::tar_header_meta(header)?;
+        let meta = ArcMeta {
+            kind,
+            atime: atime.or(meta.atime),
+            ctime: ctime.or(meta.ctime),
+            raw_name,
+            ..meta
+        };
+        let mut data = Vec::new();
+        if meta.kind == EntryKind::File {
+            let size = entry.size();
+            read_sized(&mut data, size, |data| entry.read_to_end(data))?;
+        }
+        Ok(Self { name, data, meta })
+    }
+}
+
+/// Archive entry whose data may borrow from the archive it was read from
+///
+/// Returned by [`ArcReader::read_borrowed`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ArcEntryRef<'a> {
+    pub name: String,
+    pub data: Cow<'a, [u8]>,
+    pub meta: ArcMeta,
+}
+
+impl From<ArcEntryRef<'_>> for ArcEntry {
+    fn from(entry: ArcEntryRef) -> Self {
+        Self {
+            name: entry.name,
+            data: entry.data.into_owned(),
+            meta: entry.meta,
+        }
+    }
+}
+
+impl From<ArcEntry> for ArcEntryRef<'_> {
+    fn from(entry: ArcEntry) -> Self {
+        Self {
+            name: entry.name,
+            data: Cow::Owned(entry.data),
+            meta: entry.meta,
         }
     }
 }
 
+/// Entry read policies apply to, whether it owns its data or not
+pub(crate) trait NamedEntry {
+    fn name(&self) -> &String;
+    fn meta(&self) -> &ArcMeta;
+    fn parts_mut(&mut self) -> (&mut String, &mut ArcMeta);
+}
+
+impl NamedEntry for ArcEntry {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn meta(&self) -> &ArcMeta {
+        &self.meta
+    }
+
+    fn parts_mut(&mut self) -> (&mut String, &mut ArcMeta) {
+        (&mut self.name, &mut self.meta)
+    }
+}
+
+impl NamedEntry for ArcEntryRef<'_> {
+    fn name(&self) -> &String {
+        &self.name
+    }
+
+    fn meta(&self) -> &ArcMeta {
+        &self.meta
+    }
+
+    fn parts_mut(&mut self) -> (&mut String, &mut ArcMeta) {
+        (&mut self.name, &mut self.meta)
+    }
+}
+
 /// Main error type for this library
 #[derive(Error, Debug)]
 #[error(transparent)]
 pub enum ArcError {
     IoError(#[from] io::Error),
-    ZipError(#[from] zip::result::ZipError),
-    SevenzError(#[from] sevenz_rust::Error),
+    ZipError(zip::result::ZipError),
+    SevenzError(sevenz_rust::Error),
     #[error("Unrecognized archive format")]
     UnrecognizedFormat,
+    #[error("Format can only hold a single file entry")]
+    SingleFileOnly,
+    #[error("Format can only be read")]
+    ReadOnly,
+    #[error("Entry not found: {0}")]
+    EntryNotFound(String),
+    #[error("Image not found: {0}")]
+    ImageNotFound(u32),
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(String),
+    /// Entry name escaping the extraction directory, see [`PathPolicy`]
+    #[error("Unsafe entry path: {0}")]
+    UnsafePath(String),
+    /// Entry name that isn't valid UTF-8, decoded lossily, see
+    /// [`NonUtf8Policy`]
+    #[error("Entry name isn't valid UTF-8: {0}")]
+    NonUtf8Name(String),
+    /// Entry that symlinks earlier in the archive would send outside the
+    /// extraction directory, see [`SymlinkPolicy`]
+    #[error("Entry escapes through a symlink: {0}")]
+    SymlinkEscape(String),
+    /// Archive so malformed that a decoder this crate relies on gave up
+    /// instead of returning an error, with its message
+    #[error("Malformed archive: {0}")]
+    Malformed(String),
+    /// Archive holding more entries than the limit it was read with
+    #[error("Archive has more than {0} entries")]
+    TooManyEntries(usize),
+    /// Zip entry whose data doesn't match its CRC-32, see
+    /// [`ChecksumPolicy`]
+    #[error("Checksum mismatch: {0}")]
+    ChecksumMismatch(String),
+    /// Password given to [`ArcReader::with_password`] that doesn't decrypt
+    /// the archive
+    #[error("Wrong password")]
+    WrongPassword,
+    /// Encrypted archive read without a password
+    #[error("Password required to decrypt the archive")]
+    PasswordRequired,
+    /// Glob or regular expression given to [`Pattern`] that isn't valid
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+    /// Entry with the same name as an earlier one, see [`DuplicatePolicy`]
+    /// and [`ArcWriter::reject_duplicates`]
+    #[error("Duplicate entry: {0}")]
+    DuplicateEntry(String),
+    /// Extraction stopped at this entry by [`ExtractOptions::veto`]
+    #[error("Extraction aborted at entry: {0}")]
+    Aborted(String),
+    #[cfg(feature = "rar")]
+    RarError(#[from] unrar::error::UnrarError),
+    #[cfg(feature = "object_store")]
+    ObjectStoreError(#[from] object_store::Error),
+}
+
+impl From<ZipError> for ArcError {
+    fn from(err: ZipError) -> Self {
+        match err {
+            ZipError::InvalidPassword => ArcError::WrongPassword,
+            ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => ArcError::PasswordRequired,
+            err => ArcError::ZipError(err),
+        }
+    }
+}
+
+impl From<sevenz_rust::Error> for ArcError {
+    fn from(err: sevenz_rust::Error) -> Self {
+        match err {
+            sevenz_rust::Error::MaybeBadPassword(_) => ArcError::WrongPassword,
+            sevenz_rust::Error::PasswordRequired => ArcError::PasswordRequired,
+            err => ArcError::SevenzError(err),
+        }
+    }
 }
 
 pub type ArcResult<T> = Result<T, ArcError>;
 
 /// This struct allows you to easily read an archive
+///
+/// Entry names that could escape the extraction directory are rejected, see
+/// [`ArcReader::with_path_policy`] to sanitize or allow them instead.
 pub struct ArcReader {
     format: ArcFormat,
     entries: Vec<ArcEntry>,
+    checksum_mismatches: Vec<String>,
     i: usize,
 }
 
-impl ArcReader {
-    /// Takes the archive to read as a slice of bytes and reads it
-    pub fn new(buf: &[u8]) -> ArcResult<Self> {
-        let format = get(buf).unwrap().try_into()?;
-        Ok(Self {
-            format,
-            entries: match format {
-                ArcFormat::Zip => ArcReader::read_zip(buf),
-                ArcFormat::Tar => ArcReader::read_tar(buf),
-                ArcFormat::Sevenz => ArcReader::read_7z(buf),
-            }?,
-            i: 0,
-        })
-    }
+/// How [`ArcReader`] reads archives, built from [`ReadOptions::new`] with its
+/// setters and given to [`ArcReader::with_options`], [`ArcReader::open`],
+/// [`ArcReader::from_seekable`] or [`ArcReader::from_volumes`]
+///
+/// `ReadOptions::new().password(b"uwu").path_policy(PathPolicy::Sanitize)`
+/// reads an encrypted archive, sanitizing its unsafe entry names.
+#[derive(Clone, Copy, Default)]
+pub struct ReadOptions<'a> {
+    names: NonUtf8Policy,
+    paths: PathPolicy,
+    symlinks: SymlinkPolicy,
+    duplicates: DuplicatePolicy,
+    checksums: ChecksumPolicy,
+    password: Option<&'a [u8]>,
+    format: Option<ArcFormat>,
+    zip_names: ZipNameEncoding,
+    max_entries: Option<usize>,
+    progress: Option<&'a dyn Fn(&Progress)>,
+}
 
-    /// Returns the format of the archive
-    pub fn format(&self) -> ArcFormat {
-        self.format
+impl<'a> ReadOptions<'a> {
+    /// Returns the options [`ArcReader::new`] reads with
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Returns a reference to all archive entries
-    pub fn entries(&self) -> &Vec<ArcEntry> {
-        &self.entries
+    /// Handles unsafe entry names with `policy`
+    pub fn path_policy(mut self, policy: PathPolicy) -> Self {
+        self.paths = policy;
+        self
     }
 
-    fn read_zip(buf: &[u8]) -> ArcResult<Vec<ArcEntry>> {
-        let mut archive = ZipArchive::new(Cursor::new(buf)).unwrap();
-        let len = archive.len();
-        let mut entries = Vec::with_capacity(len);
-        for i in 0..len {
-            entries.push(archive.by_index(i)?.into());
-        }
-        Ok(entries)
+    /// Handles entries whose name isn't valid UTF-8 with `policy`
+    ///
+    /// Only tarballs and ar archives store names that can be invalid, zip
+    /// names are decoded as [`ReadOptions::zip_name_encoding`] says.
+    pub fn non_utf8_policy(mut self, policy: NonUtf8Policy) -> Self {
+        self.names = policy;
+        self
     }
 
-    fn read_tar(buf: &[u8]) -> ArcResult<Vec<ArcEntry>> {
-        Ok(TarArchive::new(buf)
-            .entries()?
-            .map(|entry| entry.unwrap().into())
-            .collect())
+    /// Handles entries that earlier symlinks send outside the extraction
+    /// directory with `policy`
+    ///
+    /// Like `../uwu` behind a symlink `owo` to `..`, or `owo/passwd` behind a
+    /// symlink `owo` to `/etc`. Symlinks are followed as they'd be on
+    /// extraction, entries only going through those before them.
+    pub fn symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
     }
 
-    fn read_7z(buf: &[u8]) -> ArcResult<Vec<ArcEntry>> {
-        let mut entries = Vec::new();
-        SevenZReader::new(Cursor::new(buf), buf.len() as u64, Password::empty())?
-            .for_each_entries(|entry, reader| {
-                if entry.is_directory {
-                    entries.push(ArcEntry::Directory(entry.name.clone()));
-                } else {
-                    let mut data = Vec::with_capacity(entry.size as usize);
-                    reader.read_to_end(&mut data).unwrap();
-                    entries.push(ArcEntry::File(entry.name.clone(), data));
-                }
-                Ok(true)
-            })
-            .unwrap();
-        Ok(entries)
+    /// Handles entries with the same name as an earlier one with `policy`
+    ///
+    /// Names are compared after unsafe ones are handled.
+    pub fn duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicates = policy;
+        self
     }
-}
 
-impl Iterator for ArcReader {
-    type Item = ArcEntry;
+    /// Handles zip entries whose data doesn't match their CRC-32 with
+    /// `policy`
+    pub fn checksum_policy(mut self, policy: ChecksumPolicy) -> Self {
+        self.checksums = policy;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.i == self.entries.len() {
-            None
-        } else {
-            self.i += 1;
-            Some(self.entries[self.i - 1].clone())
-        }
+    /// Decrypts encrypted zip entries, ZipCrypto or AES, and encrypted 7z
+    /// archives with `password`
+    ///
+    /// 7z passwords are text, so `password` is read as UTF-8 for them.
+    /// Reading fails with [`ArcError::WrongPassword`] if `password` doesn't
+    /// fit an entry, so it can be asked for again, and with
+    /// [`ArcError::PasswordRequired`] when reading encrypted entries without
+    /// one. ZipCrypto only lets a wrong password be told apart 255 times out
+    /// of 256, the rest end up as [`ArcError::ChecksumMismatch`].
+    ///
+    /// `password` is only borrowed, so a [`Secret`] can be passed to keep it
+    /// wiped once dropped.
+    pub fn password(mut self, password: &'a (impl AsRef<[u8]> + ?Sized)) -> Self {
+        self.password = Some(password.as_ref());
+        self
     }
-}
 
-/// Struct for creating archives
-pub struct ArcWriter {
-    pub format: ArcFormat,
-    entries: Vec<ArcEntry>,
-}
+    /// Reads archives as `format` archives, instead of detecting their format
+    ///
+    /// This reads archives detection gets wrong, like tarballs from before
+    /// the `ustar` magic number or archives too small to be recognized.
+    /// Compressed formats are read as tarballs or single files as `format`
+    /// says rather than by looking at their contents.
+    pub fn format(mut self, format: ArcFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
 
-impl ArcWriter {
-    /// Returns a new writer for the specified archive format
-    pub fn new(format: ArcFormat) -> Self {
-        Self {
-            format,
-            entries: Vec::new(),
-        }
+    /// Decodes names of zip entries without the UTF-8 flag with `encoding`
+    ///
+    /// Names of other formats are read as they are.
+    pub fn zip_name_encoding(mut self, encoding: ZipNameEncoding) -> Self {
+        self.zip_names = encoding;
+        self
     }
 
-    /// Adds an entry to the writer
-    pub fn push(&mut self, entry: ArcEntry) {
-        self.entries.push(entry)
+    /// Fails reading with [`ArcError::TooManyEntries`] if archives have more
+    /// than `max` entries
+    ///
+    /// Tarballs, zip-based and 7z archives in memory are checked from their
+    /// headers before any file is decoded, other formats and sources once
+    /// read. Room reserved for the data of an entry from the size its
+    /// headers give is capped and freed if the data turns out shorter, so
+    /// along with this limit, forged headers can't make small archives hold
+    /// large allocations.
+    pub fn max_entries(mut self, max: usize) -> Self {
+        self.max_entries = Some(max);
+        self
     }
 
-    /// Adds all entries from slice to the writer
-    pub fn extend(&mut self, entries: &[ArcEntry]) {
-        self.entries.extend_from_slice(entries)
+    /// Calls `progress` as entries are decoded
+    ///
+    /// Zip, 7z and tar archives, compressed or not, report the data of their
+    /// files as it's decoded and each entry once it's done. Other formats,
+    /// and tarballs read as a [`ReadOptions::format`] given, report their
+    /// entries once they're read whole. Totals aren't known up front.
+    pub fn progress(mut self, progress: &'a dyn Fn(&Progress)) -> Self {
+        self.progress = Some(progress);
+        self
     }
 
-    /// Creates the finished archive
-    /// Panics on Windows if target format is `Tar`
-    pub fn archive(&self) -> ArcResult<Vec<u8>> {
-        match self.format {
-            ArcFormat::Zip => self.archive_zip(),
-            ArcFormat::Tar => self.archive_tar(),
-            ArcFormat::Sevenz => self.archive_7z(),
+    /// Returns how to decode archives, reporting progress to `tracker`
+    fn decoding<'t>(&self, tracker: Option<&'t Tracker<'t>>) -> Decoding<'t>
+    where
+        'a: 't,
+    {
+        Decoding {
+            checksums: self.checksums,
+            password: self.password,
+            format: self.format,
+            zip_names: self.zip_names,
+            progress: tracker,
         }
     }
 
-    fn archive_zip(&self) -> ArcResult<Vec<u8>> {
-        let mut inner = Vec::new();
-        {
-            let mut writer = ZipWriter::new(Cursor::new(&mut inner));
-            for entry in &self.entries {
-                match entry {
-                    ArcEntry::Directory(name) => {
-                        writer.add_directory(name, SimpleFileOptions::default())?
-                    }
-                    ArcEntry::File(name, data) => {
-                        writer.start_file(name.as_str(), SimpleFileOptions::default())?;
-                        writer.write_all(data)?;
-                    }
-                }
-            }
-            writer.finish()?;
+    /// Returns the tracker reporting to the progress callback, if any
+    fn tracker(&self) -> Option<Tracker<'a>> {
+        self.progress
+            .map(|progress| Tracker::new(progress, None, None))
+    }
+
+    /// Applies the policies and entry limit to the entries read
+    fn apply<E: NamedEntry>(&self, entries: Vec<E>) -> ArcResult<Vec<E>> {
+        let entries = self.names.apply(entries)?;
+        let entries = self.paths.apply(entries)?;
+        let entries = self.symlinks.apply(entries)?;
+        let entries = self.duplicates.apply(entries)?;
+        match self.max_entries {
+            Some(max) if entries.len() > max => Err(ArcError::TooManyEntries(max)),
+            _ => Ok(entries),
         }
-        Ok(inner)
     }
 
-    #[cfg(not(target_os = "windows"))]
-    fn archive_tar(&self) -> ArcResult<Vec<u8>> {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        use tar::{Builder as TarBuilder, Header};
-        use uzers::{
-            get_current_gid, get_current_groupname, get_current_uid, get_current_username,
-        };
+    /// Returns whether archives are decoded as by [`ArcReader::new`], but for
+    /// the password and format, so shortcuts reading part of them can be
+    /// taken
+    fn plain_decoding(&self) -> bool {
+        self.checksums == ChecksumPolicy::default()
+            && matches!(self.zip_names, ZipNameEncoding::Cp437)
+            && self.progress.is_none()
+    }
 
-        let mut inner = Vec::new();
-        {
-            let mut builder = TarBuilder::new(&mut inner);
-            for entry in &self.entries {
-                let mut header = Header::new_gnu();
-                header.set_mode(0o766);
-                header.set_mtime(
-                    SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                );
-                header.set_uid(get_current_uid() as u64);
-                header
-                    .set_username(get_current_username().unwrap().to_str().unwrap())
-                    .unwrap();
-                header.set_gid(get_current_gid() as u64);
-                header
-                    .set_groupname(get_current_groupname().unwrap().to_str().unwrap())
-                    .unwrap();
-                match entry {
-                    ArcEntry::Directory(name) => {
-                        header.set_entry_type(tar::EntryType::Directory);
-                        builder.append_data(&mut header, name, &[][..])?;
-                    }
-                    ArcEntry::File(name, data) => {
-                        header.set_entry_type(tar::EntryType::Regular);
-                        header.set_size(data.len() as u64);
-                        builder.append_data(&mut header, name, &data[..])?;
-                    }
+    /// Returns whether the policies and entry limit are the defaults, which
+    /// looking up a single entry leaves out
+    fn plain_policies(&self) -> bool {
+        self.names == NonUtf8Policy::default()
+            && self.paths == PathPolicy::default()
+            && self.symlinks == SymlinkPolicy::default()
+            && self.duplicates == DuplicatePolicy::default()
+            && self.max_entries.is_none()
+    }
+}
+
+impl std::fmt::Debug for ReadOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadOptions")
+            .field("names", &self.names)
+            .field("paths", &self.paths)
+            .field("symlinks", &self.symlinks)
+            .field("duplicates", &self.duplicates)
+            .field("checksums", &self.checksums)
+            .field("password", &self.password.map(|_| ".."))
+            .field("format", &self.format)
+            .field("zip_names", &self.zip_names)
+            .field("max_entries", &self.max_entries)
+            .field("progress", &self.progress.map(|_| ".."))
+            .finish()
+    }
+}
+
+/// How archives are decoded, from the [`ReadOptions`] they're read with
+#[derive(Clone, Copy, Default)]
+struct Decoding<'a> {
+    checksums: ChecksumPolicy,
+    /// Password of encrypted zip entries and 7z archives
+    password: Option<&'a [u8]>,
+    /// Format to read archives as instead of detecting it
+    format: Option<ArcFormat>,
+    zip_names: ZipNameEncoding,
+    /// Reports each entry as it's decoded
+    progress: Option<&'a Tracker<'a>>,
+}
+
+impl ArcReader {
+    /// Takes the archive to read as a slice of bytes and reads it
+    pub fn new(buf: &[u8]) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::default())
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], with `options`
+    pub fn with_options(buf: &[u8], options: &ReadOptions) -> ArcResult<Self> {
+        if let Some(max) = options.max_entries {
+            meta::check_count(buf, max)?;
+        }
+        let tracker = options.tracker();
+        let decoding = options.decoding(tracker.as_ref());
+        guard(|| ArcReader::read_unchecked(buf, decoding))?.checked(options)
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], handling unsafe
+    /// entry names with `policy`, see [`ReadOptions::path_policy`]
+    pub fn with_path_policy(buf: &[u8], policy: PathPolicy) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().path_policy(policy))
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], handling entries
+    /// whose name isn't valid UTF-8 with `policy`, see
+    /// [`ReadOptions::non_utf8_policy`]
+    pub fn with_non_utf8_policy(buf: &[u8], policy: NonUtf8Policy) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().non_utf8_policy(policy))
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], handling entries
+    /// that earlier symlinks send outside the extraction directory with
+    /// `policy`, see [`ReadOptions::symlink_policy`]
+    pub fn with_symlink_policy(buf: &[u8], policy: SymlinkPolicy) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().symlink_policy(policy))
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], handling entries
+    /// with the same name as an earlier one with `policy`, see
+    /// [`ReadOptions::duplicate_policy`]
+    pub fn with_duplicate_policy(buf: &[u8], policy: DuplicatePolicy) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().duplicate_policy(policy))
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], handling zip
+    /// entries whose data doesn't match their CRC-32 with `policy`, see
+    /// [`ReadOptions::checksum_policy`]
+    pub fn with_checksum_policy(buf: &[u8], policy: ChecksumPolicy) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().checksum_policy(policy))
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], decrypting it
+    /// with `password`, see [`ReadOptions::password`]
+    pub fn with_password(buf: &[u8], password: impl AsRef<[u8]>) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().password(&password))
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`] as a `format`
+    /// archive, instead of detecting its format, see [`ReadOptions::format`]
+    pub fn with_format(buf: &[u8], format: ArcFormat) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().format(format))
+    }
+
+    /// Applies the policies and entry limit of `options` to the entries read
+    fn checked(mut self, options: &ReadOptions) -> ArcResult<Self> {
+        self.entries = options.apply(self.entries)?;
+        Ok(self)
+    }
+
+    /// Reads as many entries as possible from the damaged or truncated
+    /// archive in `buf`, returning them with the error that stopped reading
+    /// at the first one that couldn't be, if any
+    ///
+    /// Zip-based archives missing their central directory are read from the
+    /// headers before each entry, without metadata, and tarballs, compressed
+    /// or not, up to where they break off. 7z archives keep their headers at
+    /// the end, so only damage to later blocks leaves entries to salvage.
+    /// Other formats are read whole or not at all. Unsafe names fail reading
+    /// as with [`ArcReader::new`], leaving no entries.
+    pub fn recover(buf: &[u8]) -> (Self, Option<ArcError>) {
+        ArcReader::recover_with(buf, &ReadOptions::default())
+    }
+
+    /// Reads as many entries as possible from the archive in `buf` like
+    /// [`ArcReader::recover`], with `options`
+    ///
+    /// Entries the policies of `options` reject fail reading, leaving none.
+    pub fn recover_with(buf: &[u8], options: &ReadOptions) -> (Self, Option<ArcError>) {
+        let tracker = options.tracker();
+        let (format, entries, err) = recover::recover(buf, options.decoding(tracker.as_ref()));
+        match ArcReader::from_entries(format, entries, options) {
+            Ok(reader) => (reader, err),
+            Err(err) => (
+                Self {
+                    format,
+                    entries: Vec::new(),
+                    checksum_mismatches: Vec::new(),
+                    i: 0,
+                },
+                Some(err),
+            ),
+        }
+    }
+
+    /// Checks the archive in `buf` for problems without keeping anything
+    /// read from it
+    ///
+    /// Reports archives that can't be read, zip entries failing their
+    /// CRC-32, unsafe entry names and hard link targets, entries escaping
+    /// through symlinks, and duplicate names, rather than failing on the
+    /// first of them.
+    pub fn validate(buf: &[u8]) -> ValidationReport {
+        validate::validate(buf)
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], failing with
+    /// [`ArcError::TooManyEntries`] if it has more than `max` entries, see
+    /// [`ReadOptions::max_entries`]
+    pub fn with_max_entries(buf: &[u8], max: usize) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().max_entries(max))
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], calling `progress`
+    /// as entries are decoded, see [`ReadOptions::progress`]
+    pub fn with_progress(buf: &[u8], progress: impl Fn(&Progress)) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().progress(&progress))
+    }
+
+    /// Reads the archive in `buf` with `options`, without checking entry
+    /// names
+    fn read_unchecked(buf: &[u8], options: Decoding) -> ArcResult<Self> {
+        let hint = options.format;
+        // detected tarballs are read as they're decompressed, so progress is
+        // reported as their data comes out
+        if hint.is_none() {
+            if let TarStream::Tar(format, decoder) = ArcReader::tar_stream(buf)? {
+                let entries = ArcReader::read_tar(decoder, options.progress)?;
+                return Ok(Self {
+                    format,
+                    entries,
+                    checksum_mismatches: Vec::new(),
+                    i: 0,
+                });
+            }
+        }
+        let mut format = hint.unwrap_or_else(|| ArcReader::detect(buf));
+        // decompressed data is read as a tarball if the format given says so,
+        // or if it looks like one
+        let tarball = |data: &[u8], tar_format: ArcFormat| match hint {
+            Some(hint) => hint == tar_format,
+            None => is_tar(data),
+        };
+        let mut mismatches = Vec::new();
+        let entries = match format {
+            ArcFormat::Epub => ArcReader::read_zip(Cursor::new(buf), options, &mut mismatches)?,
+            ArcFormat::Crx => {
+                let zip = Cursor::new(crx::split(buf)?.1);
+                ArcReader::read_zip(zip, options, &mut mismatches)?
+            }
+            ArcFormat::Zip | ArcFormat::Jar => {
+                let entries = ArcReader::read_zip(Cursor::new(buf), options, &mut mismatches)?;
+                if hint.is_none() {
+                    format = ArcReader::zip_format(&entries);
+                }
+                entries
+            }
+            ArcFormat::Tar => ArcReader::read_tar(buf, options.progress)?,
+            ArcFormat::Sevenz => {
+                let password = ArcReader::sevenz_password(options.password);
+                ArcReader::read_7z(
+                    Cursor::new(buf),
+                    buf.len() as u64,
+                    password,
+                    options.progress,
+                )?
+            }
+            ArcFormat::Gzip | ArcFormat::TarGz => {
+                let (name, data) = ArcReader::gunzip(buf)?;
+                if tarball(&data, ArcFormat::TarGz) {
+                    format = ArcFormat::TarGz;
+                    ArcReader::read_tar(&data[..], options.progress)?
+                } else if hint.is_none() && data.starts_with(warc::MAGIC) {
+                    format = ArcFormat::Warc;
+                    warc::read(&data)?
+                } else {
+                    format = ArcFormat::Gzip;
+                    vec![ArcEntry::file(name, data)]
+                }
+            }
+            ArcFormat::TarXz => {
+                ArcReader::read_compressed_tar(XzDecoder::new(buf), options.progress)?
+            }
+            ArcFormat::TarBz2 => {
+                ArcReader::read_compressed_tar(BzDecoder::new(buf), options.progress)?
+            }
+            ArcFormat::Zstd | ArcFormat::TarZst => {
+                let mut data = Vec::new();
+                ZstdDecoder::new(buf)?.read_to_end(&mut data)?;
+                if tarball(&data, ArcFormat::TarZst) {
+                    format = ArcFormat::TarZst;
+                    ArcReader::read_tar(&data[..], options.progress)?
+                } else {
+                    format = ArcFormat::Zstd;
+                    vec![ArcEntry::file(String::new(), data)]
+                }
+            }
+            ArcFormat::Lz4 | ArcFormat::TarLz4 => {
+                let mut data = Vec::new();
+                Lz4Decoder::new(buf).read_to_end(&mut data)?;
+                if tarball(&data, ArcFormat::TarLz4) {
+                    format = ArcFormat::TarLz4;
+                    ArcReader::read_tar(&data[..], options.progress)?
+                } else {
+                    format = ArcFormat::Lz4;
+                    vec![ArcEntry::file(String::new(), data)]
+                }
+            }
+            ArcFormat::Snappy | ArcFormat::TarSz => {
+                let mut data = Vec::new();
+                SnappyDecoder::new(buf).read_to_end(&mut data)?;
+                if tarball(&data, ArcFormat::TarSz) {
+                    format = ArcFormat::TarSz;
+                    ArcReader::read_tar(&data[..], options.progress)?
+                } else {
+                    format = ArcFormat::Snappy;
+                    vec![ArcEntry::file(String::new(), data)]
+                }
+            }
+            ArcFormat::Brotli | ArcFormat::TarBr => {
+                let data = ArcReader::unbrotli(buf).ok_or(ArcError::UnrecognizedFormat)?;
+                if tarball(&data, ArcFormat::TarBr) {
+                    format = ArcFormat::TarBr;
+                    ArcReader::read_tar(&data[..], options.progress)?
+                } else {
+                    format = ArcFormat::Brotli;
+                    vec![ArcEntry::file(String::new(), data)]
+                }
+            }
+            ArcFormat::Cpio => ArcReader::read_cpio(buf)?,
+            ArcFormat::Ar | ArcFormat::Deb => ArcReader::read_ar(buf)?,
+            ArcFormat::Rpm => ArcReader::read_rpm(buf)?,
+            ArcFormat::Cab => ArcReader::read_cab(Cursor::new(buf))?,
+            ArcFormat::Wim => match wim::image_count(buf)? {
+                0 => Vec::new(),
+                _ => wim::read(buf, 1)?,
+            },
+            ArcFormat::Xar => xar::read(buf)?,
+            ArcFormat::Warc => match is_gz(buf) {
+                true => warc::read(&ArcReader::gunzip(buf)?.1)?,
+                false => warc::read(buf)?,
+            },
+            #[cfg(feature = "rar")]
+            ArcFormat::Rar => ArcReader::read_rar(buf)?,
+        };
+        if let Some(tracker) = options.progress {
+            tracker.catch_up(&entries);
+        }
+        Ok(Self {
+            format,
+            entries,
+            checksum_mismatches: mismatches,
+            i: 0,
+        })
+    }
+
+    /// Returns a reader of `entries`, checking them against `options`
+    fn from_entries(
+        format: ArcFormat,
+        entries: Vec<ArcEntry>,
+        options: &ReadOptions,
+    ) -> ArcResult<Self> {
+        let reader = Self {
+            format,
+            entries,
+            checksum_mismatches: Vec::new(),
+            i: 0,
+        };
+        reader.checked(options)
+    }
+
+    /// Reads an archive from `reader`
+    ///
+    /// Tarballs, compressed or not, are decoded as they're read. Other
+    /// formats need to be seen whole, so they're read into memory first.
+    pub fn from_reader(reader: impl Read) -> ArcResult<Self> {
+        ArcReader::from_reader_with(reader, &ReadOptions::default())
+    }
+
+    /// Reads an archive from `reader` like [`ArcReader::from_reader`], with
+    /// `options`
+    ///
+    /// Only tarballs whose format is detected are decoded as they're read.
+    pub fn from_reader_with(reader: impl Read, options: &ReadOptions) -> ArcResult<Self> {
+        let (mut head, mut reader) = match options.format {
+            Some(_) => (Vec::new(), reader),
+            None => match ArcReader::tar_stream(reader)? {
+                TarStream::Tar(format, decoder) => {
+                    let tracker = options.tracker();
+                    return guard(|| {
+                        let entries = ArcReader::read_tar(decoder, tracker.as_ref())?;
+                        ArcReader::from_entries(format, entries, options)
+                    });
+                }
+                TarStream::Other(head, reader) => (head, reader),
+            },
+        };
+        reader.read_to_end(&mut head)?;
+        ArcReader::with_options(&head, options)
+    }
+
+    /// Reads the archive filling a seekable `source`, like a file or a
+    /// cursor, with `options`
+    ///
+    /// Zip-based, 7z and cabinet archives are read in place, seeking to each
+    /// file, and tarballs are decoded as they're read. Other formats are read
+    /// into memory first.
+    pub fn from_seekable(mut source: impl Read + Seek, options: &ReadOptions) -> ArcResult<Self> {
+        let tracker = options.tracker();
+        let decoding = options.decoding(tracker.as_ref());
+        guard(move || {
+            // enough for every signature detection looks at
+            const HEAD_SIZE: u64 = 8 * 1024;
+
+            source.rewind()?;
+            let mut head = Vec::new();
+            source.by_ref().take(HEAD_SIZE).read_to_end(&mut head)?;
+            source.rewind()?;
+            let mut format = options.format.unwrap_or_else(|| ArcReader::detect(&head));
+            let mut mismatches = Vec::new();
+            let entries = match format {
+                ArcFormat::Zip | ArcFormat::Jar => {
+                    let entries = ArcReader::read_zip(source, decoding, &mut mismatches)?;
+                    if options.format.is_none() {
+                        format = ArcReader::zip_format(&entries);
+                    }
+                    entries
+                }
+                ArcFormat::Epub => ArcReader::read_zip(source, decoding, &mut mismatches)?,
+                ArcFormat::Sevenz => {
+                    let len = source.seek(SeekFrom::End(0))?;
+                    source.rewind()?;
+                    let password = ArcReader::sevenz_password(options.password);
+                    ArcReader::read_7z(source, len, password, decoding.progress)?
+                }
+                ArcFormat::Cab => ArcReader::read_cab(source)?,
+                _ => return ArcReader::from_reader_with(source, options),
+            };
+            if let Some(tracker) = decoding.progress {
+                tracker.catch_up(&entries);
+            }
+            let reader = Self {
+                format,
+                entries,
+                checksum_mismatches: mismatches,
+                i: 0,
+            };
+            reader.checked(options)
+        })
+    }
+
+    /// Reads an archive from standard input like [`ArcReader::from_reader`],
+    /// so tarballs piped in are decoded as they arrive
+    pub fn from_stdin() -> ArcResult<Self> {
+        ArcReader::from_stdin_with(&ReadOptions::default())
+    }
+
+    /// Reads an archive from standard input like [`ArcReader::from_stdin`],
+    /// with `options`, see [`ArcReader::from_reader_with`]
+    pub fn from_stdin_with(options: &ReadOptions) -> ArcResult<Self> {
+        ArcReader::from_reader_with(io::stdin().lock(), options)
+    }
+
+    /// Detects whether `reader` holds a tarball, compressed or not
+    pub(crate) fn tar_stream<'a, R: Read + 'a>(mut reader: R) -> io::Result<TarStream<'a, R>> {
+        // enough to detect the format and decompress the first tar header
+        const HEAD_SIZE: u64 = 64 * 1024;
+
+        let mut head = Vec::new();
+        reader.by_ref().take(HEAD_SIZE).read_to_end(&mut head)?;
+        let format = ArcReader::detect(&head);
+        let tar_format = match ArcReader::tar_decoder(format, &head[..])? {
+            Some((tar_format, decoder)) => {
+                let mut start = Vec::new();
+                // the head is likely cut off mid-stream, so errors are expected
+                let _ = decoder.take(512).read_to_end(&mut start);
+                is_tar(&start).then_some(tar_format)
+            }
+            None => None,
+        };
+        Ok(match tar_format {
+            Some(tar_format) => {
+                let stream = Cursor::new(head).chain(reader);
+                // the same format gave a decoder for the head
+                let (_, decoder) = ArcReader::tar_decoder(format, stream)?
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidData))?;
+                TarStream::Tar(tar_format, decoder)
+            }
+            None => TarStream::Other(head, reader),
+        })
+    }
+
+    /// Returns a decoder for the compression `format` uses, along with the
+    /// format of a tarball compressed that way, or `None` if it can't hold a
+    /// tarball
+    fn tar_decoder<'a>(
+        format: ArcFormat,
+        reader: impl Read + 'a,
+    ) -> io::Result<Option<(ArcFormat, Box<dyn Read + 'a>)>> {
+        Ok(Some(match format {
+            ArcFormat::Tar => (ArcFormat::Tar, Box::new(reader)),
+            ArcFormat::Gzip | ArcFormat::TarGz => {
+                (ArcFormat::TarGz, Box::new(MultiGzDecoder::new(reader)))
+            }
+            ArcFormat::TarXz => (ArcFormat::TarXz, Box::new(XzDecoder::new(reader))),
+            ArcFormat::TarBz2 => (ArcFormat::TarBz2, Box::new(BzDecoder::new(reader))),
+            ArcFormat::Zstd | ArcFormat::TarZst => {
+                (ArcFormat::TarZst, Box::new(ZstdDecoder::new(reader)?))
+            }
+            ArcFormat::Lz4 | ArcFormat::TarLz4 => {
+                (ArcFormat::TarLz4, Box::new(Lz4Decoder::new(reader)))
+            }
+            ArcFormat::Snappy | ArcFormat::TarSz => {
+                (ArcFormat::TarSz, Box::new(SnappyDecoder::new(reader)))
+            }
+            ArcFormat::Brotli | ArcFormat::TarBr => {
+                (ArcFormat::TarBr, Box::new(BrotliDecoder::new(reader, 4096)))
+            }
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Reads the archive at `path` with `options`, memory-mapping it instead
+    /// of reading the whole file into memory first
+    ///
+    /// The file must not be modified while it's being read.
+    pub fn open(path: impl AsRef<Path>, options: &ReadOptions) -> ArcResult<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the map is only read from, and only until this returns
+        let map = unsafe { Mmap::map(&file)? };
+        ArcReader::with_options(&map, options)
+    }
+
+    /// Reads the archive at `path` through a buffer of `buffer_size` bytes
+    /// instead of memory-mapping it, see [`ArcReader::from_seekable`]
+    pub fn open_buffered(
+        path: impl AsRef<Path>,
+        buffer_size: usize,
+        options: &ReadOptions,
+    ) -> ArcResult<Self> {
+        let file = BufReader::with_capacity(buffer_size, File::open(path)?);
+        ArcReader::from_seekable(file, options)
+    }
+
+    /// Reads the archive at `path` like [`ArcReader::open`], keeping the data
+    /// of files longer than `threshold` bytes in temporary files instead of
+    /// memory
+    ///
+    /// Zip, 7z and tar-based archives are decoded one file at a time, so only
+    /// files up to `threshold` bytes are ever held in memory. Other formats
+    /// are decoded whole before their files are spilled.
+    pub fn open_spilled(path: impl AsRef<Path>, threshold: u64) -> ArcResult<Vec<SpilledEntry>> {
+        let file = File::open(path)?;
+        // SAFETY: the map is only read from, and only until this returns
+        let map = unsafe { Mmap::map(&file)? };
+        guard(|| spill::read(&map, threshold))
+    }
+
+    /// Reads an archive from `reader` like [`ArcReader::from_reader`],
+    /// keeping the data of files longer than `threshold` bytes in temporary
+    /// files instead of memory
+    ///
+    /// Only tarballs are read in bounded memory, other formats are read into
+    /// memory first.
+    pub fn from_reader_spilled(reader: impl Read, threshold: u64) -> ArcResult<Vec<SpilledEntry>> {
+        guard(|| spill::from_reader(reader, threshold))
+    }
+
+    /// Returns the metadata of the entries of the archive in `buf`, by name,
+    /// in archive order
+    ///
+    /// Only headers are read for zip-based, 7z, cpio and ar archives, the
+    /// data of tarballs is decompressed but skipped. Kinds aren't read and
+    /// are left as [`EntryKind::File`], and other formats give an empty list.
+    /// Zip and 7z archives only store modes of entries archived on Unix, and
+    /// zip times without an NTFS or extended timestamp field are taken as
+    /// UTC.
+    pub fn metas(buf: &[u8]) -> ArcResult<Vec<(String, ArcMeta)>> {
+        meta::read(buf)
+    }
+
+    /// Lists the entries of the archive in `buf` in order, with their sizes
+    /// and compression methods
+    ///
+    /// Only headers are read for zip-based and 7z archives, the data of
+    /// tarballs is decompressed but skipped. Other formats are read in full
+    /// and only give sizes.
+    pub fn list(buf: &[u8]) -> ArcResult<Vec<EntryInfo>> {
+        meta::list(buf)
+    }
+
+    /// Returns the `D` digests of the files of the archive in `buf`, by name
+    ///
+    /// Zip, 7z and tar-based archives are hashed as they're decoded, without
+    /// holding more than one chunk of a file, other formats are decoded whole
+    /// first.
+    pub fn digests<D: Digest>(buf: &[u8]) -> ArcResult<HashMap<String, Vec<u8>>> {
+        let mut digests = HashMap::new();
+        guard(|| {
+            visit::visit(buf, &mut |Visited { name, data, .. }| {
+                if let Some(data) = data {
+                    digests.insert(name, hash::digest::<D>(data)?);
+                }
+                Ok(())
+            })
+        })?;
+        Ok(digests)
+    }
+
+    /// Returns the SHA-256 digests of the files of the archive in `buf`, by
+    /// name, computed like [`ArcReader::digests`]
+    pub fn sha256s(buf: &[u8]) -> ArcResult<HashMap<String, Vec<u8>>> {
+        ArcReader::digests::<Sha256>(buf)
+    }
+
+    /// Returns the comment at the end of the zip-based archive in `buf`
+    ///
+    /// Other formats have no comment and give an empty one.
+    pub fn zip_comment(buf: &[u8]) -> ArcResult<Vec<u8>> {
+        Ok(ArcReader::zip_archive(buf)?
+            .map(|archive| archive.comment().to_vec())
+            .unwrap_or_default())
+    }
+
+    /// Reads the archive in `buf` like [`ArcReader::new`], decoding names of
+    /// zip entries without the UTF-8 flag with `encoding`, see
+    /// [`ReadOptions::zip_name_encoding`]
+    pub fn with_zip_name_encoding(buf: &[u8], encoding: ZipNameEncoding) -> ArcResult<Self> {
+        ArcReader::with_options(buf, &ReadOptions::new().zip_name_encoding(encoding))
+    }
+
+    /// Returns the names of the entries of the zip-based archive in `buf` as
+    /// raw bytes, in the same order as the entries read from it
+    ///
+    /// Names with an Info-ZIP Unicode Path extra field are given in UTF-8 as
+    /// stored in it. Other formats give an empty list.
+    pub fn zip_raw_names(buf: &[u8]) -> ArcResult<Vec<Vec<u8>>> {
+        let mut names = Vec::new();
+        if let Some(mut archive) = ArcReader::zip_archive(buf)? {
+            for i in 0..archive.len() {
+                names.push(archive.by_index_raw(i)?.name_raw().to_vec());
+            }
+        }
+        Ok(names)
+    }
+
+    /// Returns the zip archive in the zip-based archive `buf`, or `None` for
+    /// other formats
+    fn zip_buf(buf: &[u8]) -> ArcResult<Option<&[u8]>> {
+        match ArcReader::detect(buf) {
+            ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub => Ok(Some(buf)),
+            ArcFormat::Crx => Ok(Some(crx::split(buf)?.1)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Opens the zip-based archive in `buf`, or returns `None` for other
+    /// formats
+    fn zip_archive(buf: &[u8]) -> ArcResult<Option<ZipArchive<Cursor<&[u8]>>>> {
+        match ArcReader::zip_buf(buf)? {
+            Some(buf) => Ok(Some(ZipArchive::new(Cursor::new(buf))?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Calls `each` with the data of every file of the archive in `buf` for
+    /// which `filter` returns `true`, in chunks of `chunk_size` bytes
+    ///
+    /// The last chunk of each file may be shorter, and empty files get a
+    /// single empty chunk. Zip, 7z and tar-based archives are decoded one
+    /// chunk at a time, other formats are decoded whole first. Errors returned
+    /// by `each` stop the iteration and are returned.
+    /// Panics if `chunk_size` is `0`
+    pub fn for_each_chunk(
+        buf: &[u8],
+        chunk_size: usize,
+        mut filter: impl FnMut(&str) -> bool,
+        mut each: impl FnMut(&str, &[u8]) -> ArcResult<()>,
+    ) -> ArcResult<()> {
+        assert!(chunk_size > 0, "chunk size must not be 0");
+        let mut chunk = vec![0; chunk_size];
+        guard(|| {
+            visit::visit(buf, &mut |Visited { name, data, .. }| {
+                let Some(data) = data.filter(|_| filter(&name)) else {
+                    return Ok(());
+                };
+                let mut first = true;
+                loop {
+                    let mut len = 0;
+                    while len < chunk_size {
+                        match data.read(&mut chunk[len..]) {
+                            Ok(0) => break,
+                            Ok(read) => len += read,
+                            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                            Err(err) => return Err(err.into()),
+                        }
+                    }
+                    if len > 0 || first {
+                        each(&name, &chunk[..len])?;
+                    }
+                    if len < chunk_size {
+                        return Ok(());
+                    }
+                    first = false;
+                }
+            })
+        })
+    }
+
+    /// Reads an archive split into volumes, given in order, with `options`
+    ///
+    /// Spanned zip archives are given as the `.z01`, `.z02`, ... volumes
+    /// followed by the `.zip` one. Any other volumes, like the `.7z.001`,
+    /// `.7z.002`, ... parts of a split 7z archive, are joined back together
+    /// and read as a single archive.
+    pub fn from_volumes(volumes: &[&[u8]], options: &ReadOptions) -> ArcResult<Self> {
+        let first = volumes.first().ok_or(ArcError::UnrecognizedFormat)?;
+        if volumes.len() == 1 {
+            return ArcReader::with_options(first, options);
+        }
+        if !first.starts_with(split_zip::MAGIC) {
+            return ArcReader::with_options(&volumes.concat(), options);
+        }
+        let joined = split_zip::join(volumes)?;
+        if let Some(max) = options.max_entries {
+            meta::check_count(&joined, max)?;
+        }
+        let tracker = options.tracker();
+        let decoding = options.decoding(tracker.as_ref());
+        guard(|| {
+            let mut mismatches = Vec::new();
+            let entries = ArcReader::read_zip(Cursor::new(joined), decoding, &mut mismatches)?;
+            let reader = Self {
+                format: ArcFormat::Zip,
+                entries,
+                checksum_mismatches: mismatches,
+                i: 0,
+            };
+            reader.checked(options)
+        })
+    }
+
+    /// Returns the format of the archive
+    pub fn format(&self) -> ArcFormat {
+        self.format
+    }
+
+    /// Returns a reference to all archive entries
+    pub fn entries(&self) -> &Vec<ArcEntry> {
+        &self.entries
+    }
+
+    /// Writes all entries under the directory `dir`, creating it and the
+    /// parents of entries as needed, and returns what was written
+    ///
+    /// Fails with [`ArcError::UnsafePath`] or [`ArcError::SymlinkEscape`]
+    /// before writing anything if an entry would end up outside `dir`, even
+    /// if the reader's policies allowed it, and with
+    /// [`ArcError::SymlinkEscape`] when an entry is reached whose parents
+    /// under `dir` include a symlink already there leading outside of it.
+    /// Files and links already in the way are replaced.
+    pub fn extract_to(&self, dir: impl AsRef<Path>) -> ArcResult<ExtractSummary> {
+        self.extract_with(dir, &ExtractOptions::default())
+    }
+
+    /// Writes all entries under the directory `dir` like
+    /// [`ArcReader::extract_to`], as `options` say
+    pub fn extract_with(
+        &self,
+        dir: impl AsRef<Path>,
+        options: &ExtractOptions,
+    ) -> ArcResult<ExtractSummary> {
+        extract::extract(&self.entries, dir.as_ref(), options)
+    }
+
+    /// Returns what [`ArcReader::extract_with`] would do under `dir` with
+    /// `options`, without touching the filesystem, to confirm it first
+    ///
+    /// Fails like extraction does if an entry would end up outside `dir`,
+    /// even through symlinks already there, and reports what's in the way
+    /// as conflicts rather than failing. [`ExtractOptions::veto`] is asked
+    /// about each entry, like extraction does.
+    pub fn plan_extraction(
+        &self,
+        dir: impl AsRef<Path>,
+        options: &ExtractOptions,
+    ) -> ArcResult<ExtractPlan> {
+        plan::plan(&self.entries, dir.as_ref(), options)
+    }
+
+    /// Writes the files and directories of the archive in `buf` whose names
+    /// match any of `patterns` under the directory `dir`, like
+    /// [`ArcReader::extract_with`] with `options`
+    ///
+    /// Files are decoded one at a time and streamed to disk, and the ones
+    /// that don't match are skipped without decoding them where the format
+    /// allows, like [`ArcReader::for_each_chunk`]. Links and special files
+    /// aren't extracted, and progress callbacks aren't given totals. Unsafe
+    /// names fail with [`ArcError::UnsafePath`] when they're reached, leaving
+    /// the files before them written unless the extraction is atomic.
+    pub fn extract_matching(
+        buf: &[u8],
+        dir: impl AsRef<Path>,
+        patterns: &[Pattern],
+        options: &ExtractOptions,
+    ) -> ArcResult<ExtractSummary> {
+        guard(|| extract::extract_matching(buf, dir.as_ref(), patterns, options))
+    }
+
+    /// Returns the names of zip entries whose data didn't match their CRC-32,
+    /// only ever non-empty with [`ChecksumPolicy::Warn`]
+    pub fn checksum_mismatches(&self) -> &[String] {
+        &self.checksum_mismatches
+    }
+
+    /// Reads the 1-based `image` of a WIM file
+    ///
+    /// Only uncompressed and XPRESS-compressed images can be read. LZX, which
+    /// DISM and ImageX use by default, so most `install.wim` files, as well
+    /// as LZMS and solid resources fail with an
+    /// [`io::ErrorKind::Unsupported`] error.
+    pub fn wim_image(buf: &[u8], image: u32) -> ArcResult<Self> {
+        ArcReader::wim_image_with(buf, image, &ReadOptions::default())
+    }
+
+    /// Reads the 1-based `image` of a WIM file like [`ArcReader::wim_image`],
+    /// applying the policies and entry limit of `options` to its entries
+    pub fn wim_image_with(buf: &[u8], image: u32, options: &ReadOptions) -> ArcResult<Self> {
+        guard(|| {
+            let entries = wim::read(buf, image)?;
+            ArcReader::from_entries(ArcFormat::Wim, entries, options)
+        })
+    }
+
+    /// Returns the number of images in a WIM file
+    pub fn wim_image_count(buf: &[u8]) -> ArcResult<u32> {
+        guard(|| wim::image_count(buf))
+    }
+
+    /// Parses the signed header of a CRX3 extension
+    pub fn crx_header(buf: &[u8]) -> ArcResult<CrxHeader> {
+        guard(|| crx::header(buf))
+    }
+
+    /// Returns the headers of every record in a WARC file, plain or
+    /// gzip-compressed, in the same order as the entries read from it
+    pub fn warc_headers(buf: &[u8]) -> ArcResult<Vec<Vec<(String, String)>>> {
+        guard(|| {
+            let data;
+            let buf = match buf.starts_with(warc::MAGIC) {
+                true => buf,
+                false => {
+                    data = ArcReader::gunzip(buf)?.1;
+                    &data
+                }
+            };
+            let records = warc::records(buf)?;
+            Ok(records.into_iter().map(|(headers, _)| headers).collect())
+        })
+    }
+
+    /// Reads the data of the file `name` from the archive in `buf`
+    ///
+    /// Zip-based and 7z archives are looked up in their index and only the
+    /// file itself is decoded, along with the files before it in its 7z solid
+    /// block. Other formats are decoded whole.
+    pub fn read_entry(buf: &[u8], name: &str) -> ArcResult<Vec<u8>> {
+        ArcReader::read_entry_with(buf, name, &ReadOptions::default())
+    }
+
+    /// Reads the data of the file `name` from the archive in `buf` like
+    /// [`ArcReader::read_entry`], with `options`
+    ///
+    /// Files are only looked up in the index with the format and password of
+    /// `options`. Other options have the archive read whole like
+    /// [`ArcReader::with_options`] does, and the file looked up among the
+    /// entries it keeps.
+    pub fn read_entry_with(buf: &[u8], name: &str, options: &ReadOptions) -> ArcResult<Vec<u8>> {
+        let indexed = options.plain_decoding() && options.plain_policies();
+        let password = options.password;
+        guard(
+            || match options.format.unwrap_or_else(|| ArcReader::detect(buf)) {
+                ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub if indexed => {
+                    ArcReader::read_zip_entry(buf, name, password)
                 }
+                ArcFormat::Crx if indexed => {
+                    ArcReader::read_zip_entry(crx::split(buf)?.1, name, password)
+                }
+                ArcFormat::Sevenz if indexed => ArcReader::read_7z_entry(buf, name, password),
+                _ => ArcReader::with_options(buf, options)?
+                    .entries
+                    .into_iter()
+                    .find(|entry| entry.is_file() && entry.name == name)
+                    .map(|entry| entry.data)
+                    .ok_or_else(|| ArcError::EntryNotFound(name.to_owned())),
+            },
+        )
+    }
+
+    /// Writes the data of the file `name` from the archive in `buf` to the
+    /// file `dest` and returns its size, streaming it to disk instead of
+    /// holding it in memory
+    ///
+    /// Files are looked up like with [`ArcReader::read_entry`], and tarballs
+    /// are decoded only up to the file. Other formats are decoded whole.
+    /// `dest` is only created or truncated once the file is found, and is
+    /// removed if writing it fails.
+    pub fn extract_entry(buf: &[u8], name: &str, dest: impl AsRef<Path>) -> ArcResult<u64> {
+        ArcReader::extract_entry_with(buf, name, dest, &ReadOptions::default())
+    }
+
+    /// Writes the data of the file `name` from the archive in `buf` to the
+    /// file `dest` like [`ArcReader::extract_entry`], with `options`
+    ///
+    /// Files are looked up as by [`ArcReader::read_entry_with`], tarballs
+    /// only being decoded up to the file without other options than the
+    /// password.
+    pub fn extract_entry_with(
+        buf: &[u8],
+        name: &str,
+        dest: impl AsRef<Path>,
+        options: &ReadOptions,
+    ) -> ArcResult<u64> {
+        extract::extract_entry(buf, name, dest.as_ref(), options)
+    }
+
+    /// Reads the entries of the archive in `buf`, borrowing the data of
+    /// uncompressed tar members and stored zip entries from `buf` instead of
+    /// copying it
+    /// The data of entries of other formats is decoded as by
+    /// [`ArcReader::new`].
+    pub fn read_borrowed(buf: &[u8]) -> ArcResult<Vec<ArcEntryRef<'_>>> {
+        ArcReader::read_borrowed_with(buf, &ReadOptions::default())
+    }
+
+    /// Reads the entries of the archive in `buf` like
+    /// [`ArcReader::read_borrowed`], with `options`
+    ///
+    /// Borrowing is only possible without a password and with the other
+    /// options decoding archives as [`ArcReader::new`] does, the data being
+    /// decoded as by [`ArcReader::with_options`] otherwise. The policies and
+    /// entry limit apply either way.
+    pub fn read_borrowed_with<'b>(
+        buf: &'b [u8],
+        options: &ReadOptions,
+    ) -> ArcResult<Vec<ArcEntryRef<'b>>> {
+        let borrowed = options.plain_decoding() && options.password.is_none();
+        if let Some(max) = options.max_entries.filter(|_| borrowed) {
+            meta::check_count(buf, max)?;
+        }
+        let entries = guard(
+            || match options.format.unwrap_or_else(|| ArcReader::detect(buf)) {
+                ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub if borrowed => {
+                    ArcReader::read_zip_borrowed(buf)
+                }
+                ArcFormat::Crx if borrowed => ArcReader::read_zip_borrowed(crx::split(buf)?.1),
+                ArcFormat::Tar if borrowed => ArcReader::read_tar_borrowed(buf),
+                _ => Ok(ArcReader::with_options(buf, options)?
+                    .entries
+                    .into_iter()
+                    .map(ArcEntryRef::from)
+                    .collect()),
+            },
+        )?;
+        options.apply(entries)
+    }
+
+    /// Detects the format of `buf`, falling back to brotli, which has no magic
+    /// number
+    fn detect(buf: &[u8]) -> ArcFormat {
+        match ArcReader::infer(buf).map(ArcFormat::try_from) {
+            Some(Ok(format)) => format,
+            _ => ArcFormat::Brotli,
+        }
+    }
+
+    /// Detects the type of `buf`, also recognizing formats infer doesn't know
+    fn infer(buf: &[u8]) -> Option<infer::Type> {
+        let mut info = Infer::new();
+        info.add("application/x-lz4", "lz4", |buf| {
+            buf.starts_with(&[0x04, 0x22, 0x4d, 0x18])
+        });
+        info.add("application/x-snappy-framed", "sz", |buf| {
+            buf.starts_with(b"\xff\x06\x00\x00sNaPpY")
+        });
+        info.add("application/x-ms-wim", "wim", |buf| {
+            buf.starts_with(wim::MAGIC)
+        });
+        info.add("application/x-xar", "xar", |buf| {
+            buf.starts_with(xar::MAGIC)
+        });
+        info.add("application/warc", "warc", |buf| {
+            buf.starts_with(warc::MAGIC)
+        });
+        info.get(buf)
+    }
+
+    /// Reads the `control.tar.*` member of a Debian package
+    pub fn deb_control(&self) -> ArcResult<ArcReader> {
+        self.nested("control.tar")
+    }
+
+    /// Reads the `data.tar.*` member of a Debian package
+    pub fn deb_data(&self) -> ArcResult<ArcReader> {
+        self.nested("data.tar")
+    }
+
+    /// Parses the manifest of a JAR
+    pub fn jar_manifest(&self) -> ArcResult<Manifest> {
+        self.entries
+            .iter()
+            .find(|entry| jar::is_manifest(entry))
+            .ok_or_else(|| ArcError::EntryNotFound(jar::MANIFEST.to_owned()))
+            .and_then(|entry| Manifest::parse(&entry.data))
+    }
+
+    /// Reads the first file whose name starts with `prefix` as an archive
+    fn nested(&self, prefix: &str) -> ArcResult<ArcReader> {
+        self.entries
+            .iter()
+            .find(|entry| entry.is_file() && entry.name.starts_with(prefix))
+            .ok_or_else(|| ArcError::EntryNotFound(prefix.to_owned()))
+            .and_then(|entry| ArcReader::new(&entry.data))
+    }
+
+    /// Reads a zip archive with `options`, adding the entries whose
+    /// checksum didn't match to `mismatches`
+    fn read_zip(
+        reader: impl Read + Seek,
+        options: Decoding,
+        mismatches: &mut Vec<String>,
+    ) -> ArcResult<Vec<ArcEntry>> {
+        let mut archive = ZipArchive::new(reader)?;
+        let len = archive.len();
+        let mut entries = Vec::with_capacity(len);
+        for i in 0..len {
+            let file = match options.password {
+                Some(password) => archive.by_index_decrypt(i, password),
+                None => archive.by_index(i),
+            }?;
+            // the zip crate decoded the name as CP437 if it differs from the
+            // raw bytes
+            let name = (file.name().as_bytes() != file.name_raw())
+                .then(|| options.zip_names.decode(file.name_raw()))
+                .flatten();
+            let progress = options.progress;
+            let mut entry = ArcEntry::from_zip(file, options.checksums, mismatches, progress)?;
+            if let Some(name) = name {
+                entry.name = name;
+            }
+            if let Some(tracker) = progress {
+                tracker.finish(&entry.name, 0);
+            }
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    fn read_zip_borrowed(buf: &[u8]) -> ArcResult<Vec<ArcEntryRef<'_>>> {
+        let mut archive = ZipArchive::new(Cursor::new(buf))?;
+        let mut entries = Vec::with_capacity(archive.len());
+        for i in 0..archive.len() {
+            let file = archive.by_index(i)?;
+            if file.compression() != CompressionMethod::Stored
+                || file.encrypted()
+                || file.is_symlink()
+            {
+                entries.push(ArcEntry::try_from(file)?.into());
+                continue;
+            }
+            let start = file.data_start() as usize;
+            let (data, kind) = match file.is_dir() {
+                true => (&[][..], EntryKind::Directory),
+                false => (
+                    usize::try_from(file.compressed_size())
+                        .ok()
+                        .and_then(|len| buf.get(start..start.checked_add(len)?))
+                        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?,
+                    EntryKind::File,
+                ),
+            };
+            // the zip crate isn't there to check it
+            if checksum::crc32(data) != file.crc32() {
+                return Err(ArcError::ChecksumMismatch(file.name().to_owned()));
+            }
+            entries.push(ArcEntryRef {
+                name: file.name().to_owned(),
+                data: Cow::Borrowed(data),
+                meta: ArcMeta {
+                    kind,
+                    ..meta::zip_meta(&file)
+                },
+            });
+        }
+        Ok(entries)
+    }
+
+    fn read_zip_entry(buf: &[u8], name: &str, password: Option<&[u8]>) -> ArcResult<Vec<u8>> {
+        let mut archive = ZipArchive::new(Cursor::new(buf))?;
+        let file = match password {
+            Some(password) => archive.by_name_decrypt(name, password),
+            None => archive.by_name(name),
+        };
+        let mut file = match file {
+            Ok(file) if file.is_file() => file,
+            Ok(_) | Err(ZipError::FileNotFound) => {
+                return Err(ArcError::EntryNotFound(name.to_owned()))
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let mut data = Vec::new();
+        let size = file.size();
+        match read_sized(&mut data, size, |data| {
+            checksum::read_zip(&mut file, data, None)
+        })? {
+            true => Ok(data),
+            false => Err(ArcError::ChecksumMismatch(name.to_owned())),
+        }
+    }
+
+    fn read_tar(reader: impl Read, progress: Option<&Tracker>) -> ArcResult<Vec<ArcEntry>> {
+        let mut reader = ArcStreamReader::from_decoder(ArcFormat::Tar, Box::new(reader));
+        let mut entries = Vec::new();
+        while let Some(entry) = reader.next_entry()? {
+            let entry = guard(|| entry.into_entry(progress))?;
+            if let Some(tracker) = progress {
+                tracker.finish(&entry.name, 0);
+            }
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    /// Decompresses the whole stream and reads it as a tar archive
+    fn read_tar_borrowed(buf: &[u8]) -> ArcResult<Vec<ArcEntryRef<'_>>> {
+        let pos = Cell::new(0);
+        let tracked = TrackedReader { buf, pos: &pos };
+        let mut reader = ArcStreamReader::from_decoder(ArcFormat::Tar, Box::new(tracked));
+        let mut entries = Vec::new();
+        while let Some(entry) = reader.next_entry()? {
+            // sparse files aren't stored contiguously, links and special files
+            // have no data
+            if entry.is_dir()
+                || entry.symlink_target().is_some()
+                || entry.hard_link_target().is_some()
+                || entry.special().is_some()
+                || entry.sparse_regions().is_some()
+            {
+                entries.push(entry.into_entry(None)?.into());
+                continue;
+            }
+            let start = pos.get();
+            let data = usize::try_from(entry.size())
+                .ok()
+                .and_then(|size| start.checked_add(size))
+                .and_then(|end| buf.get(start..end))
+                .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+            entries.push(ArcEntryRef {
+                name: entry.name().to_owned(),
+                data: Cow::Borrowed(data),
+                meta: meta::tar_meta(&entry)?,
+            });
+        }
+        Ok(entries)
+    }
+
+    fn read_compressed_tar(
+        mut decoder: impl Read,
+        progress: Option<&Tracker>,
+    ) -> ArcResult<Vec<ArcEntry>> {
+        let mut data = Vec::new();
+        decoder.read_to_end(&mut data)?;
+        if !is_tar(&data) {
+            return Err(ArcError::UnrecognizedFormat);
+        }
+        ArcReader::read_tar(&data[..], progress)
+    }
+
+    /// Tells zip archives and JARs apart by the presence of a manifest
+    fn zip_format(entries: &[ArcEntry]) -> ArcFormat {
+        match entries.iter().any(jar::is_manifest) {
+            true => ArcFormat::Jar,
+            false => ArcFormat::Zip,
+        }
+    }
+
+    /// Returns the 7z password of the UTF-8 `password`, empty if there's none
+    fn sevenz_password(password: Option<&[u8]>) -> Password {
+        match password {
+            Some(password) => {
+                // the copy made valid UTF-8 is wiped once converted
+                let password = Zeroizing::new(String::from_utf8_lossy(password).into_owned());
+                password.as_str().into()
+            }
+            None => Password::empty(),
+        }
+    }
+
+    fn read_7z(
+        reader: impl Read + Seek,
+        len: u64,
+        password: Password,
+        progress: Option<&Tracker>,
+    ) -> ArcResult<Vec<ArcEntry>> {
+        let mut entries = Vec::new();
+        ArcReader::read_7z_into(reader, len, password, &mut entries, progress)?;
+        Ok(entries)
+    }
+
+    /// Reads a 7z archive, adding its entries to `entries` as they're
+    /// decoded, so the ones before an error are kept
+    fn read_7z_into(
+        reader: impl Read + Seek,
+        len: u64,
+        password: Password,
+        entries: &mut Vec<ArcEntry>,
+        progress: Option<&Tracker>,
+    ) -> ArcResult<()> {
+        let mut sevenz = SevenZReader::new(reader, len, password)?;
+        let mut methods = meta::sevenz_methods(sevenz.archive()).into_iter();
+        sevenz.for_each_entries(|entry, reader| {
+            let mut data = Vec::new();
+            let kind = if entry.is_directory {
+                EntryKind::Directory
+            } else {
+                let mut reader = CountingReader {
+                    inner: reader,
+                    name: &entry.name,
+                    // link targets aren't counted as data
+                    tracker: progress.filter(|_| !meta::is_sevenz_symlink(entry)),
+                };
+                read_sized(&mut data, entry.size, |data| reader.read_to_end(data))?;
+                match meta::is_sevenz_symlink(entry) {
+                    true => EntryKind::Symlink(
+                        String::from_utf8_lossy(&std::mem::take(&mut data)).into_owned(),
+                    ),
+                    false => EntryKind::File,
+                }
+            };
+            entries.push(ArcEntry {
+                name: entry.name.clone(),
+                data,
+                meta: ArcMeta {
+                    kind,
+                    method: methods.next().flatten(),
+                    ..meta::sevenz_meta(entry)
+                },
+            });
+            if let Some(tracker) = progress {
+                tracker.finish(&entry.name, 0);
+            }
+            Ok(true)
+        })?;
+        Ok(())
+    }
+
+    fn read_7z_entry(buf: &[u8], name: &str, password: Option<&[u8]>) -> ArcResult<Vec<u8>> {
+        let mut data = Vec::new();
+        ArcReader::copy_7z_entry(buf, name, password, || Ok(&mut data))?;
+        Ok(data)
+    }
+
+    /// Copies the data of the file `name` from the 7z archive in `buf`,
+    /// decrypted with `password`, to what `create` returns once it's found,
+    /// returning its size
+    pub(crate) fn copy_7z_entry<W: Write>(
+        buf: &[u8],
+        name: &str,
+        password: Option<&[u8]>,
+        create: impl FnOnce() -> io::Result<W>,
+    ) -> ArcResult<u64> {
+        let password = ArcReader::sevenz_password(password);
+        let mut source = Cursor::new(buf);
+        let archive = Archive::read(&mut source, buf.len() as u64, password.as_slice())?;
+        let index = archive
+            .files
+            .iter()
+            .position(|file| !file.is_directory && file.name == name)
+            .ok_or_else(|| ArcError::EntryNotFound(name.to_owned()))?;
+        let Some(block) = archive.stream_map.file_folder_index[index] else {
+            create()?;
+            return Ok(0);
+        };
+
+        // files in a block are decoded in order, so the ones before are skipped
+        let mut current = archive.stream_map.folder_first_file_index[block];
+        let mut create = Some(create);
+        let mut copied = 0;
+        BlockDecoder::new(block, &archive, password.as_slice(), &mut source).for_each_entries(
+            &mut |_, reader| {
+                if current < index {
+                    current += 1;
+                    io::copy(reader, &mut io::sink())?;
+                    return Ok(true);
+                }
+                if let Some(create) = create.take() {
+                    copied = io::copy(reader, &mut create()?)?;
+                }
+                Ok(false)
+            },
+        )?;
+        Ok(copied)
+    }
+
+    fn read_cpio(mut buf: &[u8]) -> ArcResult<Vec<ArcEntry>> {
+        let mut entries = Vec::new();
+        loop {
+            let mut reader = NewcReader::new(buf)?;
+            let entry = reader.entry();
+            if entry.is_trailer() {
+                break;
+            }
+            let name = entry.name().to_owned();
+            let meta = meta::cpio_meta(entry);
+            let file_type = entry.mode() & 0o170000;
+            let mut data = Vec::new();
+            let kind = if file_type == u32::from(ModeFileType::Directory) {
+                EntryKind::Directory
+            } else if let Some(special) = meta::cpio_special(entry) {
+                EntryKind::Special(special)
+            } else {
+                let size = entry.file_size().into();
+                read_sized(&mut data, size, |data| reader.read_to_end(data))?;
+                match file_type == u32::from(ModeFileType::Symlink) {
+                    true => EntryKind::Symlink(
+                        String::from_utf8_lossy(&std::mem::take(&mut data)).into_owned(),
+                    ),
+                    false => EntryKind::File,
+                }
+            };
+            entries.push(ArcEntry {
+                name,
+                data,
+                meta: ArcMeta { kind, ..meta },
+            });
+            buf = reader.finish()?;
+        }
+        Ok(entries)
+    }
+
+    fn read_ar(buf: &[u8]) -> ArcResult<Vec<ArcEntry>> {
+        let mut archive = ArArchive::new(buf);
+        let mut entries = Vec::new();
+        while let Some(entry) = archive.next_entry() {
+            let mut entry = entry?;
+            let name = String::from_utf8_lossy(entry.header().identifier()).into_owned();
+            let meta = meta::ar_meta(entry.header());
+            let mut data = Vec::new();
+            let size = entry.header().size();
+            read_sized(&mut data, size, |data| entry.read_to_end(data))?;
+            entries.push(ArcEntry { name, data, meta });
+        }
+        Ok(entries)
+    }
+
+    fn read_rpm(buf: &[u8]) -> ArcResult<Vec<ArcEntry>> {
+        const LEAD_SIZE: usize = 96;
+
+        let signature_end = ArcReader::rpm_header_end(buf, LEAD_SIZE)?;
+        // the signature header is padded to a multiple of 8 bytes
+        let header_end = ArcReader::rpm_header_end(buf, signature_end.next_multiple_of(8))?;
+        let payload = &buf[header_end..];
+
+        let mut cpio = Vec::new();
+        match ArcReader::infer(payload).map(|kind| kind.extension()) {
+            Some("gz") => GzDecoder::new(payload).read_to_end(&mut cpio)?,
+            Some("xz") => XzDecoder::new(payload).read_to_end(&mut cpio)?,
+            Some("bz2") => BzDecoder::new(payload).read_to_end(&mut cpio)?,
+            Some("zst") => ZstdDecoder::new(payload)?.read_to_end(&mut cpio)?,
+            Some("lz4") => Lz4Decoder::new(payload).read_to_end(&mut cpio)?,
+            Some("cpio") => return ArcReader::read_cpio(payload),
+            _ => return Err(ArcError::UnrecognizedFormat),
+        };
+        ArcReader::read_cpio(&cpio)
+    }
+
+    /// Returns the offset just past the RPM header structure starting at
+    /// `offset`
+    fn rpm_header_end(buf: &[u8], offset: usize) -> io::Result<usize> {
+        const MAGIC: [u8; 4] = [0x8e, 0xad, 0xe8, 0x01];
+        const INTRO_SIZE: usize = 16;
+        const INDEX_ENTRY_SIZE: usize = 16;
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid RPM header");
+        let intro = buf.get(offset..offset + INTRO_SIZE).ok_or_else(invalid)?;
+        if intro[..4] != MAGIC {
+            return Err(invalid());
+        }
+        let index_len = u32::from_be_bytes(intro[8..12].try_into().unwrap()) as usize;
+        let store_len = u32::from_be_bytes(intro[12..16].try_into().unwrap()) as usize;
+        let end = offset + INTRO_SIZE + index_len * INDEX_ENTRY_SIZE + store_len;
+        if end > buf.len() {
+            return Err(invalid());
+        }
+        Ok(end)
+    }
+
+    fn read_cab(reader: impl Read + Seek) -> ArcResult<Vec<ArcEntry>> {
+        let mut cabinet = Cabinet::new(reader)?;
+        let names: Vec<_> = cabinet
+            .folder_entries()
+            .flat_map(|folder| folder.file_entries())
+            .map(|file| file.name().to_owned())
+            .collect();
+        let mut entries = Vec::with_capacity(names.len());
+        for name in names {
+            let mut data = Vec::new();
+            cabinet.read_file(&name)?.read_to_end(&mut data)?;
+            entries.push(ArcEntry::file(name.replace('\\', "/"), data));
+        }
+        Ok(entries)
+    }
+
+    /// The unrar library can only open archives from the filesystem, so the
+    /// buffer is written to a temporary file for the duration of the read
+    #[cfg(feature = "rar")]
+    fn read_rar(buf: &[u8]) -> ArcResult<Vec<ArcEntry>> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use unrar::Archive as RarArchive;
+
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "cra-{}-{}.rar",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, buf)?;
+
+        let read = || -> ArcResult<Vec<ArcEntry>> {
+            let mut entries = Vec::new();
+            let mut archive = RarArchive::new(&path).open_for_processing()?;
+            while let Some(header) = archive.read_header()? {
+                let name = header.entry().filename.to_string_lossy().into_owned();
+                archive = if header.entry().is_directory() {
+                    entries.push(ArcEntry::directory(name));
+                    header.skip()?
+                } else {
+                    let (data, rest) = header.read()?;
+                    entries.push(ArcEntry::file(name, data));
+                    rest
+                };
+            }
+            Ok(entries)
+        };
+        let entries = read();
+        std::fs::remove_file(&path)?;
+        entries
+    }
+
+    /// Decompresses a brotli stream, returning `None` unless the whole buffer
+    /// is exactly one valid stream
+    fn unbrotli(buf: &[u8]) -> Option<Vec<u8>> {
+        let mut state = BrotliState::new(
+            HeapAlloc::<u8>::default(),
+            HeapAlloc::<u32>::default(),
+            HeapAlloc::<HuffmanCode>::default(),
+        );
+        let mut data = Vec::new();
+        let mut chunk = vec![0; 1 << 16];
+        let (mut available_in, mut input_offset, mut total_out) = (buf.len(), 0, 0);
+        loop {
+            let (mut available_out, mut output_offset) = (chunk.len(), 0);
+            let result = BrotliDecompressStream(
+                &mut available_in,
+                &mut input_offset,
+                buf,
+                &mut available_out,
+                &mut output_offset,
+                &mut chunk,
+                &mut total_out,
+                &mut state,
+            );
+            data.extend_from_slice(&chunk[..output_offset]);
+            match result {
+                BrotliResult::ResultSuccess if available_in == 0 => return Some(data),
+                BrotliResult::NeedsMoreOutput => {}
+                _ => return None,
+            }
+        }
+    }
+
+    /// Decompresses a gzip stream of one or more members, returning the
+    /// original file name stored in the first header (empty if absent) and
+    /// the decompressed data
+    fn gunzip(buf: &[u8]) -> ArcResult<(String, Vec<u8>)> {
+        let mut decoder = MultiGzDecoder::new(buf);
+        let mut data = Vec::new();
+        decoder.read_to_end(&mut data)?;
+        let name = decoder
+            .header()
+            .and_then(|header| header.filename())
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .unwrap_or_default();
+        Ok((name, data))
+    }
+}
+
+impl Iterator for ArcReader {
+    type Item = ArcEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i == self.entries.len() {
+            None
+        } else {
+            self.i += 1;
+            Some(self.entries[self.i - 1].clone())
+        }
+    }
+}
+
+/// Start of a stream, sniffed for a tarball
+pub(crate) enum TarStream<'a, R> {
+    /// A tarball in the given format, with a reader for the decoded tar
+    Tar(ArcFormat, Box<dyn Read + 'a>),
+    /// Anything else, with the bytes already read and the rest of the stream
+    Other(Vec<u8>, R),
+}
+
+/// Default for [`ArcWriter::buffer_size`]
+pub const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Runs `read`, turning a panic in one of the decoders this crate relies on
+/// into [`ArcError::Malformed`]
+///
+/// Builds with `panic = "abort"` still abort.
+pub(crate) fn guard<T>(read: impl FnOnce() -> ArcResult<T>) -> ArcResult<T> {
+    panic::catch_unwind(AssertUnwindSafe(read)).unwrap_or_else(|payload| {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => payload
+                .downcast_ref::<&str>()
+                .map_or("decoder panicked", |message| message)
+                .to_owned(),
+        };
+        Err(ArcError::Malformed(message))
+    })
+}
+
+/// Returns how much to allocate upfront for data whose headers say it's
+/// `size` bytes long, trusting them only so far since they may be forged
+pub(crate) fn capacity_for(size: u64) -> usize {
+    const MAX_UPFRONT: u64 = 16 * 1024 * 1024;
+
+    size.min(MAX_UPFRONT) as usize
+}
+
+/// Runs `read` on `data` with room reserved for the `size` bytes headers say
+/// it'll hold, as far as [`capacity_for`] trusts them, giving back the room a
+/// forged size left unused
+pub(crate) fn read_sized<T>(
+    data: &mut Vec<u8>,
+    size: u64,
+    read: impl FnOnce(&mut Vec<u8>) -> T,
+) -> T {
+    data.reserve(capacity_for(size));
+    let read = read(data);
+    // growing on its own, a vector never ends up more than half empty
+    if data.capacity() / 2 > data.len() {
+        data.shrink_to_fit();
+    }
+    read
+}
+
+/// Struct for creating archives
+pub struct ArcWriter {
+    pub format: ArcFormat,
+    /// Compression level used for zstd, `0` selects zstd's default
+    pub zstd_level: i32,
+    /// Compression quality used for brotli, from `0` to `11`
+    pub brotli_quality: u32,
+    /// Base 2 logarithm of the window size used for brotli, from `10` to `24`
+    pub brotli_window: u32,
+    /// Header format used for tar-based formats
+    pub tar_format: TarFormat,
+    /// Compression method used for 7z files without [`Method::Lzma`] or
+    /// [`Method::Lzma2`] in their metadata, without BCJ filters as they can't
+    /// be written, see [`SevenzCodec`]
+    ///
+    /// Files with different methods never share a solid block.
+    pub sevenz_codec: SevenzCodec,
+    /// Compression preset used for 7z, from `0` to `9`
+    pub sevenz_level: u32,
+    /// Dictionary size used for 7z in bytes, `0` selects the preset's default
+    pub sevenz_dict_size: u32,
+    /// Compresses 7z files together in solid blocks instead of one by one
+    ///
+    /// Solid blocks compress better, but reading a single file means
+    /// decompressing everything before it in its block. Files in solid blocks
+    /// are stored after directories and empty files.
+    pub sevenz_solid: bool,
+    /// Maximum number of files in a 7z solid block, `0` for no limit
+    pub sevenz_solid_files: usize,
+    /// Maximum uncompressed size of a 7z solid block in bytes, `0` for no
+    /// limit
+    pub sevenz_solid_size: u64,
+    /// Puts only files with the same extension in each 7z solid block
+    pub sevenz_solid_by_extension: bool,
+    /// Password encrypting the data of 7z archives with AES-256
+    pub sevenz_password: Option<Secret>,
+    /// Encrypts the header of 7z archives too, hiding the names of their
+    /// entries, only with a `sevenz_password`
+    pub sevenz_encrypt_header: bool,
+    /// Maximum size of each volume created by [`ArcWriter::archive_volumes`]
+    /// for zip, at least 64 KiB, `0` disables splitting
+    pub zip_volume_size: usize,
+    /// Stores zip entries uncompressed instead of deflating them, unless
+    /// their [`ArcMeta::method`] says otherwise
+    pub zip_stored: bool,
+    /// Password encrypting the files of zip-based archives with AES-256
+    ///
+    /// Directories and symbolic links are left unencrypted, and so are JAR
+    /// manifests and the EPUB `mimetype`, which have to be readable as is.
+    pub zip_password: Option<Secret>,
+    /// Fails with [`ArcError::DuplicateEntry`] when creating an archive
+    /// holding two entries with the same name, instead of writing both
+    ///
+    /// Zip archives can't hold duplicates either way.
+    pub reject_duplicates: bool,
+    /// What happens to entry names that aren't safe relative paths, like
+    /// absolute ones or ones with `..` components, kept by default
+    ///
+    /// Names are sanitized as entries are pushed, so the policy has to be
+    /// set first, and rejected when an archive is created.
+    pub path_policy: PathPolicy,
+    /// Comment at the end of zip-based archives, at most 65535 bytes
+    pub zip_comment: Vec<u8>,
+    /// Writes the times of zip entries with a modification time to NTFS
+    /// extra fields too, keeping them to 100 nanoseconds
+    pub zip_ntfs_times: bool,
+    /// Files pushed with more data than this many bytes are moved to
+    /// temporary files until the writer is dropped, `0` keeps everything in
+    /// memory
+    ///
+    /// Files are kept in memory if the temporary file can't be written, and
+    /// so are JAR manifests and the EPUB `mimetype` and
+    /// `META-INF/container.xml`.
+    pub spill_threshold: u64,
+    /// Size in bytes of the buffers used to read files pushed from readers or
+    /// spilled, and to write to files and standard output
+    pub buffer_size: usize,
+    /// Owner of tar entries without one in their metadata, `None` for
+    /// [`Owner::current`]
+    pub tar_default_owner: Option<Owner>,
+    /// Leaves user and group names out of tar headers, keeping only the
+    /// numeric ids
+    pub tar_numeric_owner: bool,
+    /// Permission bits of tar entries other than directories without a mode
+    /// in their metadata, `0o766` by default
+    pub tar_file_mode: u32,
+    /// Permission bits of tar directories without a mode in their metadata,
+    /// `0o766` by default
+    pub tar_dir_mode: u32,
+    /// Stores files of tar-based archives that hold blocks of zeros as sparse
+    /// files, leaving the zeros out
+    ///
+    /// They're written in the old GNU format with [`TarFormat::Gnu`] and in
+    /// the PAX 1.0 format with [`TarFormat::Pax`]. Files pushed from readers
+    /// are stored whole, their data can only be read once.
+    pub tar_sparse: bool,
+    /// Hasher computing a digest of the data of each file as it's archived,
+    /// like `Box::new(Sha256::new())`, see [`ArcWriter::digests`]
+    ///
+    /// It's cloned and reset for every file.
+    pub digest: Option<Box<dyn DynDigest + Send + Sync>>,
+    /// What [`ArcWriter::add_path`] does with the symlinks it comes across,
+    /// storing them as symlinks by default
+    pub add_symlinks: AddSymlinkPolicy,
+    /// Leaves out what the `.gitignore` and `.ignore` files met by
+    /// [`ArcWriter::add_path`] exclude, wherever they are in the tree
+    #[cfg(feature = "ignore")]
+    pub add_ignore_files: bool,
+    /// Gitignore-style globs of what [`ArcWriter::add_path`] leaves out,
+    /// like `target/` or `*.o`, relative to the base directory
+    ///
+    /// Invalid globs fail with [`ArcError::InvalidPattern`].
+    #[cfg(feature = "ignore")]
+    pub add_excludes: Vec<String>,
+    /// Called with the path and metadata of everything
+    /// [`ArcWriter::add_path`] comes across, leaving out what it returns
+    /// `false` for, along with everything under directories
+    ///
+    /// Followed symlinks come with the metadata of what they point to.
+    pub add_filter: Option<Box<AddFilterFn>>,
+    /// Called as the data of each file is read to be archived, and once it's
+    /// all been read, with the number and total size of the files
+    ///
+    /// Only files count as entries, as the rest are written in no time, and
+    /// entries pushed to an [`ArcStreamWriter`] aren't reported.
+    pub progress: Option<Box<ProgressFn>>,
+    entries: Vec<ArcEntry>,
+    /// Digests of the files archived so far, by name
+    digests: Mutex<HashMap<String, Vec<u8>>>,
+    /// Files and bytes archived so far, reported to `progress`
+    counts: Mutex<progress::Counts>,
+    /// Data of the files pushed from readers or spilled to temporary files,
+    /// by index in `entries`
+    readers: HashMap<usize, ReaderData>,
+}
+
+/// Data of a file that isn't held in `entries`
+struct ReaderData {
+    source: DataSource,
+    len: u64,
+}
+
+enum DataSource {
+    /// Added with [`ArcWriter::push_from_reader`], taken by the first call
+    /// creating an archive
+    Reader(Mutex<Option<Box<dyn Read + Send>>>),
+    /// Moved to a temporary file because of [`ArcWriter::spill_threshold`]
+    Spilled(NamedTempFile),
+}
+
+/// Reader yielding exactly `remaining` bytes of `inner`, failing if it ends
+/// earlier
+struct ExactReader<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: Read> Read for ExactReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf
+            .len()
+            .min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        if len == 0 {
+            return Ok(0);
+        }
+        let read = self.inner.read(&mut buf[..len])?;
+        if read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "reader ended before the pushed length",
+            ));
+        }
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// Reader of `buf` keeping track of how far it was read
+struct TrackedReader<'a> {
+    buf: &'a [u8],
+    pos: &'a Cell<usize>,
+}
+
+impl Read for TrackedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = (&self.buf[self.pos.get()..]).read(buf)?;
+        self.pos.set(self.pos.get() + read);
+        Ok(read)
+    }
+}
+
+impl ArcWriter {
+    /// Returns a new writer for the specified archive format
+    pub fn new(format: ArcFormat) -> Self {
+        Self {
+            format,
+            zstd_level: zstd::DEFAULT_COMPRESSION_LEVEL,
+            brotli_quality: 11,
+            brotli_window: 22,
+            tar_format: TarFormat::default(),
+            sevenz_codec: SevenzCodec::default(),
+            sevenz_level: 6,
+            sevenz_dict_size: 0,
+            sevenz_solid: false,
+            sevenz_solid_files: 0,
+            sevenz_solid_size: 0,
+            sevenz_solid_by_extension: false,
+            sevenz_password: None,
+            sevenz_encrypt_header: false,
+            zip_volume_size: 0,
+            zip_stored: false,
+            zip_password: None,
+            reject_duplicates: false,
+            path_policy: PathPolicy::Allow,
+            zip_comment: Vec::new(),
+            zip_ntfs_times: false,
+            spill_threshold: 0,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            tar_default_owner: None,
+            tar_numeric_owner: false,
+            tar_file_mode: 0o766,
+            tar_dir_mode: 0o766,
+            tar_sparse: false,
+            digest: None,
+            add_symlinks: AddSymlinkPolicy::Store,
+            #[cfg(feature = "ignore")]
+            add_ignore_files: false,
+            #[cfg(feature = "ignore")]
+            add_excludes: Vec::new(),
+            add_filter: None,
+            progress: None,
+            entries: Vec::new(),
+            digests: Mutex::new(HashMap::new()),
+            counts: Mutex::new(progress::Counts::default()),
+            readers: HashMap::new(),
+        }
+    }
+
+    /// Stamps tar entries without a set owner or mode as owned by `root:root`,
+    /// with mode `0o644` for files and `0o755` for directories, so archives
+    /// don't depend on who creates them
+    pub fn tar_root_defaults(&mut self) {
+        self.tar_default_owner = Some(Owner::root());
+        self.tar_file_mode = 0o644;
+        self.tar_dir_mode = 0o755;
+    }
+
+    /// Adds an entry to the writer
+    pub fn push(&mut self, mut entry: ArcEntry) {
+        if self.path_policy == PathPolicy::Sanitize && !path::sanitize_entry(&mut entry) {
+            return;
+        }
+        if entry.is_file() && self.spills(&entry.name, entry.data.len()) {
+            if let Ok(file) = spill::to_file(&entry.data) {
+                let spilled = ReaderData {
+                    source: DataSource::Spilled(file),
+                    len: entry.data.len() as u64,
+                };
+                self.readers.insert(self.entries.len(), spilled);
+                entry.data = Vec::new();
             }
-            builder.finish()?;
         }
-        Ok(inner)
+        self.entries.push(entry);
+    }
+
+    /// Adds all entries from slice to the writer
+    pub fn extend(&mut self, entries: &[ArcEntry]) {
+        for entry in entries {
+            self.push(entry.clone());
+        }
+    }
+
+    /// Returns whether the file `name` holding `len` bytes is moved to a
+    /// temporary file when pushed
+    fn spills(&self, name: &str, len: usize) -> bool {
+        self.spill_threshold != 0 && len as u64 > self.spill_threshold && !Self::needs_data(name)
+    }
+
+    /// Returns whether the file `name` has to be pushed with its data, since
+    /// it's checked or parsed when the archive is created
+    fn needs_data(name: &str) -> bool {
+        [jar::MANIFEST, epub::MIMETYPE_NAME, epub::CONTAINER].contains(&name)
+    }
+
+    /// Adds the file `name`, whose `len` bytes of data are only read from
+    /// `reader` when the archive is created
+    ///
+    /// Only the first `len` bytes are archived, and it's an error for the
+    /// reader to end earlier. The reader is used up by the first call
+    /// creating an archive, later ones fail. JAR manifests and the EPUB
+    /// `mimetype` and `META-INF/container.xml` have to be pushed with their
+    /// data instead.
+    pub fn push_from_reader(&mut self, name: &str, reader: impl Read + Send + 'static, len: u64) {
+        self.push_reader(ArcEntry::file(name, Vec::new()), reader, len);
+    }
+
+    /// Adds the file `entry`, whose `len` bytes of data are read from
+    /// `reader` like with [`ArcWriter::push_from_reader`]
+    fn push_reader(&mut self, mut entry: ArcEntry, reader: impl Read + Send + 'static, len: u64) {
+        if self.path_policy == PathPolicy::Sanitize && !path::sanitize_entry(&mut entry) {
+            return;
+        }
+        let reader = ReaderData {
+            source: DataSource::Reader(Mutex::new(Some(Box::new(reader)))),
+            len,
+        };
+        self.readers.insert(self.entries.len(), reader);
+        self.entries.push(entry);
+    }
+
+    /// Adds the file or directory `path` and everything under it, named by
+    /// their path relative to `base` with `/` separators
+    ///
+    /// Directories are walked in name order, and symlinks are added as
+    /// [`ArcWriter::add_symlinks`] says. Permissions and modification times
+    /// are taken from the filesystem, and file data is only read when the
+    /// archive is created, like with [`ArcWriter::push_from_reader`]. FIFOs,
+    /// sockets and devices are left out, and so is what
+    /// [`ArcWriter::add_filter`] rejects and what `add_ignore_files` and
+    /// `add_excludes` match with the `ignore` feature. Fails if `path` isn't
+    /// under `base`.
+    pub fn add_path(&mut self, path: impl AsRef<Path>, base: impl AsRef<Path>) -> ArcResult<()> {
+        add::add_path(self, path.as_ref(), base.as_ref())
+    }
+
+    /// Adds the file or directory `path` and everything under it like
+    /// [`ArcWriter::add_path`], named by their path relative to `path` under
+    /// `prefix` instead
+    ///
+    /// Calling it for several roots maps each to its own place in the
+    /// archive, like `build/bin` to `bin/` and `assets` to `share/assets/`.
+    /// `path` itself becomes the directory or file `prefix`, without entries
+    /// for the parents of `prefix`. An empty `prefix` adds the contents of a
+    /// directory at the top, or a file under its own name. Exclude globs are
+    /// relative to `path`.
+    pub fn add_path_as(&mut self, path: impl AsRef<Path>, prefix: &str) -> ArcResult<()> {
+        add::add_path_as(self, path.as_ref(), prefix)
+    }
+
+    /// Returns the length of the data of the file at `index`, holding `data`
+    /// unless it was pushed from a reader
+    fn file_len(&self, index: usize, data: &[u8]) -> u64 {
+        self.readers
+            .get(&index)
+            .map_or(data.len() as u64, |reader| reader.len)
+    }
+
+    /// Returns a reader for the data of the file at `index`, holding `data`
+    /// unless it was pushed from a reader or spilled
+    ///
+    /// Files are hashed with [`ArcWriter::digest`] and reported to
+    /// [`ArcWriter::progress`] as they're read, unless `entries` was handed
+    /// over to an [`ArcStreamWriter`].
+    fn file_data<'a>(&'a self, index: usize, data: &'a [u8]) -> io::Result<Box<dyn Read + 'a>> {
+        let mut data = self.raw_file_data(index, data)?;
+        let Some(entry) = self.entries.get(index).filter(|entry| entry.is_file()) else {
+            return Ok(data);
+        };
+        if let Some(digest) = &self.digest {
+            let mut hasher = digest.box_clone();
+            hasher.reset();
+            data = Box::new(hash::DigestReader {
+                inner: data,
+                name: &entry.name,
+                hasher: Some(hasher),
+                digests: &self.digests,
+            });
+        }
+        if let Some(report) = &self.progress {
+            data = Box::new(progress::ProgressReader {
+                inner: data,
+                name: &entry.name,
+                report: report.as_ref(),
+                counts: &self.counts,
+                finished: false,
+            });
+        }
+        Ok(data)
+    }
+
+    /// Returns a reader for the data of the file at `index` like
+    /// [`ArcWriter::file_data`], without hashing it
+    fn raw_file_data<'a>(&self, index: usize, data: &'a [u8]) -> io::Result<Box<dyn Read + 'a>> {
+        let Some(source) = self.readers.get(&index) else {
+            return Ok(Box::new(data));
+        };
+        let inner: Box<dyn Read> = match &source.source {
+            DataSource::Reader(reader) => reader.lock().unwrap().take().ok_or_else(|| {
+                io::Error::other("reader was already used up by an earlier archive")
+            })?,
+            DataSource::Spilled(file) => Box::new(file.reopen()?),
+        };
+        Ok(Box::new(ExactReader {
+            inner: BufReader::with_capacity(self.buffer_size, inner),
+            remaining: source.len,
+        }))
+    }
+
+    /// Returns the digests of the files archived so far by name, computed
+    /// with [`ArcWriter::digest`] while their data was written
+    ///
+    /// Files are only listed once an archive holding them was created, and
+    /// the digests of files pushed from readers are the only record of
+    /// their data left.
+    pub fn digests(&self) -> HashMap<String, Vec<u8>> {
+        self.digests.lock().unwrap().clone()
+    }
+
+    /// Checks the entries before an archive is created and resets the counts
+    /// reported to `progress`
+    fn begin(&self) -> ArcResult<()> {
+        self.check_entries()?;
+        let files = self.entries.iter().enumerate();
+        let files: Vec<_> = files.filter(|(_, entry)| entry.is_file()).collect();
+        *self.counts.lock().unwrap() = progress::Counts {
+            total_entries: files.len(),
+            total_bytes: files
+                .iter()
+                .map(|(i, entry)| self.file_len(*i, &entry.data))
+                .sum(),
+            ..progress::Counts::default()
+        };
+        Ok(())
+    }
+
+    /// Fails if two entries share a name and `reject_duplicates` is set, or
+    /// if a name isn't safe and `path_policy` rejects it
+    fn check_entries(&self) -> ArcResult<()> {
+        if self.path_policy == PathPolicy::Reject {
+            path::reject_unsafe(
+                self.entries
+                    .iter()
+                    .map(|entry| (&entry.name, &entry.meta.kind)),
+            )?;
+        }
+        match self.reject_duplicates {
+            true => duplicate::reject(self.entries.iter().map(|entry| entry.name.as_str())),
+            false => Ok(()),
+        }
     }
 
-    #[cfg(target_os = "windows")]
-    fn archive_tar(&self) -> ArcResult<Vec<u8>> {
-        panic!("Cannot archive tar on Windows");
+    /// Fails if any of the files `names` was pushed from a reader
+    fn require_data(&self, names: &[&str]) -> io::Result<()> {
+        for (&index, source) in &self.readers {
+            if !matches!(source.source, DataSource::Reader(_)) {
+                continue;
+            }
+            let name = &self.entries[index].name;
+            if names.contains(&name.as_str()) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{name} can't be pushed from a reader"),
+                ));
+            }
+        }
+        Ok(())
     }
 
-    fn archive_7z(&self) -> ArcResult<Vec<u8>> {
+    /// Creates the finished archive
+    /// Fails with [`ArcError::SingleFileOnly`] if target format is `Gzip`,
+    /// `Zstd`, `Lz4`, `Brotli` or `Snappy` and the writer doesn't hold exactly
+    /// one file
+    /// Fails with [`ArcError::ReadOnly`] if target format is `Deb`, `Rpm`,
+    /// `Crx`, `Cab`, `Wim`, `Xar`, `Warc` or `Rar`
+    pub fn archive(&self) -> ArcResult<Vec<u8>> {
         let mut inner = Vec::new();
-        let mut archive = SevenZWriter::new(Cursor::new(&mut inner))?;
-        for entry in &self.entries {
-            let mut szentry = SevenZArchiveEntry::default();
-            szentry.has_last_modified_date = true;
-            szentry.last_modified_date = FileTime::now();
-            match entry {
-                ArcEntry::Directory(name) => {
-                    szentry.is_directory = true;
-                    szentry.name.clone_from(name);
+        self.archive_to(&mut inner)?;
+        Ok(inner)
+    }
+
+    /// Writes the finished archive to `writer`, failing like
+    /// [`ArcWriter::archive`]
+    /// Zip-based and 7z archives are built in memory first, since headers
+    /// have to be updated after the data they describe is written, the other
+    /// formats are streamed straight into `writer`
+    pub fn archive_to(&self, mut writer: impl Write) -> ArcResult<()> {
+        self.begin()?;
+        match self.format {
+            ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub | ArcFormat::Sevenz => {
+                let inner = self.archive_to_seekable(Cursor::new(Vec::new()))?;
+                writer.write_all(&inner.into_inner())?
+            }
+            ArcFormat::Tar => self.write_tar(writer)?,
+            ArcFormat::TarGz
+            | ArcFormat::TarXz
+            | ArcFormat::TarBz2
+            | ArcFormat::TarZst
+            | ArcFormat::TarLz4
+            | ArcFormat::TarBr
+            | ArcFormat::TarSz => self.compress(writer, "", |encoder| self.write_tar(encoder))?,
+            ArcFormat::Gzip
+            | ArcFormat::Zstd
+            | ArcFormat::Lz4
+            | ArcFormat::Brotli
+            | ArcFormat::Snappy => {
+                let (name, data) = self.single_file()?;
+                self.compress(writer, name, |encoder| {
+                    io::copy(&mut self.file_data(0, data)?, encoder)?;
+                    Ok(())
+                })?
+            }
+            ArcFormat::Cpio => self.write_cpio(writer)?,
+            ArcFormat::Ar => self.write_ar(writer)?,
+            ArcFormat::Crx
+            | ArcFormat::Deb
+            | ArcFormat::Rpm
+            | ArcFormat::Cab
+            | ArcFormat::Wim
+            | ArcFormat::Xar
+            | ArcFormat::Warc => return Err(ArcError::ReadOnly),
+            #[cfg(feature = "rar")]
+            ArcFormat::Rar => return Err(ArcError::ReadOnly),
+        }
+        Ok(())
+    }
+
+    /// Writes the finished archive to the seekable `writer`, which has to be
+    /// at its start, failing like [`ArcWriter::archive`]
+    /// Unlike with [`ArcWriter::archive_to`], zip-based and 7z archives are
+    /// written in place instead of being built in memory first
+    pub fn archive_to_seekable<W: Write + Seek>(&self, mut writer: W) -> ArcResult<W> {
+        self.begin()?;
+        match self.format {
+            ArcFormat::Zip => self.write_zip(writer),
+            ArcFormat::Jar => self.write_jar(writer),
+            ArcFormat::Epub => self.write_epub(writer),
+            ArcFormat::Sevenz => self.write_7z(writer),
+            _ => {
+                self.archive_to(&mut writer)?;
+                Ok(writer)
+            }
+        }
+    }
+
+    /// Writes the finished archive to the file at `path` through a buffered
+    /// handle, failing like [`ArcWriter::archive`]
+    /// Use [`ArcFormat::from_path`] to pick the format from the extension of
+    /// `path`
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> ArcResult<()> {
+        let file = BufWriter::with_capacity(self.buffer_size, File::create(path)?);
+        let file = self.archive_to_seekable(file)?;
+        file.into_inner().map_err(|err| err.into_error())?;
+        Ok(())
+    }
+
+    /// Writes the finished archive to standard output through a buffered
+    /// handle, failing like [`ArcWriter::archive`]
+    /// Formats written straight into [`ArcWriter::archive_to`]'s writer are
+    /// streamed to the pipe, the others are built in memory first
+    pub fn archive_to_stdout(&self) -> ArcResult<()> {
+        let mut stdout = BufWriter::with_capacity(self.buffer_size, io::stdout().lock());
+        self.archive_to(&mut stdout)?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Writes the finished archive into the file at `path` through a memory
+    /// map of `capacity` bytes, then truncates the file to the archive's
+    /// length and returns it, failing like [`ArcWriter::archive`]
+    /// Zip-based and 7z archives are written in place, which avoids growing
+    /// a buffer for very large archives. Fails if the archive is longer than
+    /// `capacity`, leaving a partial file behind.
+    pub fn write_to_mmap(&self, path: impl AsRef<Path>, capacity: u64) -> ArcResult<u64> {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(capacity)?;
+        // SAFETY: the file was just truncated and is only written through the map
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        let len = self.archive_to_seekable(MmapWriter::new(map))?.finish()?;
+        file.set_len(len)?;
+        Ok(len)
+    }
+
+    /// Switches to writing a tarball into `writer` entry by entry, writing
+    /// the entries pushed so far first
+    /// Fails if the target format isn't tar-based
+    pub fn into_stream<W: Write>(self, writer: W) -> ArcResult<ArcStreamWriter<W>> {
+        ArcStreamWriter::with_options(self, writer)
+    }
+
+    /// Creates the finished archive split into volumes, in the order they're
+    /// named `.z01`, `.z02`, ... and finally `.zip`
+    /// Only zip archives are split, other formats and archives that fit in a
+    /// single volume are returned as one volume
+    pub fn archive_volumes(&self) -> ArcResult<Vec<Vec<u8>>> {
+        let archive = self.archive()?;
+        match self.format {
+            ArcFormat::Zip if self.zip_volume_size != 0 => {
+                split_zip::split(&archive, self.zip_volume_size)
+            }
+            _ => Ok(vec![archive]),
+        }
+    }
+
+    /// Creates the finished archive prefixed with the executable `stub`,
+    /// making it self-extracting
+    /// Fails with an [`io::ErrorKind::Unsupported`] error if target format
+    /// isn't `Zip` or `Sevenz`
+    pub fn archive_sfx(&self, stub: &[u8]) -> ArcResult<Vec<u8>> {
+        self.begin()?;
+        match self.format {
+            ArcFormat::Zip => {
+                let mut inner = Cursor::new(stub.to_vec());
+                inner.seek(SeekFrom::End(0))?;
+                Ok(self.write_zip(inner)?.into_inner())
+            }
+            // 7z offsets are relative to its own signature header
+            ArcFormat::Sevenz => {
+                let inner = self.write_7z(Cursor::new(Vec::new()))?;
+                Ok([stub, inner.get_ref()].concat())
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "only zip and 7z archives can be self-extracting",
+            )
+            .into()),
+        }
+    }
+
+    /// Writes the zip archive at `inner`'s position, with offsets counted
+    /// from the start of `inner`
+    fn write_zip<W: Write + Seek>(&self, inner: W) -> ArcResult<W> {
+        let (mut writer, ending) = self.zip_writer(inner)?;
+        self.write_zip_entries(&mut writer, self.entries.iter().enumerate())?;
+        ending.set(true);
+        writer.finish()?.finish()
+    }
+
+    /// Writes a zip archive starting with the uncompressed manifest, falling
+    /// back to a minimal one if none was pushed
+    fn write_jar<W: Write + Seek>(&self, inner: W) -> ArcResult<W> {
+        self.require_data(&[jar::MANIFEST])?;
+        let manifest = self
+            .entries
+            .iter()
+            .find(|entry| jar::is_manifest(entry))
+            .map_or(jar::DEFAULT_MANIFEST, |entry| &entry.data[..]);
+        Manifest::parse(manifest)?;
+        let entries = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !jar::is_manifest(entry));
+        self.write_zip_stored_first(inner, jar::MANIFEST, manifest, entries)
+    }
+
+    /// Writes a zip archive starting with the uncompressed `mimetype` file,
+    /// after checking the required members are present
+    fn write_epub<W: Write + Seek>(&self, inner: W) -> ArcResult<W> {
+        self.require_data(&[epub::MIMETYPE_NAME, epub::CONTAINER])?;
+        epub::validate(&self.entries)?;
+        let entries = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !epub::is_mimetype(entry));
+        self.write_zip_stored_first(inner, epub::MIMETYPE_NAME, epub::MIMETYPE, entries)
+    }
+
+    /// Writes a zip archive whose first entry is the file `name`, stored
+    /// uncompressed, followed by `entries`
+    fn write_zip_stored_first<'a, W: Write + Seek>(
+        &self,
+        inner: W,
+        name: &str,
+        data: &[u8],
+        entries: impl IntoIterator<Item = (usize, &'a ArcEntry)>,
+    ) -> ArcResult<W> {
+        let (mut writer, ending) = self.zip_writer(inner)?;
+        let stored = FullFileOptions::default().compression_method(CompressionMethod::Stored);
+        writer.start_file(name, stored)?;
+        writer.write_all(data)?;
+        self.write_zip_entries(&mut writer, entries)?;
+        ending.set(true);
+        writer.finish()?.finish()
+    }
+
+    /// Returns a zip writer into `inner` ending the archive with the comment,
+    /// and the flag to set once every entry is written so entry comments and
+    /// DOS attributes get added
+    fn zip_writer<W: Write + Seek>(
+        &self,
+        inner: W,
+    ) -> ArcResult<(ZipWriter<CommentWriter<'_, W>>, Ending)> {
+        let comments: HashMap<_, _> = (self.entries.iter())
+            .filter_map(|entry| Some((entry.name.as_str(), entry.meta.comment.as_deref()?)))
+            .collect();
+        let lens = comments.values().map(|comment| comment.len());
+        if lens.chain([self.zip_comment.len()]).max() > Some(u16::MAX as usize) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "zip comments can't be longer than 65535 bytes",
+            )
+            .into());
+        }
+        let attributes = (self.entries.iter())
+            .filter_map(|entry| Some((entry.name.as_str(), entry.meta.dos_attributes?)))
+            .collect();
+        let (inner, ending) = CommentWriter::new(inner, comments, attributes)?;
+        let mut writer = ZipWriter::new(inner);
+        writer.set_raw_comment(self.zip_comment.clone().into());
+        Ok((writer, ending))
+    }
+
+    fn write_zip_entries<'a, W: Write + Seek>(
+        &self,
+        writer: &mut ZipWriter<W>,
+        entries: impl IntoIterator<Item = (usize, &'a ArcEntry)>,
+    ) -> ArcResult<()> {
+        for (i, entry) in entries {
+            let (name, data) = (&entry.name, &entry.data);
+            match &entry.meta.kind {
+                EntryKind::Directory => writer
+                    .add_directory(name, self.zip_metadata(entry, FullFileOptions::default())?)?,
+                EntryKind::File => {
+                    let options = self.zip_options(entry, self.file_len(i, data))?;
+                    writer.start_file(name.as_str(), options)?;
+                    io::copy(&mut self.file_data(i, data)?, writer)?;
+                }
+                EntryKind::Symlink(target) => writer.add_symlink(
+                    name,
+                    target,
+                    self.zip_metadata(entry, FullFileOptions::default())?,
+                )?,
+                EntryKind::HardLink(_) => return Err(self.cant_contain("hard links")),
+                EntryKind::Special(_) => return Err(self.cant_contain("special files")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the options for the zip `entry` of `len` bytes, compressing it
+    /// with its method and enabling ZIP64 when it could end up over 4 GiB
+    fn zip_options(&self, entry: &ArcEntry, len: u64) -> ArcResult<FullFileOptions<'_>> {
+        // deflate can slightly expand incompressible data, so leave headroom
+        const LARGE_FILE: u64 = u32::MAX as u64 - (u32::MAX as u64 >> 10);
+        let method = match entry.meta.method.as_ref().and_then(Method::zip) {
+            Some(method) => method,
+            None if self.zip_stored => CompressionMethod::Stored,
+            None => CompressionMethod::Deflated,
+        };
+        let options = FullFileOptions::default()
+            .compression_method(method)
+            .large_file(len >= LARGE_FILE);
+        let options = match &self.zip_password {
+            Some(password) => options.with_aes_encryption(AesMode::Aes256, password.expose()),
+            None => options,
+        };
+        self.zip_metadata(entry, options)
+    }
+
+    /// Sets the mode, times and extra fields of `entry` in `options`
+    fn zip_metadata<'k>(
+        &self,
+        entry: &ArcEntry,
+        mut options: FullFileOptions<'k>,
+    ) -> ArcResult<FullFileOptions<'k>> {
+        for field in &entry.meta.extra_fields {
+            options.add_extra_data(field.id, field.data.clone().into(), false)?;
+        }
+        if let Some(mtime) = entry.meta.mtime.filter(|_| self.zip_ntfs_times) {
+            let data = meta::ntfs_times(mtime, entry.meta.atime, entry.meta.ctime);
+            options.add_extra_data(meta::NTFS_ID, data.into(), false)?;
+        }
+        if entry.meta.atime.is_some() || entry.meta.ctime.is_some() {
+            let data =
+                meta::extended_timestamp(entry.meta.mtime, entry.meta.atime, entry.meta.ctime);
+            options.add_extra_data(meta::EXTENDED_TIMESTAMP_ID, data.into(), false)?;
+        }
+        if let Some(mode) = entry.meta.mode {
+            options = options.unix_permissions(mode);
+        }
+        match entry.meta.mtime.and_then(meta::to_zip_time) {
+            Some(time) => Ok(options.last_modified_time(time)),
+            None => Ok(options),
+        }
+    }
+
+    fn write_tar(&self, writer: impl Write) -> ArcResult<()> {
+        let mut builder = tar::Builder::new(writer);
+        for (i, entry) in self.entries.iter().enumerate() {
+            let data = &entry.data;
+            match &entry.meta.kind {
+                EntryKind::Directory => self.append_tar(&mut builder, entry, io::empty(), 0)?,
+                EntryKind::File => match self.sparse_map(i, data)? {
+                    Some(map) => {
+                        self.append_tar_sparse(&mut builder, entry, &map, self.file_data(i, data)?)?
+                    }
+                    None => {
+                        let len = self.file_len(i, data);
+                        self.append_tar(&mut builder, entry, self.file_data(i, data)?, len)?
+                    }
+                },
+                EntryKind::Symlink(target) => {
+                    self.append_tar_link(&mut builder, entry, target, tar::EntryType::Symlink)?
+                }
+                EntryKind::HardLink(target) => {
+                    self.append_tar_link(&mut builder, entry, target, tar::EntryType::Link)?
+                }
+                EntryKind::Special(special) => {
+                    self.append_tar_special(&mut builder, entry, *special)?
+                }
+            }
+        }
+        builder.finish()?;
+        Ok(())
+    }
+
+    /// Appends a file or directory with the configured header format
+    fn append_tar<W: Write>(
+        &self,
+        builder: &mut tar::Builder<W>,
+        entry: &ArcEntry,
+        data: impl Read,
+        len: u64,
+    ) -> io::Result<()> {
+        let name = entry.name.as_str();
+        let mut header = self.tar_header(
+            entry,
+            match entry.is_dir() {
+                true => tar::EntryType::Directory,
+                false => tar::EntryType::Regular,
+            },
+        )?;
+        header.set_size(len);
+        let records = self.pax_records(entry);
+        match self.tar_format {
+            TarFormat::Gnu => {
+                ArcWriter::append_pax_extensions(builder, records)?;
+                builder.append_data(&mut header, name, data)
+            }
+            TarFormat::Pax => {
+                ArcWriter::append_pax(builder, header, name, None, records, data, len)
+            }
+        }
+    }
+
+    /// Appends a link of type `kind` to `target` with the configured header
+    /// format
+    fn append_tar_link<W: Write>(
+        &self,
+        builder: &mut tar::Builder<W>,
+        entry: &ArcEntry,
+        target: &str,
+        kind: tar::EntryType,
+    ) -> io::Result<()> {
+        let name = entry.name.as_str();
+        let mut header = self.tar_header(entry, kind)?;
+        header.set_size(0);
+        let records = self.pax_records(entry);
+        match self.tar_format {
+            TarFormat::Gnu => {
+                ArcWriter::append_pax_extensions(builder, records)?;
+                builder.append_link(&mut header, name, target)
+            }
+            TarFormat::Pax => {
+                ArcWriter::append_pax(builder, header, name, Some(target), records, io::empty(), 0)
+            }
+        }
+    }
+
+    /// Appends a FIFO or device node with the configured header format
+    fn append_tar_special<W: Write>(
+        &self,
+        builder: &mut tar::Builder<W>,
+        entry: &ArcEntry,
+        special: SpecialFile,
+    ) -> io::Result<()> {
+        let name = entry.name.as_str();
+        let (kind, (major, minor)) = match special {
+            SpecialFile::Fifo => (tar::EntryType::Fifo, (0, 0)),
+            SpecialFile::CharDevice(major, minor) => (tar::EntryType::Char, (major, minor)),
+            SpecialFile::BlockDevice(major, minor) => (tar::EntryType::Block, (major, minor)),
+        };
+        let mut header = self.tar_header(entry, kind)?;
+        header.set_device_major(major)?;
+        header.set_device_minor(minor)?;
+        header.set_size(0);
+        let records = self.pax_records(entry);
+        match self.tar_format {
+            TarFormat::Gnu => {
+                ArcWriter::append_pax_extensions(builder, records)?;
+                builder.append_data(&mut header, name, io::empty())
+            }
+            TarFormat::Pax => {
+                ArcWriter::append_pax(builder, header, name, None, records, io::empty(), 0)
+            }
+        }
+    }
+
+    /// Returns the map of the file at `index` holding `data` if it's to be
+    /// stored as a sparse file
+    fn sparse_map(&self, index: usize, data: &[u8]) -> io::Result<Option<SparseMap>> {
+        let from_reader = self
+            .readers
+            .get(&index)
+            .is_some_and(|source| matches!(source.source, DataSource::Reader(_)));
+        match self.tar_sparse && !from_reader {
+            true => SparseMap::find(self.raw_file_data(index, data)?),
+            false => Ok(None),
+        }
+    }
+
+    /// Appends the sparse file `entry` mapped by `map`, reading its whole
+    /// data, holes included, from `data`
+    fn append_tar_sparse<W: Write>(
+        &self,
+        builder: &mut tar::Builder<W>,
+        entry: &ArcEntry,
+        map: &SparseMap,
+        data: impl Read,
+    ) -> io::Result<()> {
+        let name = entry.name.as_str();
+        let stored = sparse::Stored::new(data, map);
+        let records = self.pax_records(entry);
+        match self.tar_format {
+            TarFormat::Gnu => {
+                let mut header = self.tar_header(entry, tar::EntryType::GNUSparse)?;
+                let blocks = map.to_gnu(&mut header)?;
+                header.set_size(map.stored_len());
+                ArcWriter::append_pax_extensions(builder, records)?;
+                // the extension blocks go between the header and the data
+                builder.append_data(&mut header, name, (&blocks[..]).chain(stored))
+            }
+            TarFormat::Pax => {
+                let pax_map = map.to_pax();
+                let len = pax_map.len() as u64 + map.stored_len();
+                let mut header = self.tar_header(entry, tar::EntryType::Regular)?;
+                header.set_size(len);
+                let mut extensions = records;
+                extensions.extend([
+                    ("GNU.sparse.major".into(), b"1".to_vec()),
+                    ("GNU.sparse.minor".into(), b"0".to_vec()),
+                    ("GNU.sparse.name".into(), name.into()),
+                    ("GNU.sparse.realsize".into(), map.size.to_string().into()),
+                ]);
+                let path = sparse::pax_path(name);
+                let data = (&pax_map[..]).chain(stored);
+                ArcWriter::append_pax(builder, header, &path, None, extensions, data, len)
+            }
+        }
+    }
+
+    /// Returns the PAX records of the extended attributes of `entry`, and of
+    /// its access and creation times with [`TarFormat::Pax`]
+    fn pax_records(&self, entry: &ArcEntry) -> Vec<(String, Vec<u8>)> {
+        let mut records: Vec<_> = entry
+            .meta
+            .xattrs
+            .iter()
+            .map(|(key, value)| (format!("{}{key}", meta::PAX_XATTR), value.clone()))
+            .collect();
+        if self.tar_format == TarFormat::Pax {
+            let times = [("atime", entry.meta.atime), ("ctime", entry.meta.ctime)];
+            for (key, time) in times {
+                if let Some(time) = time {
+                    records.push((key.into(), meta::to_pax_time(time).into()));
+                }
+            }
+        }
+        records
+    }
+
+    /// Appends a PAX header holding `extensions` for the next entry, unless
+    /// there are none
+    fn append_pax_extensions<W: Write>(
+        builder: &mut tar::Builder<W>,
+        extensions: Vec<(String, Vec<u8>)>,
+    ) -> io::Result<()> {
+        if extensions.is_empty() {
+            return Ok(());
+        }
+        builder.append_pax_extensions(
+            extensions
+                .iter()
+                .map(|(key, value)| (key.as_str(), &value[..])),
+        )
+    }
+
+    /// Returns a header of type `kind` with the mode, time and owner of
+    /// `entry`
+    fn tar_header(&self, entry: &ArcEntry, kind: tar::EntryType) -> io::Result<tar::Header> {
+        use tar::Header;
+
+        let mut header = match self.tar_format {
+            TarFormat::Gnu => Header::new_gnu(),
+            TarFormat::Pax => Header::new_ustar(),
+        };
+        let default_mode = match entry.is_dir() {
+            true => self.tar_dir_mode,
+            false => self.tar_file_mode,
+        };
+        header.set_mode(self.mode(entry, default_mode));
+        header.set_mtime(meta::secs(self.mtime(entry)));
+        if let Some(gnu) = header.as_gnu_mut() {
+            // PAX headers get records instead
+            if let Some(atime) = entry.meta.atime {
+                gnu.set_atime(meta::secs(atime));
+            }
+            if let Some(ctime) = entry.meta.ctime {
+                gnu.set_ctime(meta::secs(ctime));
+            }
+        }
+        let current;
+        let owner = match entry
+            .meta
+            .owner
+            .as_ref()
+            .or(self.tar_default_owner.as_ref())
+        {
+            Some(owner) => owner,
+            None => {
+                current = Owner::current();
+                &current
+            }
+        };
+        header.set_uid(owner.uid);
+        header.set_gid(owner.gid);
+        if let (Some(user), false) = (&owner.user, self.tar_numeric_owner) {
+            header.set_username(user)?;
+        }
+        if let (Some(group), false) = (&owner.group, self.tar_numeric_owner) {
+            header.set_groupname(group)?;
+        }
+        header.set_entry_type(kind);
+        Ok(header)
+    }
+
+    /// Appends an entry with a ustar header, moving its path, link target and
+    /// size to a PAX extended header when they don't fit
+    fn append_pax<W: Write>(
+        builder: &mut tar::Builder<W>,
+        mut header: tar::Header,
+        name: &str,
+        link: Option<&str>,
+        mut extensions: Vec<(String, Vec<u8>)>,
+        data: impl Read,
+        len: u64,
+    ) -> io::Result<()> {
+        const USTAR_MAX_SIZE: u64 = 0o77777777777;
+
+        if header.set_path(name).is_err() {
+            extensions.push(("path".into(), name.into()));
+            // strict readers that ignore PAX headers get a truncated path
+            let ustar = header.as_ustar_mut().unwrap();
+            ustar.prefix.fill(0);
+            ustar.name.fill(0);
+            let len = name.len().min(ustar.name.len());
+            ustar.name[..len].copy_from_slice(&name.as_bytes()[..len]);
+        }
+        if let Some(link) = link {
+            if header.set_link_name(link).is_err() {
+                extensions.push(("linkpath".into(), link.into()));
+            }
+        }
+        if len > USTAR_MAX_SIZE {
+            extensions.push(("size".into(), len.to_string().into()));
+        }
+        ArcWriter::append_pax_extensions(builder, extensions)?;
+        header.set_cksum();
+        builder.append(&header, data)
+    }
+
+    /// Returns the error for entries of a kind the target format can't store
+    fn cant_contain(&self, kind: &str) -> ArcError {
+        let format = format!("{:?}", self.format).to_lowercase();
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{format} archives can't contain {kind}"),
+        )
+        .into()
+    }
+
+    /// Writes the 7z archive, `inner` has to be at its start
+    fn write_7z<W: Write + Seek>(&self, inner: W) -> ArcResult<W> {
+        let mut archive = SevenZWriter::new(inner)?;
+        archive.set_encrypt_header(self.sevenz_encrypt_header);
+        // the header is encrypted with the last methods set, so even
+        // archives without files get it
+        if let Some(aes) = self.sevenz_aes() {
+            archive.set_content_methods(vec![aes, SevenZMethod::LZMA2.into()]);
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            let data = &entry.data;
+            match &entry.meta.kind {
+                EntryKind::Directory => {
+                    let szentry = self.sevenz_entry(entry);
                     archive.push_archive_entry::<&[u8]>(szentry, None)?;
                 }
-                ArcEntry::File(name, data) => {
-                    szentry.name.clone_from(name);
-                    archive.push_archive_entry(szentry, Some(&data[..]))?;
+                EntryKind::File if !self.sevenz_solid || self.file_len(i, data) == 0 => {
+                    archive.set_content_methods(self.sevenz_methods(entry));
+                    let szentry = self.sevenz_entry(entry);
+                    archive.push_archive_entry(szentry, Some(self.file_data(i, data)?))?;
+                }
+                EntryKind::File => {}
+                EntryKind::Symlink(target) => {
+                    let mut szentry = self.sevenz_entry(entry);
+                    szentry.has_windows_attributes = true;
+                    szentry.windows_attributes = (meta::SYMLINK_TYPE | self.mode(entry, 0o777))
+                        << 16
+                        | meta::SEVENZ_UNIX_EXTENSION;
+                    archive.push_archive_entry(szentry, Some(target.as_bytes()))?;
                 }
+                EntryKind::HardLink(_) => return Err(self.cant_contain("hard links")),
+                EntryKind::Special(_) => return Err(self.cant_contain("special files")),
             }
         }
-        archive.finish()?;
-        Ok(inner)
+        if self.sevenz_solid {
+            for block in self.sevenz_blocks() {
+                archive.set_content_methods(self.sevenz_methods(block[0].1));
+                let mut entries = Vec::with_capacity(block.len());
+                let mut readers = Vec::with_capacity(block.len());
+                for (i, entry) in block {
+                    let mut szentry = self.sevenz_entry(entry);
+                    szentry.has_stream = true;
+                    entries.push(szentry);
+                    readers.push(SourceReader::new(self.file_data(i, &entry.data)?));
+                }
+                archive.push_archive_entries(entries, SeqReader::new(readers))?;
+            }
+        }
+        Ok(archive.finish()?)
+    }
+
+    fn sevenz_entry(&self, entry: &ArcEntry) -> SevenZArchiveEntry {
+        const DIRECTORY_ATTRIBUTE: u32 = 0x10;
+
+        let directory = entry.is_dir();
+        let mut szentry = SevenZArchiveEntry::default();
+        szentry.name = entry.name.clone();
+        szentry.is_directory = directory;
+        szentry.has_last_modified_date = true;
+        szentry.last_modified_date =
+            FileTime::try_from(self.mtime(entry)).unwrap_or(FileTime::NT_TIME_EPOCH);
+        if let Some(atime) = entry.meta.atime {
+            szentry.has_access_date = true;
+            szentry.access_date = FileTime::try_from(atime).unwrap_or(FileTime::NT_TIME_EPOCH);
+        }
+        if let Some(ctime) = entry.meta.ctime {
+            szentry.has_creation_date = true;
+            szentry.creation_date = FileTime::try_from(ctime).unwrap_or(FileTime::NT_TIME_EPOCH);
+        }
+        if let Some(mode) = entry.meta.mode {
+            // stored in the upper half of the attributes, like p7zip does
+            szentry.has_windows_attributes = true;
+            szentry.windows_attributes = (mode & 0o7777) << 16 | meta::SEVENZ_UNIX_EXTENSION;
+            if directory {
+                szentry.windows_attributes |= DIRECTORY_ATTRIBUTE;
+            }
+        }
+        szentry
+    }
+
+    /// Groups the non-empty files into 7z solid blocks, following the
+    /// configured limits, as their indices and entries
+    fn sevenz_blocks(&self) -> Vec<Vec<(usize, &ArcEntry)>> {
+        struct Block<'a> {
+            codec: SevenzCodec,
+            extension: Option<&'a OsStr>,
+            size: u64,
+            files: Vec<(usize, &'a ArcEntry)>,
+        }
+
+        let mut blocks: Vec<Block> = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            if !entry.is_file() {
+                continue;
+            }
+            let len = self.file_len(index, &entry.data);
+            if len == 0 {
+                continue;
+            }
+            let extension = match self.sevenz_solid_by_extension {
+                true => Path::new(&entry.name).extension(),
+                false => None,
+            };
+            let codec = self.sevenz_codec(entry);
+            let open = blocks
+                .iter()
+                .rposition(|block| block.codec == codec && block.extension == extension)
+                .filter(|&i| {
+                    let block = &blocks[i];
+                    (self.sevenz_solid_files == 0 || block.files.len() < self.sevenz_solid_files)
+                        && (self.sevenz_solid_size == 0
+                            || block.size + len <= self.sevenz_solid_size)
+                });
+            let i = open.unwrap_or_else(|| {
+                blocks.push(Block {
+                    codec,
+                    extension,
+                    size: 0,
+                    files: Vec::new(),
+                });
+                blocks.len() - 1
+            });
+            blocks[i].size += len;
+            blocks[i].files.push((index, entry));
+        }
+        blocks.into_iter().map(|block| block.files).collect()
+    }
+
+    /// Returns the codec of the 7z `entry`
+    fn sevenz_codec(&self, entry: &ArcEntry) -> SevenzCodec {
+        let method = entry.meta.method.as_ref().and_then(Method::sevenz);
+        method.unwrap_or(self.sevenz_codec)
+    }
+
+    /// Returns the 7z methods of `entry`, its compression method preceded by
+    /// encryption with `sevenz_password`
+    fn sevenz_methods(&self, entry: &ArcEntry) -> Vec<SevenZMethodConfiguration> {
+        self.sevenz_aes()
+            .into_iter()
+            .chain([self.sevenz_method(entry)])
+            .collect()
+    }
+
+    /// Returns the 7z encryption method for `sevenz_password`, if set
+    fn sevenz_aes(&self) -> Option<SevenZMethodConfiguration> {
+        let password = self.sevenz_password.as_ref()?;
+        Some(AesEncoderOptions::new(password.expose().into()).into())
+    }
+
+    /// Returns the 7z compression method of `entry` built from its codec and
+    /// the configured level and dictionary size
+    fn sevenz_method(&self, entry: &ArcEntry) -> SevenZMethodConfiguration {
+        let mut options = LZMA2Options::with_preset(self.sevenz_level.min(9));
+        if self.sevenz_dict_size != 0 {
+            options.dict_size = self.sevenz_dict_size.clamp(DICT_SIZE_MIN, DICT_SIZE_MAX);
+        }
+        let method = match self.sevenz_codec(entry) {
+            SevenzCodec::Lzma => SevenZMethod::LZMA,
+            SevenzCodec::Lzma2 => SevenZMethod::LZMA2,
+        };
+        SevenZMethodConfiguration::new(method).with_options(options.into())
+    }
+
+    fn write_cpio(&self, mut writer: impl Write) -> ArcResult<()> {
+        for (i, entry) in self.entries.iter().enumerate() {
+            let (data, file_type, (major, minor)) = match &entry.meta.kind {
+                EntryKind::Directory => (&[][..], ModeFileType::Directory, (0, 0)),
+                EntryKind::File => (&entry.data[..], ModeFileType::Regular, (0, 0)),
+                // the target is stored as the link's data
+                EntryKind::Symlink(target) => (target.as_bytes(), ModeFileType::Symlink, (0, 0)),
+                EntryKind::HardLink(_) => return Err(self.cant_contain("hard links")),
+                EntryKind::Special(special) => match *special {
+                    SpecialFile::Fifo => (&[][..], ModeFileType::Fifo, (0, 0)),
+                    SpecialFile::CharDevice(major, minor) => {
+                        (&[][..], ModeFileType::Char, (major, minor))
+                    }
+                    SpecialFile::BlockDevice(major, minor) => {
+                        (&[][..], ModeFileType::Block, (major, minor))
+                    }
+                },
+            };
+            let size = u32::try_from(self.file_len(i, data)).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "file too large for cpio")
+            })?;
+            let mut writer = NewcBuilder::new(&entry.name)
+                .ino(i as u32 + 1)
+                .mode(self.mode(entry, 0o766))
+                .set_mode_file_type(file_type)
+                .rdev_major(major)
+                .rdev_minor(minor)
+                .mtime(meta::secs(self.mtime(entry)).try_into().unwrap_or(u32::MAX))
+                .write(&mut writer, size);
+            io::copy(&mut self.file_data(i, data)?, &mut writer)?;
+            writer.finish()?;
+        }
+        cpio::newc::trailer(writer)?;
+        Ok(())
+    }
+
+    fn write_ar(&self, writer: impl Write) -> ArcResult<()> {
+        let mut builder = ArBuilder::new(writer);
+        for (i, entry) in self.entries.iter().enumerate() {
+            match &entry.meta.kind {
+                EntryKind::Directory => return Err(self.cant_contain("directories")),
+                EntryKind::Symlink(_) => return Err(self.cant_contain("symlinks")),
+                EntryKind::HardLink(_) => return Err(self.cant_contain("hard links")),
+                EntryKind::Special(_) => return Err(self.cant_contain("special files")),
+                EntryKind::File => {
+                    let len = self.file_len(i, &entry.data);
+                    let mut header = ArHeader::new(entry.name.as_bytes().to_vec(), len);
+                    header.set_mode(0o100000 | self.mode(entry, 0o644));
+                    header.set_mtime(meta::secs(self.mtime(entry)));
+                    builder.append(&header, self.file_data(i, &entry.data)?)?;
+                }
+            }
+        }
+        builder.into_inner()?;
+        Ok(())
+    }
+
+    /// Returns the permission bits of `entry`, or `default`
+    fn mode(&self, entry: &ArcEntry, default: u32) -> u32 {
+        entry.meta.mode.map_or(default, |mode| mode & 0o7777)
+    }
+
+    /// Returns the modification time of `entry`, or the current time
+    fn mtime(&self, entry: &ArcEntry) -> SystemTime {
+        entry.meta.mtime.unwrap_or_else(SystemTime::now)
+    }
+
+    /// Returns the name and data of the only entry, which must be a file
+    fn single_file(&self) -> ArcResult<(&str, &[u8])> {
+        match &self.entries[..] {
+            [entry] if entry.is_file() => Ok((&entry.name, &entry.data)),
+            _ => Err(ArcError::SingleFileOnly),
+        }
+    }
+
+    /// Compresses everything `write` writes into `writer` with the compressor
+    /// of the target format, storing `name` in gzip headers unless it's empty
+    fn compress<W: Write>(
+        &self,
+        writer: W,
+        name: &str,
+        write: impl FnOnce(&mut dyn Write) -> ArcResult<()>,
+    ) -> ArcResult<()> {
+        let mut encoder = Encoder::new(self, writer, name)?;
+        write(&mut encoder)?;
+        encoder.finish()?;
+        Ok(())
     }
 }
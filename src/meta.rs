@@ -0,0 +1,616 @@
+//! Reading entry metadata from archive headers, without decoding file data
+//! where the format allows it
+
+use crate::{
+    crx, guard, name, ArcError, ArcFormat, ArcMeta, ArcReader, ArcResult, ArcStreamEntry,
+    ArcStreamReader, DosAttributes, EntryInfo, Method, Owner, SpecialFile, TarStream,
+    ZipExtraField,
+};
+use ar::{Archive as ArArchive, Header as ArHeader};
+use cpio::{newc::Entry as CpioEntry, NewcReader};
+use sevenz_rust::{Archive, SevenZArchiveEntry, SevenZMethod};
+use std::{
+    io::Cursor,
+    str::Utf8Error,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use zip::{
+    extra_fields::ExtraField, read::ZipFile, CompressionMethod, DateTime, HasZipMetadata,
+    ZipArchive,
+};
+
+/// Windows attribute flag marking Unix permissions in the upper 16 bits
+pub(crate) const SEVENZ_UNIX_EXTENSION: u32 = 0x8000;
+/// File type bits of a symbolic link's Unix mode
+pub(crate) const SYMLINK_TYPE: u32 = 0o120000;
+/// Prefix of the PAX records holding extended attributes
+pub(crate) const PAX_XATTR: &str = "SCHILY.xattr.";
+/// Header id of the zip extended timestamp field
+pub(crate) const EXTENDED_TIMESTAMP_ID: u16 = 0x5455;
+/// Header id of the zip NTFS field
+pub(crate) const NTFS_ID: u16 = 0x000a;
+/// Header ids of the zip fields describing how an entry is stored, ZIP64 and
+/// AES
+const STORAGE_IDS: [u16; 2] = [0x0001, 0x9901];
+
+/// Reads the metadata of every entry by name, or of none if the format doesn't
+/// store any
+///
+/// The kinds of the entries aren't read.
+pub(crate) fn read(buf: &[u8]) -> ArcResult<Vec<(String, ArcMeta)>> {
+    guard(|| read_unguarded(buf))
+}
+
+fn read_unguarded(buf: &[u8]) -> ArcResult<Vec<(String, ArcMeta)>> {
+    if let TarStream::Tar(format, decoder) = ArcReader::tar_stream(buf)? {
+        return read_tar(ArcStreamReader::from_decoder(format, decoder));
+    }
+    match ArcReader::detect(buf) {
+        ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub => read_zip(buf),
+        ArcFormat::Crx => read_zip(crx::split(buf)?.1),
+        ArcFormat::Sevenz => read_7z(buf),
+        ArcFormat::Cpio => read_cpio(buf),
+        ArcFormat::Ar | ArcFormat::Deb => read_ar(buf),
+        _ => Ok(Vec::new()),
+    }
+}
+
+fn read_tar(mut reader: ArcStreamReader) -> ArcResult<Vec<(String, ArcMeta)>> {
+    let mut metas = Vec::new();
+    while let Some(entry) = reader.next_entry()? {
+        metas.push((entry.name().to_owned(), tar_meta(&entry)?));
+    }
+    Ok(metas)
+}
+
+/// Returns the metadata of the tar `entry`, except its kind
+pub(crate) fn tar_meta(entry: &ArcStreamEntry) -> ArcResult<ArcMeta> {
+    let meta = tar_header_meta(entry.header())?;
+    Ok(ArcMeta {
+        atime: entry.atime().or(meta.atime),
+        ctime: entry.ctime().or(meta.ctime),
+        xattrs: entry.xattrs().clone(),
+        raw_name: entry.raw_name().map(<[u8]>::to_vec),
+        ..meta
+    })
+}
+
+/// Returns the metadata in the tar `header`, except its kind and what
+/// extension headers hold
+pub(crate) fn tar_header_meta(header: &tar::Header) -> ArcResult<ArcMeta> {
+    Ok(ArcMeta {
+        mode: Some(header.mode()? & 0o7777),
+        mtime: Some(from_secs(header.mtime()?)),
+        // only GNU headers have room for these, left zero when unknown
+        atime: header
+            .as_gnu()
+            .and_then(|gnu| gnu.atime().ok())
+            .filter(|&secs| secs != 0)
+            .map(from_secs),
+        ctime: header
+            .as_gnu()
+            .and_then(|gnu| gnu.ctime().ok())
+            .filter(|&secs| secs != 0)
+            .map(from_secs),
+        owner: Some(Owner {
+            uid: header.uid()?,
+            gid: header.gid()?,
+            user: owner_name(header.username()),
+            group: owner_name(header.groupname()),
+        }),
+        ..ArcMeta::default()
+    })
+}
+
+/// Returns a user or group name, if it's set and valid UTF-8
+fn owner_name(name: Result<Option<&str>, Utf8Error>) -> Option<String> {
+    name.ok()
+        .flatten()
+        .filter(|name| !name.is_empty())
+        .map(str::to_owned)
+}
+
+fn read_zip(buf: &[u8]) -> ArcResult<Vec<(String, ArcMeta)>> {
+    let mut archive = ZipArchive::new(Cursor::new(buf))?;
+    let mut metas = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        metas.push((file.name().to_owned(), zip_meta(&file)));
+    }
+    Ok(metas)
+}
+
+/// Returns the metadata of the zip `file`, except its kind
+pub(crate) fn zip_meta(file: &ZipFile) -> ArcMeta {
+    let [mtime, atime, ctime] = zip_times(file.extra_data_fields());
+    ArcMeta {
+        mode: file.unix_mode().map(|mode| mode & 0o7777),
+        mtime: mtime.or_else(|| file.last_modified().and_then(from_zip_time)),
+        atime,
+        ctime,
+        method: Some(zip_method(file.compression())),
+        comment: Some(file.comment().to_owned()).filter(|comment| !comment.is_empty()),
+        // kept in the low byte of the external attributes
+        dos_attributes: Some(DosAttributes::from_bits(
+            file.get_metadata().external_attributes as u8,
+        ))
+        .filter(|attributes| *attributes != DosAttributes::default()),
+        extra_fields: zip_extra_fields(file.extra_data().unwrap_or_default()),
+        ..ArcMeta::default()
+    }
+}
+
+/// Returns the extra fields in the central header `data` of a zip entry,
+/// except the ones held elsewhere in [`ArcMeta`] or describing how it's
+/// stored
+fn zip_extra_fields(mut data: &[u8]) -> Vec<ZipExtraField> {
+    let mut fields = Vec::new();
+    while let [id0, id1, len0, len1, rest @ ..] = data {
+        let len = usize::from(u16::from_le_bytes([*len0, *len1]));
+        // a truncated field ends them
+        let Some(field) = rest.get(..len) else {
+            break;
+        };
+        let id = u16::from_le_bytes([*id0, *id1]);
+        if !STORAGE_IDS.contains(&id) && ![NTFS_ID, EXTENDED_TIMESTAMP_ID].contains(&id) {
+            fields.push(ZipExtraField {
+                id,
+                data: field.to_vec(),
+            });
+        }
+        data = &rest[len..];
+    }
+    fields
+}
+
+/// Returns the method of zip entries compressed with `method`
+fn zip_method(method: CompressionMethod) -> Method {
+    match method {
+        CompressionMethod::Stored => Method::Stored,
+        CompressionMethod::Deflated => Method::Deflated,
+        CompressionMethod::Bzip2 => Method::Bzip2,
+        CompressionMethod::Zstd => Method::Zstd,
+        CompressionMethod::Xz => Method::Xz,
+        CompressionMethod::Lzma => Method::Lzma,
+        method => Method::Other(method.to_string()),
+    }
+}
+
+fn read_7z(buf: &[u8]) -> ArcResult<Vec<(String, ArcMeta)>> {
+    let archive = Archive::read(&mut Cursor::new(buf), buf.len() as u64, &[])?;
+    Ok(archive
+        .files
+        .iter()
+        .zip(&archive.stream_map.file_folder_index)
+        .map(|(file, &block)| {
+            let meta = ArcMeta {
+                method: block.map(|block| sevenz_method(&archive, block)),
+                ..sevenz_meta(file)
+            };
+            (file.name.clone(), meta)
+        })
+        .collect())
+}
+
+/// Returns the compression method of the 7z `block`, leaving out encryption
+pub(crate) fn sevenz_method(archive: &Archive, block: usize) -> Method {
+    let methods: Vec<_> = archive.folders[block]
+        .coders
+        .iter()
+        .map(|coder| SevenZMethod::by_id(coder.decompression_method_id()))
+        .filter(|&method| method != Some(SevenZMethod::AES256SHA256))
+        .collect();
+    match methods[..] {
+        [Some(SevenZMethod::COPY)] => Method::Stored,
+        [Some(SevenZMethod::DEFLATE)] => Method::Deflated,
+        [Some(SevenZMethod::BZIP2)] => Method::Bzip2,
+        [Some(SevenZMethod::ZSTD)] => Method::Zstd,
+        [Some(SevenZMethod::LZMA)] => Method::Lzma,
+        [Some(SevenZMethod::LZMA2)] => Method::Lzma2,
+        _ => Method::Other(
+            methods
+                .iter()
+                .map(|method| method.map_or("Unknown", |method| method.name()))
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+    }
+}
+
+/// Returns the compression methods of the files of the 7z `archive`, in the
+/// order `SevenZReader::for_each_entries` visits them as long as none of them
+/// stops it
+pub(crate) fn sevenz_methods(archive: &Archive) -> Vec<Option<Method>> {
+    let mut methods = Vec::with_capacity(archive.files.len());
+    for (block, folder) in archive.folders.iter().enumerate() {
+        let method = sevenz_method(archive, block);
+        methods.extend((0..folder.num_unpack_sub_streams).map(|_| Some(method.clone())));
+    }
+    let unpacked = archive.stream_map.file_folder_index.iter();
+    methods.extend(unpacked.filter(|block| block.is_none()).map(|_| None));
+    methods
+}
+
+/// Returns the metadata of the 7z `file`, except its kind
+pub(crate) fn sevenz_meta(file: &SevenZArchiveEntry) -> ArcMeta {
+    ArcMeta {
+        mode: (file.has_windows_attributes && file.windows_attributes & SEVENZ_UNIX_EXTENSION != 0)
+            .then_some((file.windows_attributes >> 16) & 0o7777),
+        mtime: file
+            .has_last_modified_date
+            .then(|| file.last_modified_date.into()),
+        atime: file.has_access_date.then(|| file.access_date.into()),
+        ctime: file.has_creation_date.then(|| file.creation_date.into()),
+        ..ArcMeta::default()
+    }
+}
+
+/// Lists the entries of `buf` with their sizes, falling back to reading the
+/// whole archive for formats whose headers aren't read
+pub(crate) fn list(buf: &[u8]) -> ArcResult<Vec<EntryInfo>> {
+    guard(|| list_unguarded(buf))
+}
+
+fn list_unguarded(buf: &[u8]) -> ArcResult<Vec<EntryInfo>> {
+    if let TarStream::Tar(format, decoder) = ArcReader::tar_stream(buf)? {
+        return list_tar(ArcStreamReader::from_decoder(format, decoder));
+    }
+    match ArcReader::detect(buf) {
+        ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub => list_zip(buf),
+        ArcFormat::Crx => list_zip(crx::split(buf)?.1),
+        ArcFormat::Sevenz => list_7z(buf),
+        _ => Ok(ArcReader::new(buf)?
+            .entries()
+            .iter()
+            .map(|entry| EntryInfo {
+                name: entry.name.clone(),
+                directory: entry.is_dir(),
+                size: entry.data.len() as u64,
+                compressed_size: None,
+                method: None,
+            })
+            .collect()),
+    }
+}
+
+fn list_tar(mut reader: ArcStreamReader) -> ArcResult<Vec<EntryInfo>> {
+    let mut infos = Vec::new();
+    while let Some(entry) = reader.next_entry()? {
+        infos.push(EntryInfo {
+            name: entry.name().to_owned(),
+            directory: entry.is_dir(),
+            size: entry.size(),
+            compressed_size: None,
+            method: None,
+        });
+    }
+    Ok(infos)
+}
+
+fn list_zip(buf: &[u8]) -> ArcResult<Vec<EntryInfo>> {
+    let mut archive = ZipArchive::new(Cursor::new(buf))?;
+    let mut infos = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        infos.push(EntryInfo {
+            name: file.name().to_owned(),
+            directory: file.is_dir(),
+            size: file.size(),
+            compressed_size: Some(file.compressed_size()),
+            method: Some(zip_method(file.compression())),
+        });
+    }
+    Ok(infos)
+}
+
+fn list_7z(buf: &[u8]) -> ArcResult<Vec<EntryInfo>> {
+    let archive = Archive::read(&mut Cursor::new(buf), buf.len() as u64, &[])?;
+    Ok(archive
+        .files
+        .iter()
+        .zip(&archive.stream_map.file_folder_index)
+        .map(|(file, &block)| {
+            let folder = block.map(|block| &archive.folders[block]);
+            EntryInfo {
+                name: file.name.clone(),
+                directory: file.is_directory,
+                size: file.size,
+                // the packed size of a solid block is only its first file's
+                compressed_size: match folder {
+                    Some(folder) => {
+                        (folder.num_unpack_sub_streams == 1).then_some(file.compressed_size)
+                    }
+                    None => Some(0),
+                },
+                method: block.map(|block| sevenz_method(&archive, block)),
+            }
+        })
+        .collect())
+}
+
+/// Fails with [`ArcError::TooManyEntries`] if the headers of `buf` show more
+/// than `max` entries, stopping at the first one over for tarballs
+///
+/// Formats whose headers aren't read pass.
+pub(crate) fn check_count(buf: &[u8], max: usize) -> ArcResult<()> {
+    let count = if let TarStream::Tar(format, decoder) = ArcReader::tar_stream(buf)? {
+        let mut reader = ArcStreamReader::from_decoder(format, decoder);
+        let mut count = 0;
+        while count <= max && reader.next_entry()?.is_some() {
+            count += 1;
+        }
+        count
+    } else {
+        match ArcReader::detect(buf) {
+            ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub => {
+                ZipArchive::new(Cursor::new(buf))?.len()
+            }
+            ArcFormat::Crx => ZipArchive::new(Cursor::new(crx::split(buf)?.1))?.len(),
+            ArcFormat::Sevenz => Archive::read(&mut Cursor::new(buf), buf.len() as u64, &[])?
+                .files
+                .len(),
+            _ => 0,
+        }
+    };
+    match count > max {
+        true => Err(ArcError::TooManyEntries(max)),
+        false => Ok(()),
+    }
+}
+
+/// Returns whether the 7z `entry` is a symbolic link, whose data is its target
+pub(crate) fn is_sevenz_symlink(entry: &SevenZArchiveEntry) -> bool {
+    entry.has_windows_attributes
+        && entry.windows_attributes & SEVENZ_UNIX_EXTENSION != 0
+        && (entry.windows_attributes >> 16) & 0o170000 == SYMLINK_TYPE
+}
+
+/// Returns what special file the tar `header` describes, if any
+pub(crate) fn tar_special(header: &tar::Header) -> Option<SpecialFile> {
+    let major = || header.device_major().ok().flatten().unwrap_or(0);
+    let minor = || header.device_minor().ok().flatten().unwrap_or(0);
+    match header.entry_type() {
+        tar::EntryType::Fifo => Some(SpecialFile::Fifo),
+        tar::EntryType::Char => Some(SpecialFile::CharDevice(major(), minor())),
+        tar::EntryType::Block => Some(SpecialFile::BlockDevice(major(), minor())),
+        _ => None,
+    }
+}
+
+/// Returns what special file the cpio `entry` is, if any
+pub(crate) fn cpio_special(entry: &CpioEntry) -> Option<SpecialFile> {
+    let (major, minor) = (entry.rdev_major(), entry.rdev_minor());
+    match entry.mode() & 0o170000 {
+        0o010000 => Some(SpecialFile::Fifo),
+        0o020000 => Some(SpecialFile::CharDevice(major, minor)),
+        0o060000 => Some(SpecialFile::BlockDevice(major, minor)),
+        _ => None,
+    }
+}
+
+fn read_cpio(mut buf: &[u8]) -> ArcResult<Vec<(String, ArcMeta)>> {
+    let mut metas = Vec::new();
+    loop {
+        let reader = NewcReader::new(buf)?;
+        let entry = reader.entry();
+        if entry.is_trailer() {
+            break;
+        }
+        metas.push((entry.name().to_owned(), cpio_meta(entry)));
+        buf = reader.finish()?;
+    }
+    Ok(metas)
+}
+
+/// Returns the metadata of the cpio `entry`, except its kind
+pub(crate) fn cpio_meta(entry: &CpioEntry) -> ArcMeta {
+    ArcMeta {
+        mode: Some(entry.mode() & 0o7777),
+        mtime: Some(from_secs(entry.mtime().into())),
+        ..ArcMeta::default()
+    }
+}
+
+fn read_ar(buf: &[u8]) -> ArcResult<Vec<(String, ArcMeta)>> {
+    let mut archive = ArArchive::new(buf);
+    let mut metas = Vec::new();
+    while let Some(entry) = archive.next_entry() {
+        let entry = entry?;
+        let name = String::from_utf8_lossy(entry.header().identifier()).into_owned();
+        metas.push((name, ar_meta(entry.header())));
+    }
+    Ok(metas)
+}
+
+/// Returns the metadata in the ar `header`, except its kind
+pub(crate) fn ar_meta(header: &ArHeader) -> ArcMeta {
+    ArcMeta {
+        mode: Some(header.mode() & 0o7777),
+        mtime: Some(from_secs(header.mtime())),
+        raw_name: name::raw(header.identifier()),
+        ..ArcMeta::default()
+    }
+}
+
+fn from_secs(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// Returns the seconds since the Unix epoch, `0` for earlier times
+pub(crate) fn secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Returns the modification, access and creation times of the NTFS field, to
+/// 100 nanoseconds, or else of the extended timestamp field, which unlike the
+/// DOS time is in UTC and to the second
+fn zip_times<'a>(fields: impl Iterator<Item = &'a ExtraField>) -> [Option<SystemTime>; 3] {
+    let mut extended = [None; 3];
+    for field in fields {
+        match field {
+            ExtraField::Ntfs(ntfs) => {
+                let atime = (ntfs.atime() != 0).then(|| from_file_time(ntfs.atime()));
+                let ctime = (ntfs.ctime() != 0).then(|| from_file_time(ntfs.ctime()));
+                return [
+                    from_file_time(ntfs.mtime()),
+                    atime.flatten(),
+                    ctime.flatten(),
+                ];
+            }
+            ExtraField::ExtendedTimestamp(timestamp) => {
+                let times = [
+                    timestamp.mod_time(),
+                    timestamp.ac_time(),
+                    timestamp.cr_time(),
+                ];
+                extended = times.map(|secs| secs.map(|secs| from_secs(secs.into())));
+            }
+        }
+    }
+    extended
+}
+
+/// Returns the data of an extended timestamp field holding the times that are
+/// known, in seconds
+pub(crate) fn extended_timestamp(
+    mtime: Option<SystemTime>,
+    atime: Option<SystemTime>,
+    ctime: Option<SystemTime>,
+) -> Vec<u8> {
+    let mut data = vec![0];
+    for (bit, time) in [mtime, atime, ctime].into_iter().enumerate() {
+        if let Some(time) = time {
+            data[0] |= 1 << bit;
+            let secs = u32::try_from(secs(time)).unwrap_or(u32::MAX);
+            data.extend_from_slice(&secs.to_le_bytes());
+        }
+    }
+    data
+}
+
+/// Returns the data of an NTFS field holding the times, `0` for the ones that
+/// aren't known
+pub(crate) fn ntfs_times(
+    mtime: SystemTime,
+    atime: Option<SystemTime>,
+    ctime: Option<SystemTime>,
+) -> Vec<u8> {
+    // reserved, then the tag and size of the attribute holding the times
+    let mut data = vec![0; 4];
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&24u16.to_le_bytes());
+    for time in [Some(mtime), atime, ctime] {
+        data.extend_from_slice(&time.map_or(0, to_file_time).to_le_bytes());
+    }
+    data
+}
+
+/// Parses the value of a PAX time record, seconds since the Unix epoch with an
+/// optional fraction
+pub(crate) fn from_pax_time(value: &[u8]) -> Option<SystemTime> {
+    let value = std::str::from_utf8(value).ok()?;
+    let (secs, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > 9 || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let nanos = match fraction {
+        "" => 0,
+        _ => format!("{fraction:0<9}").parse().ok()?,
+    };
+    Some(UNIX_EPOCH + Duration::new(secs.parse().ok()?, nanos))
+}
+
+/// Formats `time` as the value of a PAX time record, `0` for times before the
+/// Unix epoch
+pub(crate) fn to_pax_time(time: SystemTime) -> String {
+    let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    match since.subsec_nanos() {
+        0 => since.as_secs().to_string(),
+        nanos => {
+            let fraction = format!("{nanos:09}");
+            format!("{}.{}", since.as_secs(), fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+/// Time between the Windows file time epoch, 1601, and the Unix epoch
+const FILE_TIME_EPOCH: Duration = Duration::from_secs(11_644_473_600);
+/// Windows file time ticks per second
+const FILE_TIME_TICKS: u64 = 10_000_000;
+
+/// Converts a Windows file time, counting 100 nanoseconds since 1601
+pub(crate) fn from_file_time(time: u64) -> Option<SystemTime> {
+    let since = Duration::new(
+        time / FILE_TIME_TICKS,
+        (time % FILE_TIME_TICKS) as u32 * 100,
+    );
+    UNIX_EPOCH.checked_sub(FILE_TIME_EPOCH)?.checked_add(since)
+}
+
+/// Converts `time` to a Windows file time, `0` for times before 1601
+pub(crate) fn to_file_time(time: SystemTime) -> u64 {
+    UNIX_EPOCH
+        .checked_sub(FILE_TIME_EPOCH)
+        .and_then(|epoch| time.duration_since(epoch).ok())
+        .map_or(0, |since| {
+            since
+                .as_secs()
+                .saturating_mul(FILE_TIME_TICKS)
+                .saturating_add(u64::from(since.subsec_nanos() / 100))
+        })
+}
+
+/// Converts a DOS time, taken as UTC
+fn from_zip_time(time: DateTime) -> Option<SystemTime> {
+    let days = days_from_civil(time.year().into(), time.month().into(), time.day().into());
+    let secs = days * 86400
+        + i64::from(time.hour()) * 3600
+        + i64::from(time.minute()) * 60
+        + i64::from(time.second());
+    Some(from_secs(u64::try_from(secs).ok()?))
+}
+
+/// Converts `time` to a DOS time in UTC, or `None` if it's outside the
+/// years 1980 to 2107 DOS times can hold
+pub(crate) fn to_zip_time(time: SystemTime) -> Option<DateTime> {
+    let secs = secs(time) as i64;
+    let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+    let secs = secs.rem_euclid(86400);
+    DateTime::from_date_and_time(
+        u16::try_from(year).ok()?,
+        month as u8,
+        day as u8,
+        (secs / 3600) as u8,
+        (secs / 60 % 60) as u8,
+        (secs % 60) as u8,
+    )
+    .ok()
+}
+
+/// Returns the number of days between the Unix epoch and a date of the
+/// proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // years start in March, so leap days end them
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of [`days_from_civil`], returning the year, month and day
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,63 @@
+//! Writing archives into memory-mapped files of a fixed capacity
+
+use memmap2::MmapMut;
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// Writer into a memory map, failing once writes would go past its end
+pub(crate) struct MmapWriter {
+    map: MmapMut,
+    pos: u64,
+    /// Furthest position written to, the length of the written data
+    len: u64,
+}
+
+impl MmapWriter {
+    pub(crate) fn new(map: MmapMut) -> Self {
+        Self {
+            map,
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    /// Flushes the map to its file, returning the length of the written data
+    pub(crate) fn finish(self) -> io::Result<u64> {
+        self.map.flush()?;
+        Ok(self.len)
+    }
+}
+
+impl Write for MmapWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let start = self.pos.min(self.map.len() as u64) as usize;
+        let written = buf.len().min(self.map.len() - start);
+        if written == 0 && !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "archive is larger than the mapped capacity",
+            ));
+        }
+        self.map[start..start + written].copy_from_slice(&buf[..written]);
+        self.pos = (start + written) as u64;
+        self.len = self.len.max(self.pos);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for MmapWriter {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "seek to a negative position")
+        })?;
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,52 @@
+//! Reading entry names that aren't valid UTF-8
+//!
+//! Tar and ar store names as bytes, which older tools wrote in whatever
+//! encoding the system used, like Latin-1 or Shift JIS.
+
+use crate::{ArcError, ArcResult, NamedEntry};
+
+/// What readers do with entries whose name isn't valid UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonUtf8Policy {
+    /// Keeps them with the name decoded lossily, invalid sequences replaced
+    /// with `U+FFFD`, and its bytes in [`ArcMeta::raw_name`](crate::ArcMeta::raw_name)
+    #[default]
+    Lossy,
+    /// Drops them
+    Skip,
+    /// Fails with [`ArcError::NonUtf8Name`]
+    Reject,
+}
+
+impl NonUtf8Policy {
+    /// Applies the policy to `entries`
+    pub(crate) fn apply<E: NamedEntry>(self, mut entries: Vec<E>) -> ArcResult<Vec<E>> {
+        match self {
+            NonUtf8Policy::Lossy => {}
+            NonUtf8Policy::Skip => entries.retain(|entry| entry.meta().raw_name.is_none()),
+            NonUtf8Policy::Reject => {
+                if let Some(entry) = entries.iter().find(|entry| entry.meta().raw_name.is_some()) {
+                    return Err(ArcError::NonUtf8Name(entry.name().clone()));
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Decodes the name `raw` lossily, also returning its bytes if it isn't
+/// valid UTF-8
+pub(crate) fn decode(raw: Vec<u8>) -> (String, Option<Vec<u8>>) {
+    match String::from_utf8(raw) {
+        Ok(name) => (name, None),
+        Err(err) => {
+            let raw = err.into_bytes();
+            (String::from_utf8_lossy(&raw).into_owned(), Some(raw))
+        }
+    }
+}
+
+/// Returns the bytes of the name `raw` if it isn't valid UTF-8
+pub(crate) fn raw(raw: &[u8]) -> Option<Vec<u8>> {
+    std::str::from_utf8(raw).is_err().then(|| raw.to_vec())
+}
@@ -0,0 +1,221 @@
+//! Checking entry names for paths that would escape the extraction directory
+//!
+//! Names with `..` components, absolute paths, drive letters or backslashes
+//! can point outside the directory an archive is extracted to ("zip slip").
+//! Safe names can too, when they go through a symlink extracted before them.
+
+use crate::{ArcError, ArcResult, EntryKind, NamedEntry};
+use std::collections::{HashMap, VecDeque};
+
+/// Most symlinks followed while resolving a single name, like Linux's limit
+const MAX_SYMLINK_HOPS: usize = 40;
+
+/// What readers, and writers through [`crate::ArcWriter::path_policy`], do with
+/// entry names that aren't safe relative paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathPolicy {
+    /// Fails with [`ArcError::UnsafePath`]
+    #[default]
+    Reject,
+    /// Rewrites them with [`sanitize_path`], dropping entries left without a
+    /// name
+    Sanitize,
+    /// Keeps them as they are
+    Allow,
+}
+
+impl PathPolicy {
+    /// Applies the policy to the names of `entries` and the targets of hard
+    /// links, which name other entries
+    pub(crate) fn apply<E: NamedEntry>(self, mut entries: Vec<E>) -> ArcResult<Vec<E>> {
+        match self {
+            PathPolicy::Reject => reject_unsafe(
+                entries
+                    .iter()
+                    .map(|entry| (entry.name(), &entry.meta().kind)),
+            )?,
+            PathPolicy::Sanitize => entries.retain_mut(sanitize_entry),
+            PathPolicy::Allow => {}
+        }
+        Ok(entries)
+    }
+}
+
+/// Rewrites the name and hard link target of `entry` with [`sanitize_path`],
+/// returning whether it's left with a name
+pub(crate) fn sanitize_entry(entry: &mut impl NamedEntry) -> bool {
+    let (name, meta) = entry.parts_mut();
+    // single-file formats may have no name to begin with
+    let unnamed = name.is_empty();
+    *name = sanitize_path(name);
+    if let EntryKind::HardLink(target) = &mut meta.kind {
+        *target = sanitize_path(target);
+    }
+    unnamed || !name.is_empty()
+}
+
+/// Fails with [`ArcError::UnsafePath`] on the first unsafe name or hard link
+/// target of the entries given by name and kind
+pub(crate) fn reject_unsafe<'a>(
+    entries: impl IntoIterator<Item = (&'a String, &'a EntryKind)>,
+) -> ArcResult<()> {
+    for (name, kind) in entries {
+        if !is_safe_path(name) {
+            return Err(ArcError::UnsafePath(name.clone()));
+        }
+        if let EntryKind::HardLink(target) = kind {
+            if !is_safe_path(target) {
+                return Err(ArcError::UnsafePath(target.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What readers do with entries that symlinks earlier in the archive would
+/// send outside the extraction directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Fails with [`ArcError::SymlinkEscape`]
+    #[default]
+    Reject,
+    /// Keeps them, for archives that are trusted
+    Allow,
+}
+
+impl SymlinkPolicy {
+    /// Applies the policy to the names of `entries` and the targets of hard
+    /// links
+    pub(crate) fn apply<E: NamedEntry>(self, entries: Vec<E>) -> ArcResult<Vec<E>> {
+        if self == SymlinkPolicy::Reject {
+            reject_symlink_escapes(
+                entries
+                    .iter()
+                    .map(|entry| (entry.name(), &entry.meta().kind)),
+            )?;
+        }
+        Ok(entries)
+    }
+}
+
+/// Fails with [`ArcError::SymlinkEscape`] on the first entry, given by name
+/// and kind, that earlier symlinks send outside the extraction directory
+pub(crate) fn reject_symlink_escapes<'a>(
+    entries: impl IntoIterator<Item = (&'a String, &'a EntryKind)>,
+) -> ArcResult<()> {
+    let mut symlinks = Symlinks::default();
+    for (name, kind) in entries {
+        if symlinks.escapes(name, kind) {
+            return Err(ArcError::SymlinkEscape(name.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Symlinks extracted so far, by their normalized name
+#[derive(Default)]
+pub(crate) struct Symlinks {
+    targets: HashMap<String, String>,
+}
+
+impl Symlinks {
+    /// Returns whether the entry `name` of `kind`, or the entry it's a hard
+    /// link to, goes through symlinks to outside the extraction directory,
+    /// then remembers it if it's a symlink
+    ///
+    /// Names that aren't safe on their own are left to [`PathPolicy`].
+    pub(crate) fn escapes(&mut self, name: &str, kind: &EntryKind) -> bool {
+        let escapes = |name: &str| is_safe_path(name) && self.resolve(name).is_none();
+        let escapes = match kind {
+            EntryKind::HardLink(target) => escapes(name) || escapes(target),
+            _ => escapes(name),
+        };
+        if let EntryKind::Symlink(target) = kind {
+            self.targets.insert(normalize(name), target.clone());
+        }
+        escapes
+    }
+
+    /// Resolves `name` against the extraction directory, following
+    /// symlinks, or returns `None` if it ends up outside of it
+    ///
+    /// Symlink loops and chains too long to follow count as outside.
+    fn resolve(&self, name: &str) -> Option<Vec<String>> {
+        let mut resolved: Vec<String> = Vec::new();
+        let mut pending: VecDeque<String> = name.split('/').map(str::to_owned).collect();
+        let mut hops = 0;
+        while let Some(component) = pending.pop_front() {
+            match component.as_str() {
+                "" | "." => continue,
+                ".." => {
+                    resolved.pop()?;
+                    continue;
+                }
+                _ => resolved.push(component),
+            }
+            let Some(target) = self.targets.get(&resolved.join("/")) else {
+                continue;
+            };
+            hops += 1;
+            if hops > MAX_SYMLINK_HOPS || !is_safe_start(target) {
+                return None;
+            }
+            // the link is replaced by its target, relative to its directory
+            resolved.pop();
+            for component in target.split('/').rev() {
+                pending.push_front(component.to_owned());
+            }
+        }
+        Some(resolved)
+    }
+}
+
+/// Returns `name` without empty and `.` components
+fn normalize(name: &str) -> String {
+    let components: Vec<_> = name
+        .split('/')
+        .filter(|component| !matches!(*component, "" | "."))
+        .collect();
+    components.join("/")
+}
+
+/// Returns whether `target` is relative, so it can only leave the
+/// extraction directory through `..` components
+fn is_safe_start(target: &str) -> bool {
+    !target.starts_with('/') && !target.contains('\\') && !has_drive(target)
+}
+
+/// Returns whether `name` is a relative path that stays inside the directory
+/// it's extracted to, using only `/` as the separator
+pub fn is_safe_path(name: &str) -> bool {
+    !name.starts_with('/')
+        && !name.contains('\\')
+        && !has_drive(name)
+        && name.split('/').all(|component| component != "..")
+}
+
+/// Turns `name` into a safe relative path, reading backslashes as separators
+/// and dropping drive letters, root, `.` and `..` components
+///
+/// A trailing `/` marking a directory is kept.
+pub fn sanitize_path(name: &str) -> String {
+    let name = name.replace('\\', "/");
+    let relative = match has_drive(&name) {
+        true => &name[2..],
+        false => &name[..],
+    };
+    let components: Vec<_> = relative
+        .split('/')
+        .filter(|component| !matches!(*component, "" | "." | ".."))
+        .collect();
+    let mut sanitized = components.join("/");
+    if !sanitized.is_empty() && name.ends_with('/') {
+        sanitized.push('/');
+    }
+    sanitized
+}
+
+/// Returns whether `name` starts with a Windows drive letter, like `C:`
+fn has_drive(name: &str) -> bool {
+    matches!(name.as_bytes(), [letter, b':', ..] if letter.is_ascii_alphabetic())
+}
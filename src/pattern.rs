@@ -0,0 +1,83 @@
+//! Glob and regex patterns picking entries by name
+
+use crate::{ArcError, ArcResult};
+use regex::Regex;
+
+/// Pattern matched against whole entry names, without the trailing `/` of
+/// directories
+///
+/// ```
+/// use cra::Pattern;
+///
+/// let pattern = Pattern::glob("docs/**/*.md").unwrap();
+/// assert!(pattern.matches("docs/README.md"));
+/// assert!(pattern.matches("docs/api/reader.md"));
+/// assert!(!pattern.matches("src/lib.rs"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    /// Returns a pattern matching names like the shell glob `glob`
+    ///
+    /// `*` and `?` match any characters but `/`, `**` also matches across
+    /// directories, and `[abc]`, `[a-z]` and `[!abc]` match one character
+    /// from a set. Fails with [`ArcError::InvalidPattern`] on unclosed sets.
+    pub fn glob(glob: &str) -> ArcResult<Self> {
+        let mut regex = String::from("^");
+        let mut chars = glob.chars().peekable();
+        while let Some(char) = chars.next() {
+            match char {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    match chars.peek() {
+                        Some('/') => {
+                            chars.next();
+                            regex.push_str("(?:.*/)?");
+                        }
+                        _ => regex.push_str(".*"),
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                '[' => {
+                    regex.push('[');
+                    if chars.next_if(|&char| char == '!' || char == '^').is_some() {
+                        regex.push('^');
+                    }
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(char @ ('\\' | '[' | '&' | '~')) => {
+                                regex.push('\\');
+                                regex.push(char);
+                            }
+                            Some(char) => regex.push(char),
+                            None => return Err(ArcError::InvalidPattern(glob.to_owned())),
+                        }
+                    }
+                    regex.push(']');
+                }
+                char => regex.push_str(&regex::escape(char.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        regex.push('$');
+        Self::regex(&regex).map_err(|_| ArcError::InvalidPattern(glob.to_owned()))
+    }
+
+    /// Returns a pattern matching names in which the regular expression
+    /// `regex` finds a match
+    ///
+    /// Fails with [`ArcError::InvalidPattern`] if it isn't valid.
+    pub fn regex(regex: &str) -> ArcResult<Self> {
+        Regex::new(regex)
+            .map(Pattern)
+            .map_err(|_| ArcError::InvalidPattern(regex.to_owned()))
+    }
+
+    /// Returns whether the pattern matches the entry `name`
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.strip_suffix('/').unwrap_or(name);
+        self.0.is_match(name)
+    }
+}
@@ -0,0 +1,184 @@
+//! Working out what extracting an archive would do without touching the
+//! filesystem
+
+use crate::{
+    extract::{check_symlinks, destination, exists},
+    path, ArcEntry, ArcResult, EntryKind, ExtractOptions, ExtractOutcome, OverwritePolicy,
+};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// What extracting an archive would do, from
+/// [`crate::ArcReader::plan_extraction`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExtractPlan {
+    /// Paths of the files, links and directories that would be created,
+    /// including missing parents, in the order they would be
+    pub created: Vec<PathBuf>,
+    /// Paths of the files and links that would be replaced
+    pub overwritten: Vec<PathBuf>,
+    /// Paths that would make extraction fail, as something is in the way,
+    /// like a file where a directory goes or anything there with
+    /// [`OverwritePolicy::Error`]
+    pub conflicts: Vec<PathBuf>,
+    /// What would happen to each entry, by name, in archive order
+    pub entries: Vec<(String, ExtractOutcome)>,
+}
+
+/// Works out what extracting `entries` under `dir` as `options` say would do,
+/// failing like extraction would if an entry ended up outside of it, even
+/// through symlinks already there, or [`ExtractOptions::veto`] aborts it
+pub(crate) fn plan(
+    entries: &[ArcEntry],
+    dir: &Path,
+    options: &ExtractOptions,
+) -> ArcResult<ExtractPlan> {
+    let named = || entries.iter().map(|entry| (&entry.name, &entry.meta.kind));
+    path::reject_unsafe(named())?;
+    path::reject_symlink_escapes(named())?;
+
+    let mut planner = Planner {
+        dir,
+        options,
+        on_disk: true,
+        planned: HashMap::new(),
+        plan: ExtractPlan::default(),
+    };
+    if options.atomic && exists(dir)? {
+        planner.plan.conflicts.push(dir.to_owned());
+    }
+    // atomic extraction writes into a new directory
+    planner.on_disk = !options.atomic;
+    if !planner.make_parents(dir, dir)? {
+        planner.plan.conflicts.push(dir.to_owned());
+        return Ok(planner.plan);
+    }
+    for entry in entries {
+        let size = entry.data.len() as u64;
+        let outcome = match options.allows(&entry.name, size, &entry.meta.kind)? {
+            true => planner.entry(entry)?,
+            false => ExtractOutcome::Vetoed,
+        };
+        if let ExtractOutcome::Conflict(path) = &outcome {
+            planner.plan.conflicts.push(path.clone());
+        }
+        planner.plan.entries.push((entry.name.clone(), outcome));
+    }
+    Ok(planner.plan)
+}
+
+/// Plan of the extraction of entries under `dir` in progress
+struct Planner<'a> {
+    dir: &'a Path,
+    options: &'a ExtractOptions,
+    /// Whether what's already on disk is in the way
+    on_disk: bool,
+    /// Paths the entries so far would have been written to, and whether
+    /// they're directories
+    planned: HashMap<PathBuf, bool>,
+    plan: ExtractPlan,
+}
+
+impl Planner<'_> {
+    /// Returns what would happen to `entry`, as extraction does
+    fn entry(&mut self, entry: &ArcEntry) -> ArcResult<ExtractOutcome> {
+        let Some(path) = destination(self.dir, &entry.name) else {
+            return Ok(ExtractOutcome::Unsupported);
+        };
+        if self.on_disk {
+            check_symlinks(self.dir, &path, &entry.name, &entry.meta.kind)?;
+            if let EntryKind::HardLink(target) = &entry.meta.kind {
+                if let Some(target) = destination(self.dir, target) {
+                    check_symlinks(self.dir, &target, &entry.name, &EntryKind::File)?;
+                }
+            }
+        }
+        if let EntryKind::Directory = entry.meta.kind {
+            return match self.kind(&path, true)? {
+                Some(true) => Ok(ExtractOutcome::Existing(path)),
+                Some(false) => Ok(ExtractOutcome::Conflict(path)),
+                None => match self.make_parents(self.dir, &path)? {
+                    true => Ok(ExtractOutcome::Created(path)),
+                    false => Ok(ExtractOutcome::Conflict(path)),
+                },
+            };
+        }
+        let supported = match &entry.meta.kind {
+            EntryKind::Symlink(_) => cfg!(unix),
+            EntryKind::Special(_) => false,
+            _ => true,
+        };
+        if !supported {
+            return Ok(ExtractOutcome::Unsupported);
+        }
+        let parent = path.parent().unwrap_or(self.dir);
+        if !self.make_parents(self.dir, parent)? {
+            return Ok(ExtractOutcome::Conflict(path));
+        }
+        // links in the way are replaced rather than followed
+        let kind = self.kind(&path, false)?;
+        match (self.options.overwrite, kind) {
+            (OverwritePolicy::Error, Some(_)) | (OverwritePolicy::Overwrite, Some(true)) => {
+                return Ok(ExtractOutcome::Conflict(path));
+            }
+            _ => {}
+        }
+        let (path, outcome) = (self.options.overwrite)
+            .apply_with(path, |path| Ok(self.kind(path, false)?.is_some()))?;
+        match &outcome {
+            ExtractOutcome::Overwritten(_) => self.plan.overwritten.push(path.clone()),
+            ExtractOutcome::Skipped(_) => return Ok(outcome),
+            _ => self.plan.created.push(path.clone()),
+        }
+        self.planned.insert(path, false);
+        Ok(outcome)
+    }
+
+    /// Plans creating the directory `path` and its missing parents, down
+    /// from `root`, and returns whether anything that isn't a directory is
+    /// in the way
+    fn make_parents(&mut self, root: &Path, path: &Path) -> io::Result<bool> {
+        let mut dirs = Vec::new();
+        for dir in path.ancestors() {
+            dirs.push(dir);
+            if dir == root {
+                break;
+            }
+        }
+        for dir in dirs.into_iter().rev() {
+            match self.kind(dir, true)? {
+                Some(true) => {}
+                Some(false) => return Ok(false),
+                None => {
+                    self.plan.created.push(dir.to_owned());
+                    self.planned.insert(dir.to_owned(), true);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns whether a directory or something else would be at `path` by
+    /// now, following symlinks if `follow` is set, or `None` if nothing would
+    fn kind(&self, path: &Path, follow: bool) -> io::Result<Option<bool>> {
+        if let Some(&is_dir) = self.planned.get(path) {
+            return Ok(Some(is_dir));
+        }
+        if !self.on_disk && path.starts_with(self.dir) {
+            return Ok(None);
+        }
+        let meta = match follow {
+            true => fs::metadata(path),
+            false => fs::symlink_metadata(path),
+        };
+        match meta {
+            Ok(meta) => Ok(Some(meta.is_dir())),
+            // broken links are still in the way
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(exists(path)?.then_some(false)),
+            Err(err) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Reporting how far reading, extracting or creating an archive got
+
+use crate::ArcEntry;
+use std::{
+    cell::Cell,
+    io::{self, Read},
+    sync::Mutex,
+};
+
+/// Update on how far reading, extracting or creating an archive got, given
+/// to progress callbacks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress<'a> {
+    /// Name of the entry the update is about
+    pub name: &'a str,
+    /// Entries done so far, counting this one once it's finished
+    pub entries: usize,
+    /// Bytes of entry data done so far
+    pub bytes: u64,
+    /// Entries in total, when known up front
+    pub total_entries: Option<usize>,
+    /// Bytes of entry data in total, when known up front
+    pub total_bytes: Option<u64>,
+}
+
+/// Progress callback kept in options, like [`crate::ExtractOptions::progress`]
+pub type ProgressFn = dyn Fn(&Progress) + Send + Sync;
+
+/// Keeps count of the entries and bytes done and reports them to a
+/// callback, through a shared reference so it can go in options that are
+/// copied around
+pub(crate) struct Tracker<'a> {
+    report: &'a (dyn Fn(&Progress) + 'a),
+    entries: Cell<usize>,
+    bytes: Cell<u64>,
+    total_entries: Option<usize>,
+    total_bytes: Option<u64>,
+}
+
+impl<'a> Tracker<'a> {
+    pub(crate) fn new(
+        report: &'a (dyn Fn(&Progress) + 'a),
+        total_entries: Option<usize>,
+        total_bytes: Option<u64>,
+    ) -> Self {
+        Self {
+            report,
+            entries: Cell::new(0),
+            bytes: Cell::new(0),
+            total_entries,
+            total_bytes,
+        }
+    }
+
+    /// Reports `len` more bytes of the entry `name`
+    pub(crate) fn advance(&self, name: &str, len: u64) {
+        self.bytes.set(self.bytes.get() + len);
+        self.report(name);
+    }
+
+    /// Reports the entry `name` as finished, with `len` bytes of it not
+    /// reported yet
+    pub(crate) fn finish(&self, name: &str, len: u64) {
+        self.entries.set(self.entries.get() + 1);
+        self.advance(name, len);
+    }
+
+    /// Reports the decoded `entry` as finished
+    pub(crate) fn entry(&self, entry: &ArcEntry) {
+        self.finish(&entry.name, entry.data.len() as u64);
+    }
+
+    /// Reports the entries of `entries` past the ones already reported, for
+    /// formats decoded whole before any entry could be
+    pub(crate) fn catch_up(&self, entries: &[ArcEntry]) {
+        for entry in entries.iter().skip(self.entries.get()) {
+            self.entry(entry);
+        }
+    }
+
+    fn report(&self, name: &str) {
+        (self.report)(&Progress {
+            name,
+            entries: self.entries.get(),
+            bytes: self.bytes.get(),
+            total_entries: self.total_entries,
+            total_bytes: self.total_bytes,
+        });
+    }
+}
+
+/// Reader reporting what's read from `inner` to `tracker` as data of the
+/// entry `name`, if there's a tracker
+pub(crate) struct CountingReader<'r, 't, R> {
+    pub(crate) inner: R,
+    pub(crate) name: &'r str,
+    pub(crate) tracker: Option<&'r Tracker<'t>>,
+}
+
+impl<R: Read> Read for CountingReader<'_, '_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if let (Some(tracker), 1..) = (self.tracker, read) {
+            tracker.advance(self.name, read as u64);
+        }
+        Ok(read)
+    }
+}
+
+/// Files and bytes of an archive being created, done so far and in total
+#[derive(Default)]
+pub(crate) struct Counts {
+    pub(crate) entries: usize,
+    pub(crate) bytes: u64,
+    pub(crate) total_entries: usize,
+    pub(crate) total_bytes: u64,
+}
+
+/// Reader reporting the data of the file `name` to `report` as it's read,
+/// and the file as finished once it reaches the end
+pub(crate) struct ProgressReader<'a> {
+    pub(crate) inner: Box<dyn Read + 'a>,
+    pub(crate) name: &'a str,
+    pub(crate) report: &'a ProgressFn,
+    pub(crate) counts: &'a Mutex<Counts>,
+    pub(crate) finished: bool,
+}
+
+impl Read for ProgressReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if self.finished || (read == 0 && buf.is_empty()) {
+            return Ok(read);
+        }
+        let progress = {
+            let mut counts = self.counts.lock().unwrap();
+            counts.bytes += read as u64;
+            if read == 0 {
+                self.finished = true;
+                counts.entries += 1;
+            }
+            Progress {
+                name: self.name,
+                entries: counts.entries,
+                bytes: counts.bytes,
+                total_entries: Some(counts.total_entries),
+                total_bytes: Some(counts.total_bytes),
+            }
+        };
+        (self.report)(&progress);
+        Ok(read)
+    }
+}
@@ -0,0 +1,147 @@
+//! Salvaging the entries of damaged and truncated archives
+
+use crate::{
+    checksum, crx, guard,
+    remote::{self, RemoteEntry},
+    zip_records::LOCAL_SIG,
+    ArcEntry, ArcError, ArcFormat, ArcReader, ArcResult, ArcStreamReader, Decoding,
+};
+use flate2::read::DeflateDecoder;
+use infer::archive::is_tar;
+use std::io::{self, Cursor, Read};
+
+const DESCRIPTOR_MAGIC: u32 = 0x08074b50;
+
+/// Reads the entries of the archive in `buf` decoded as `options` say up to
+/// the first damaged one, returning its format, the entries read and the
+/// error that stopped reading
+pub(crate) fn recover(
+    buf: &[u8],
+    options: Decoding,
+) -> (ArcFormat, Vec<ArcEntry>, Option<ArcError>) {
+    let err = match guard(|| ArcReader::read_unchecked(buf, options)) {
+        Ok(reader) => return (reader.format, reader.entries, None),
+        Err(err) => err,
+    };
+    let mut format = options.format.unwrap_or_else(|| ArcReader::detect(buf));
+    let mut entries = Vec::new();
+    let salvaged = guard(|| match format {
+        ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub => zip(buf, &mut entries),
+        ArcFormat::Crx => zip(crx::split(buf)?.1, &mut entries),
+        ArcFormat::Sevenz => {
+            let len = buf.len() as u64;
+            let password = ArcReader::sevenz_password(options.password);
+            ArcReader::read_7z_into(
+                Cursor::new(buf),
+                len,
+                password,
+                &mut entries,
+                options.progress,
+            )
+        }
+        _ => tarball(buf, &mut format, &mut entries),
+    });
+    if format == ArcFormat::Zip {
+        format = ArcReader::zip_format(&entries);
+    }
+    // stopping without an error of its own leaves the first one as the reason
+    (format, entries, Some(salvaged.err().unwrap_or(err)))
+}
+
+/// Reads the entries of `buf` as a tarball compressed the way `format` says,
+/// if it holds one, until one can't be, setting `format` to the tarball's
+fn tarball(buf: &[u8], format: &mut ArcFormat, entries: &mut Vec<ArcEntry>) -> ArcResult<()> {
+    let Some((tar_format, mut decoder)) = ArcReader::tar_decoder(*format, buf)? else {
+        return Ok(());
+    };
+    let mut start = Vec::new();
+    // an error cutting the first header short is met again reading it
+    let _ = decoder.by_ref().take(512).read_to_end(&mut start);
+    if !is_tar(&start) {
+        return Ok(());
+    }
+    *format = tar_format;
+    let stream = Cursor::new(start).chain(decoder);
+    for entry in ArcStreamReader::from_decoder(tar_format, Box::new(stream)) {
+        entries.push(entry?);
+    }
+    Ok(())
+}
+
+/// Reads the entries of the zip in `buf` from their local headers, for zips
+/// whose central directory is missing, stopping at the first thing that
+/// isn't a local header
+///
+/// Entries are read without their metadata, which is only complete in the
+/// central directory.
+fn zip(buf: &[u8], entries: &mut Vec<ArcEntry>) -> ArcResult<()> {
+    let mut rest = buf;
+    while rest.starts_with(&LOCAL_SIG.to_le_bytes()) {
+        let (entry, header_len) = remote::local_entry(rest)?;
+        if entry.flags & 1 != 0 {
+            return Err(ArcError::PasswordRequired);
+        }
+        rest = &rest[header_len..];
+        let (data, len) = match entry.flags & 0x08 != 0 {
+            true => with_descriptor(&entry, rest)?,
+            false => {
+                let compressed = usize::try_from(entry.compressed_size)
+                    .ok()
+                    .and_then(|len| rest.get(..len))
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                (entry.decode(compressed.to_vec())?, compressed.len())
+            }
+        };
+        entries.push(match entry.name.ends_with('/') {
+            true => ArcEntry::directory(entry.name),
+            false => ArcEntry::file(entry.name, data),
+        });
+        rest = &rest[len..];
+    }
+    Ok(())
+}
+
+/// Decodes the data at the start of `buf` of the zip `entry`, whose checksum
+/// and sizes follow it in a data descriptor, returning it with the length of
+/// the data and the descriptor
+///
+/// Only deflated data can be read this way, as it's the only kind that
+/// tells where it ends.
+fn with_descriptor(entry: &RemoteEntry, buf: &[u8]) -> ArcResult<(Vec<u8>, usize)> {
+    if entry.method != 8 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} has data of unknown size", entry.name),
+        )
+        .into());
+    }
+    let mut decoder = DeflateDecoder::new(buf);
+    let mut data = Vec::new();
+    decoder.read_to_end(&mut data)?;
+    let len = decoder.total_in() as usize;
+
+    let mut descriptor = &buf[len..];
+    if descriptor.starts_with(&DESCRIPTOR_MAGIC.to_le_bytes()) {
+        descriptor = &descriptor[4..];
+    }
+    let field = |start: usize, size: usize| {
+        let bytes = descriptor.get(start..start + size)?;
+        Some(
+            bytes
+                .iter()
+                .rev()
+                .fold(0u64, |value, &byte| value << 8 | u64::from(byte)),
+        )
+    };
+    let sizes = (len as u64, data.len() as u64);
+    // sizes are 8 bytes long instead of 4 in zip64 archives
+    let size_len = [4, 8]
+        .into_iter()
+        .find(|&size| (field(4, size), field(4 + size, size)) == (Some(sizes.0), Some(sizes.1)))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "bad data descriptor"))?;
+    if field(0, 4) != Some(u64::from(checksum::crc32(&data))) {
+        return Err(ArcError::ChecksumMismatch(entry.name.clone()));
+    }
+    let descriptor_len = buf.len() - len - descriptor.len() + 4 + 2 * size_len;
+    Ok((data, len + descriptor_len))
+}
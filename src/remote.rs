@@ -0,0 +1,305 @@
+//! Reading zip archives through ranged reads, such as HTTP range requests
+//!
+//! Opening an archive fetches its end and its central directory, reading a
+//! file fetches its local header and its data, nothing else is fetched.
+
+use crate::{
+    capacity_for, checksum,
+    zip_records::{
+        find_end, has_sig, invalid, CentralHeader, Field, LocalHeader, END_SIZE, LOCAL_SIZE,
+        MAX_COMMENT_LEN, ZIP64_END_SIG, ZIP64_END_SIZE, ZIP64_LOCATOR_SIG, ZIP64_LOCATOR_SIZE,
+    },
+    ArcError, ArcResult,
+};
+use bzip2::read::BzDecoder;
+use flate2::read::DeflateDecoder;
+use std::io::{self, Read};
+use zstd::Decoder as ZstdDecoder;
+
+/// Source of byte ranges of a remote archive
+pub trait RangeFetcher {
+    /// Returns the total size of the archive in bytes
+    fn size(&self) -> io::Result<u64>;
+
+    /// Returns the `len` bytes starting at `offset`, which never reach past
+    /// the end of the archive
+    fn fetch(&self, offset: u64, len: u64) -> io::Result<Vec<u8>>;
+}
+
+/// File listed in the central directory
+pub(crate) struct RemoteEntry {
+    pub(crate) name: String,
+    pub(crate) flags: u16,
+    pub(crate) method: u16,
+    pub(crate) crc: u32,
+    pub(crate) compressed_size: u64,
+    pub(crate) size: u64,
+    pub(crate) header_offset: u64,
+}
+
+/// Central directory as described by the end of central directory record
+pub(crate) struct Directory {
+    count: u64,
+    size: u64,
+    offset: u64,
+    /// Offset of the record following the directory
+    end: u64,
+}
+
+/// What the end of central directory record leads to
+pub(crate) enum Eocd {
+    Directory(Directory),
+    /// Offset of the zip64 locator, which has to be read for the directory
+    Zip64(u64),
+}
+
+/// Returns the range at the end of an archive of `size` bytes that holds its
+/// end of central directory record, as an offset and a length
+pub(crate) fn tail_range(size: u64) -> (u64, u64) {
+    let len = size.min((END_SIZE + MAX_COMMENT_LEN) as u64);
+    (size - len, len)
+}
+
+/// Parses the end of central directory record in `tail`, read at `offset`
+pub(crate) fn eocd(tail: &[u8], offset: u64) -> io::Result<Eocd> {
+    let eocd = find_end(tail).ok_or_else(|| invalid("missing end of central directory"))?;
+    let eocd_offset = offset + eocd as u64;
+    let directory = Directory {
+        count: Field::new(eocd + 10, 2).get(tail)?,
+        size: Field::new(eocd + 12, 4).get(tail)?,
+        offset: Field::new(eocd + 16, 4).get(tail)?,
+        end: eocd_offset,
+    };
+    if directory.count == 0xffff || directory.size == 0xffff_ffff || directory.offset == 0xffff_ffff
+    {
+        return eocd_offset
+            .checked_sub(ZIP64_LOCATOR_SIZE as u64)
+            .map(Eocd::Zip64)
+            .ok_or_else(|| invalid("missing zip64 locator"));
+    }
+    Ok(Eocd::Directory(directory))
+}
+
+/// Parses the zip64 locator read at `offset`, returning the offset of the
+/// zip64 end of central directory record
+pub(crate) fn zip64_locator(locator: &[u8], offset: u64) -> io::Result<u64> {
+    if !has_sig(locator, 0, ZIP64_LOCATOR_SIG) {
+        return Err(invalid("missing zip64 locator"));
+    }
+    let eocd64_offset = Field::new(8, 8).get(locator)?;
+    if eocd64_offset.saturating_add(ZIP64_END_SIZE as u64) > offset {
+        return Err(invalid("zip64 end of central directory out of bounds"));
+    }
+    Ok(eocd64_offset)
+}
+
+/// Parses the zip64 end of central directory record read at `offset`
+pub(crate) fn zip64_eocd(eocd64: &[u8], offset: u64) -> io::Result<Directory> {
+    if !has_sig(eocd64, 0, ZIP64_END_SIG) {
+        return Err(invalid("missing zip64 end of central directory"));
+    }
+    Ok(Directory {
+        count: Field::new(32, 8).get(eocd64)?,
+        size: Field::new(40, 8).get(eocd64)?,
+        offset: Field::new(48, 8).get(eocd64)?,
+        end: offset,
+    })
+}
+
+impl Directory {
+    /// Data prepended to the archive, like a self-extracting stub, shifts
+    /// every offset stored in it
+    fn shift(&self) -> io::Result<u64> {
+        self.end
+            .checked_sub(self.size)
+            .and_then(|start| start.checked_sub(self.offset))
+            .ok_or_else(|| invalid("central directory out of bounds"))
+    }
+
+    /// Returns where the directory actually is, as an offset and a length
+    pub(crate) fn range(&self) -> io::Result<(u64, u64)> {
+        Ok((self.offset + self.shift()?, self.size))
+    }
+
+    /// Parses the entries of the directory read from [`Directory::range`]
+    pub(crate) fn entries(&self, buf: &[u8]) -> io::Result<Vec<RemoteEntry>> {
+        let shift = self.shift()?;
+        let mut entries = Vec::new();
+        let mut pos = 0;
+        for _ in 0..self.count {
+            let (entry, len) = central_entry(buf, pos, shift)?;
+            entries.push(entry);
+            pos += len;
+        }
+        Ok(entries)
+    }
+}
+
+/// Returns the names of `entries`, directories ending with `/`
+pub(crate) fn names(entries: &[RemoteEntry]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.name.as_str()).collect()
+}
+
+/// Finds the file `name` in `entries`, failing if it can't be decrypted
+pub(crate) fn find<'a>(entries: &'a [RemoteEntry], name: &str) -> ArcResult<&'a RemoteEntry> {
+    let entry = entries
+        .iter()
+        .find(|entry| entry.name == name && !entry.name.ends_with('/'))
+        .ok_or_else(|| ArcError::EntryNotFound(name.to_owned()))?;
+    if entry.flags & 1 != 0 {
+        return Err(
+            io::Error::new(io::ErrorKind::Unsupported, format!("{name} is encrypted")).into(),
+        );
+    }
+    Ok(entry)
+}
+
+impl RemoteEntry {
+    /// Returns the range of the local file header, as an offset and a length
+    pub(crate) fn header_range(&self) -> (u64, u64) {
+        (self.header_offset, LOCAL_SIZE as u64)
+    }
+
+    /// Parses the local file `header`, returning the range of the file's
+    /// data, as an offset and a length
+    pub(crate) fn data_range(&self, header: &[u8]) -> io::Result<(u64, u64)> {
+        let len = LocalHeader::len(header, 0)? as u64;
+        let offset = self
+            .header_offset
+            .checked_add(len)
+            .ok_or_else(|| invalid("local file header out of bounds"))?;
+        Ok((offset, self.compressed_size))
+    }
+
+    /// Decodes the file's `compressed` data and checks its checksum
+    pub(crate) fn decode(&self, compressed: Vec<u8>) -> ArcResult<Vec<u8>> {
+        let data = match self.method {
+            0 => compressed,
+            method => {
+                let decoder: Box<dyn Read + '_> = match method {
+                    8 => Box::new(DeflateDecoder::new(&compressed[..])),
+                    12 => Box::new(BzDecoder::new(&compressed[..])),
+                    93 => Box::new(ZstdDecoder::new(&compressed[..])?),
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::Unsupported,
+                            format!("unsupported zip compression method: {method}"),
+                        )
+                        .into())
+                    }
+                };
+                // a byte past the size is enough to tell the data is too long
+                let mut data = Vec::with_capacity(capacity_for(self.size));
+                decoder
+                    .take(self.size.saturating_add(1))
+                    .read_to_end(&mut data)?;
+                data
+            }
+        };
+        if data.len() as u64 != self.size {
+            return Err(invalid("file data doesn't match its size").into());
+        }
+        if checksum::crc32(&data) != self.crc {
+            return Err(ArcError::ChecksumMismatch(self.name.clone()));
+        }
+        Ok(data)
+    }
+}
+
+/// Fails unless `len` bytes were fetched
+pub(crate) fn check_len(data: Vec<u8>, len: u64) -> io::Result<Vec<u8>> {
+    match data.len() as u64 == len {
+        true => Ok(data),
+        false => Err(invalid("fetched range has the wrong length")),
+    }
+}
+
+/// Reader for zip archives that are only accessible through ranged reads
+///
+/// Opening the archive fetches its central directory, reading a file fetches
+/// nothing else but its local header and data. Stored, deflated, bzip2 and
+/// zstd files can be read, encrypted ones can't.
+pub struct RemoteZipReader<F: RangeFetcher> {
+    fetcher: F,
+    entries: Vec<RemoteEntry>,
+}
+
+impl<F: RangeFetcher> RemoteZipReader<F> {
+    /// Opens the zip archive behind `fetcher`, fetching its central directory
+    pub fn new(fetcher: F) -> ArcResult<Self> {
+        let fetch = |(offset, len)| check_len(fetcher.fetch(offset, len)?, len);
+        let tail = tail_range(fetcher.size()?);
+        let directory = match eocd(&fetch(tail)?, tail.0)? {
+            Eocd::Directory(directory) => directory,
+            Eocd::Zip64(offset) => {
+                let locator = fetch((offset, ZIP64_LOCATOR_SIZE as u64))?;
+                let offset = zip64_locator(&locator, offset)?;
+                zip64_eocd(&fetch((offset, ZIP64_END_SIZE as u64))?, offset)?
+            }
+        };
+        let entries = directory.entries(&fetch(directory.range()?)?)?;
+        Ok(Self { fetcher, entries })
+    }
+
+    /// Fetches `len` bytes at `offset`, failing if fewer were returned
+    fn fetch(&self, (offset, len): (u64, u64)) -> io::Result<Vec<u8>> {
+        check_len(self.fetcher.fetch(offset, len)?, len)
+    }
+
+    /// Returns the names of all entries in the order of the central
+    /// directory, directories ending with `/`
+    pub fn names(&self) -> Vec<&str> {
+        names(&self.entries)
+    }
+
+    /// Fetches and decodes the data of the file `name`
+    pub fn read_entry(&self, name: &str) -> ArcResult<Vec<u8>> {
+        let entry = find(&self.entries, name)?;
+        let data = entry.data_range(&self.fetch(entry.header_range())?)?;
+        entry.decode(self.fetch(data)?)
+    }
+
+    /// Returns the fetcher the archive is read through
+    pub fn into_inner(self) -> F {
+        self.fetcher
+    }
+}
+
+/// Parses the central directory header at `pos` in `buf`, returning it and
+/// its length
+fn central_entry(buf: &[u8], pos: usize, shift: u64) -> io::Result<(RemoteEntry, usize)> {
+    let header = CentralHeader::parse(buf, pos)?;
+    let entry = RemoteEntry {
+        name: String::from_utf8_lossy(&buf[header.name]).into_owned(),
+        flags: header.flags.get(buf)? as u16,
+        method: header.method.get(buf)? as u16,
+        crc: header.crc.get(buf)? as u32,
+        compressed_size: header.compressed_size.get(buf)?,
+        size: header.size.get(buf)?,
+        header_offset: header
+            .offset
+            .get(buf)?
+            .checked_add(shift)
+            .ok_or_else(|| invalid("local file header out of bounds"))?,
+    };
+    Ok((entry, header.len))
+}
+
+/// Parses the local file header at the start of `buf`, returning the file it
+/// describes and the header's length
+///
+/// Files with the data descriptor flag have their checksum and sizes after
+/// their data instead, and `0` in the header.
+pub(crate) fn local_entry(buf: &[u8]) -> io::Result<(RemoteEntry, usize)> {
+    let header = LocalHeader::parse(buf, 0)?;
+    let entry = RemoteEntry {
+        name: String::from_utf8_lossy(&buf[header.name]).into_owned(),
+        flags: header.flags.get(buf)? as u16,
+        method: header.method.get(buf)? as u16,
+        crc: header.crc.get(buf)? as u32,
+        compressed_size: header.compressed_size.get(buf)?,
+        size: header.size.get(buf)?,
+        header_offset: 0,
+    };
+    Ok((entry, header.len))
+}
@@ -0,0 +1,49 @@
+//! Passwords wiped from memory once they're dropped
+
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// Password, overwritten with zeros when dropped and never shown by `Debug`
+///
+/// Built from a `&str` or a `String`, which is taken without copying:
+///
+/// ```
+/// use cra::{ArcFormat, ArcWriter};
+///
+/// let mut writer = ArcWriter::new(ArcFormat::Zip);
+/// writer.zip_password = Some("uwu".into());
+/// assert_eq!(format!("{:?}", writer.zip_password), "Some(Secret(..))");
+/// ```
+#[derive(Clone)]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    /// Returns the password
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(password: String) -> Self {
+        Secret(Zeroizing::new(password))
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(password: &str) -> Self {
+        password.to_owned().into()
+    }
+}
+
+impl AsRef<[u8]> for Secret {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
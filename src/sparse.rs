@@ -0,0 +1,377 @@
+//! Sparse files of tarballs, which only store their data regions
+//!
+//! Everything between the regions listed in a sparse file's map reads as
+//! zeros. The old GNU format lists them in the entry's header and in
+//! extension blocks following it, the PAX formats in PAX records (0.0 and
+//! 0.1) or at the start of the entry's data (1.0).
+
+use std::io::{self, Read};
+use tar::{GnuExtSparseHeader, GnuSparseHeader, Header};
+
+const BLOCK_SIZE: usize = 512;
+
+/// Data region of a sparse file, as an offset and a length
+pub(crate) type Region = (u64, u64);
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid sparse file: {msg}"),
+    )
+}
+
+fn parse(number: &[u8]) -> io::Result<u64> {
+    std::str::from_utf8(number)
+        .ok()
+        .and_then(|number| number.trim().parse().ok())
+        .ok_or_else(|| invalid("bad number in sparse map"))
+}
+
+/// Pairs up the offsets and lengths of `numbers`
+fn pairs(numbers: &[u64]) -> io::Result<Vec<Region>> {
+    if !numbers.len().is_multiple_of(2) {
+        return Err(invalid("offset without a length"));
+    }
+    Ok(numbers.chunks(2).map(|pair| (pair[0], pair[1])).collect())
+}
+
+/// Data regions of a sparse file and the size it expands to
+#[derive(Debug, Clone)]
+pub(crate) struct SparseMap {
+    pub(crate) regions: Vec<Region>,
+    pub(crate) size: u64,
+}
+
+impl SparseMap {
+    /// Fails unless the regions are in order and within the file
+    fn checked(self) -> io::Result<Self> {
+        let mut end = 0;
+        for &(offset, len) in &self.regions {
+            if offset < end {
+                return Err(invalid("regions out of order"));
+            }
+            end = offset
+                .checked_add(len)
+                .filter(|&end| end <= self.size)
+                .ok_or_else(|| invalid("region past the end of the file"))?;
+        }
+        Ok(self)
+    }
+
+    /// Returns how many bytes the regions hold
+    pub(crate) fn stored_len(&self) -> u64 {
+        self.regions.iter().map(|&(_, len)| len).sum()
+    }
+
+    /// Scans the data read from `reader` for blocks of zeros, returning its
+    /// map unless there are none
+    pub(crate) fn find(mut reader: impl Read) -> io::Result<Option<Self>> {
+        let mut regions: Vec<Region> = Vec::new();
+        let mut block = [0; BLOCK_SIZE];
+        let mut size = 0;
+        loop {
+            let mut filled = 0;
+            while filled < BLOCK_SIZE {
+                match reader.read(&mut block[filled..])? {
+                    0 => break,
+                    n => filled += n,
+                }
+            }
+            if block[..filled].iter().any(|&byte| byte != 0) {
+                match regions.last_mut() {
+                    Some((offset, len)) if *offset + *len == size => *len += filled as u64,
+                    _ => regions.push((size, filled as u64)),
+                }
+            }
+            size += filled as u64;
+            if filled < BLOCK_SIZE {
+                break;
+            }
+        }
+        let map = SparseMap { regions, size };
+        if map.stored_len() == size {
+            return Ok(None);
+        }
+        // GNU tar ends files ending in a hole with an empty region
+        if map.regions.last().map_or(0, |&(offset, len)| offset + len) < size {
+            return Ok(Some(SparseMap {
+                regions: map.regions.into_iter().chain([(size, 0)]).collect(),
+                size,
+            }));
+        }
+        Ok(Some(map))
+    }
+
+    /// Reads the map of the old GNU sparse file `header`, reading extension
+    /// blocks with `read_block`
+    pub(crate) fn from_gnu(
+        header: &Header,
+        mut read_block: impl FnMut() -> io::Result<[u8; BLOCK_SIZE]>,
+    ) -> io::Result<Self> {
+        let gnu = header
+            .as_gnu()
+            .ok_or_else(|| invalid("sparse entry without a GNU header"))?;
+        let mut regions = Vec::new();
+        let mut push = |fields: &[GnuSparseHeader]| -> io::Result<()> {
+            for field in fields.iter().take_while(|field| !field.is_empty()) {
+                regions.push((field.offset()?, field.length()?));
+            }
+            Ok(())
+        };
+        push(&gnu.sparse)?;
+        let mut extended = gnu.is_extended();
+        while extended {
+            let mut ext = GnuExtSparseHeader::new();
+            *ext.as_mut_bytes() = read_block()?;
+            push(ext.sparse())?;
+            extended = ext.is_extended();
+        }
+        SparseMap {
+            regions,
+            size: gnu.real_size()?,
+        }
+        .checked()
+    }
+
+    /// Writes the map into the old GNU sparse file `header`, returning the
+    /// extension blocks to follow it
+    pub(crate) fn to_gnu(&self, header: &mut Header) -> io::Result<Vec<u8>> {
+        let gnu = header
+            .as_gnu_mut()
+            .ok_or_else(|| io::Error::other("sparse entry without a GNU header"))?;
+        let (first, rest) = self
+            .regions
+            .split_at(self.regions.len().min(gnu.sparse.len()));
+        for (field, &(offset, len)) in gnu.sparse.iter_mut().zip(first) {
+            field.set_offset(offset);
+            field.set_length(len);
+        }
+        gnu.set_real_size(self.size);
+        gnu.set_is_extended(!rest.is_empty());
+
+        let mut blocks = Vec::new();
+        let mut chunks = rest
+            .chunks(GnuExtSparseHeader::new().sparse().len())
+            .peekable();
+        while let Some(chunk) = chunks.next() {
+            let mut ext = GnuExtSparseHeader::new();
+            for (field, &(offset, len)) in ext.sparse_mut().iter_mut().zip(chunk) {
+                field.set_offset(offset);
+                field.set_length(len);
+            }
+            ext.set_is_extended(chunks.peek().is_some());
+            blocks.extend_from_slice(ext.as_bytes());
+        }
+        Ok(blocks)
+    }
+
+    /// Returns the map as PAX 1.0 stores it at the start of the data, padded
+    /// to a whole block
+    pub(crate) fn to_pax(&self) -> Vec<u8> {
+        let mut map = format!("{}\n", self.regions.len());
+        for (offset, len) in &self.regions {
+            map.push_str(&format!("{offset}\n{len}\n"));
+        }
+        let mut map = map.into_bytes();
+        map.resize(map.len().next_multiple_of(BLOCK_SIZE), 0);
+        map
+    }
+}
+
+/// Returns the path PAX 1.0 sparse files are stored under, which readers that
+/// don't know the format extract the stored data to
+pub(crate) fn pax_path(name: &str) -> String {
+    match name.rsplit_once('/') {
+        Some((dir, file)) => format!("{dir}/GNUSparseFile.0/{file}"),
+        None => format!("GNUSparseFile.0/{name}"),
+    }
+}
+
+/// What the PAX records of an entry say about it being sparse
+#[derive(Default)]
+pub(crate) struct PaxSparse {
+    /// Real name of the entry, stored under another path
+    pub(crate) name: Option<Vec<u8>>,
+    size: Option<u64>,
+    major: Option<u64>,
+    /// Offsets and lengths of the regions, in turn
+    numbers: Vec<u64>,
+}
+
+impl PaxSparse {
+    /// Takes in the PAX record `key`, returning whether it's about sparse
+    /// files
+    pub(crate) fn record(&mut self, key: &[u8], value: &[u8]) -> io::Result<bool> {
+        match key {
+            b"GNU.sparse.name" => self.name = Some(value.to_vec()),
+            b"GNU.sparse.size" | b"GNU.sparse.realsize" => self.size = Some(parse(value)?),
+            b"GNU.sparse.major" => self.major = Some(parse(value)?),
+            // 0.0 repeats these for every region
+            b"GNU.sparse.offset" | b"GNU.sparse.numbytes" => self.numbers.push(parse(value)?),
+            b"GNU.sparse.map" if value.is_empty() => {}
+            b"GNU.sparse.map" => {
+                for number in value.split(|&byte| byte == b',') {
+                    self.numbers.push(parse(number)?);
+                }
+            }
+            _ => return Ok(key.starts_with(b"GNU.sparse.")),
+        }
+        Ok(true)
+    }
+
+    /// Returns the map of the entry unless it isn't sparse, reading the
+    /// blocks at the start of its data with `read_block` for PAX 1.0
+    pub(crate) fn map(
+        self,
+        read_block: impl FnMut() -> io::Result<[u8; BLOCK_SIZE]>,
+    ) -> io::Result<Option<SparseMap>> {
+        let Some(size) = self.size else {
+            return Ok(None);
+        };
+        let regions = match self.major {
+            None | Some(0) => pairs(&self.numbers)?,
+            Some(1) => read_pax_map(read_block)?,
+            Some(_) => return Err(invalid("unsupported PAX sparse format version")),
+        };
+        SparseMap { regions, size }.checked().map(Some)
+    }
+}
+
+/// Reads the decimal numbers of a PAX 1.0 map, one per line: the count of
+/// regions, then the offset and length of every region
+fn read_pax_map(
+    mut read_block: impl FnMut() -> io::Result<[u8; BLOCK_SIZE]>,
+) -> io::Result<Vec<Region>> {
+    let mut text = Vec::new();
+    let mut parsed = 0;
+    let mut count = None;
+    let mut numbers = Vec::new();
+    loop {
+        while let Some(end) = text[parsed..].iter().position(|&byte| byte == b'\n') {
+            let number = parse(&text[parsed..parsed + end])?;
+            parsed += end + 1;
+            match count {
+                None => count = Some(number),
+                Some(_) => numbers.push(number),
+            }
+        }
+        if count.is_some_and(|count| numbers.len() as u64 >= count.saturating_mul(2)) {
+            break;
+        }
+        text.extend_from_slice(&read_block()?);
+    }
+    numbers.truncate(count.unwrap_or(0) as usize * 2);
+    pairs(&numbers)
+}
+
+/// Position in a sparse file being read, handing out zeros for its holes
+pub(crate) struct Expander {
+    map: SparseMap,
+    pos: u64,
+    /// Index of the first region not ending before `pos`
+    region: usize,
+}
+
+impl Expander {
+    pub(crate) fn new(map: SparseMap) -> Self {
+        Self {
+            map,
+            pos: 0,
+            region: 0,
+        }
+    }
+
+    pub(crate) fn map(&self) -> &SparseMap {
+        &self.map
+    }
+
+    /// Reads into `buf` from the current position, reading stored data with
+    /// `read_stored`
+    pub(crate) fn read(
+        &mut self,
+        buf: &mut [u8],
+        read_stored: impl FnOnce(&mut [u8]) -> io::Result<usize>,
+    ) -> io::Result<usize> {
+        let regions = &self.map.regions;
+        while regions
+            .get(self.region)
+            .is_some_and(|&(offset, len)| offset + len <= self.pos)
+        {
+            self.region += 1;
+        }
+        if self.pos >= self.map.size || buf.is_empty() {
+            return Ok(0);
+        }
+        let read = match regions.get(self.region) {
+            Some(&(offset, len)) if offset <= self.pos => {
+                let left = offset + len - self.pos;
+                let len = buf.len().min(usize::try_from(left).unwrap_or(usize::MAX));
+                match read_stored(&mut buf[..len])? {
+                    0 => return Err(invalid("stored data shorter than its map")),
+                    read => read,
+                }
+            }
+            next => {
+                let end = next.map_or(self.map.size, |&(offset, _)| offset);
+                let len = buf
+                    .len()
+                    .min(usize::try_from(end - self.pos).unwrap_or(usize::MAX));
+                buf[..len].fill(0);
+                len
+            }
+        };
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+/// Reader handing out only the data regions of the file read from `inner`
+pub(crate) struct Stored<R> {
+    inner: R,
+    regions: Vec<Region>,
+    pos: u64,
+    region: usize,
+}
+
+impl<R: Read> Stored<R> {
+    pub(crate) fn new(inner: R, map: &SparseMap) -> Self {
+        Self {
+            inner,
+            regions: map.regions.clone(),
+            pos: 0,
+            region: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for Stored<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while let Some(&(offset, len)) = self.regions.get(self.region) {
+            if self.pos < offset {
+                let hole = offset - self.pos;
+                let skipped = io::copy(&mut self.inner.by_ref().take(hole), &mut io::sink())?;
+                self.pos += skipped;
+                if skipped < hole {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                continue;
+            }
+            let left = offset + len - self.pos;
+            if left == 0 {
+                self.region += 1;
+                continue;
+            }
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            let len = buf.len().min(usize::try_from(left).unwrap_or(usize::MAX));
+            let read = self.inner.read(&mut buf[..len])?;
+            if read == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            self.pos += read as u64;
+            return Ok(read);
+        }
+        Ok(0)
+    }
+}
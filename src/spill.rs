@@ -0,0 +1,111 @@
+//! Reading archives with large files moved to temporary files
+//!
+//! Files up to the threshold are kept in memory, longer ones are copied to
+//! temporary files as they're decoded.
+
+use crate::{
+    visit::{self, Visited},
+    ArcResult,
+};
+use std::{
+    io::{self, Read, Write},
+    path::Path,
+};
+use tempfile::NamedTempFile;
+
+/// Data of a file read with a spill threshold
+#[derive(Debug)]
+pub enum SpilledData {
+    /// Data no longer than the threshold, kept in memory
+    Memory(Vec<u8>),
+    /// Longer data in a temporary file, deleted when this is dropped, and its
+    /// length
+    File(NamedTempFile, u64),
+}
+
+/// Entry read with a spill threshold, see [`ArcReader::open_spilled`]
+#[derive(Debug)]
+pub enum SpilledEntry {
+    File(String, SpilledData),
+    Directory(String),
+}
+
+impl SpilledData {
+    /// Returns the length of the data in bytes
+    pub fn len(&self) -> u64 {
+        match self {
+            SpilledData::Memory(data) => data.len() as u64,
+            SpilledData::File(_, len) => *len,
+        }
+    }
+
+    /// Returns whether the data is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the path of the temporary file, if the data was spilled
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            SpilledData::Memory(_) => None,
+            SpilledData::File(file, _) => Some(file.path()),
+        }
+    }
+
+    /// Returns a reader for the data, starting from the beginning
+    pub fn reader(&self) -> io::Result<Box<dyn Read + '_>> {
+        Ok(match self {
+            SpilledData::Memory(data) => Box::new(&data[..]),
+            SpilledData::File(file, _) => Box::new(file.reopen()?),
+        })
+    }
+}
+
+/// Writes `data` to a new temporary file
+pub(crate) fn to_file(data: &[u8]) -> io::Result<NamedTempFile> {
+    let mut file = NamedTempFile::new()?;
+    file.write_all(data)?;
+    Ok(file)
+}
+
+/// Reads `reader` into memory, or into a temporary file once it's longer than
+/// `threshold`
+fn spill(mut reader: impl Read, threshold: u64) -> io::Result<SpilledData> {
+    let mut data = Vec::new();
+    reader
+        .by_ref()
+        .take(threshold.saturating_add(1))
+        .read_to_end(&mut data)?;
+    if data.len() as u64 <= threshold {
+        return Ok(SpilledData::Memory(data));
+    }
+    let mut file = to_file(&data)?;
+    let len = data.len() as u64 + io::copy(&mut reader, &mut file)?;
+    Ok(SpilledData::File(file, len))
+}
+
+pub(crate) fn read(buf: &[u8], threshold: u64) -> ArcResult<Vec<SpilledEntry>> {
+    let mut entries = Vec::new();
+    visit::visit(buf, &mut collect(&mut entries, threshold))?;
+    Ok(entries)
+}
+
+pub(crate) fn from_reader(reader: impl Read, threshold: u64) -> ArcResult<Vec<SpilledEntry>> {
+    let mut entries = Vec::new();
+    visit::visit_reader(reader, &mut collect(&mut entries, threshold))?;
+    Ok(entries)
+}
+
+/// Returns a visitor pushing every entry to `entries`
+fn collect(
+    entries: &mut Vec<SpilledEntry>,
+    threshold: u64,
+) -> impl FnMut(Visited) -> ArcResult<()> + '_ {
+    move |Visited { name, data, .. }| {
+        entries.push(match data {
+            Some(data) => SpilledEntry::File(name, spill(data, threshold)?),
+            None => SpilledEntry::Directory(name),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+//! Conversion between spanned zip archives and single-volume ones
+//!
+//! Offsets in a spanned archive are relative to the volume they point into,
+//! so joining the volumes rebases them onto the concatenated buffer and
+//! splitting does the reverse. Headers are never split across volumes, only
+//! file data is.
+
+use crate::{
+    zip_records::{
+        find_end, find_zip64_locator, has_sig, invalid, CentralHeader, Field, LocalHeader,
+        ZIP64_END_SIG,
+    },
+    ArcResult,
+};
+use std::io;
+
+/// Signature at the start of the first volume of a spanned archive
+pub(crate) const MAGIC: &[u8] = b"PK\x07\x08";
+
+/// Smallest volume size allowed by the zip specification
+const MIN_VOLUME_SIZE: usize = 64 * 1024;
+
+/// Where the central directory and end records are placed
+struct Layout {
+    disk: u64,
+    disks: u64,
+    entries_on_disk: u64,
+    cd_disk: u64,
+    cd_offset: u64,
+    records_offset: u64,
+}
+
+/// End of central directory records
+struct End {
+    eocd: usize,
+    zip64: Option<(usize, usize)>,
+    entries: u64,
+    cd_disk: u64,
+    cd_offset: u64,
+}
+
+impl End {
+    /// Finds the end records of the archive in `buf`, made of volumes starting
+    /// at `starts`
+    fn find(buf: &[u8], starts: &[usize]) -> ArcResult<Self> {
+        let eocd = find_end(buf).ok_or_else(|| invalid("missing end of central directory"))?;
+        let locator = find_zip64_locator(buf, eocd);
+        if let Some(locator) = locator {
+            let disk = Field::new(locator + 4, 4).get(buf)? as usize;
+            let record = starts
+                .get(disk)
+                .ok_or_else(|| invalid("missing volume"))?
+                .checked_add(Field::new(locator + 8, 8).get(buf)? as usize)
+                .filter(|&record| has_sig(buf, record, ZIP64_END_SIG))
+                .ok_or_else(|| invalid("bad zip64 end of central directory"))?;
+            Ok(Self {
+                eocd,
+                zip64: Some((record, locator)),
+                entries: Field::new(record + 32, 8).get(buf)?,
+                cd_disk: Field::new(record + 20, 4).get(buf)?,
+                cd_offset: Field::new(record + 48, 8).get(buf)?,
+            })
+        } else {
+            Ok(Self {
+                eocd,
+                zip64: None,
+                entries: Field::new(eocd + 10, 2).get(buf)?,
+                cd_disk: Field::new(eocd + 6, 2).get(buf)?,
+                cd_offset: Field::new(eocd + 16, 4).get(buf)?,
+            })
+        }
+    }
+
+    /// Returns where the end records start
+    fn start(&self) -> usize {
+        self.zip64.map_or(self.eocd, |(record, _)| record)
+    }
+
+    /// Writes `layout` to a copy of the records starting at the beginning of
+    /// `buf`
+    fn update(&self, buf: &mut [u8], layout: Layout) -> ArcResult<()> {
+        let Layout {
+            disk,
+            disks,
+            entries_on_disk,
+            cd_disk,
+            cd_offset,
+            records_offset,
+        } = layout;
+        let start = self.start();
+        let eocd = self.eocd - start;
+        Field::new(eocd + 4, 2).set(buf, disk)?;
+        Field::new(eocd + 6, 2).set(buf, cd_disk)?;
+        if let Some((record, locator)) = self.zip64 {
+            let (record, locator) = (record - start, locator - start);
+            Field::new(record + 16, 4).set(buf, disk)?;
+            Field::new(record + 20, 4).set(buf, cd_disk)?;
+            Field::new(record + 24, 8).set(buf, entries_on_disk)?;
+            Field::new(record + 48, 8).set(buf, cd_offset)?;
+            Field::new(locator + 4, 4).set(buf, disk)?;
+            Field::new(locator + 8, 8).set(buf, records_offset)?;
+            Field::new(locator + 16, 4).set(buf, disks)?;
+        }
+        for (field, value) in [
+            (Field::new(eocd + 8, 2), entries_on_disk),
+            (Field::new(eocd + 16, 4), cd_offset),
+        ] {
+            if self.zip64.is_none() || !field.saturated(buf)? {
+                field.set(buf, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Joins the volumes of a spanned archive into a single-volume archive
+pub(crate) fn join(volumes: &[&[u8]]) -> ArcResult<Vec<u8>> {
+    let mut buf = Vec::with_capacity(volumes.iter().map(|volume| volume.len()).sum());
+    let mut starts = Vec::with_capacity(volumes.len());
+    for volume in volumes {
+        starts.push(buf.len());
+        buf.extend_from_slice(volume);
+    }
+    let start = |disk: u64| {
+        starts
+            .get(disk as usize)
+            .map(|&start| start as u64)
+            .ok_or_else(|| invalid("missing volume"))
+    };
+
+    let end = End::find(&buf, &starts)?;
+    let cd_start = start(end.cd_disk)? + end.cd_offset;
+    let mut pos = cd_start as usize;
+    for _ in 0..end.entries {
+        let entry = CentralHeader::parse(&buf, pos)?;
+        let offset = start(entry.disk.get(&buf)?)? + entry.offset.get(&buf)?;
+        entry.disk.set(&mut buf, 0)?;
+        entry.offset.set(&mut buf, offset)?;
+        pos += entry.len;
+    }
+
+    let records = end.start();
+    end.update(
+        &mut buf[records..],
+        Layout {
+            disk: 0,
+            disks: 1,
+            entries_on_disk: end.entries,
+            cd_disk: 0,
+            cd_offset: cd_start,
+            records_offset: records as u64,
+        },
+    )?;
+    Ok(buf)
+}
+
+/// Volumes being filled up to a maximum size
+struct Volumes {
+    size: usize,
+    volumes: Vec<Vec<u8>>,
+}
+
+impl Volumes {
+    /// Returns the volume and offset `len` bytes that mustn't be split would
+    /// be placed at
+    fn position(&self, len: usize) -> ArcResult<(usize, usize)> {
+        if len > self.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "zip header larger than the volume size",
+            )
+            .into());
+        }
+        let last = self.volumes.len() - 1;
+        match self.volumes[last].len() {
+            used if used + len > self.size => Ok((last + 1, 0)),
+            used => Ok((last, used)),
+        }
+    }
+
+    /// Appends bytes that mustn't be split, returning where they were placed
+    fn place(&mut self, data: &[u8]) -> ArcResult<(usize, usize)> {
+        let (volume, offset) = self.position(data.len())?;
+        if volume == self.volumes.len() {
+            self.volumes.push(Vec::new());
+        }
+        self.volumes[volume].extend_from_slice(data);
+        Ok((volume, offset))
+    }
+
+    /// Appends bytes, splitting them across as many volumes as needed
+    fn spill(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.volumes.last().unwrap().len() == self.size {
+                self.volumes.push(Vec::new());
+            }
+            let last = self.volumes.last_mut().unwrap();
+            let len = (self.size - last.len()).min(data.len());
+            last.extend_from_slice(&data[..len]);
+            data = &data[len..];
+        }
+    }
+}
+
+/// Splits a single-volume archive into volumes of at most `size` bytes
+pub(crate) fn split(buf: &[u8], size: usize) -> ArcResult<Vec<Vec<u8>>> {
+    if size < MIN_VOLUME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "zip volumes must be at least 64 KiB",
+        )
+        .into());
+    }
+    if buf.len() <= size {
+        return Ok(vec![buf.to_vec()]);
+    }
+
+    let end = End::find(buf, &[0])?;
+    let cd_start = end.cd_offset as usize;
+    let mut central = Vec::new();
+    let mut pos = cd_start;
+    for _ in 0..end.entries {
+        let entry = CentralHeader::parse(buf, pos)?;
+        pos += entry.len;
+        central.push((pos - entry.len, entry));
+    }
+
+    let mut locals = central
+        .iter()
+        .map(|(_, entry)| Ok(entry.offset.get(buf)? as usize))
+        .collect::<ArcResult<Vec<_>>>()?;
+    locals.sort_unstable();
+    let mut volumes = Volumes {
+        size,
+        volumes: vec![MAGIC.to_vec()],
+    };
+    let mut placed = Vec::with_capacity(locals.len());
+    for (i, &local) in locals.iter().enumerate() {
+        let header_end = local + LocalHeader::len(buf, local)?;
+        let data_end = locals.get(i + 1).copied().unwrap_or(cd_start);
+        let header = buf
+            .get(local..header_end)
+            .ok_or_else(|| invalid("truncated"))?;
+        let data = buf
+            .get(header_end..data_end)
+            .ok_or_else(|| invalid("truncated"))?;
+        placed.push(volumes.place(header)?);
+        volumes.spill(data);
+    }
+
+    let mut cd_position = None;
+    let mut cd_volumes = Vec::with_capacity(central.len());
+    for (pos, entry) in central {
+        let local = entry.offset.get(buf)? as usize;
+        let (volume, offset) = placed[locals.binary_search(&local).unwrap()];
+        let mut record = buf[pos..pos + entry.len].to_vec();
+        entry.disk.rebased(pos).set(&mut record, volume as u64)?;
+        entry.offset.rebased(pos).set(&mut record, offset as u64)?;
+        let position = volumes.place(&record)?;
+        cd_position.get_or_insert(position);
+        cd_volumes.push(position.0);
+    }
+
+    let mut records = buf[end.start()..].to_vec();
+    let (volume, offset) = volumes.position(records.len())?;
+    let (cd_volume, cd_offset) = cd_position.unwrap_or((volume, offset));
+    end.update(
+        &mut records,
+        Layout {
+            disk: volume as u64,
+            disks: volume as u64 + 1,
+            entries_on_disk: cd_volumes.iter().filter(|&&cd| cd == volume).count() as u64,
+            cd_disk: cd_volume as u64,
+            cd_offset: cd_offset as u64,
+            records_offset: offset as u64,
+        },
+    )?;
+    volumes.place(&records)?;
+    Ok(volumes.volumes)
+}
@@ -0,0 +1,640 @@
+//! Lazy reading and writing of tarballs, one entry at a time
+
+use crate::sparse::{Expander, PaxSparse, SparseMap};
+use crate::{
+    guard, read_sized, ArcEntry, ArcError, ArcFormat, ArcMeta, ArcReader, ArcResult, ArcWriter,
+    Encoder, EntryKind, ExactReader, PathPolicy, SpecialFile, TarStream,
+};
+use crate::{
+    meta, name, path,
+    progress::{CountingReader, Tracker},
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::{self, Read, Write},
+    time::SystemTime,
+};
+use tar::{Builder, EntryType, Header};
+
+const BLOCK_SIZE: u64 = 512;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid tar: {msg}"))
+}
+
+/// Streaming reader for tarballs, compressed or not
+///
+/// Unlike [`ArcReader`], entries are only decoded as the iterator reaches
+/// them, so memory use is bounded by the largest entry rather than the
+/// whole archive. [`ArcStreamReader::next_entry`] doesn't even hold a whole
+/// entry, handing out its data as a reader instead.
+pub struct ArcStreamReader<'a> {
+    format: ArcFormat,
+    reader: Box<dyn Read + 'a>,
+    /// Unread bytes of the current entry's data
+    remaining: u64,
+    /// Padding after the current entry's data
+    padding: u64,
+    done: bool,
+}
+
+/// Entry of an [`ArcStreamReader`], reading its data from the archive
+///
+/// Data left unread is skipped when the next entry is requested.
+pub struct ArcStreamEntry<'r, 'a> {
+    reader: &'r mut ArcStreamReader<'a>,
+    name: String,
+    /// Bytes of the name if they aren't valid UTF-8
+    raw_name: Option<Vec<u8>>,
+    directory: bool,
+    /// Target of a symbolic link
+    symlink: Option<String>,
+    /// Earlier entry a hard link shares its data with
+    hard_link: Option<String>,
+    size: u64,
+    header: Header,
+    xattrs: BTreeMap<String, Vec<u8>>,
+    atime: Option<SystemTime>,
+    ctime: Option<SystemTime>,
+    /// Position in the expanded data of a sparse file
+    sparse: Option<Expander>,
+}
+
+/// What the headers of the next entry of an [`ArcStreamReader`] say about it
+struct EntryHeader {
+    name: String,
+    raw_name: Option<Vec<u8>>,
+    directory: bool,
+    symlink: Option<String>,
+    hard_link: Option<String>,
+    /// Size of the expanded data for sparse files
+    size: u64,
+    header: Header,
+    xattrs: BTreeMap<String, Vec<u8>>,
+    /// Times of the `atime` and `ctime` PAX records
+    atime: Option<SystemTime>,
+    ctime: Option<SystemTime>,
+    sparse: Option<SparseMap>,
+}
+
+impl<'a> ArcStreamReader<'a> {
+    /// Starts reading a tarball from `reader`
+    /// Fails with [`ArcError::UnrecognizedFormat`] if it isn't a tar-based
+    /// format
+    pub fn new(reader: impl Read + 'a) -> ArcResult<Self> {
+        match ArcReader::tar_stream(reader)? {
+            TarStream::Tar(format, reader) => Ok(Self::from_decoder(format, reader)),
+            TarStream::Other(..) => Err(ArcError::UnrecognizedFormat),
+        }
+    }
+
+    /// Starts reading a tarball of `format` from its decompressed stream
+    pub(crate) fn from_decoder(format: ArcFormat, reader: Box<dyn Read + 'a>) -> Self {
+        Self {
+            format,
+            reader,
+            remaining: 0,
+            padding: 0,
+            done: false,
+        }
+    }
+
+    /// Returns the format of the archive
+    pub fn format(&self) -> ArcFormat {
+        self.format
+    }
+
+    /// Advances to the next entry, returning `None` at the end of the archive
+    pub fn next_entry(&mut self) -> ArcResult<Option<ArcStreamEntry<'_, 'a>>> {
+        if self.done {
+            return Ok(None);
+        }
+        let entry = guard(|| Ok(self.read_entry()?));
+        // stop after the end of the archive or the first error
+        self.done = !matches!(entry, Ok(Some(_)));
+        Ok(entry?.map(|header| ArcStreamEntry {
+            reader: self,
+            name: header.name,
+            raw_name: header.raw_name,
+            directory: header.directory,
+            symlink: header.symlink,
+            hard_link: header.hard_link,
+            size: header.size,
+            header: header.header,
+            xattrs: header.xattrs,
+            atime: header.atime,
+            ctime: header.ctime,
+            sparse: header.sparse.map(Expander::new),
+        }))
+    }
+
+    /// Skips the rest of the current entry and its padding
+    fn skip(&mut self) -> io::Result<()> {
+        let len = self.remaining + self.padding;
+        let skipped = io::copy(&mut self.reader.by_ref().take(len), &mut io::sink())?;
+        if skipped < len {
+            return Err(invalid("truncated entry"));
+        }
+        self.remaining = 0;
+        self.padding = 0;
+        Ok(())
+    }
+
+    /// Reads the next header, returning `None` at the end of the archive
+    fn read_header(&mut self) -> io::Result<Option<Header>> {
+        self.skip()?;
+        let mut block = [0; BLOCK_SIZE as usize];
+        let mut filled = 0;
+        while filled < block.len() {
+            match self.reader.read(&mut block[filled..])? {
+                0 if filled == 0 => return Ok(None),
+                0 => return Err(invalid("truncated header")),
+                n => filled += n,
+            }
+        }
+        if block.iter().all(|&byte| byte == 0) {
+            return Ok(None);
+        }
+
+        let header = Header::from_byte_slice(&block).clone();
+        let sum = block[..148]
+            .iter()
+            .chain(&block[156..])
+            .map(|&byte| u32::from(byte))
+            .sum::<u32>()
+            + 8 * u32::from(b' ');
+        if sum != header.cksum()? {
+            return Err(invalid("header checksum mismatch"));
+        }
+        self.remaining = header.entry_size()?;
+        self.padding = self.remaining.next_multiple_of(BLOCK_SIZE) - self.remaining;
+        Ok(Some(header))
+    }
+
+    /// Reads the current entry's data whole, for metadata entries
+    fn read_data(&mut self) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let len = self.remaining;
+        self.reader.by_ref().take(len).read_to_end(&mut data)?;
+        if (data.len() as u64) < len {
+            return Err(invalid("truncated entry"));
+        }
+        self.remaining = 0;
+        Ok(data)
+    }
+
+    /// Reads a block without counting it as part of the current entry's data,
+    /// for the extension blocks of old GNU sparse files
+    fn read_block(&mut self) -> io::Result<[u8; BLOCK_SIZE as usize]> {
+        let mut block = [0; BLOCK_SIZE as usize];
+        self.reader
+            .read_exact(&mut block)
+            .map_err(|_| invalid("truncated header"))?;
+        Ok(block)
+    }
+
+    /// Reads a block of the current entry's data, for PAX sparse maps
+    fn read_data_block(&mut self) -> io::Result<[u8; BLOCK_SIZE as usize]> {
+        if self.remaining < BLOCK_SIZE {
+            return Err(invalid("truncated sparse map"));
+        }
+        let block = self.read_block()?;
+        self.remaining -= BLOCK_SIZE;
+        Ok(block)
+    }
+
+    /// Reads headers up to the next entry
+    fn read_entry(&mut self) -> io::Result<Option<EntryHeader>> {
+        // set by GNU long name and pax headers for the entry that follows
+        let mut long_name = None;
+        let mut long_link = None;
+        let mut xattrs = BTreeMap::new();
+        let (mut atime, mut ctime) = (None, None);
+        let mut pax_sparse = PaxSparse::default();
+        while let Some(header) = self.read_header()? {
+            match header.entry_type() {
+                EntryType::GNULongName => {
+                    let data = self.read_data()?;
+                    let end = data.iter().position(|&byte| byte == 0);
+                    long_name = Some(data[..end.unwrap_or(data.len())].to_vec());
+                }
+                EntryType::GNULongLink => {
+                    let data = self.read_data()?;
+                    let end = data.iter().position(|&byte| byte == 0);
+                    long_link = Some(data[..end.unwrap_or(data.len())].to_vec());
+                }
+                EntryType::XHeader => {
+                    let data = self.read_data()?;
+                    for record in pax_records(&data) {
+                        let (key, value) = record?;
+                        match key {
+                            b"path" => long_name = Some(value.to_vec()),
+                            b"linkpath" => long_link = Some(value.to_vec()),
+                            b"atime" => atime = meta::from_pax_time(value),
+                            b"ctime" => ctime = meta::from_pax_time(value),
+                            _ if pax_sparse.record(key, value)? => {}
+                            _ => {
+                                if let Some(key) = key.strip_prefix(meta::PAX_XATTR.as_bytes()) {
+                                    let key = String::from_utf8_lossy(key).into_owned();
+                                    xattrs.insert(key, value.to_vec());
+                                }
+                            }
+                        }
+                    }
+                }
+                EntryType::XGlobalHeader => {}
+                kind => {
+                    let name = pax_sparse.name.take().or(long_name);
+                    let name = name.unwrap_or_else(|| header.path_bytes().into_owned());
+                    let (name, raw_name) = name::decode(name);
+                    let target = (kind.is_symlink() || kind.is_hard_link()).then(|| {
+                        let target = long_link
+                            .or_else(|| header.link_name_bytes().map(|link| link.into_owned()))
+                            .unwrap_or_default();
+                        String::from_utf8_lossy(&target).into_owned()
+                    });
+                    let (symlink, hard_link) = match kind.is_hard_link() {
+                        true => (None, target),
+                        false => (target, None),
+                    };
+                    let sparse = match kind {
+                        EntryType::GNUSparse => {
+                            Some(SparseMap::from_gnu(&header, || self.read_block())?)
+                        }
+                        _ => pax_sparse.map(|| self.read_data_block())?,
+                    };
+                    return Ok(Some(EntryHeader {
+                        name,
+                        raw_name,
+                        directory: kind.is_dir(),
+                        symlink,
+                        hard_link,
+                        size: sparse.as_ref().map_or(self.remaining, |map| map.size),
+                        header,
+                        xattrs,
+                        atime,
+                        ctime,
+                        sparse,
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Splits the data of a PAX extended header into its key and value records
+///
+/// Unlike `tar::PaxExtensions`, records are split by their length, so values
+/// may contain newlines.
+fn pax_records(mut data: &[u8]) -> impl Iterator<Item = io::Result<(&[u8], &[u8])>> {
+    std::iter::from_fn(move || {
+        // some writers pad the header with NULs
+        if data.first().is_none_or(|&byte| byte == 0) {
+            return None;
+        }
+        let record = next_pax_record(&mut data);
+        if record.is_err() {
+            data = &[];
+        }
+        Some(record)
+    })
+}
+
+/// Splits the first `<length> <key>=<value>\n` record off `data`
+fn next_pax_record<'d>(data: &mut &'d [u8]) -> io::Result<(&'d [u8], &'d [u8])> {
+    let malformed = || invalid("malformed pax record");
+    let space = data
+        .iter()
+        .position(|&byte| byte == b' ')
+        .ok_or_else(malformed)?;
+    let len = std::str::from_utf8(&data[..space])
+        .ok()
+        .and_then(|len| len.parse::<usize>().ok())
+        .filter(|&len| len > space && len <= data.len())
+        .ok_or_else(malformed)?;
+    let (record, rest) = data.split_at(len);
+    *data = rest;
+    let record = record[space + 1..]
+        .strip_suffix(b"\n")
+        .ok_or_else(malformed)?;
+    let equals = record
+        .iter()
+        .position(|&byte| byte == b'=')
+        .ok_or_else(malformed)?;
+    Ok((&record[..equals], &record[equals + 1..]))
+}
+
+impl Iterator for ArcStreamReader<'_> {
+    type Item = ArcResult<ArcEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_entry() {
+            Ok(entry) => entry.map(|entry| guard(|| entry.into_entry(None))),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl ArcStreamEntry<'_, '_> {
+    /// Reads the whole entry, reporting its data to `progress` as it's read
+    pub(crate) fn into_entry(mut self, progress: Option<&Tracker>) -> ArcResult<ArcEntry> {
+        let meta = meta::tar_meta(&self)?;
+        let kind = if self.directory {
+            EntryKind::Directory
+        } else if let Some(target) = self.symlink.take() {
+            EntryKind::Symlink(target)
+        } else if let Some(target) = self.hard_link.take() {
+            EntryKind::HardLink(target)
+        } else if let Some(special) = self.special() {
+            EntryKind::Special(special)
+        } else {
+            EntryKind::File
+        };
+        let name = std::mem::take(&mut self.name);
+        let mut data = Vec::new();
+        if kind == EntryKind::File {
+            let size = self.size;
+            let mut reader = CountingReader {
+                inner: &mut self,
+                name: &name,
+                tracker: progress,
+            };
+            read_sized(&mut data, size, |data| reader.read_to_end(data))?;
+        }
+        Ok(ArcEntry {
+            name,
+            data,
+            meta: ArcMeta { kind, ..meta },
+        })
+    }
+
+    /// Returns the path of the entry
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the bytes of the path of the entry if they aren't valid UTF-8,
+    /// [`ArcStreamEntry::name`] holding them decoded lossily
+    pub fn raw_name(&self) -> Option<&[u8]> {
+        self.raw_name.as_deref()
+    }
+
+    /// Returns whether the entry is a directory
+    pub fn is_dir(&self) -> bool {
+        self.directory
+    }
+
+    /// Returns the target of the entry if it's a symbolic link
+    pub fn symlink_target(&self) -> Option<&str> {
+        self.symlink.as_deref()
+    }
+
+    /// Returns the name of the earlier entry the entry shares its data with if
+    /// it's a hard link
+    pub fn hard_link_target(&self) -> Option<&str> {
+        self.hard_link.as_deref()
+    }
+
+    /// Returns the kind of the entry if it's a FIFO or a device node
+    pub fn special(&self) -> Option<SpecialFile> {
+        meta::tar_special(&self.header)
+    }
+
+    /// Returns the size of the entry's data in bytes, holes included for
+    /// sparse files
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the offsets and lengths of the data regions of the entry if
+    /// it's a sparse file, everything between them reads as zeros
+    pub fn sparse_regions(&self) -> Option<&[(u64, u64)]> {
+        self.sparse.as_ref().map(|sparse| &sparse.map().regions[..])
+    }
+
+    /// Returns the extended attributes of the entry, read from `SCHILY.xattr`
+    /// PAX records
+    pub fn xattrs(&self) -> &BTreeMap<String, Vec<u8>> {
+        &self.xattrs
+    }
+
+    /// Returns the access time of the entry, read from its `atime` PAX record
+    pub fn atime(&self) -> Option<SystemTime> {
+        self.atime
+    }
+
+    /// Returns the status change time of the entry, read from its `ctime` PAX
+    /// record, which this crate writes as the creation time
+    pub fn ctime(&self) -> Option<SystemTime> {
+        self.ctime
+    }
+
+    /// Returns the entry's own header, without what PAX and GNU extension
+    /// headers override
+    pub(crate) fn header(&self) -> &Header {
+        &self.header
+    }
+}
+
+impl Read for ArcStreamEntry<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let reader = &mut *self.reader;
+        match &mut self.sparse {
+            Some(sparse) => sparse.read(buf, |buf| read_stored(reader, buf)),
+            None => read_stored(reader, buf),
+        }
+    }
+}
+
+/// Reads the stored data of the current entry of `reader`
+fn read_stored(reader: &mut ArcStreamReader, buf: &mut [u8]) -> io::Result<usize> {
+    let len = buf
+        .len()
+        .min(usize::try_from(reader.remaining).unwrap_or(usize::MAX));
+    if len == 0 {
+        return Ok(0);
+    }
+    let read = match guard(|| Ok(reader.reader.read(&mut buf[..len])?)) {
+        Ok(read) => read,
+        Err(ArcError::IoError(err)) => return Err(err),
+        Err(err) => {
+            reader.done = true;
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+    };
+    if read == 0 {
+        reader.done = true;
+        return Err(invalid("truncated entry"));
+    }
+    reader.remaining -= read as u64;
+    Ok(read)
+}
+
+/// Streaming writer for tarballs, compressed or not
+///
+/// Unlike [`ArcWriter`], each entry is encoded and written to the underlying
+/// writer as soon as it's pushed, so nothing but the compressor's state is
+/// held in memory. If pushing an entry fails, the archive is left incomplete.
+pub struct ArcStreamWriter<W: Write> {
+    options: ArcWriter,
+    builder: Builder<Encoder<W>>,
+    /// Names written so far, only kept with
+    /// [`ArcWriter::reject_duplicates`]
+    names: HashSet<String>,
+}
+
+impl<W: Write> ArcStreamWriter<W> {
+    /// Starts writing a tarball of `format` into `writer`, with the default
+    /// options of [`ArcWriter::new`]
+    /// Fails if `format` isn't a tar-based format
+    pub fn new(format: ArcFormat, writer: W) -> ArcResult<Self> {
+        ArcWriter::new(format).into_stream(writer)
+    }
+
+    /// Starts writing with the options of `options`, writing the entries
+    /// already pushed to it first
+    pub(crate) fn with_options(mut options: ArcWriter, writer: W) -> ArcResult<Self> {
+        match options.format {
+            ArcFormat::Tar
+            | ArcFormat::TarGz
+            | ArcFormat::TarXz
+            | ArcFormat::TarBz2
+            | ArcFormat::TarZst
+            | ArcFormat::TarLz4
+            | ArcFormat::TarBr
+            | ArcFormat::TarSz => {}
+            format => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{format:?} archives can't be written entry by entry"),
+                )
+                .into())
+            }
+        }
+        options.begin()?;
+        let entries = std::mem::take(&mut options.entries);
+        let names = match options.reject_duplicates {
+            true => entries.iter().map(|entry| entry.name.clone()).collect(),
+            false => HashSet::new(),
+        };
+        let mut builder = Builder::new(Encoder::new(&options, writer, "")?);
+        for (i, entry) in entries.iter().enumerate() {
+            let data = &entry.data;
+            match &entry.meta.kind {
+                EntryKind::Directory => options.append_tar(&mut builder, entry, io::empty(), 0)?,
+                EntryKind::File => match options.sparse_map(i, data)? {
+                    Some(map) => options.append_tar_sparse(
+                        &mut builder,
+                        entry,
+                        &map,
+                        options.file_data(i, data)?,
+                    )?,
+                    None => {
+                        let len = options.file_len(i, data);
+                        options.append_tar(&mut builder, entry, options.file_data(i, data)?, len)?
+                    }
+                },
+                EntryKind::Symlink(target) => {
+                    options.append_tar_link(&mut builder, entry, target, EntryType::Symlink)?
+                }
+                EntryKind::HardLink(target) => {
+                    options.append_tar_link(&mut builder, entry, target, EntryType::Link)?
+                }
+                EntryKind::Special(special) => {
+                    options.append_tar_special(&mut builder, entry, *special)?
+                }
+            }
+        }
+        options.readers.clear();
+        Ok(Self {
+            options,
+            builder,
+            names,
+        })
+    }
+
+    /// Returns the format of the archive
+    pub fn format(&self) -> ArcFormat {
+        self.options.format
+    }
+
+    /// Encodes and writes an entry
+    pub fn push(&mut self, mut entry: ArcEntry) -> ArcResult<()> {
+        if self.options.path_policy == PathPolicy::Sanitize && !path::sanitize_entry(&mut entry) {
+            return Ok(());
+        }
+        self.check_entry(&entry)?;
+        let builder = &mut self.builder;
+        let data = &entry.data[..];
+        match &entry.meta.kind {
+            EntryKind::Directory => self.options.append_tar(builder, &entry, io::empty(), 0)?,
+            EntryKind::File => {
+                let map = match self.options.tar_sparse {
+                    true => SparseMap::find(data)?,
+                    false => None,
+                };
+                match map {
+                    Some(map) => self
+                        .options
+                        .append_tar_sparse(builder, &entry, &map, data)?,
+                    None => {
+                        let len = data.len() as u64;
+                        self.options.append_tar(builder, &entry, data, len)?
+                    }
+                }
+            }
+            EntryKind::Symlink(target) => {
+                self.options
+                    .append_tar_link(builder, &entry, target, EntryType::Symlink)?
+            }
+            EntryKind::HardLink(target) => {
+                self.options
+                    .append_tar_link(builder, &entry, target, EntryType::Link)?
+            }
+            EntryKind::Special(special) => {
+                self.options.append_tar_special(builder, &entry, *special)?
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes and writes the file `name`, copying its `len` bytes of data
+    /// from `reader`
+    ///
+    /// Only the first `len` bytes are archived, and it's an error for the
+    /// reader to end earlier.
+    pub fn push_from_reader(&mut self, name: &str, reader: impl Read, len: u64) -> ArcResult<()> {
+        let mut entry = ArcEntry::file(name, Vec::new());
+        if self.options.path_policy == PathPolicy::Sanitize && !path::sanitize_entry(&mut entry) {
+            return Ok(());
+        }
+        self.check_entry(&entry)?;
+        let data = ExactReader {
+            inner: reader,
+            remaining: len,
+        };
+        self.options
+            .append_tar(&mut self.builder, &entry, data, len)?;
+        Ok(())
+    }
+
+    /// Fails if an entry with the name of `entry` was already written and
+    /// [`ArcWriter::reject_duplicates`] is set, or if its name isn't safe and
+    /// [`ArcWriter::path_policy`] rejects it
+    fn check_entry(&mut self, entry: &ArcEntry) -> ArcResult<()> {
+        if self.options.path_policy == PathPolicy::Reject {
+            path::reject_unsafe([(&entry.name, &entry.meta.kind)])?;
+        }
+        let name = &entry.name;
+        if self.options.reject_duplicates && !self.names.insert(name.clone()) {
+            return Err(ArcError::DuplicateEntry(name.clone()));
+        }
+        Ok(())
+    }
+
+    /// Ends the archive, returning the underlying writer
+    pub fn finish(self) -> ArcResult<W> {
+        Ok(self.builder.into_inner()?.finish()?)
+    }
+}
@@ -0,0 +1,57 @@
+//! Async wrappers over tokio's I/O traits
+
+use crate::{async_io::wrappers, ArcFormat, ArcReader, ArcResult, ArcWriter};
+use std::io;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    task,
+};
+
+/// [`ArcReader`] for archives read from a tokio [`AsyncRead`]
+///
+/// The archive is read asynchronously and decoded once it's complete. The
+/// reader dereferences to the [`ArcReader`] holding the entries.
+pub struct AsyncArcReader(ArcReader);
+
+impl AsyncArcReader {
+    /// Reads the archive from `reader` to its end
+    ///
+    /// Decoding runs on tokio's blocking thread pool, so it doesn't hold up
+    /// other tasks.
+    pub async fn new(mut reader: impl AsyncRead + Unpin) -> ArcResult<Self> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await?;
+        let reader = task::spawn_blocking(move || ArcReader::new(&buf))
+            .await
+            .map_err(io::Error::from)??;
+        Ok(Self(reader))
+    }
+}
+
+/// [`ArcWriter`] writing archives to a tokio [`AsyncWrite`]
+///
+/// Entries and options are set through the [`ArcWriter`] it dereferences to.
+pub struct AsyncArcWriter(ArcWriter);
+
+impl AsyncArcWriter {
+    /// Creates the finished archive and writes it to `writer`, failing like
+    /// [`ArcWriter::archive`], and gives the writer back
+    ///
+    /// Encoding runs on tokio's blocking thread pool, so it doesn't hold up
+    /// other tasks. The writer is moved there, so it's dropped if this fails
+    /// or the future is.
+    pub async fn archive_to(self, mut writer: impl AsyncWrite + Unpin) -> ArcResult<Self> {
+        let inner = self.0;
+        let (inner, archive) = task::spawn_blocking(move || {
+            let archive = inner.archive()?;
+            ArcResult::Ok((inner, archive))
+        })
+        .await
+        .map_err(io::Error::from)??;
+        writer.write_all(&archive).await?;
+        writer.flush().await?;
+        Ok(Self(inner))
+    }
+}
+
+wrappers!(AsyncArcReader, AsyncArcWriter);
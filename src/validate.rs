@@ -0,0 +1,88 @@
+//! Checking archives for problems without extracting them
+
+use crate::{
+    guard, is_safe_path, path::Symlinks, ArcError, ArcFormat, ArcReader, ChecksumPolicy, Decoding,
+    EntryKind,
+};
+use std::collections::HashSet;
+
+/// Problem found by [`ArcReader::validate`]
+#[derive(Debug)]
+pub enum Problem {
+    /// Error that stopped the archive from being read, leaving its entries
+    /// unchecked
+    Unreadable(ArcError),
+    /// Zip entry whose data doesn't match its CRC-32
+    ChecksumMismatch(String),
+    /// Entry name or hard link target escaping the extraction directory
+    UnsafePath(String),
+    /// Entry that symlinks earlier in the archive send outside the
+    /// extraction directory
+    SymlinkEscape(String),
+    /// Entry with the same name as an earlier one
+    DuplicateEntry(String),
+}
+
+/// Outcome of [`ArcReader::validate`]
+#[derive(Debug)]
+pub struct ValidationReport {
+    /// Format of the archive, as detected
+    pub format: ArcFormat,
+    /// Number of entries checked
+    pub entries: usize,
+    /// Problems found, in archive order
+    pub problems: Vec<Problem>,
+}
+
+impl ValidationReport {
+    /// Returns whether no problems were found
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+pub(crate) fn validate(buf: &[u8]) -> ValidationReport {
+    let options = Decoding {
+        checksums: ChecksumPolicy::Warn,
+        ..Decoding::default()
+    };
+    let reader = match guard(|| ArcReader::read_unchecked(buf, options)) {
+        Ok(reader) => reader,
+        Err(err) => {
+            return ValidationReport {
+                format: ArcReader::detect(buf),
+                entries: 0,
+                problems: vec![Problem::Unreadable(err)],
+            }
+        }
+    };
+    let mut problems: Vec<_> = reader
+        .checksum_mismatches
+        .iter()
+        .cloned()
+        .map(Problem::ChecksumMismatch)
+        .collect();
+    let mut names = HashSet::new();
+    let mut symlinks = Symlinks::default();
+    for entry in &reader.entries {
+        if !is_safe_path(&entry.name) {
+            problems.push(Problem::UnsafePath(entry.name.clone()));
+        }
+        if let EntryKind::HardLink(target) = &entry.meta.kind {
+            if !is_safe_path(target) {
+                problems.push(Problem::UnsafePath(target.clone()));
+            }
+        }
+        if symlinks.escapes(&entry.name, &entry.meta.kind) {
+            problems.push(Problem::SymlinkEscape(entry.name.clone()));
+        }
+        if !names.insert(&entry.name) {
+            problems.push(Problem::DuplicateEntry(entry.name.clone()));
+        }
+    }
+    ValidationReport {
+        format: reader.format,
+        entries: reader.entries.len(),
+        problems,
+    }
+}
@@ -0,0 +1,162 @@
+//! Decoding archives one file at a time, handing out each file's data as a
+//! reader instead of keeping whole entries
+//!
+//! Zip, 7z and tar-based archives are decoded as they're visited, other
+//! formats are decoded whole first. Symbolic and hard links, FIFOs and device
+//! nodes are skipped.
+
+use crate::{
+    crx, meta, ArcFormat, ArcMeta, ArcReader, ArcResult, ArcStreamReader, EntryKind, TarStream,
+};
+use sevenz_rust::{Password, SevenZReader};
+use std::io::{self, Cursor, Read};
+use zip::ZipArchive;
+
+/// Entry reached by a visit
+pub(crate) struct Visited<'r> {
+    pub(crate) name: String,
+    /// Size of the data, as the headers say
+    pub(crate) size: u64,
+    pub(crate) meta: ArcMeta,
+    /// Reader for the data of files, `None` for directories
+    pub(crate) data: Option<&'r mut dyn Read>,
+}
+
+/// Called with every entry reached
+pub(crate) type Visitor<'f> = dyn FnMut(Visited) -> ArcResult<()> + 'f;
+
+/// Returns `meta` with the kind of a directory, or of a file if `is_file`
+fn with_kind(meta: ArcMeta, is_file: bool) -> ArcMeta {
+    let kind = match is_file {
+        true => EntryKind::File,
+        false => EntryKind::Directory,
+    };
+    ArcMeta { kind, ..meta }
+}
+
+pub(crate) fn visit(buf: &[u8], each: &mut Visitor) -> ArcResult<()> {
+    if let TarStream::Tar(format, decoder) = ArcReader::tar_stream(buf)? {
+        return visit_tar(ArcStreamReader::from_decoder(format, decoder), each);
+    }
+    match ArcReader::detect(buf) {
+        ArcFormat::Zip | ArcFormat::Jar | ArcFormat::Epub => visit_zip(buf, each),
+        ArcFormat::Crx => visit_zip(crx::split(buf)?.1, each),
+        ArcFormat::Sevenz => visit_7z(buf, each),
+        _ => {
+            for entry in ArcReader::new(buf)?.entries {
+                let data = match entry.meta.kind {
+                    EntryKind::File => Some(&mut &entry.data[..] as &mut dyn Read),
+                    EntryKind::Directory => None,
+                    EntryKind::Symlink(_) | EntryKind::HardLink(_) | EntryKind::Special(_) => {
+                        continue
+                    }
+                };
+                each(Visited {
+                    name: entry.name,
+                    size: entry.data.len() as u64,
+                    meta: entry.meta,
+                    data,
+                })?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Visits the archive read from `reader`, reading it into memory first
+/// unless it's a tarball
+pub(crate) fn visit_reader(reader: impl Read, each: &mut Visitor) -> ArcResult<()> {
+    match ArcReader::tar_stream(reader)? {
+        TarStream::Tar(format, decoder) => {
+            visit_tar(ArcStreamReader::from_decoder(format, decoder), each)
+        }
+        TarStream::Other(mut head, mut reader) => {
+            reader.read_to_end(&mut head)?;
+            visit(&head, each)
+        }
+    }
+}
+
+fn visit_tar(mut reader: ArcStreamReader, each: &mut Visitor) -> ArcResult<()> {
+    while let Some(mut entry) = reader.next_entry()? {
+        if entry.symlink_target().is_some()
+            || entry.hard_link_target().is_some()
+            || entry.special().is_some()
+        {
+            continue;
+        }
+        let name = entry.name().to_owned();
+        let size = entry.size();
+        let meta = with_kind(meta::tar_meta(&entry)?, !entry.is_dir());
+        each(Visited {
+            name,
+            size,
+            meta,
+            data: match entry.is_dir() {
+                true => None,
+                false => Some(&mut entry),
+            },
+        })?;
+    }
+    Ok(())
+}
+
+fn visit_zip(buf: &[u8], each: &mut Visitor) -> ArcResult<()> {
+    let mut archive = ZipArchive::new(Cursor::new(buf))?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_symlink() {
+            continue;
+        }
+        let name = file.name().to_owned();
+        let size = file.size();
+        let meta = with_kind(meta::zip_meta(&file), !file.is_dir());
+        each(Visited {
+            name,
+            size,
+            meta,
+            data: match file.is_dir() {
+                true => None,
+                false => Some(&mut file),
+            },
+        })?;
+    }
+    Ok(())
+}
+
+fn visit_7z(buf: &[u8], each: &mut Visitor) -> ArcResult<()> {
+    // errors of `each` are returned afterwards, sevenz-rust only stops
+    // decoding the current block when told to stop
+    let mut result = Ok(());
+    let mut reader = SevenZReader::new(Cursor::new(buf), buf.len() as u64, Password::empty())?;
+    let mut methods = meta::sevenz_methods(reader.archive()).into_iter();
+    reader.for_each_entries(|entry, reader| {
+        if result.is_err() {
+            return Ok(false);
+        }
+        let method = methods.next().flatten();
+        if meta::is_sevenz_symlink(entry) {
+            io::copy(reader, &mut io::sink())?;
+            return Ok(true);
+        }
+        result = each(Visited {
+            name: entry.name.clone(),
+            size: entry.size,
+            meta: with_kind(
+                ArcMeta {
+                    method,
+                    ..meta::sevenz_meta(entry)
+                },
+                !entry.is_directory,
+            ),
+            data: match entry.is_directory {
+                true => None,
+                false => Some(reader),
+            },
+        });
+        // files in a solid block follow each other in the same stream
+        io::copy(reader, &mut io::sink())?;
+        Ok(result.is_ok())
+    })?;
+    result
+}
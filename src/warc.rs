@@ -0,0 +1,85 @@
+//! Reader for WARC web archives
+//!
+//! Every record becomes a file named after its target URI, falling back to
+//! its record ID, holding the record's content block.
+
+use crate::{ArcEntry, ArcResult};
+use std::io;
+
+pub(crate) const MAGIC: &[u8] = b"WARC/";
+
+pub(crate) type Headers = Vec<(String, String)>;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid WARC: {msg}"))
+}
+
+/// Returns the value of the header `name`, compared case-insensitively
+fn header<'a>(headers: &'a Headers, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Splits off the next CRLF-terminated line
+fn line<'a>(buf: &mut &'a [u8]) -> ArcResult<&'a [u8]> {
+    let end = buf
+        .windows(2)
+        .position(|window| window == b"\r\n")
+        .ok_or_else(|| invalid("truncated header"))?;
+    let line = &buf[..end];
+    *buf = &buf[end + 2..];
+    Ok(line)
+}
+
+/// Parses all records into their headers and content blocks
+pub(crate) fn records(mut buf: &[u8]) -> ArcResult<Vec<(Headers, &[u8])>> {
+    let mut records = Vec::new();
+    while !buf.is_empty() {
+        if !line(&mut buf)?.starts_with(MAGIC) {
+            return Err(invalid("missing version line").into());
+        }
+        let mut headers: Headers = Vec::new();
+        loop {
+            let line = String::from_utf8_lossy(line(&mut buf)?).into_owned();
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with([' ', '\t']) {
+                let (_, value) = headers
+                    .last_mut()
+                    .ok_or_else(|| invalid("continuation without a header"))?;
+                value.push(' ');
+                value.push_str(line.trim());
+                continue;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| invalid("expected `name: value`"))?;
+            headers.push((name.trim().to_owned(), value.trim().to_owned()));
+        }
+
+        let len = header(&headers, "Content-Length")
+            .and_then(|len| len.parse::<usize>().ok())
+            .ok_or_else(|| invalid("missing Content-Length"))?;
+        let block = buf.get(..len).ok_or_else(|| invalid("truncated block"))?;
+        buf = buf[len..]
+            .strip_prefix(b"\r\n\r\n")
+            .ok_or_else(|| invalid("missing record terminator"))?;
+        records.push((headers, block));
+    }
+    Ok(records)
+}
+
+pub(crate) fn read(buf: &[u8]) -> ArcResult<Vec<ArcEntry>> {
+    Ok(records(buf)?
+        .into_iter()
+        .map(|(headers, block)| {
+            let name = header(&headers, "WARC-Target-URI")
+                .or_else(|| header(&headers, "WARC-Record-ID"))
+                .unwrap_or_default();
+            ArcEntry::file(name, block)
+        })
+        .collect())
+}
@@ -0,0 +1,448 @@
+//! Reader for the Windows Imaging Format
+//!
+//! Supports uncompressed and XPRESS-compressed images. LZX, LZMS and solid
+//! resources are rejected with an error.
+
+use crate::{read_sized, ArcEntry, ArcError, ArcResult};
+use std::{collections::HashSet, io};
+
+pub(crate) const MAGIC: &[u8] = b"MSWIM\0\0\0";
+
+const HEADER_SIZE: usize = 208;
+const LOOKUP_ENTRY_SIZE: usize = 50;
+const DENTRY_FIXED_SIZE: usize = 102;
+
+const HEADER_FLAG_COMPRESSION: u32 = 0x2;
+const HEADER_FLAG_XPRESS: u32 = 0x20000;
+
+const RESHDR_FLAG_METADATA: u8 = 0x2;
+const RESHDR_FLAG_COMPRESSED: u8 = 0x4;
+const RESHDR_FLAG_SOLID: u8 = 0x10;
+
+const ATTRIBUTE_DIRECTORY: u32 = 0x10;
+const ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+
+fn invalid(msg: &str) -> ArcError {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid WIM: {msg}")).into()
+}
+
+fn unsupported(msg: &str) -> ArcError {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("unsupported WIM: {msg}"),
+    )
+    .into()
+}
+
+fn slice(buf: &[u8], offset: usize, len: usize) -> ArcResult<&[u8]> {
+    offset
+        .checked_add(len)
+        .and_then(|end| buf.get(offset..end))
+        .ok_or_else(|| invalid("truncated"))
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> ArcResult<u16> {
+    Ok(u16::from_le_bytes(
+        slice(buf, offset, 2)?.try_into().unwrap(),
+    ))
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> ArcResult<u32> {
+    Ok(u32::from_le_bytes(
+        slice(buf, offset, 4)?.try_into().unwrap(),
+    ))
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> ArcResult<u64> {
+    Ok(u64::from_le_bytes(
+        slice(buf, offset, 8)?.try_into().unwrap(),
+    ))
+}
+
+fn usize_at(buf: &[u8], offset: usize) -> ArcResult<usize> {
+    usize::try_from(u64_at(buf, offset)?).map_err(|_| invalid("offset out of range"))
+}
+
+/// Location of a (possibly compressed) resource in the file
+struct Resource {
+    flags: u8,
+    size: usize,
+    offset: usize,
+    original_size: usize,
+}
+
+impl Resource {
+    fn parse(buf: &[u8], offset: usize) -> ArcResult<Self> {
+        let size_and_flags = u64_at(buf, offset)?;
+        Ok(Self {
+            flags: (size_and_flags >> 56) as u8,
+            size: (size_and_flags & 0x00ff_ffff_ffff_ffff) as usize,
+            offset: usize_at(buf, offset + 8)?,
+            original_size: usize_at(buf, offset + 16)?,
+        })
+    }
+}
+
+struct Wim<'a> {
+    buf: &'a [u8],
+    xpress: bool,
+    chunk_size: usize,
+    lookup: Vec<([u8; 20], Resource)>,
+}
+
+impl<'a> Wim<'a> {
+    fn parse(buf: &'a [u8]) -> ArcResult<Self> {
+        if !buf.starts_with(MAGIC) || buf.len() < HEADER_SIZE {
+            return Err(invalid("bad header"));
+        }
+        let flags = u32_at(buf, 16)?;
+        let xpress = flags & HEADER_FLAG_COMPRESSION != 0;
+        if xpress && flags & HEADER_FLAG_XPRESS == 0 {
+            return Err(unsupported("only XPRESS compression is supported"));
+        }
+        let chunk_size = match u32_at(buf, 20)? {
+            0 => 32768,
+            size => size as usize,
+        };
+        if u16_at(buf, 42)? != 1 {
+            return Err(unsupported("spanned images"));
+        }
+
+        let table = Resource::parse(buf, 48)?;
+        let table = Wim {
+            buf,
+            xpress,
+            chunk_size,
+            lookup: Vec::new(),
+        }
+        .resource(&table)?;
+        let lookup = table
+            .chunks_exact(LOOKUP_ENTRY_SIZE)
+            .map(|entry| {
+                let resource = Resource::parse(entry, 0)?;
+                Ok((entry[30..50].try_into().unwrap(), resource))
+            })
+            .collect::<ArcResult<_>>()?;
+
+        Ok(Self {
+            buf,
+            xpress,
+            chunk_size,
+            lookup,
+        })
+    }
+
+    fn image_count(&self) -> u32 {
+        self.metadata().count() as u32
+    }
+
+    fn metadata(&self) -> impl Iterator<Item = &Resource> {
+        self.lookup
+            .iter()
+            .map(|(_, resource)| resource)
+            .filter(|resource| resource.flags & RESHDR_FLAG_METADATA != 0)
+    }
+
+    /// Reads the contents of a resource, decompressing it if needed
+    fn resource(&self, resource: &Resource) -> ArcResult<Vec<u8>> {
+        if resource.flags & RESHDR_FLAG_SOLID != 0 {
+            return Err(unsupported("solid resources"));
+        }
+        let data = slice(self.buf, resource.offset, resource.size)?;
+        if resource.flags & RESHDR_FLAG_COMPRESSED == 0 || !self.xpress {
+            return Ok(data.to_vec());
+        }
+
+        // compressed resources start with a table of chunk offsets, omitting
+        // the first chunk which always starts right after the table
+        let chunks = resource.original_size.div_ceil(self.chunk_size);
+        let entry_size = if resource.original_size > u32::MAX as usize {
+            8
+        } else {
+            4
+        };
+        let table_size = (chunks.saturating_sub(1))
+            .checked_mul(entry_size)
+            .filter(|&size| size <= data.len())
+            .ok_or_else(|| invalid("chunk table out of bounds"))?;
+        let mut offsets = vec![0];
+        for i in 0..chunks.saturating_sub(1) {
+            offsets.push(match entry_size {
+                8 => usize_at(data, i * 8)?,
+                _ => u32_at(data, i * 4)? as usize,
+            });
+        }
+        offsets.push(data.len().saturating_sub(table_size));
+
+        let mut out = Vec::new();
+        read_sized(&mut out, resource.original_size as u64, |out| {
+            self.decompress_chunks(data, &offsets, table_size, resource.original_size, out)
+        })?;
+        Ok(out)
+    }
+
+    /// Decompresses the chunks of a resource of `original_size` bytes into
+    /// `out`, given their `offsets` after the chunk table
+    fn decompress_chunks(
+        &self,
+        data: &[u8],
+        offsets: &[usize],
+        table_size: usize,
+        original_size: usize,
+        out: &mut Vec<u8>,
+    ) -> ArcResult<()> {
+        for (i, bounds) in offsets.windows(2).enumerate() {
+            let chunk = bounds[0]
+                .checked_add(table_size)
+                .zip(bounds[1].checked_add(table_size))
+                .and_then(|(start, end)| data.get(start..end))
+                .ok_or_else(|| invalid("bad chunk table"))?;
+            let len = self.chunk_size.min(original_size - i * self.chunk_size);
+            if chunk.len() == len {
+                out.extend_from_slice(chunk);
+            } else {
+                xpress_decompress(chunk, len, out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn stream(&self, hash: &[u8; 20]) -> ArcResult<Vec<u8>> {
+        if hash == &[0; 20] {
+            return Ok(Vec::new());
+        }
+        let (_, resource) = self
+            .lookup
+            .iter()
+            .find(|(entry_hash, _)| entry_hash == hash)
+            .ok_or_else(|| invalid("missing stream"))?;
+        self.resource(resource)
+    }
+
+    fn read_image(&self, image: u32) -> ArcResult<Vec<ArcEntry>> {
+        let metadata = image
+            .checked_sub(1)
+            .and_then(|i| self.metadata().nth(i as usize))
+            .ok_or(ArcError::ImageNotFound(image))?;
+        let metadata = self.resource(metadata)?;
+
+        // the root directory entry follows the security data
+        let root = (u32_at(&metadata, 0)? as usize)
+            .checked_next_multiple_of(8)
+            .ok_or_else(|| invalid("bad security data"))?;
+        let root = Dentry::parse(&metadata, root)?;
+
+        let mut entries = Vec::new();
+        let mut dirs = vec![(String::new(), root.subdir_offset)];
+        // entries pointing back at ones already read would loop forever
+        let mut visited = HashSet::new();
+        while let Some((parent, mut offset)) = dirs.pop() {
+            if offset == 0 {
+                continue;
+            }
+            let mut children = Vec::new();
+            while u64_at(&metadata, offset)? > 8 {
+                if !visited.insert(offset) {
+                    return Err(invalid("directory loop"));
+                }
+                let dentry = Dentry::parse(&metadata, offset)?;
+                offset = dentry.next;
+                children.push(dentry);
+            }
+            // push in reverse so directories are walked in order
+            for dentry in children.into_iter().rev() {
+                let name = format!("{parent}{}", dentry.name);
+                if dentry.attributes & ATTRIBUTE_DIRECTORY != 0 {
+                    dirs.push((format!("{name}/"), dentry.subdir_offset));
+                }
+                entries.push((name, dentry));
+            }
+        }
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut out = Vec::with_capacity(entries.len());
+        for (name, dentry) in entries {
+            if dentry.attributes & ATTRIBUTE_DIRECTORY != 0 {
+                out.push(ArcEntry::directory(name));
+            } else if dentry.attributes & ATTRIBUTE_REPARSE_POINT == 0 {
+                out.push(ArcEntry::file(name, self.stream(&dentry.hash)?));
+            }
+        }
+        Ok(out)
+    }
+}
+
+struct Dentry {
+    attributes: u32,
+    subdir_offset: usize,
+    hash: [u8; 20],
+    name: String,
+    /// Offset of the next sibling
+    next: usize,
+}
+
+impl Dentry {
+    fn parse(metadata: &[u8], offset: usize) -> ArcResult<Self> {
+        let fixed = slice(metadata, offset, DENTRY_FIXED_SIZE)?;
+        let length = usize_at(fixed, 0)?;
+        let attributes = u32_at(fixed, 8)?;
+        let subdir_offset = usize_at(fixed, 16)?;
+        let mut hash: [u8; 20] = fixed[64..84].try_into().unwrap();
+        let extra_streams = u16_at(fixed, 96)?;
+        let name_len = u16_at(fixed, 100)? as usize;
+
+        let name: Vec<_> = slice(metadata, offset + DENTRY_FIXED_SIZE, name_len)?
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        let name = String::from_utf16_lossy(&name);
+
+        let mut next = length
+            .checked_next_multiple_of(8)
+            .and_then(|length| offset.checked_add(length))
+            .filter(|_| length >= DENTRY_FIXED_SIZE)
+            .ok_or_else(|| invalid("bad directory entry"))?;
+        // the unnamed data stream is stored among the extra streams when the
+        // file also has named ones
+        for _ in 0..extra_streams {
+            let length = usize_at(metadata, next)?;
+            if u16_at(metadata, next + 36)? == 0 && hash == [0; 20] {
+                hash = slice(metadata, next + 16, 20)?.try_into().unwrap();
+            }
+            next = length
+                .checked_next_multiple_of(8)
+                .and_then(|length| next.checked_add(length))
+                .filter(|_| length >= 38)
+                .ok_or_else(|| invalid("bad stream entry"))?;
+        }
+
+        Ok(Self {
+            attributes,
+            subdir_offset,
+            hash,
+            name,
+            next,
+        })
+    }
+}
+
+/// Reads the entries of the 1-based `image`
+pub(crate) fn read(buf: &[u8], image: u32) -> ArcResult<Vec<ArcEntry>> {
+    Wim::parse(buf)?.read_image(image)
+}
+
+pub(crate) fn image_count(buf: &[u8]) -> ArcResult<u32> {
+    Ok(Wim::parse(buf)?.image_count())
+}
+
+/// Decompresses one chunk in the XPRESS Huffman format, appending `len` bytes
+/// to `out`
+fn xpress_decompress(chunk: &[u8], len: usize, out: &mut Vec<u8>) -> ArcResult<()> {
+    const SYMBOLS: usize = 512;
+    const MAX_CODE_LEN: usize = 15;
+
+    let lengths = slice(chunk, 0, SYMBOLS / 2)?;
+    let lengths: Vec<usize> = lengths
+        .iter()
+        .flat_map(|byte| [(byte & 0xf) as usize, (byte >> 4) as usize])
+        .collect();
+
+    // canonical huffman code, decoded one bit at a time
+    let mut counts = [0usize; MAX_CODE_LEN + 1];
+    for &len in &lengths {
+        counts[len] += 1;
+    }
+    counts[0] = 0;
+    let mut sorted: Vec<usize> = (0..SYMBOLS).filter(|&s| lengths[s] != 0).collect();
+    sorted.sort_by_key(|&s| lengths[s]);
+
+    let mut bits = BitReader {
+        buf: chunk,
+        pos: SYMBOLS / 2,
+        bits: 0,
+        count: 0,
+    };
+    let start = out.len();
+    while out.len() - start < len {
+        let (mut code, mut first, mut index) = (0usize, 0usize, 0usize);
+        let mut symbol = None;
+        for count in &counts[1..] {
+            code |= bits.pop(1) as usize;
+            if code < first + count {
+                symbol = Some(sorted[index + code - first]);
+                break;
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        let symbol = symbol.ok_or_else(|| invalid("bad huffman code"))?;
+
+        if symbol < 256 {
+            out.push(symbol as u8);
+            continue;
+        }
+        let mut length = symbol & 0xf;
+        let offset_bits = (symbol >> 4) & 0xf;
+        let offset = (1 << offset_bits) | bits.pop(offset_bits as u32) as usize;
+        // length bytes come after the words already buffered
+        bits.ensure(16);
+        if length == 0xf {
+            length += bits.byte()? as usize;
+            if length == 0xf + 0xff {
+                length = bits.byte()? as usize | (bits.byte()? as usize) << 8;
+            }
+        }
+        length += 3;
+
+        let from = out
+            .len()
+            .checked_sub(offset)
+            .filter(|&from| from >= start)
+            .ok_or_else(|| invalid("bad match offset"))?;
+        for i in 0..length.min(len - (out.len() - start)) {
+            out.push(out[from + i]);
+        }
+    }
+    Ok(())
+}
+
+/// Bitstream of 16-bit little-endian words read most significant bit first,
+/// interleaved with literal bytes
+struct BitReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    bits: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn ensure(&mut self, n: u32) {
+        if self.count < n {
+            let word = match self.buf.get(self.pos..self.pos + 2) {
+                Some(word) => u16::from_le_bytes([word[0], word[1]]) as u32,
+                None => 0,
+            };
+            self.pos += 2;
+            self.bits |= word << (16 - self.count);
+            self.count += 16;
+        }
+    }
+
+    fn pop(&mut self, n: u32) -> u32 {
+        if n == 0 {
+            return 0;
+        }
+        self.ensure(n);
+        let value = self.bits >> (32 - n);
+        self.bits <<= n;
+        self.count -= n;
+        value
+    }
+
+    fn byte(&mut self) -> ArcResult<u8> {
+        let byte = *self.buf.get(self.pos).ok_or_else(|| invalid("truncated"))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+}
@@ -0,0 +1,112 @@
+//! Reader for xar archives, the container used by macOS `.pkg` installers
+//!
+//! The table of contents is a zlib-compressed XML document describing where
+//! each file's data lives in the heap that follows it.
+
+use crate::{ArcEntry, ArcResult};
+use bzip2::read::BzDecoder;
+use flate2::read::ZlibDecoder;
+use roxmltree::{Document, Node};
+use std::io::{self, Read};
+
+pub(crate) const MAGIC: &[u8] = b"xar!";
+
+const HEADER_SIZE: usize = 28;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid xar: {msg}"))
+}
+
+/// Returns the first child element of `node` named `tag`
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(tag))
+}
+
+/// Returns the text of the first child element of `node` named `tag`
+fn text<'a>(node: Node<'a, '_>, tag: &str) -> &'a str {
+    child(node, tag)
+        .and_then(|child| child.text())
+        .unwrap_or_default()
+}
+
+pub(crate) fn read(buf: &[u8]) -> ArcResult<Vec<ArcEntry>> {
+    let header = buf
+        .get(..HEADER_SIZE)
+        .filter(|header| header.starts_with(MAGIC))
+        .ok_or_else(|| invalid("bad header"))?;
+    let header_size = u16::from_be_bytes(header[4..6].try_into().unwrap()) as usize;
+    let toc_size = usize::try_from(u64::from_be_bytes(header[8..16].try_into().unwrap()))
+        .map_err(|_| invalid("bad header"))?;
+    let heap_start = header_size
+        .checked_add(toc_size)
+        .filter(|&end| end <= buf.len())
+        .ok_or_else(|| invalid("truncated table of contents"))?;
+
+    let mut xml = String::new();
+    ZlibDecoder::new(&buf[header_size..heap_start]).read_to_string(&mut xml)?;
+    let doc = Document::parse(&xml).map_err(|err| invalid(&err.to_string()))?;
+    let toc = child(doc.root_element(), "toc").ok_or_else(|| invalid("missing toc"))?;
+
+    let mut entries = Vec::new();
+    read_files(toc, "", &buf[heap_start..], &mut entries)?;
+    Ok(entries)
+}
+
+/// Reads the `<file>` children of `node` and everything below them
+fn read_files(
+    node: Node,
+    parent: &str,
+    heap: &[u8],
+    entries: &mut Vec<ArcEntry>,
+) -> io::Result<()> {
+    for file in node.children().filter(|child| child.has_tag_name("file")) {
+        let name = format!("{parent}{}", text(file, "name"));
+        match text(file, "type") {
+            "directory" => {
+                entries.push(ArcEntry::directory(name.clone()));
+                read_files(file, &format!("{name}/"), heap, entries)?;
+            }
+            "file" => {
+                let data = match child(file, "data") {
+                    Some(data) => read_data(data, heap)?,
+                    None => Vec::new(),
+                };
+                entries.push(ArcEntry::file(name, data));
+            }
+            // symlinks, hardlinks and device nodes have no ArcEntry equivalent
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Reads and decodes the part of the heap described by a `<data>` element
+fn read_data(data: Node, heap: &[u8]) -> io::Result<Vec<u8>> {
+    let field = |tag| {
+        text(data, tag)
+            .parse::<usize>()
+            .map_err(|_| invalid("bad data location"))
+    };
+    let (offset, length) = (field("offset")?, field("length")?);
+    let archived = offset
+        .checked_add(length)
+        .and_then(|end| heap.get(offset..end))
+        .ok_or_else(|| invalid("data out of bounds"))?;
+
+    let style = child(data, "encoding")
+        .and_then(|encoding| encoding.attribute("style"))
+        .unwrap_or("application/octet-stream");
+    let mut out = Vec::new();
+    match style {
+        "application/octet-stream" => return Ok(archived.to_vec()),
+        "application/x-gzip" => ZlibDecoder::new(archived).read_to_end(&mut out)?,
+        "application/x-bzip2" => BzDecoder::new(archived).read_to_end(&mut out)?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("unsupported xar encoding: {style}"),
+            ))
+        }
+    };
+    Ok(out)
+}
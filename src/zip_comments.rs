@@ -0,0 +1,207 @@
+//! Comments and DOS attributes of zip entries
+//!
+//! The zip crate can't write either, so [`CommentWriter`] sits between it and
+//! the output, holding back the central directory once the last entry is
+//! written and adding them before passing it on.
+
+use crate::zip_records::{
+    find_zip64_locator, has_sig, invalid, CentralHeader, Field, CENTRAL_SIG, END_SIG, END_SIZE,
+};
+use crate::{ArcResult, DosAttributes};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    io::{self, Seek, SeekFrom, Write},
+    rc::Rc,
+};
+
+/// General purpose flag of names and comments encoded in UTF-8
+const UTF8_FLAG: u64 = 1 << 11;
+
+/// Flag set once every entry of a zip archive is written
+pub(crate) type Ending = Rc<Cell<bool>>;
+
+/// Writer adding `comments` and DOS `attributes` to the central directory
+/// written through it
+pub(crate) struct CommentWriter<'c, W> {
+    inner: W,
+    comments: HashMap<&'c str, &'c str>,
+    attributes: HashMap<&'c str, DosAttributes>,
+    /// Set once every entry is written, what's appended afterwards is held
+    /// back
+    ending: Ending,
+    /// Position held back data starts at, once holding back started
+    held_from: Option<u64>,
+    held: Vec<u8>,
+    pos: u64,
+}
+
+impl<'c, W: Write + Seek> CommentWriter<'c, W> {
+    /// Returns the writer and the flag to set before finishing the zip writer
+    pub(crate) fn new(
+        mut inner: W,
+        comments: HashMap<&'c str, &'c str>,
+        attributes: HashMap<&'c str, DosAttributes>,
+    ) -> io::Result<(Self, Ending)> {
+        let ending = Rc::new(Cell::new(false));
+        let writer = Self {
+            pos: inner.stream_position()?,
+            inner,
+            comments,
+            attributes,
+            ending: ending.clone(),
+            held_from: None,
+            held: Vec::new(),
+        };
+        Ok((writer, ending))
+    }
+
+    /// Writes the held back end of the archive with the comments and
+    /// attributes added, returning the underlying writer
+    pub(crate) fn finish(mut self) -> ArcResult<W> {
+        if let Some(start) = self.held_from {
+            let end = add_comments(&self.held, start, &self.comments, &self.attributes)?;
+            self.inner.seek(SeekFrom::Start(start))?;
+            self.inner.write_all(&end)?;
+        }
+        Ok(self.inner)
+    }
+
+    /// Returns where held back data starts, starting to hold back if the
+    /// entries were all written
+    fn held_from(&mut self) -> Option<u64> {
+        if self.held_from.is_none()
+            && self.ending.get()
+            && !(self.comments.is_empty() && self.attributes.is_empty())
+        {
+            self.held_from = Some(self.pos);
+        }
+        self.held_from
+    }
+}
+
+impl<W: Write + Seek> Write for CommentWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = match self.held_from() {
+            // the zip crate going back to the last local header
+            Some(start) if self.pos < start => {
+                let len = buf.len().min((start - self.pos) as usize);
+                self.inner.write(&buf[..len])?
+            }
+            Some(start) => {
+                if self.pos != start + self.held.len() as u64 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "the end of a zip archive can only be appended to",
+                    ));
+                }
+                self.held.extend_from_slice(buf);
+                buf.len()
+            }
+            None => self.inner.write(buf)?,
+        };
+        self.pos += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write + Seek> Seek for CommentWriter<'_, W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let Some(start) = self.held_from() else {
+            self.pos = self.inner.seek(pos)?;
+            return Ok(self.pos);
+        };
+        let pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => (start + self.held.len() as u64).checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "seek to a negative position")
+        })?;
+        if self.pos <= start {
+            self.inner.seek(SeekFrom::Start(self.pos))?;
+        }
+        Ok(self.pos)
+    }
+}
+
+/// Returns `end`, the end of an archive from its offset `start` on, with
+/// `comments` and `attributes` added to the central directory in it
+fn add_comments(
+    end: &[u8],
+    start: u64,
+    comments: &HashMap<&str, &str>,
+    attributes: &HashMap<&str, DosAttributes>,
+) -> ArcResult<Vec<u8>> {
+    // the archive comment is last, so its length tells the record apart
+    let eocd = (0..=end.len().saturating_sub(END_SIZE))
+        .rev()
+        .find(|&pos| {
+            has_sig(end, pos, END_SIG)
+                && Field::new(pos + 20, 2).get(end).ok()
+                    == Some((end.len() - pos - END_SIZE) as u64)
+        })
+        .ok_or_else(|| invalid("missing end of central directory"))?;
+    let locator = find_zip64_locator(end, eocd);
+    let (cd_offset, record) = match locator {
+        Some(locator) => {
+            let record = Field::new(locator + 8, 8).get(end)?;
+            let record = record
+                .checked_sub(start)
+                .ok_or_else(|| invalid("bad zip64 end of central directory"))?
+                as usize;
+            (Field::new(record + 48, 8).get(end)?, Some(record))
+        }
+        None => (Field::new(eocd + 16, 4).get(end)?, None),
+    };
+    let cd = cd_offset
+        .checked_sub(start)
+        .ok_or_else(|| invalid("bad central directory offset"))? as usize;
+
+    let mut out = end.get(..cd).ok_or_else(|| invalid("truncated"))?.to_vec();
+    let mut pos = cd;
+    while has_sig(end, pos, CENTRAL_SIG) {
+        let entry = CentralHeader::parse(end, pos)?;
+        let mut header = end[pos..pos + entry.len].to_vec();
+        let name = std::str::from_utf8(&end[entry.name])
+            .ok()
+            .map(str::to_owned);
+        if let Some(attributes) = name.as_deref().and_then(|name| attributes.get(name)) {
+            let external = entry.external.get(end)? & !u64::from(DosAttributes::MASK)
+                | u64::from(attributes.bits());
+            entry.external.rebased(pos).set(&mut header, external)?;
+        }
+        if let Some(comment) = name.as_deref().and_then(|name| comments.get(name)) {
+            header.truncate(entry.extra.end - pos);
+            header.extend_from_slice(comment.as_bytes());
+            Field::new(32, 2).set(&mut header, comment.len() as u64)?;
+            if !comment.is_ascii() {
+                let flags = entry.flags.get(end)? | UTF8_FLAG;
+                entry.flags.rebased(pos).set(&mut header, flags)?;
+            }
+        }
+        out.extend_from_slice(&header);
+        pos += entry.len;
+    }
+
+    let cd_size = (out.len() - cd) as u64;
+    let shift = out.len() - pos;
+    out.extend_from_slice(&end[pos..]);
+    let eocd = eocd + shift;
+    if let (Some(record), Some(locator)) = (record, locator) {
+        Field::new(record + shift + 40, 8).set(&mut out, cd_size)?;
+        let locator = locator + shift;
+        let offset = Field::new(locator + 8, 8).get(&out)?;
+        Field::new(locator + 8, 8).set(&mut out, offset + shift as u64)?;
+        if Field::new(eocd + 12, 4).saturated(&out)? {
+            return Ok(out);
+        }
+    }
+    Field::new(eocd + 12, 4).set(&mut out, cd_size)?;
+    Ok(out)
+}
@@ -0,0 +1,230 @@
+//! Headers and end records of zip archives, for the code reading or
+//! rewriting them without the zip crate
+
+use std::{io, ops::Range};
+
+pub(crate) const LOCAL_SIG: u32 = 0x04034b50;
+pub(crate) const CENTRAL_SIG: u32 = 0x02014b50;
+pub(crate) const END_SIG: u32 = 0x06054b50;
+pub(crate) const ZIP64_END_SIG: u32 = 0x06064b50;
+pub(crate) const ZIP64_LOCATOR_SIG: u32 = 0x07064b50;
+
+pub(crate) const LOCAL_SIZE: usize = 30;
+pub(crate) const CENTRAL_SIZE: usize = 46;
+pub(crate) const END_SIZE: usize = 22;
+pub(crate) const ZIP64_END_SIZE: usize = 56;
+pub(crate) const ZIP64_LOCATOR_SIZE: usize = 20;
+/// Longest comment that can follow the end of central directory record
+pub(crate) const MAX_COMMENT_LEN: usize = 0xffff;
+
+pub(crate) fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid zip: {msg}"))
+}
+
+/// A little-endian integer field of 2, 4 or 8 bytes
+#[derive(Clone, Copy)]
+pub(crate) struct Field {
+    pos: usize,
+    width: usize,
+}
+
+impl Field {
+    pub(crate) fn new(pos: usize, width: usize) -> Self {
+        Self { pos, width }
+    }
+
+    pub(crate) fn get(self, buf: &[u8]) -> io::Result<u64> {
+        let bytes = self
+            .pos
+            .checked_add(self.width)
+            .and_then(|end| buf.get(self.pos..end))
+            .ok_or_else(|| invalid("truncated"))?;
+        let mut value = [0; 8];
+        value[..self.width].copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(value))
+    }
+
+    pub(crate) fn set(self, buf: &mut [u8], value: u64) -> io::Result<()> {
+        if self.width < 8 && value >> (self.width * 8) != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "value too large for its zip field",
+            ));
+        }
+        buf.get_mut(self.pos..self.pos + self.width)
+            .ok_or_else(|| invalid("truncated"))?
+            .copy_from_slice(&value.to_le_bytes()[..self.width]);
+        Ok(())
+    }
+
+    /// Returns the same field in a copy of the buffer starting at `start`
+    pub(crate) fn rebased(self, start: usize) -> Self {
+        Self::new(self.pos - start, self.width)
+    }
+
+    /// Returns whether the field is saturated, deferring to the ZIP64 extra
+    /// field or end of central directory record
+    pub(crate) fn saturated(self, buf: &[u8]) -> io::Result<bool> {
+        Ok(self.get(buf)? == u64::MAX >> (64 - self.width * 8))
+    }
+}
+
+/// Returns whether the record at `pos` in `buf` starts with `sig`
+pub(crate) fn has_sig(buf: &[u8], pos: usize, sig: u32) -> bool {
+    Field::new(pos, 4).get(buf).ok() == Some(sig.into())
+}
+
+/// Finds the end of central directory record in `buf`, holding the end of
+/// an archive, as the last one with its signature
+pub(crate) fn find_end(buf: &[u8]) -> Option<usize> {
+    let last = buf.len().checked_sub(END_SIZE)?;
+    let search = last.saturating_sub(MAX_COMMENT_LEN);
+    (search..=last)
+        .rev()
+        .find(|&pos| has_sig(buf, pos, END_SIG))
+}
+
+/// Finds the zip64 locator right before the end of central directory record
+/// at `end`, if the archive has one
+pub(crate) fn find_zip64_locator(buf: &[u8], end: usize) -> Option<usize> {
+    end.checked_sub(ZIP64_LOCATOR_SIZE)
+        .filter(|&pos| has_sig(buf, pos, ZIP64_LOCATOR_SIG))
+}
+
+/// Central directory file header, with the fields of values too big for
+/// their own moved to the zip64 extra field
+pub(crate) struct CentralHeader {
+    /// Length of the whole header
+    pub(crate) len: usize,
+    pub(crate) name: Range<usize>,
+    pub(crate) extra: Range<usize>,
+    pub(crate) flags: Field,
+    pub(crate) method: Field,
+    pub(crate) crc: Field,
+    pub(crate) compressed_size: Field,
+    pub(crate) size: Field,
+    pub(crate) disk: Field,
+    pub(crate) external: Field,
+    pub(crate) offset: Field,
+}
+
+impl CentralHeader {
+    /// Parses the header at `pos` in `buf`
+    pub(crate) fn parse(buf: &[u8], pos: usize) -> io::Result<Self> {
+        if !has_sig(buf, pos, CENTRAL_SIG) {
+            return Err(invalid("bad central directory header"));
+        }
+        let name = pos + CENTRAL_SIZE;
+        let extra = name + Field::new(pos + 28, 2).get(buf)? as usize;
+        let comment = extra + Field::new(pos + 30, 2).get(buf)? as usize;
+        let end = comment + Field::new(pos + 32, 2).get(buf)? as usize;
+        if buf.len() < end {
+            return Err(invalid("truncated central directory"));
+        }
+        let [size, compressed_size, offset, disk] = zip64_fields(
+            buf,
+            extra..comment,
+            [
+                Field::new(pos + 24, 4),
+                Field::new(pos + 20, 4),
+                Field::new(pos + 42, 4),
+                Field::new(pos + 34, 2),
+            ],
+        )?;
+        Ok(Self {
+            len: end - pos,
+            name: name..extra,
+            extra: extra..comment,
+            flags: Field::new(pos + 8, 2),
+            method: Field::new(pos + 10, 2),
+            crc: Field::new(pos + 16, 4),
+            compressed_size,
+            size,
+            disk,
+            external: Field::new(pos + 38, 4),
+            offset,
+        })
+    }
+}
+
+/// Local file header, with the fields of sizes too big for their own moved
+/// to the zip64 extra field
+pub(crate) struct LocalHeader {
+    /// Length of the whole header
+    pub(crate) len: usize,
+    pub(crate) name: Range<usize>,
+    pub(crate) flags: Field,
+    pub(crate) method: Field,
+    pub(crate) crc: Field,
+    pub(crate) compressed_size: Field,
+    pub(crate) size: Field,
+}
+
+impl LocalHeader {
+    /// Returns the length of the header at `pos` in `buf`, which only needs
+    /// to hold its fixed-size part
+    pub(crate) fn len(buf: &[u8], pos: usize) -> io::Result<usize> {
+        if !has_sig(buf, pos, LOCAL_SIG) {
+            return Err(invalid("bad local file header"));
+        }
+        Ok(LOCAL_SIZE
+            + Field::new(pos + 26, 2).get(buf)? as usize
+            + Field::new(pos + 28, 2).get(buf)? as usize)
+    }
+
+    /// Parses the header at `pos` in `buf`
+    pub(crate) fn parse(buf: &[u8], pos: usize) -> io::Result<Self> {
+        let len = Self::len(buf, pos)?;
+        if buf.len() < pos + len {
+            return Err(invalid("truncated local file header"));
+        }
+        let extra = pos + LOCAL_SIZE + Field::new(pos + 26, 2).get(buf)? as usize;
+        let [size, compressed_size] = zip64_fields(
+            buf,
+            extra..pos + len,
+            [Field::new(pos + 22, 4), Field::new(pos + 18, 4)],
+        )?;
+        Ok(Self {
+            len,
+            name: pos + LOCAL_SIZE..extra,
+            flags: Field::new(pos + 6, 2),
+            method: Field::new(pos + 8, 2),
+            crc: Field::new(pos + 14, 4),
+            compressed_size,
+            size,
+        })
+    }
+}
+
+/// Returns `fields`, with the saturated ones moved to the zip64 field among
+/// the `extra` fields in `buf`, which holds them in this order
+fn zip64_fields<const N: usize>(
+    buf: &[u8],
+    extra: Range<usize>,
+    mut fields: [Field; N],
+) -> io::Result<[Field; N]> {
+    let mut pos = extra.start;
+    while pos + 4 <= extra.end {
+        let end = pos + 4 + Field::new(pos + 2, 2).get(buf)? as usize;
+        if end > extra.end {
+            return Err(invalid("truncated extra field"));
+        }
+        if Field::new(pos, 2).get(buf)? == 0x0001 {
+            let mut value = pos + 4;
+            for field in &mut fields {
+                if field.saturated(buf)? {
+                    // disk numbers take 4 bytes there, sizes and offsets 8
+                    let width = field.width * 2;
+                    if value + width > end {
+                        return Err(invalid("truncated zip64 extra field"));
+                    }
+                    *field = Field::new(value, width);
+                    value += width;
+                }
+            }
+            break;
+        }
+        pos = end;
+    }
+    Ok(fields)
+}
@@ -0,0 +1,173 @@
+use cra::*;
+use std::fs;
+
+fn tree() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("uwu");
+    fs::create_dir_all(root.join("owo/empty")).unwrap();
+    fs::write(root.join("owo/hmmm"), "twoj stary\n").unwrap();
+    fs::write(root.join("b"), "twoja stara\n").unwrap();
+    fs::write(root.join("a"), "").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let path = root.join("b");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+        std::os::unix::fs::symlink("owo/hmmm", root.join("link")).unwrap();
+    }
+    dir
+}
+
+fn names(reader: &ArcReader) -> Vec<&str> {
+    reader
+        .entries()
+        .iter()
+        .map(|entry| entry.name.as_str())
+        .collect()
+}
+
+#[test]
+fn test_add_path() {
+    let dir = tree();
+    for format in [ArcFormat::Tar, ArcFormat::Zip, ArcFormat::Sevenz] {
+        let mut writer = ArcWriter::new(format);
+        writer.add_path(dir.path().join("uwu"), dir.path()).unwrap();
+        let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+
+        let mut expected = vec!["uwu/", "uwu/a", "uwu/b"];
+        if cfg!(unix) {
+            expected.push("uwu/link");
+        }
+        expected.extend(["uwu/owo/", "uwu/owo/empty/", "uwu/owo/hmmm"]);
+        // 7z puts entries without data last
+        let mut names = names(&reader);
+        names.sort();
+        assert_eq!(names, expected, "{format:?}");
+
+        let entry = |name: &str| {
+            let entries = reader.entries().iter();
+            entries.clone().find(|entry| entry.name == name).unwrap()
+        };
+        assert_eq!(entry("uwu/owo/hmmm").data, b"twoj stary\n");
+        assert!(entry("uwu/owo/hmmm").meta.mtime.is_some());
+        #[cfg(unix)]
+        {
+            assert_eq!(entry("uwu/b").meta.mode, Some(0o750));
+            assert_eq!(
+                entry("uwu/link").meta.kind,
+                EntryKind::Symlink("owo/hmmm".into())
+            );
+        }
+    }
+}
+
+#[test]
+fn test_add_path_base() {
+    let dir = tree();
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer
+        .add_path(dir.path().join("uwu"), dir.path().join("uwu"))
+        .unwrap();
+    writer
+        .add_path(dir.path().join("uwu/owo/hmmm"), dir.path().join("uwu/owo"))
+        .unwrap();
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    assert_eq!(names(&reader)[0], "a");
+    assert_eq!(names(&reader).last(), Some(&"hmmm"));
+
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    assert!(writer.add_path(dir.path(), dir.path().join("uwu")).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_add_path_following_symlinks() {
+    let dir = tree();
+    let root = dir.path().join("uwu");
+    std::os::unix::fs::symlink("owo", root.join("owo_link")).unwrap();
+    std::os::unix::fs::symlink("..", root.join("owo/up")).unwrap();
+
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.add_symlinks = AddSymlinkPolicy::Follow;
+    writer.add_path(&root, dir.path()).unwrap();
+    let reader =
+        ArcReader::with_symlink_policy(&writer.archive().unwrap(), SymlinkPolicy::Allow).unwrap();
+    assert_eq!(
+        names(&reader),
+        [
+            "uwu/",
+            "uwu/a",
+            "uwu/b",
+            "uwu/link",
+            "uwu/owo/",
+            "uwu/owo/empty/",
+            "uwu/owo/hmmm",
+            "uwu/owo/up",
+            "uwu/owo_link/",
+            "uwu/owo_link/empty/",
+            "uwu/owo_link/hmmm",
+            "uwu/owo_link/up",
+        ]
+    );
+    let entries = reader.entries();
+    assert_eq!(entries[3].data, b"twoj stary\n");
+    // links back into the directories being walked are kept as links
+    assert_eq!(entries[7].meta.kind, EntryKind::Symlink("..".into()));
+    assert_eq!(entries[11].meta.kind, EntryKind::Symlink("..".into()));
+
+    std::os::unix::fs::symlink("nowhere", root.join("broken")).unwrap();
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.add_symlinks = AddSymlinkPolicy::Follow;
+    assert!(writer.add_path(&root, dir.path()).is_err());
+}
+
+#[test]
+fn test_add_path_filter() {
+    let dir = tree();
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.add_filter = Some(Box::new(|path, meta| {
+        !meta.is_file() || meta.len() > 0 && !path.ends_with("owo/hmmm")
+    }));
+    writer.add_path(dir.path().join("uwu"), dir.path()).unwrap();
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    let mut expected = vec!["uwu/", "uwu/b"];
+    if cfg!(unix) {
+        expected.push("uwu/link");
+    }
+    expected.extend(["uwu/owo/", "uwu/owo/empty/"]);
+    assert_eq!(names(&reader), expected);
+
+    // directories left out aren't walked
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.add_filter = Some(Box::new(|path, _| !path.ends_with("owo")));
+    writer.add_path(dir.path().join("uwu"), dir.path()).unwrap();
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    assert!(names(&reader).iter().all(|name| !name.contains("owo")));
+}
+
+#[test]
+fn test_add_path_as() {
+    let dir = tree();
+    let root = dir.path().join("uwu");
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.add_path_as(root.join("owo"), "share/owo/").unwrap();
+    writer.add_path_as(root.join("b"), "bin/b").unwrap();
+    writer.add_path_as(root.join("a"), "").unwrap();
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    assert_eq!(
+        names(&reader),
+        [
+            "share/owo/",
+            "share/owo/empty/",
+            "share/owo/hmmm",
+            "bin/b",
+            "a"
+        ]
+    );
+    assert_eq!(reader.entries()[2].data, b"twoj stary\n");
+
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.add_path_as(root.join("owo"), "").unwrap();
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    assert_eq!(names(&reader), ["empty/", "hmmm"]);
+}
@@ -0,0 +1,37 @@
+use cra::*;
+
+#[test]
+fn test_ar_reader() {
+    let reader = ArcReader::new(include_bytes!("test.a"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Ar);
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::file("owo", ""),
+        ]
+    );
+}
+
+#[test]
+fn test_ar_writer() {
+    let entries = vec![
+        ArcEntry::file("hmmm", "twoja stara\n"),
+        ArcEntry::file("owo", ""),
+    ];
+    let mut writer = ArcWriter::new(ArcFormat::Ar);
+    writer.extend(&entries);
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Ar);
+    assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), entries);
+}
+
+#[test]
+fn test_ar_writer_directory() {
+    let mut writer = ArcWriter::new(ArcFormat::Ar);
+    writer.push(ArcEntry::directory("uwu"));
+    assert!(writer.archive().is_err());
+}
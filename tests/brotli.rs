@@ -0,0 +1,47 @@
+use cra::*;
+
+#[test]
+fn test_brotli_reader() {
+    let reader = ArcReader::new(include_bytes!("test.br"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Brotli);
+    assert_eq!(reader.entries(), &vec![ArcEntry::file("", "twoja stara\n")]);
+}
+
+#[test]
+fn test_brotli_writer() {
+    let mut writer = ArcWriter::new(ArcFormat::Brotli);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Brotli);
+    assert_eq!(reader.entries(), &vec![ArcEntry::file("", "twoja stara\n")]);
+}
+
+#[test]
+fn test_brotli_writer_single_file_only() {
+    let mut writer = ArcWriter::new(ArcFormat::Brotli);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.push(ArcEntry::file("uwu", ""));
+    assert!(matches!(writer.archive(), Err(ArcError::SingleFileOnly)));
+}
+
+#[test]
+fn test_brotli_writer_options() {
+    let mut writer = ArcWriter::new(ArcFormat::Brotli);
+    writer.brotli_quality = 4;
+    writer.brotli_window = 16;
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.entries(), &vec![ArcEntry::file("", "twoja stara\n")]);
+}
+
+#[test]
+fn test_unrecognized() {
+    assert!(matches!(
+        ArcReader::new(b"definitely not an archive"),
+        Err(ArcError::UnrecognizedFormat)
+    ));
+}
@@ -0,0 +1,32 @@
+use cra::*;
+use std::io::Cursor;
+
+#[test]
+fn test_cab_reader() {
+    let reader = ArcReader::new(include_bytes!("test.cab"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Cab);
+    assert_eq!(
+        reader.entries(),
+        &vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::file("uwu/owo", ""),
+        ]
+    );
+}
+
+#[test]
+fn test_cab_from_seekable() {
+    let reader =
+        ArcReader::from_seekable(Cursor::new(include_bytes!("test.cab")), &ReadOptions::new())
+            .unwrap();
+    assert_eq!(reader.format(), ArcFormat::Cab);
+    assert_eq!(reader.entries().len(), 2);
+}
+
+#[test]
+fn test_cab_writer() {
+    let writer = ArcWriter::new(ArcFormat::Cab);
+    assert!(matches!(writer.archive(), Err(ArcError::ReadOnly)));
+}
@@ -0,0 +1,78 @@
+use cra::*;
+
+/// Returns a stored zip holding `hmmm` and `uwu`, with a byte of `hmmm`'s
+/// data flipped
+fn corrupted() -> Vec<u8> {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.zip_stored = true;
+    writer.push(ArcEntry::file("hmmm", "twoja stara ".repeat(100)));
+    writer.push(ArcEntry::file("uwu", "owo"));
+    let mut archive = writer.archive().unwrap();
+    let start = find(&archive, b"twoja stara");
+    archive[start] ^= 0x01;
+    archive
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> usize {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .unwrap()
+}
+
+#[test]
+fn test_checksum_mismatch() {
+    let archive = corrupted();
+    assert!(matches!(
+        ArcReader::new(&archive),
+        Err(ArcError::ChecksumMismatch(name)) if name == "hmmm"
+    ));
+    assert!(matches!(
+        ArcReader::read_entry(&archive, "hmmm"),
+        Err(ArcError::ChecksumMismatch(_))
+    ));
+    assert_eq!(ArcReader::read_entry(&archive, "uwu").unwrap(), b"owo");
+    assert!(matches!(
+        ArcReader::read_borrowed(&archive),
+        Err(ArcError::ChecksumMismatch(_))
+    ));
+}
+
+#[test]
+fn test_checksum_mismatch_warning() {
+    let archive = corrupted();
+    let reader = ArcReader::with_checksum_policy(&archive, ChecksumPolicy::Warn).unwrap();
+    assert_eq!(reader.checksum_mismatches(), ["hmmm"]);
+    let entries = reader.entries();
+    assert_eq!(entries.len(), 2);
+    assert_ne!(entries[0].data, "twoja stara ".repeat(100).as_bytes());
+    assert_eq!(entries[1].data, b"owo");
+}
+
+#[test]
+fn test_wrong_checksum_deflated() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", "twoja stara ".repeat(100)));
+    let mut archive = writer.archive().unwrap();
+    // the CRC-32 fields of the local and the central header
+    let central = find(&archive, b"PK\x01\x02");
+    archive[14] ^= 0x01;
+    archive[central + 16] ^= 0x01;
+    assert!(matches!(
+        ArcReader::new(&archive),
+        Err(ArcError::ChecksumMismatch(name)) if name == "hmmm"
+    ));
+    let reader = ArcReader::with_checksum_policy(&archive, ChecksumPolicy::Warn).unwrap();
+    assert_eq!(reader.checksum_mismatches(), ["hmmm"]);
+    assert_eq!(
+        reader.entries()[0].data,
+        "twoja stara ".repeat(100).as_bytes()
+    );
+}
+
+#[test]
+fn test_checksum_match() {
+    let reader =
+        ArcReader::with_checksum_policy(include_bytes!("test.zip"), ChecksumPolicy::Warn).unwrap();
+    assert!(reader.checksum_mismatches().is_empty());
+}
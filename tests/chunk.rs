@@ -0,0 +1,87 @@
+use cra::*;
+
+fn archive(format: ArcFormat) -> Vec<u8> {
+    let mut writer = ArcWriter::new(format);
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("small", "twoja stara\n"));
+    writer.push(ArcEntry::file("big", "a".repeat(10000)));
+    writer.push(ArcEntry::file("empty", ""));
+    writer.archive().unwrap()
+}
+
+#[test]
+fn test_for_each_chunk() {
+    for format in [
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::TarGz,
+        ArcFormat::Cpio,
+    ] {
+        let mut chunks = Vec::new();
+        ArcReader::for_each_chunk(
+            &archive(format),
+            4096,
+            |name| name != "small",
+            |name, chunk| {
+                assert!(chunk.iter().all(|&byte| byte == b'a'));
+                chunks.push((name.to_owned(), chunk.len()));
+                Ok(())
+            },
+        )
+        .unwrap();
+        // 7z lists empty files last
+        chunks.sort();
+        assert_eq!(
+            chunks,
+            vec![
+                ("big".into(), 1808),
+                ("big".into(), 4096),
+                ("big".into(), 4096),
+                ("empty".into(), 0),
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_for_each_chunk_error() {
+    let mut calls = 0;
+    let result = ArcReader::for_each_chunk(
+        &archive(ArcFormat::Sevenz),
+        4096,
+        |_| true,
+        |_, _| {
+            calls += 1;
+            Err(ArcError::EntryNotFound("nope".into()))
+        },
+    );
+    assert!(matches!(result, Err(ArcError::EntryNotFound(_))));
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn test_for_each_chunk_solid() {
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.sevenz_solid = true;
+    for name in ["a", "b", "c"] {
+        writer.push(ArcEntry::file(name, name.repeat(1000)));
+    }
+    let mut data = Vec::new();
+    ArcReader::for_each_chunk(
+        &writer.archive().unwrap(),
+        4096,
+        |name| name != "a",
+        |name, chunk| {
+            data.push((name.to_owned(), chunk.to_vec()));
+            Ok(())
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        data,
+        vec![
+            ("b".into(), "b".repeat(1000).into()),
+            ("c".into(), "c".repeat(1000).into()),
+        ]
+    );
+}
@@ -0,0 +1,29 @@
+use cra::*;
+
+#[test]
+fn test_content_types() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file(
+        "uwu/nested.tar.gz",
+        &include_bytes!("test.tar.gz")[..],
+    ));
+    writer.push(ArcEntry::file(
+        "uwu/run",
+        b"\x7fELF\x02\x01\x01\x00".repeat(8),
+    ));
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let entries: Vec<_> = ArcReader::new(&writer.archive().unwrap())
+        .unwrap()
+        .collect();
+    assert_eq!(entries[0].content_type(), None);
+    let nested = entries[1].content_type().unwrap();
+    assert_eq!(nested.mime_type(), "application/gzip");
+    assert_eq!(nested.matcher_type(), infer::MatcherType::Archive);
+    assert_eq!(entries[2].mime_type(), Some("application/x-executable"));
+    assert_eq!(
+        entries[2].content_type().unwrap().matcher_type(),
+        infer::MatcherType::App
+    );
+    assert_eq!(entries[3].mime_type(), None);
+}
@@ -0,0 +1,32 @@
+use cra::*;
+
+#[test]
+fn test_cpio_reader() {
+    let reader = ArcReader::new(include_bytes!("test.cpio"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Cpio);
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu"),
+            ArcEntry::file("uwu/owo", ""),
+        ]
+    );
+}
+
+#[test]
+fn test_cpio_writer() {
+    let entries = vec![
+        ArcEntry::file("hmmm", "twoja stara\n"),
+        ArcEntry::directory("uwu"),
+        ArcEntry::file("uwu/owo", ""),
+    ];
+    let mut writer = ArcWriter::new(ArcFormat::Cpio);
+    writer.extend(&entries);
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Cpio);
+    assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), entries);
+}
@@ -0,0 +1,45 @@
+use cra::*;
+
+#[test]
+fn test_crx_reader() {
+    let reader = ArcReader::new(include_bytes!("test.crx"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Crx);
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::directory("uwu/"),
+            ArcEntry::file("uwu/owo", ""),
+            ArcEntry::file("hmmm", "twoja stara\n")
+        ]
+    );
+}
+
+#[test]
+fn test_crx_header() {
+    let header = ArcReader::crx_header(include_bytes!("test.crx")).unwrap();
+    assert_eq!(
+        header,
+        CrxHeader {
+            sha256_with_rsa: vec![CrxProof {
+                public_key: "public key".into(),
+                signature: "signature".into(),
+            }],
+            sha256_with_ecdsa: vec![],
+            signed_header_data: b"\x0a\x100123456789abcdef".to_vec(),
+        }
+    );
+}
+
+#[test]
+fn test_crx_truncated() {
+    let buf = include_bytes!("test.crx");
+    assert!(ArcReader::crx_header(&buf[..20]).is_err());
+}
+
+#[test]
+fn test_crx_writer() {
+    let writer = ArcWriter::new(ArcFormat::Crx);
+    assert!(matches!(writer.archive(), Err(ArcError::ReadOnly)));
+}
@@ -0,0 +1,40 @@
+use cra::*;
+
+#[test]
+fn test_deb_reader() {
+    let reader = ArcReader::new(include_bytes!("test.deb"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Deb);
+    let names: Vec<_> = reader
+        .entries()
+        .iter()
+        .map(|entry| entry.name.as_str())
+        .collect();
+    assert_eq!(names, ["debian-binary", "control.tar.gz", "data.tar.gz"]);
+}
+
+#[test]
+fn test_deb_nested() {
+    let reader = ArcReader::new(include_bytes!("test.deb")).unwrap();
+
+    let control = reader.deb_control().unwrap();
+    assert_eq!(control.format(), ArcFormat::TarGz);
+    assert!(control
+        .entries()
+        .iter()
+        .any(|entry| entry.is_file() && entry.name == "./control"));
+
+    let data = reader.deb_data().unwrap();
+    assert_eq!(data.format(), ArcFormat::TarGz);
+    assert!(data
+        .entries()
+        .iter()
+        .any(|entry| entry.name == "./usr/share/uwu/hmmm" && entry.data == b"twoja stara\n"));
+}
+
+#[test]
+fn test_deb_writer() {
+    let writer = ArcWriter::new(ArcFormat::Deb);
+    assert!(matches!(writer.archive(), Err(ArcError::ReadOnly)));
+}
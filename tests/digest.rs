@@ -0,0 +1,60 @@
+use cra::digest::Digest;
+use cra::*;
+use std::io::Cursor;
+
+#[test]
+fn test_read_digests() {
+    for archive in [
+        &include_bytes!("test.zip")[..],
+        include_bytes!("test.7z"),
+        include_bytes!("test.tar.gz"),
+        include_bytes!("test.cpio"),
+    ] {
+        let digests = ArcReader::sha256s(archive).unwrap();
+        assert_eq!(digests.len(), 2);
+        assert_eq!(digests["hmmm"], Sha256::digest(b"twoja stara\n").to_vec());
+        assert_eq!(digests["uwu/owo"], Sha256::digest(b"").to_vec());
+        for entry in ArcReader::new(archive).unwrap() {
+            if entry.is_file() {
+                assert_eq!(entry.sha256(), digests[&entry.name]);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_write_digests() {
+    for format in [
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::TarXz,
+        ArcFormat::Cpio,
+    ] {
+        let mut writer = ArcWriter::new(format);
+        writer.digest = Some(Box::new(Sha256::new()));
+        writer.push(ArcEntry::directory("uwu/"));
+        writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+        writer.push_from_reader("uwu/owo", Cursor::new(vec![7; 100_000]), 100_000);
+        assert!(writer.digests().is_empty());
+        let archive = writer.archive().unwrap();
+        let digests = writer.digests();
+        assert_eq!(digests.len(), 2, "{format:?}");
+        assert_eq!(
+            digests["hmmm"],
+            Sha256::digest(b"twoja stara\n").to_vec(),
+            "{format:?}"
+        );
+        assert_eq!(digests, ArcReader::sha256s(&archive).unwrap(), "{format:?}");
+    }
+}
+
+#[test]
+fn test_other_digests() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.digest = Some(Box::new(sha2::Sha512::new()));
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let archive = writer.archive().unwrap();
+    let digests = ArcReader::digests::<sha2::Sha512>(&archive).unwrap();
+    assert_eq!(digests["hmmm"].len(), 64);
+    assert_eq!(digests, writer.digests());
+}
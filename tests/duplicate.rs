@@ -0,0 +1,87 @@
+use cra::*;
+
+fn with_duplicates(format: ArcFormat) -> Vec<u8> {
+    let mut writer = ArcWriter::new(format);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.push(ArcEntry::file("uwu", "owo"));
+    writer.push(ArcEntry::file("hmmm", "twoj stary\n"));
+    writer.archive().unwrap()
+}
+
+fn read(archive: &[u8], policy: DuplicatePolicy) -> Vec<(String, Vec<u8>)> {
+    ArcReader::with_duplicate_policy(archive, policy)
+        .unwrap()
+        .map(|entry| (entry.name, entry.data))
+        .collect()
+}
+
+#[test]
+fn test_read_duplicates() {
+    for format in [ArcFormat::Tar, ArcFormat::Cpio] {
+        let archive = with_duplicates(format);
+        let hmmm = |data: &str| ("hmmm".to_owned(), data.as_bytes().to_vec());
+        let uwu = ("uwu".to_owned(), b"owo".to_vec());
+        assert_eq!(
+            read(&archive, DuplicatePolicy::KeepAll),
+            [hmmm("twoja stara\n"), uwu.clone(), hmmm("twoj stary\n")]
+        );
+        assert_eq!(ArcReader::new(&archive).unwrap().entries().len(), 3);
+        assert_eq!(
+            read(&archive, DuplicatePolicy::KeepFirst),
+            [hmmm("twoja stara\n"), uwu.clone()]
+        );
+        assert_eq!(
+            read(&archive, DuplicatePolicy::KeepLast),
+            [uwu, hmmm("twoj stary\n")]
+        );
+        assert!(matches!(
+            ArcReader::with_duplicate_policy(&archive, DuplicatePolicy::Reject),
+            Err(ArcError::DuplicateEntry(name)) if name == "hmmm"
+        ));
+        assert_eq!(
+            ArcReader::with_duplicate_policy(include_bytes!("test.tar"), DuplicatePolicy::Reject)
+                .unwrap()
+                .entries()
+                .len(),
+            3
+        );
+    }
+}
+
+#[test]
+fn test_write_duplicates() {
+    for format in [
+        ArcFormat::Tar,
+        ArcFormat::TarGz,
+        ArcFormat::Cpio,
+        ArcFormat::Sevenz,
+    ] {
+        let mut writer = ArcWriter::new(format);
+        writer.reject_duplicates = true;
+        writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+        writer.push(ArcEntry::file("uwu", "owo"));
+        assert!(writer.archive().is_ok(), "{format:?}");
+        writer.push(ArcEntry::directory("hmmm"));
+        assert!(
+            matches!(writer.archive(), Err(ArcError::DuplicateEntry(name)) if name == "hmmm"),
+            "{format:?}"
+        );
+    }
+}
+
+#[test]
+fn test_stream_duplicates() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.reject_duplicates = true;
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let mut stream = writer.into_stream(Vec::new()).unwrap();
+    stream.push(ArcEntry::file("uwu", "owo")).unwrap();
+    assert!(matches!(
+        stream.push_from_reader("hmmm", &b"twoj stary\n"[..], 11),
+        Err(ArcError::DuplicateEntry(_))
+    ));
+    assert!(matches!(
+        stream.push(ArcEntry::file("uwu", "owo")),
+        Err(ArcError::DuplicateEntry(_))
+    ));
+}
@@ -0,0 +1,66 @@
+use cra::*;
+
+const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+fn entries() -> Vec<ArcEntry> {
+    vec![
+        ArcEntry::file("META-INF/container.xml", CONTAINER),
+        ArcEntry::file("OEBPS/content.opf", "<package/>"),
+        ArcEntry::file("OEBPS/hmmm.xhtml", "twoja stara\n"),
+    ]
+}
+
+#[test]
+fn test_epub_writer() {
+    let mut writer = ArcWriter::new(ArcFormat::Epub);
+    writer.extend(&entries());
+    let archive = writer.archive().unwrap();
+    // mimetype is first, stored uncompressed and without an extra field
+    assert_eq!(&archive[30..58], b"mimetypeapplication/epub+zip");
+    assert_eq!(archive[8..10], [0, 0]);
+    assert_eq!(archive[28..30], [0, 0]);
+
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Epub);
+    let mut expected = vec![ArcEntry::file("mimetype", "application/epub+zip")];
+    expected.extend(entries());
+    assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_epub_missing_members() {
+    let mut writer = ArcWriter::new(ArcFormat::Epub);
+    writer.extend(&entries()[1..]);
+    assert!(matches!(
+        writer.archive(),
+        Err(ArcError::EntryNotFound(name)) if name == "META-INF/container.xml"
+    ));
+
+    let mut writer = ArcWriter::new(ArcFormat::Epub);
+    writer.push(entries()[0].clone());
+    assert!(matches!(
+        writer.archive(),
+        Err(ArcError::EntryNotFound(name)) if name == "OEBPS/content.opf"
+    ));
+}
+
+#[test]
+fn test_epub_wrong_mimetype() {
+    let mut writer = ArcWriter::new(ArcFormat::Epub);
+    writer.push(ArcEntry::file("mimetype", "text/plain"));
+    writer.extend(&entries());
+    assert!(writer.archive().is_err());
+}
+
+#[test]
+fn test_epub_container_from_reader() {
+    let mut writer = ArcWriter::new(ArcFormat::Epub);
+    writer.push_from_reader("META-INF/container.xml", &b""[..], 0);
+    assert!(matches!(writer.archive(), Err(ArcError::IoError(_))));
+}
@@ -0,0 +1,633 @@
+use cra::*;
+use std::fs;
+
+fn entries() -> Vec<ArcEntry> {
+    vec![
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", "twoja stara\n"),
+        ArcEntry::file("deep/down/hmmm", "twoj stary\n"),
+        ArcEntry::symlink("link", "uwu/owo"),
+        ArcEntry::hard_link("hard", "uwu/owo"),
+    ]
+}
+
+fn reader(entries: &[ArcEntry]) -> ArcReader {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.extend(entries);
+    ArcReader::with_symlink_policy(&writer.archive().unwrap(), SymlinkPolicy::Allow).unwrap()
+}
+
+#[test]
+fn test_extract_to() {
+    let dir = tempfile::tempdir().unwrap();
+    let summary = reader(&entries()).extract_to(dir.path()).unwrap();
+
+    assert_eq!(
+        summary.files,
+        [
+            dir.path().join("uwu/owo"),
+            dir.path().join("deep/down/hmmm")
+        ]
+    );
+    assert_eq!(summary.directories, [dir.path().join("uwu")]);
+    assert_eq!(summary.bytes, 23);
+    assert_eq!(
+        fs::read(dir.path().join("deep/down/hmmm")).unwrap(),
+        b"twoj stary\n"
+    );
+    assert_eq!(fs::read(dir.path().join("hard")).unwrap(), b"twoja stara\n");
+    #[cfg(unix)]
+    {
+        assert_eq!(summary.links.len(), 2);
+        assert_eq!(
+            fs::read_link(dir.path().join("link")).unwrap(),
+            std::path::Path::new("uwu/owo")
+        );
+    }
+
+    // extracting again replaces what's there
+    reader(&entries()).extract_to(dir.path()).unwrap();
+    assert_eq!(
+        fs::read(dir.path().join("uwu/owo")).unwrap(),
+        b"twoja stara\n"
+    );
+}
+
+#[test]
+fn test_extract_to_rejects_escapes() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("out");
+    let unsafe_entries = [
+        ArcEntry::file("fine", "owo"),
+        ArcEntry::file("../escaped", "owo"),
+    ];
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.extend(&unsafe_entries);
+    let archive = writer.archive().unwrap();
+    let unsafe_reader = ArcReader::with_path_policy(&archive, PathPolicy::Allow).unwrap();
+    assert!(matches!(
+        unsafe_reader.extract_to(&target),
+        Err(ArcError::UnsafePath(name)) if name == "../escaped"
+    ));
+    assert!(!target.exists());
+
+    let escaping = reader(&[
+        ArcEntry::symlink("etc", "/etc"),
+        ArcEntry::file("etc/passwd", "root::0:0::/:/bin/sh\n"),
+    ]);
+    assert!(matches!(
+        escaping.extract_to(&target),
+        Err(ArcError::SymlinkEscape(_))
+    ));
+    assert!(!dir.path().join("escaped").exists());
+}
+
+#[test]
+fn test_extract_preserving_metadata() {
+    let mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+    let owner = Owner {
+        uid: 1234,
+        gid: 4321,
+        ..Owner::default()
+    };
+    let reader = reader(&[
+        ArcEntry::directory("uwu/")
+            .with_mode(0o700)
+            .with_mtime(mtime),
+        ArcEntry::file("uwu/owo", "twoja stara\n")
+            .with_mode(0o640)
+            .with_mtime(mtime)
+            .with_owner(owner),
+    ]);
+    let dir = tempfile::tempdir().unwrap();
+    let options = ExtractOptions {
+        preserve_metadata: true,
+        ..ExtractOptions::default()
+    };
+    reader.extract_with(dir.path(), &options).unwrap();
+
+    let file = fs::metadata(dir.path().join("uwu/owo")).unwrap();
+    assert_eq!(file.modified().unwrap(), mtime);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+        let uwu = fs::metadata(dir.path().join("uwu")).unwrap();
+        assert_eq!(uwu.modified().unwrap(), mtime);
+        assert_eq!(uwu.permissions().mode() & 0o777, 0o700);
+        assert_eq!(file.permissions().mode() & 0o777, 0o640);
+        // owners are only restored by root
+        let running_as_root = fs::metadata(dir.path()).unwrap().uid() == 0;
+        assert_eq!(file.uid() == 1234, running_as_root);
+    }
+
+    // nothing is restored by default
+    let dir = tempfile::tempdir().unwrap();
+    reader.extract_to(dir.path()).unwrap();
+    let file = fs::metadata(dir.path().join("uwu/owo")).unwrap();
+    assert_ne!(file.modified().unwrap(), mtime);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_extract_through_existing_symlinks() {
+    let dir = tempfile::tempdir().unwrap();
+    let outside = dir.path().join("outside");
+    let target = dir.path().join("out");
+    fs::create_dir_all(&outside).unwrap();
+    fs::create_dir_all(target.join("uwu")).unwrap();
+    std::os::unix::fs::symlink(&outside, target.join("sub")).unwrap();
+    std::os::unix::fs::symlink("uwu", target.join("inner")).unwrap();
+
+    let entries = [
+        ArcEntry::directory("sub/etc/"),
+        ArcEntry::file("sub/passwd", "root::0:0::/:/bin/sh\n"),
+    ];
+    for entry in entries {
+        assert!(matches!(
+            reader(&[entry]).extract_to(&target),
+            Err(ArcError::SymlinkEscape(_))
+        ));
+    }
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("sub/passwd", "root::0:0::/:/bin/sh\n"));
+    let patterns = [Pattern::glob("**").unwrap()];
+    assert!(matches!(
+        ArcReader::extract_matching(
+            &writer.archive().unwrap(),
+            &target,
+            &patterns,
+            &ExtractOptions::default()
+        ),
+        Err(ArcError::SymlinkEscape(_))
+    ));
+    assert_eq!(fs::read_dir(&outside).unwrap().count(), 0);
+
+    // links staying inside are followed
+    reader(&[ArcEntry::file("inner/owo", "twoja stara\n")])
+        .extract_to(&target)
+        .unwrap();
+    assert!(target.join("uwu/owo").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_extract_restores_only_directories_inside() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = tempfile::tempdir().unwrap();
+    let outside = dir.path().join("outside");
+    let target = dir.path().join("out");
+    fs::create_dir_all(&outside).unwrap();
+    let mode = fs::metadata(&outside).unwrap().permissions().mode();
+
+    // a directory swapped for a symlink before it gets its metadata
+    let swapped = target.join("a");
+    let link = outside.clone();
+    let options = ExtractOptions {
+        preserve_metadata: true,
+        veto: Some(Box::new(move |name, _, _| {
+            if name == "b" {
+                fs::remove_dir(&swapped).unwrap();
+                std::os::unix::fs::symlink(&link, &swapped).unwrap();
+            }
+            EntryDecision::Extract
+        })),
+        ..Default::default()
+    };
+    let swapping = reader(&[
+        ArcEntry::directory("a/").with_mode(0o700),
+        ArcEntry::file("b", "uwu"),
+    ]);
+    assert!(swapping.extract_with(&target, &options).is_err());
+    assert_eq!(fs::metadata(&outside).unwrap().permissions().mode(), mode);
+
+    // symlinks to directories inside are left as they are
+    let target = dir.path().join("inner");
+    fs::create_dir_all(target.join("uwu")).unwrap();
+    std::os::unix::fs::symlink("uwu", target.join("link")).unwrap();
+    let mode = fs::metadata(target.join("uwu"))
+        .unwrap()
+        .permissions()
+        .mode();
+    let options = ExtractOptions {
+        preserve_metadata: true,
+        ..Default::default()
+    };
+    reader(&[ArcEntry::directory("link/").with_mode(0o700)])
+        .extract_with(&target, &options)
+        .unwrap();
+    assert_eq!(
+        fs::metadata(target.join("uwu"))
+            .unwrap()
+            .permissions()
+            .mode(),
+        mode
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_extract_directory_onto_existing_symlink() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = tempfile::tempdir().unwrap();
+    let outside = dir.path().join("outside");
+    let target = dir.path().join("out");
+    fs::create_dir_all(&outside).unwrap();
+    fs::create_dir_all(target.join("uwu")).unwrap();
+    std::os::unix::fs::symlink(&outside, target.join("a")).unwrap();
+    std::os::unix::fs::symlink("uwu", target.join("inner")).unwrap();
+
+    let entry = ArcEntry::directory("a/").with_mode(0o700);
+    let options = ExtractOptions {
+        preserve_metadata: true,
+        ..Default::default()
+    };
+    assert!(matches!(
+        reader(&[entry]).extract_with(&target, &options),
+        Err(ArcError::SymlinkEscape(name)) if name == "a/"
+    ));
+    assert_ne!(
+        fs::metadata(&outside).unwrap().permissions().mode() & 0o777,
+        0o700
+    );
+
+    // links staying inside are kept as they are
+    let summary = reader(&[ArcEntry::directory("inner/")])
+        .extract_to(&target)
+        .unwrap();
+    assert!(fs::symlink_metadata(target.join("inner"))
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert_eq!(
+        summary.entries,
+        [(
+            "inner/".into(),
+            ExtractOutcome::Existing(target.join("inner"))
+        )]
+    );
+}
+
+#[test]
+fn test_extract_matching() {
+    let files = [
+        ArcEntry::directory("docs/"),
+        ArcEntry::file("docs/README.md", "# uwu\n"),
+        ArcEntry::file("docs/api/reader.md", "# owo\n"),
+        ArcEntry::file("docs/api/logo.png", "not really\n"),
+        ArcEntry::file("src/lib.rs", "fn main() {}\n"),
+    ];
+    let patterns = [
+        Pattern::glob("docs/**/*.md").unwrap(),
+        Pattern::regex(r"^src/.*\.rs$").unwrap(),
+    ];
+    for format in [ArcFormat::Zip, ArcFormat::TarGz, ArcFormat::Sevenz] {
+        let mut writer = ArcWriter::new(format);
+        writer.extend(&files);
+        let archive = writer.archive().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let options = ExtractOptions::default();
+        let summary =
+            ArcReader::extract_matching(&archive, dir.path(), &patterns, &options).unwrap();
+
+        let mut written = summary.files.clone();
+        written.sort();
+        assert_eq!(
+            written,
+            [
+                dir.path().join("docs/README.md"),
+                dir.path().join("docs/api/reader.md"),
+                dir.path().join("src/lib.rs"),
+            ],
+            "{format:?}"
+        );
+        assert!(summary.directories.is_empty());
+        assert_eq!(summary.bytes, 25);
+        assert_eq!(
+            fs::read(dir.path().join("docs/api/reader.md")).unwrap(),
+            b"# owo\n"
+        );
+        assert!(!dir.path().join("docs/api/logo.png").exists());
+    }
+
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("../docs/uwu.md", "owo"));
+    let archive = writer.archive().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let everything = [Pattern::glob("**").unwrap()];
+    assert!(matches!(
+        ArcReader::extract_matching(
+            &archive,
+            dir.path().join("out"),
+            &everything,
+            &ExtractOptions::default()
+        ),
+        Err(ArcError::UnsafePath(_))
+    ));
+    assert!(!dir.path().join("docs").exists());
+
+    // options apply like they do to extract_with
+    let mut writer = ArcWriter::new(ArcFormat::TarGz);
+    writer.extend(&files);
+    let archive = writer.archive().unwrap();
+    fs::create_dir_all(dir.path().join("docs")).unwrap();
+    fs::write(dir.path().join("docs/README.md"), "old").unwrap();
+    let options = ExtractOptions {
+        overwrite: OverwritePolicy::Skip,
+        veto: Some(Box::new(|name, _, _| match name.starts_with("src/") {
+            true => EntryDecision::Skip,
+            false => EntryDecision::Extract,
+        })),
+        ..ExtractOptions::default()
+    };
+    let summary = ArcReader::extract_matching(&archive, dir.path(), &patterns, &options).unwrap();
+    assert_eq!(summary.files, [dir.path().join("docs/api/reader.md")]);
+    assert_eq!(summary.skipped, ["docs/README.md", "src/lib.rs"]);
+    assert_eq!(fs::read(dir.path().join("docs/README.md")).unwrap(), b"old");
+
+    let options = ExtractOptions {
+        atomic: true,
+        preserve_metadata: true,
+        ..ExtractOptions::default()
+    };
+    let target = dir.path().join("atomic");
+    let summary = ArcReader::extract_matching(&archive, &target, &patterns, &options).unwrap();
+    assert_eq!(summary.files.len(), 3);
+    assert!(target.join("src/lib.rs").exists());
+}
+
+#[test]
+fn test_overwrite_policies() {
+    let reader = reader(&[
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", "twoja stara\n"),
+        ArcEntry::file("hmmm", "twoj stary\n"),
+    ]);
+    let extract = |overwrite| {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("uwu")).unwrap();
+        fs::write(dir.path().join("uwu/owo"), "old").unwrap();
+        let options = ExtractOptions {
+            overwrite,
+            ..ExtractOptions::default()
+        };
+        let summary = reader.extract_with(dir.path(), &options);
+        (dir, summary)
+    };
+
+    let (dir, summary) = extract(OverwritePolicy::Overwrite);
+    let path = |name| dir.path().join(name);
+    assert_eq!(
+        summary.unwrap().entries,
+        [
+            ("uwu/".into(), ExtractOutcome::Existing(path("uwu"))),
+            (
+                "uwu/owo".into(),
+                ExtractOutcome::Overwritten(path("uwu/owo"))
+            ),
+            ("hmmm".into(), ExtractOutcome::Created(path("hmmm"))),
+        ]
+    );
+    assert_eq!(fs::read(path("uwu/owo")).unwrap(), b"twoja stara\n");
+
+    let (dir, summary) = extract(OverwritePolicy::Skip);
+    let summary = summary.unwrap();
+    assert_eq!(summary.skipped, ["uwu/owo"]);
+    assert_eq!(summary.files, [dir.path().join("hmmm")]);
+    assert_eq!(fs::read(dir.path().join("uwu/owo")).unwrap(), b"old");
+
+    let (dir, summary) = extract(OverwritePolicy::Error);
+    assert!(matches!(
+        summary,
+        Err(ArcError::IoError(err)) if err.kind() == std::io::ErrorKind::AlreadyExists
+    ));
+    assert!(!dir.path().join("hmmm").exists());
+
+    let (dir, summary) = extract(OverwritePolicy::Rename);
+    let renamed = dir.path().join("uwu/owo.1");
+    assert_eq!(
+        summary.unwrap().entries[1],
+        ("uwu/owo".into(), ExtractOutcome::Renamed(renamed.clone()))
+    );
+    assert_eq!(fs::read(dir.path().join("uwu/owo")).unwrap(), b"old");
+    assert_eq!(fs::read(renamed).unwrap(), b"twoja stara\n");
+}
+
+#[test]
+fn test_atomic_extraction() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("out");
+    let options = ExtractOptions {
+        atomic: true,
+        ..ExtractOptions::default()
+    };
+    let summary = reader(&entries()).extract_with(&target, &options).unwrap();
+    assert_eq!(
+        summary.files,
+        [target.join("uwu/owo"), target.join("deep/down/hmmm")]
+    );
+    assert_eq!(
+        summary.entries[0],
+        ("uwu/".into(), ExtractOutcome::Created(target.join("uwu")))
+    );
+    assert_eq!(fs::read(target.join("uwu/owo")).unwrap(), b"twoja stara\n");
+    // the staging directory is gone
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    // the destination has to be new
+    let result = reader(&entries()).extract_with(&target, &options);
+    assert!(matches!(
+        result,
+        Err(ArcError::IoError(err)) if err.kind() == std::io::ErrorKind::AlreadyExists
+    ));
+
+    // failures leave nothing behind
+    let broken = reader(&[
+        ArcEntry::file("uwu/owo", "twoja stara\n"),
+        ArcEntry::hard_link("hard", "nowhere"),
+    ]);
+    let target = dir.path().join("broken");
+    assert!(broken.extract_with(&target, &options).is_err());
+    assert!(!target.exists());
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn test_plan_extraction() {
+    let reader = reader(&[
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", "twoja stara\n"),
+        ArcEntry::file("deep/down/hmmm", "twoj stary\n"),
+        ArcEntry::file("blocked/hmmm", "twoj stary\n"),
+        ArcEntry::file("hmmm", "twoj stary\n"),
+        ArcEntry::file("hmmm", "twoj stary\n"),
+    ]);
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir(dir.path().join("uwu")).unwrap();
+    fs::write(dir.path().join("uwu/owo"), "old").unwrap();
+    fs::write(dir.path().join("blocked"), "old").unwrap();
+    let path = |name| dir.path().join(name);
+
+    let plan = reader
+        .plan_extraction(dir.path(), &ExtractOptions::default())
+        .unwrap();
+    assert_eq!(
+        plan.created,
+        [
+            path("deep"),
+            path("deep/down"),
+            path("deep/down/hmmm"),
+            path("hmmm")
+        ]
+    );
+    assert_eq!(plan.overwritten, [path("uwu/owo"), path("hmmm")]);
+    assert_eq!(plan.conflicts, [path("blocked/hmmm")]);
+    assert_eq!(
+        plan.entries[0],
+        ("uwu/".into(), ExtractOutcome::Existing(path("uwu")))
+    );
+    // nothing was written
+    assert!(!path("deep").exists());
+    assert_eq!(fs::read(path("uwu/owo")).unwrap(), b"old");
+
+    let options = ExtractOptions {
+        overwrite: OverwritePolicy::Error,
+        ..ExtractOptions::default()
+    };
+    let plan = reader.plan_extraction(dir.path(), &options).unwrap();
+    assert_eq!(
+        plan.conflicts,
+        [path("uwu/owo"), path("blocked/hmmm"), path("hmmm")]
+    );
+
+    let options = ExtractOptions {
+        overwrite: OverwritePolicy::Rename,
+        ..ExtractOptions::default()
+    };
+    let plan = reader.plan_extraction(dir.path(), &options).unwrap();
+    assert_eq!(
+        plan.entries[5],
+        ("hmmm".into(), ExtractOutcome::Renamed(path("hmmm.1")))
+    );
+
+    let options = ExtractOptions {
+        atomic: true,
+        ..ExtractOptions::default()
+    };
+    let plan = reader.plan_extraction(dir.path(), &options).unwrap();
+    assert_eq!(plan.conflicts[0], dir.path());
+    let plan = reader.plan_extraction(path("new"), &options).unwrap();
+    assert!(plan.conflicts.is_empty());
+    assert_eq!(plan.created[..2], [path("new"), path("new/uwu")]);
+}
+
+#[test]
+fn test_plan_extraction_veto() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = ExtractOptions {
+        veto: Some(Box::new(|name, _, kind| match kind {
+            EntryKind::HardLink(_) => EntryDecision::Abort,
+            _ if name.starts_with("deep/") => EntryDecision::Skip,
+            _ => EntryDecision::Extract,
+        })),
+        ..ExtractOptions::default()
+    };
+    let mut unlinked = entries();
+    unlinked.pop();
+    let plan = reader(&unlinked)
+        .plan_extraction(dir.path(), &options)
+        .unwrap();
+    assert_eq!(
+        plan.entries[2],
+        ("deep/down/hmmm".into(), ExtractOutcome::Vetoed)
+    );
+    assert!(!plan.created.contains(&dir.path().join("deep")));
+
+    let result = reader(&entries()).plan_extraction(dir.path(), &options);
+    assert!(matches!(result, Err(ArcError::Aborted(name)) if name == "hard"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_plan_extraction_through_existing_symlink() {
+    let dir = tempfile::tempdir().unwrap();
+    let outside = dir.path().join("outside");
+    let target = dir.path().join("out");
+    fs::create_dir_all(&outside).unwrap();
+    fs::create_dir_all(&target).unwrap();
+    std::os::unix::fs::symlink(&outside, target.join("a")).unwrap();
+
+    for entry in [ArcEntry::file("a/owo", "uwu"), ArcEntry::directory("a/")] {
+        let name = entry.name.clone();
+        assert!(matches!(
+            reader(&[entry]).plan_extraction(&target, &ExtractOptions::default()),
+            Err(ArcError::SymlinkEscape(escaped)) if escaped == name
+        ));
+    }
+    // the link itself is replaced rather than written through
+    let plan = reader(&[ArcEntry::file("a", "uwu")])
+        .plan_extraction(&target, &ExtractOptions::default())
+        .unwrap();
+    assert_eq!(plan.overwritten, [target.join("a")]);
+}
+
+#[test]
+fn test_extract_veto() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = ExtractOptions {
+        veto: Some(Box::new(|name, size, kind| match kind {
+            EntryKind::HardLink(_) => EntryDecision::Abort,
+            _ if name.starts_with("deep/") || size > 100 => EntryDecision::Skip,
+            _ => EntryDecision::Extract,
+        })),
+        ..ExtractOptions::default()
+    };
+    let mut unlinked = entries();
+    unlinked.pop();
+    let summary = reader(&unlinked)
+        .extract_with(dir.path(), &options)
+        .unwrap();
+    assert_eq!(summary.files, [dir.path().join("uwu/owo")]);
+    assert_eq!(summary.skipped[0], "deep/down/hmmm");
+    assert_eq!(
+        summary.entries[2],
+        ("deep/down/hmmm".into(), ExtractOutcome::Vetoed)
+    );
+    assert!(!dir.path().join("deep").exists());
+
+    let result = reader(&entries()).extract_with(dir.path().join("out"), &options);
+    assert!(matches!(result, Err(ArcError::Aborted(name)) if name == "hard"));
+    assert!(dir.path().join("out/uwu/owo").exists());
+}
+
+#[test]
+fn test_extract_entry() {
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("hmmm");
+    for format in [
+        ArcFormat::Tar,
+        ArcFormat::TarGz,
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::Cpio,
+    ] {
+        let mut writer = ArcWriter::new(format);
+        writer.extend(&[
+            ArcEntry::directory("uwu/"),
+            ArcEntry::file("uwu/owo", "twoja stara\n"),
+            ArcEntry::file("deep/down/hmmm", "twoj stary\n"),
+        ]);
+        let archive = writer.archive().unwrap();
+        let size = ArcReader::extract_entry(&archive, "deep/down/hmmm", &dest).unwrap();
+        assert_eq!(size, 11, "{format:?}");
+        assert_eq!(fs::read(&dest).unwrap(), b"twoj stary\n", "{format:?}");
+
+        // missing entries leave what's at the destination alone
+        fs::write(&dest, "old").unwrap();
+        assert!(matches!(
+            ArcReader::extract_entry(&archive, "uwu/", &dest),
+            Err(ArcError::EntryNotFound(_))
+        ));
+        assert_eq!(fs::read(&dest).unwrap(), b"old");
+        fs::remove_file(&dest).unwrap();
+    }
+}
@@ -0,0 +1,55 @@
+use cra::*;
+
+/// Returns `tar` with the `ustar` magic of its first header cleared, like
+/// tarballs from before POSIX.1-1988
+fn pre_ustar(mut tar: Vec<u8>) -> Vec<u8> {
+    tar[257..265].fill(0);
+    // the checksum is summed with its own field as spaces
+    tar[148..156].fill(b' ');
+    let sum: u32 = tar[..512].iter().map(|&byte| u32::from(byte)).sum();
+    tar[148..156].copy_from_slice(format!("{sum:06o}\0 ").as_bytes());
+    tar
+}
+
+fn tarball(format: ArcFormat) -> Vec<u8> {
+    let mut writer = ArcWriter::new(format);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.archive().unwrap()
+}
+
+#[test]
+fn test_pre_ustar_tar() {
+    let tar = pre_ustar(tarball(ArcFormat::Tar));
+    assert!(ArcReader::new(&tar).is_err());
+    let reader = ArcReader::with_format(&tar, ArcFormat::Tar).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Tar);
+    assert_eq!(reader.entries()[0].name, "hmmm");
+    assert_eq!(reader.entries()[0].data, b"twoja stara\n");
+}
+
+#[test]
+fn test_pre_ustar_tar_gz() {
+    let mut writer = ArcWriter::new(ArcFormat::Gzip);
+    writer.push(ArcEntry::file("", pre_ustar(tarball(ArcFormat::Tar))));
+    let archive = writer.archive().unwrap();
+    assert_eq!(ArcReader::new(&archive).unwrap().format(), ArcFormat::Gzip);
+    let reader = ArcReader::with_format(&archive, ArcFormat::TarGz).unwrap();
+    assert_eq!(reader.format(), ArcFormat::TarGz);
+    assert_eq!(reader.entries()[0].data, b"twoja stara\n");
+}
+
+#[test]
+fn test_format_overrides_contents() {
+    let archive = tarball(ArcFormat::TarGz);
+    let reader = ArcReader::with_format(&archive, ArcFormat::Gzip).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Gzip);
+    assert_eq!(reader.entries().len(), 1);
+    assert_eq!(reader.entries()[0].data.len() % 512, 0);
+}
+
+#[test]
+fn test_wrong_format() {
+    assert!(ArcReader::with_format(include_bytes!("test.zip"), ArcFormat::Sevenz).is_err());
+    let reader = ArcReader::with_format(include_bytes!("test.zip"), ArcFormat::Zip).unwrap();
+    assert_eq!(reader.entries().len(), 3);
+}
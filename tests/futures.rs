@@ -0,0 +1,42 @@
+#![cfg(feature = "futures")]
+
+use cra::*;
+use futures::executor::block_on;
+
+#[test]
+fn test_futures_reader() {
+    let reader = block_on(FuturesArcReader::new(&include_bytes!("test.tar.gz")[..])).unwrap();
+    assert_eq!(reader.format(), ArcFormat::TarGz);
+    assert_eq!(
+        reader
+            .into_inner()
+            .map(ArcEntry::stripped)
+            .collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu/"),
+            ArcEntry::file("uwu/owo", ""),
+        ]
+    );
+}
+
+#[test]
+fn test_futures_writer() {
+    let entries = vec![
+        ArcEntry::file("hmmm", "twoja stara\n"),
+        ArcEntry::directory("uwu/"),
+    ];
+    let mut writer = FuturesArcWriter::new(ArcFormat::Zip);
+    writer.extend(&entries);
+    let mut archive = Vec::new();
+    block_on(writer.archive_to(&mut archive)).unwrap();
+    let reader = block_on(FuturesArcReader::new(&archive[..])).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zip);
+    assert_eq!(
+        reader
+            .into_inner()
+            .map(ArcEntry::stripped)
+            .collect::<Vec<_>>(),
+        entries
+    );
+}
@@ -0,0 +1,43 @@
+use cra::*;
+
+#[test]
+fn test_gzip_reader() {
+    let reader = ArcReader::new(include_bytes!("test.gz"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Gzip);
+    assert_eq!(
+        reader.entries(),
+        &vec![ArcEntry::file("hmmm", "twoja stara\n")]
+    );
+}
+
+#[test]
+fn test_gzip_writer() {
+    let mut writer = ArcWriter::new(ArcFormat::Gzip);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Gzip);
+    assert_eq!(
+        reader.entries(),
+        &vec![ArcEntry::file("hmmm", "twoja stara\n")]
+    );
+}
+
+#[test]
+fn test_gzip_writer_single_file_only() {
+    let mut writer = ArcWriter::new(ArcFormat::Gzip);
+    writer.push(ArcEntry::directory("uwu/"));
+    assert!(matches!(writer.archive(), Err(ArcError::SingleFileOnly)));
+}
+
+#[test]
+fn test_gzip_from_reader() {
+    let reader = ArcReader::from_reader(&include_bytes!("test.gz")[..]).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Gzip);
+    assert_eq!(
+        reader.entries(),
+        &vec![ArcEntry::file("hmmm", "twoja stara\n")]
+    );
+}
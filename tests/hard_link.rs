@@ -0,0 +1,81 @@
+use cra::*;
+
+fn entries() -> Vec<ArcEntry> {
+    vec![
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", "twoja stara\n"),
+        ArcEntry::hard_link("link", "uwu/owo"),
+        ArcEntry::hard_link(format!("{}/owo", "uwu".repeat(50)), "uwu/owo"),
+    ]
+}
+
+#[test]
+fn test_hard_link_round_trip() {
+    for tar_format in [TarFormat::Gnu, TarFormat::Pax] {
+        for format in [ArcFormat::Tar, ArcFormat::TarGz] {
+            let mut writer = ArcWriter::new(format);
+            writer.tar_format = tar_format;
+            writer.extend(&entries());
+            let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+            assert_eq!(
+                reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+                entries(),
+                "{format:?} {tar_format:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_hard_link_stream() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.extend(&entries());
+    let archive = writer.archive().unwrap();
+
+    let mut reader = ArcStreamReader::new(&archive[..]).unwrap();
+    let mut targets = Vec::new();
+    while let Some(entry) = reader.next_entry().unwrap() {
+        assert_eq!(entry.symlink_target(), None);
+        targets.push(entry.hard_link_target().map(str::to_owned));
+    }
+    assert_eq!(targets[..3], [None, None, Some("uwu/owo".into())]);
+
+    let reader = ArcStreamReader::new(&archive[..]).unwrap();
+    assert_eq!(
+        reader
+            .map(|entry| entry.unwrap().stripped())
+            .collect::<Vec<_>>(),
+        entries()
+    );
+}
+
+#[test]
+fn test_hard_link_read_borrowed() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.extend(&entries());
+    let archive = writer.archive().unwrap();
+    let entries: Vec<ArcEntry> = ArcReader::read_borrowed(&archive)
+        .unwrap()
+        .into_iter()
+        .map(ArcEntry::from)
+        .map(ArcEntry::stripped)
+        .collect();
+    assert_eq!(entries, self::entries());
+}
+
+#[test]
+fn test_hard_link_unsupported() {
+    for format in [
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::Cpio,
+        ArcFormat::Ar,
+    ] {
+        let mut writer = ArcWriter::new(format);
+        writer.push(ArcEntry::hard_link("link", "owo"));
+        assert!(
+            matches!(writer.archive(), Err(ArcError::IoError(_))),
+            "{format:?}"
+        );
+    }
+}
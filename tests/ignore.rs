@@ -0,0 +1,75 @@
+#![cfg(feature = "ignore")]
+
+use cra::*;
+use std::fs;
+
+fn tree() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("uwu");
+    fs::create_dir_all(root.join("target/debug")).unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+    fs::write(root.join("src/.ignore"), "*.tmp\n!keep.log\n").unwrap();
+    for file in [
+        "target/debug/owo",
+        "build.log",
+        "src/lib.rs",
+        "src/scratch.tmp",
+        "src/keep.log",
+        "src/lib.o",
+    ] {
+        fs::write(root.join(file), "twoja stara\n").unwrap();
+    }
+    dir
+}
+
+fn names(writer: &ArcWriter) -> Vec<String> {
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    reader.map(|entry| entry.name).collect()
+}
+
+#[test]
+fn test_ignore_files() {
+    let dir = tree();
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.add_ignore_files = true;
+    writer.add_path(dir.path().join("uwu"), dir.path()).unwrap();
+    assert_eq!(
+        names(&writer),
+        [
+            "uwu/",
+            "uwu/.gitignore",
+            "uwu/src/",
+            "uwu/src/.ignore",
+            "uwu/src/keep.log",
+            "uwu/src/lib.o",
+            "uwu/src/lib.rs",
+        ]
+    );
+}
+
+#[test]
+fn test_excludes() {
+    let dir = tree();
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.add_excludes = vec!["*.o".into(), "/uwu/target".into(), ".*".into()];
+    writer.add_path(dir.path().join("uwu"), dir.path()).unwrap();
+    assert_eq!(
+        names(&writer),
+        [
+            "uwu/",
+            "uwu/build.log",
+            "uwu/src/",
+            "uwu/src/keep.log",
+            "uwu/src/lib.rs",
+            "uwu/src/scratch.tmp",
+        ]
+    );
+
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.add_excludes = vec!["[uwu".into()];
+    assert!(matches!(
+        writer.add_path(dir.path().join("uwu"), dir.path()),
+        Err(ArcError::InvalidPattern(_))
+    ));
+}
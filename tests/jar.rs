@@ -0,0 +1,72 @@
+use cra::*;
+
+const MANIFEST: &str = "Manifest-Version: 1.0\r\n\
+Main-Class: uwu.Owo\r\n\
+Class-Path: a-rather-long-dependency-name-1.0.jar another-long-dependen\r\n cy-2.0.jar\r\n\
+\r\n\
+Name: uwu/Owo.class\r\n\
+Sealed: true\r\n\
+\r\n";
+
+#[test]
+fn test_jar_writer() {
+    let entries = vec![
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/Owo.class", "twoja stara\n"),
+        ArcEntry::file("META-INF/MANIFEST.MF", MANIFEST),
+    ];
+    let mut writer = ArcWriter::new(ArcFormat::Jar);
+    writer.extend(&entries);
+    let archive = writer.archive().unwrap();
+    // the manifest is the first entry and stored uncompressed
+    assert_eq!(&archive[30..50], b"META-INF/MANIFEST.MF");
+    assert_eq!(archive[8..10], [0, 0]);
+
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Jar);
+    assert_eq!(
+        reader
+            .entries()
+            .iter()
+            .cloned()
+            .map(ArcEntry::stripped)
+            .collect::<Vec<_>>(),
+        vec![entries[2].clone(), entries[0].clone(), entries[1].clone()]
+    );
+    let manifest = reader.jar_manifest().unwrap();
+    assert_eq!(manifest.get("main-class"), Some("uwu.Owo"));
+    assert_eq!(
+        manifest.get("Class-Path"),
+        Some("a-rather-long-dependency-name-1.0.jar another-long-dependency-2.0.jar")
+    );
+    assert_eq!(manifest.get_for("uwu/Owo.class", "Sealed"), Some("true"));
+}
+
+#[test]
+fn test_jar_default_manifest() {
+    let mut writer = ArcWriter::new(ArcFormat::Jar);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Jar);
+    assert_eq!(
+        reader.jar_manifest().unwrap().get("Manifest-Version"),
+        Some("1.0")
+    );
+}
+
+#[test]
+fn test_jar_invalid_manifest() {
+    for manifest in [
+        "Manifest-Version 1.0\r\n",
+        "Manifest-Version: 1.0",
+        "Manifest-Version: 1.0\r\n\r\nSealed: true\r\n",
+        " continued\r\n",
+    ] {
+        let mut writer = ArcWriter::new(ArcFormat::Jar);
+        writer.push(ArcEntry::file("META-INF/MANIFEST.MF", manifest));
+        assert!(matches!(
+            writer.archive(),
+            Err(ArcError::InvalidManifest(_))
+        ));
+    }
+}
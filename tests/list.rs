@@ -0,0 +1,52 @@
+use cra::*;
+
+#[test]
+fn test_list_zip() {
+    let data = "twoja stara ".repeat(100);
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("uwu/owo", data.clone()));
+    writer.push(ArcEntry::file("hmmm", data.clone()).with_method(Method::Stored));
+    let list = ArcReader::list(&writer.archive().unwrap()).unwrap();
+    assert_eq!(list.len(), 3);
+    assert!(list[0].directory);
+    assert_eq!(list[1].name, "uwu/owo");
+    assert_eq!(list[1].size, data.len() as u64);
+    assert_eq!(list[1].method, Some(Method::Deflated));
+    assert!(list[1].ratio().unwrap() < 0.1);
+    assert_eq!(list[2].method, Some(Method::Stored));
+    assert_eq!(list[2].compressed_size, Some(data.len() as u64));
+    assert_eq!(list[2].ratio(), Some(1.0));
+}
+
+#[test]
+fn test_list_7z() {
+    let data = "twoja stara ".repeat(100);
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("uwu/owo", data.clone()));
+    writer.push(ArcEntry::file("hmmm", "owo"));
+    let list = ArcReader::list(&writer.archive().unwrap()).unwrap();
+    let owo = list.iter().find(|info| info.name == "uwu/owo").unwrap();
+    assert_eq!(owo.size, data.len() as u64);
+    assert!(owo.compressed_size.unwrap() < owo.size);
+    assert_eq!(owo.method, Some(Method::Lzma2));
+    assert!(list
+        .iter()
+        .any(|info| info.name == "uwu/" && info.directory));
+}
+
+#[test]
+fn test_list_tar() {
+    for archive in [
+        &include_bytes!("test.tar")[..],
+        include_bytes!("test.tar.gz"),
+        include_bytes!("test.a"),
+    ] {
+        let list = ArcReader::list(archive).unwrap();
+        let hmmm = list.iter().find(|info| info.name == "hmmm").unwrap();
+        assert_eq!(hmmm.size, 12);
+        assert_eq!(hmmm.compressed_size, None);
+        assert_eq!(hmmm.method, None);
+    }
+}
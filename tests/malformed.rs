@@ -0,0 +1,104 @@
+use cra::*;
+
+const ARCHIVES: &[&[u8]] = &[
+    include_bytes!("test.7z"),
+    include_bytes!("test.a"),
+    include_bytes!("test.cab"),
+    include_bytes!("test.cpio"),
+    include_bytes!("test.crx"),
+    include_bytes!("test.deb"),
+    include_bytes!("test.gz"),
+    include_bytes!("test.rpm"),
+    include_bytes!("test.tar"),
+    include_bytes!("test.tar.gz"),
+    include_bytes!("test.tar.xz"),
+    include_bytes!("test.warc"),
+    include_bytes!("test.wim"),
+    include_bytes!("test.xar"),
+    include_bytes!("test.zip"),
+    include_bytes!("test_sparse_pax.tar"),
+];
+
+/// Returns copies of `archive` cut short and with single bytes flipped
+fn corrupted(archive: &[u8]) -> Vec<Vec<u8>> {
+    let step = (archive.len() / 64).max(1);
+    let mut corrupted = Vec::new();
+    for i in (0..archive.len()).step_by(step) {
+        corrupted.push(archive[..i].to_vec());
+        let mut flipped = archive.to_vec();
+        flipped[i] ^= 0xff;
+        corrupted.push(flipped);
+    }
+    corrupted
+}
+
+#[test]
+fn test_malformed_archives_dont_panic() {
+    for archive in ARCHIVES {
+        for buf in corrupted(archive) {
+            let _ = ArcReader::new(&buf);
+            let _ = ArcReader::read_borrowed(&buf);
+            let _ = ArcReader::list(&buf);
+            let _ = ArcReader::metas(&buf);
+            let _ = ArcReader::from_reader(&buf[..]);
+            let _ = ArcReader::from_seekable(std::io::Cursor::new(&buf), &ReadOptions::new());
+            let _ = ArcReader::read_entry(&buf, "hmmm");
+            let _ = ArcReader::recover(&buf);
+            let _ = ArcReader::for_each_chunk(&buf, 4096, |_| true, |_, _| Ok(()));
+            let _ = ArcReader::from_reader_spilled(&buf[..], 1024);
+            let (first, last) = buf.split_at(buf.len() / 2);
+            let _ = ArcReader::from_volumes(&[first, last], &ReadOptions::new());
+            let _ = ArcReader::with_zip_name_encoding(&buf, ZipNameEncoding::Utf8OrCp437);
+            let _ = ArcReader::wim_image_count(&buf);
+            let _ = ArcReader::wim_image(&buf, 1);
+            let _ = ArcReader::crx_header(&buf);
+            let _ = ArcReader::warc_headers(&buf);
+            if let Ok(stream) = ArcStreamReader::new(&buf[..]) {
+                stream.for_each(drop);
+            }
+        }
+    }
+}
+
+/// Returns a zip of `entries` files whose headers claim 256 MiB of data
+/// each
+fn forged_sizes(entries: usize, stored: bool) -> Vec<u8> {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.zip_stored = stored;
+    for i in 0..entries {
+        writer.push(ArcEntry::file(format!("uwu{i}"), "owo".repeat(100)));
+    }
+    let mut archive = writer.archive().unwrap();
+    let forged = (256u32 << 20).to_le_bytes();
+    for (signature, offset) in [(b"PK\x03\x04", 22), (b"PK\x01\x02", 24)] {
+        let starts: Vec<_> = archive
+            .windows(4)
+            .enumerate()
+            .filter(|(_, window)| window == signature)
+            .map(|(start, _)| start)
+            .collect();
+        for start in starts {
+            archive[start + offset..start + offset + 4].copy_from_slice(&forged);
+        }
+    }
+    archive
+}
+
+#[test]
+fn test_forged_sizes_dont_keep_allocations() {
+    for stored in [true, false] {
+        let archive = forged_sizes(64, stored);
+        let reader = ArcReader::new(&archive).unwrap();
+        let capacity: usize = reader.entries().iter().map(|e| e.data.capacity()).sum();
+        assert!(capacity < 64 * 1024, "{capacity}");
+        assert_eq!(
+            ArcReader::read_entry(&archive, "uwu0").unwrap().capacity(),
+            300
+        );
+        let reader = ArcReader::from_reader(&archive[..]).unwrap();
+        assert!(reader
+            .entries()
+            .iter()
+            .all(|entry| entry.data.capacity() == 300));
+    }
+}
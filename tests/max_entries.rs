@@ -0,0 +1,39 @@
+use cra::*;
+
+fn archive(format: ArcFormat, count: usize) -> Vec<u8> {
+    let mut writer = ArcWriter::new(format);
+    for i in 0..count {
+        writer.push(ArcEntry::file(format!("{i}"), "uwu"));
+    }
+    writer.archive().unwrap()
+}
+
+#[test]
+fn test_max_entries() {
+    for format in [
+        ArcFormat::Tar,
+        ArcFormat::TarGz,
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::Cpio,
+    ] {
+        let archive = archive(format, 10);
+        let reader = ArcReader::with_max_entries(&archive, 10).unwrap();
+        assert_eq!(reader.count(), 10, "{format:?}");
+        assert!(
+            matches!(
+                ArcReader::with_max_entries(&archive, 9),
+                Err(ArcError::TooManyEntries(9))
+            ),
+            "{format:?}"
+        );
+    }
+}
+
+#[test]
+fn test_max_entries_error() {
+    let Err(err) = ArcReader::with_max_entries(&archive(ArcFormat::Tar, 3), 2) else {
+        panic!("read an archive over the limit");
+    };
+    assert_eq!(err.to_string(), "Archive has more than 2 entries");
+}
@@ -0,0 +1,79 @@
+use cra::*;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn test_read_meta() {
+    let reader = ArcReader::new(include_bytes!("test.tar")).unwrap();
+    for entry in reader {
+        let expected = if entry.is_dir() { 0o755 } else { 0o644 };
+        assert_eq!(entry.meta.mode, Some(expected), "{}", entry.name);
+        assert!(entry.meta.mtime.is_some(), "{}", entry.name);
+        assert!(entry.meta.owner.is_some(), "{}", entry.name);
+    }
+}
+
+#[test]
+fn test_write_meta() {
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    let owner = Owner {
+        uid: 1000,
+        gid: 100,
+        user: Some("uwu".into()),
+        group: Some("users".into()),
+    };
+    for format in [ArcFormat::Tar, ArcFormat::Zip, ArcFormat::Sevenz] {
+        let mut writer = ArcWriter::new(format);
+        writer.push(
+            ArcEntry::file("run", "#!/bin/sh\n")
+                .with_mode(0o755)
+                .with_mtime(mtime)
+                .with_owner(owner.clone())
+                .with_xattr("user.comment", "hi"),
+        );
+        let entry = ArcReader::new(&writer.archive().unwrap())
+            .unwrap()
+            .next()
+            .unwrap();
+        assert_eq!(entry.meta.mode, Some(0o755), "{format:?}");
+        assert_eq!(entry.meta.mtime, Some(mtime), "{format:?}");
+        if format == ArcFormat::Tar {
+            assert_eq!(entry.meta.owner, Some(owner.clone()));
+            assert_eq!(entry.meta.xattrs["user.comment"], b"hi");
+        }
+    }
+}
+
+#[test]
+fn test_access_and_creation_times() {
+    let atime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let ctime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+    let writers = [ArcFormat::Tar, ArcFormat::Zip, ArcFormat::Sevenz].map(ArcWriter::new);
+    let mut pax = ArcWriter::new(ArcFormat::TarGz);
+    pax.tar_format = TarFormat::Pax;
+    for mut writer in writers.into_iter().chain([pax]) {
+        writer.push(
+            ArcEntry::file("hmmm", "twoja stara\n")
+                .with_atime(atime)
+                .with_ctime(ctime),
+        );
+        writer.push(ArcEntry::file("uwu", "owo"));
+        let archive = writer.archive().unwrap();
+        let entries: Vec<_> = ArcReader::new(&archive).unwrap().collect();
+        let format = writer.format;
+        assert_eq!(entries[0].meta.atime, Some(atime), "{format:?}");
+        assert_eq!(entries[0].meta.ctime, Some(ctime), "{format:?}");
+        assert_eq!(entries[1].meta.atime, None, "{format:?}");
+        assert_eq!(entries[1].meta.ctime, None, "{format:?}");
+    }
+}
+
+#[test]
+fn test_pax_times_to_the_nanosecond() {
+    let atime = SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 500_000_000);
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.tar_format = TarFormat::Pax;
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n").with_atime(atime));
+    let archive = writer.archive().unwrap();
+    let mut reader = ArcStreamReader::new(&archive[..]).unwrap();
+    assert_eq!(reader.next_entry().unwrap().unwrap().atime(), Some(atime));
+}
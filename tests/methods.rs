@@ -0,0 +1,119 @@
+use cra::*;
+
+/// Returns the methods of the files of `archive`, in order
+fn methods(archive: &[u8]) -> Vec<(String, Option<Method>)> {
+    ArcReader::list(archive)
+        .unwrap()
+        .into_iter()
+        .filter(|info| !info.directory)
+        .map(|info| (info.name, info.method))
+        .collect()
+}
+
+#[test]
+fn test_read_methods() {
+    let methods = methods(include_bytes!("test.zip"));
+    assert_eq!(methods.len(), 2);
+    assert!(methods.iter().all(|(_, method)| method.is_some()));
+    assert!(ArcReader::new(include_bytes!("test.zip"))
+        .unwrap()
+        .entries()
+        .iter()
+        .filter(|entry| !entry.is_dir())
+        .all(|entry| entry.meta.method.is_some()));
+    for entry in ArcReader::new(include_bytes!("test.tar")).unwrap() {
+        assert_eq!(entry.meta.method, None);
+    }
+}
+
+#[test]
+fn test_read_duplicate_methods() {
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.push(ArcEntry::file("a", "uwu").with_method(Method::Lzma));
+    writer.push(ArcEntry::file("a", "uwu").with_method(Method::Lzma2));
+    let archive = writer.archive().unwrap();
+    assert_eq!(
+        methods(&archive),
+        [
+            ("a".into(), Some(Method::Lzma)),
+            ("a".into(), Some(Method::Lzma2)),
+        ]
+    );
+    let metas = ArcReader::metas(&archive).unwrap();
+    assert_eq!(metas[0].1.method, Some(Method::Lzma));
+    assert_eq!(metas[1].1.method, Some(Method::Lzma2));
+}
+
+#[test]
+fn test_write_zip_methods() {
+    let data = "twoja stara ".repeat(100);
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("stored", data.clone()));
+    for method in [Method::Deflated, Method::Bzip2, Method::Zstd, Method::Xz] {
+        writer.push(ArcEntry::file(method.to_string(), data.clone()).with_method(method));
+    }
+    writer.push(ArcEntry::file("lzma", data.clone()).with_method(Method::Lzma));
+    writer.zip_stored = true;
+    let archive = writer.archive().unwrap();
+    assert_eq!(
+        methods(&archive),
+        [
+            ("stored".into(), Some(Method::Stored)),
+            ("Deflated".into(), Some(Method::Deflated)),
+            ("Bzip2".into(), Some(Method::Bzip2)),
+            ("Zstd".into(), Some(Method::Zstd)),
+            ("Xz".into(), Some(Method::Xz)),
+            // zip archives can't be written with it
+            ("lzma".into(), Some(Method::Stored)),
+        ]
+    );
+    for entry in ArcReader::new(&archive).unwrap() {
+        assert_eq!(entry.data, data.as_bytes(), "{}", entry.name);
+    }
+}
+
+#[test]
+fn test_write_sevenz_methods() {
+    let data = "twoja stara ".repeat(100);
+    for solid in [false, true] {
+        let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+        writer.push(ArcEntry::file("lzma", data.clone()).with_method(Method::Lzma));
+        writer.push(ArcEntry::file("lzma2", data.clone()));
+        writer.sevenz_solid = solid;
+        let archive = writer.archive().unwrap();
+        assert_eq!(
+            methods(&archive),
+            [
+                ("lzma".into(), Some(Method::Lzma)),
+                ("lzma2".into(), Some(Method::Lzma2)),
+            ],
+            "solid: {solid}"
+        );
+        for entry in ArcReader::new(&archive).unwrap() {
+            assert_eq!(entry.data, data.as_bytes(), "{}", entry.name);
+            assert_eq!(
+                entry.meta.method.map(|method| method.to_string()),
+                Some(entry.name.to_uppercase())
+            );
+        }
+    }
+}
+
+#[test]
+fn test_methods_round_trip() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("stored", "uwu").with_method(Method::Stored));
+    writer.push(ArcEntry::file("zstd", "uwu").with_method(Method::Zstd));
+    let archive = writer.archive().unwrap();
+
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.extend(ArcReader::new(&archive).unwrap().entries());
+    let archive = writer.archive().unwrap();
+    assert_eq!(
+        methods(&archive),
+        [
+            ("stored".into(), Some(Method::Stored)),
+            ("zstd".into(), Some(Method::Zstd)),
+        ]
+    );
+}
@@ -0,0 +1,58 @@
+use cra::*;
+use std::collections::HashMap;
+
+/// Returns the modes of the entries of `archive` that store them, by name
+fn modes(archive: &[u8]) -> HashMap<String, u32> {
+    ArcReader::metas(archive)
+        .unwrap()
+        .into_iter()
+        .filter_map(|(name, meta)| Some((name, meta.mode?)))
+        .collect()
+}
+
+#[test]
+fn test_read_modes() {
+    let modes = HashMap::from([
+        ("hmmm".to_owned(), 0o644),
+        ("uwu/".to_owned(), 0o755),
+        ("uwu/owo".to_owned(), 0o644),
+    ]);
+    assert_eq!(self::modes(include_bytes!("test.tar")), modes);
+    assert_eq!(self::modes(include_bytes!("test.zip")), modes);
+    assert_eq!(self::modes(include_bytes!("test.tar.xz")), modes);
+    assert!(self::modes(include_bytes!("test.gz")).is_empty());
+}
+
+#[test]
+fn test_write_modes() {
+    for format in [
+        ArcFormat::Tar,
+        ArcFormat::TarGz,
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::Cpio,
+    ] {
+        let mut writer = ArcWriter::new(format);
+        writer.push(ArcEntry::directory("bin/").with_mode(0o750));
+        writer.push(ArcEntry::file("bin/run", "#!/bin/sh\n").with_mode(0o755));
+        writer.push(ArcEntry::file("secret", "uwu").with_mode(0o600));
+        let modes = modes(&writer.archive().unwrap());
+        // 7z stores directory names without the trailing slash
+        let directory = modes.get("bin/").or(modes.get("bin"));
+        assert_eq!(directory, Some(&0o750), "{format:?}");
+        assert_eq!(modes.get("bin/run"), Some(&0o755), "{format:?}");
+        assert_eq!(modes.get("secret"), Some(&0o600), "{format:?}");
+    }
+}
+
+#[test]
+fn test_write_modes_ar() {
+    let mut writer = ArcWriter::new(ArcFormat::Ar);
+    writer.push(ArcEntry::file("run", "#!/bin/sh\n").with_mode(0o755));
+    writer.push(ArcEntry::file("data", "uwu"));
+    let modes = modes(&writer.archive().unwrap());
+    assert_eq!(
+        modes,
+        HashMap::from([("run".to_owned(), 0o755), ("data".to_owned(), 0o644)])
+    );
+}
@@ -0,0 +1,56 @@
+use cra::*;
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Returns the modification times of the entries of `archive` that store
+/// them, by name
+fn mtimes(archive: &[u8]) -> HashMap<String, SystemTime> {
+    ArcReader::metas(archive)
+        .unwrap()
+        .into_iter()
+        .filter_map(|(name, meta)| Some((name, meta.mtime?)))
+        .collect()
+}
+
+#[test]
+fn test_read_mtimes() {
+    // 2024-01-30 10:22:xx, the zip stores local time to 2 seconds
+    for archive in [&include_bytes!("test.tar")[..], include_bytes!("test.zip")] {
+        let mtimes = mtimes(archive);
+        assert_eq!(mtimes.len(), 3);
+        let secs = mtimes["hmmm"].duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert!((1_706_600_000..1_706_700_000).contains(&secs), "{secs}");
+    }
+    assert!(mtimes(include_bytes!("test.zst")).is_empty());
+}
+
+#[test]
+fn test_write_mtimes() {
+    let time = UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    for format in [
+        ArcFormat::Tar,
+        ArcFormat::TarZst,
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::Cpio,
+        ArcFormat::Ar,
+    ] {
+        let mut writer = ArcWriter::new(format);
+        writer.push(ArcEntry::file("old", "uwu").with_mtime(time));
+        writer.push(ArcEntry::file("new", "owo"));
+        let before = SystemTime::now() - Duration::from_secs(2);
+        let mtimes = mtimes(&writer.archive().unwrap());
+        assert_eq!(mtimes["old"], time, "{format:?}");
+        assert!(mtimes["new"] >= before, "{format:?}");
+    }
+}
+
+#[test]
+fn test_zip_mtime_out_of_range() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("ancient", "uwu").with_mtime(UNIX_EPOCH));
+    let mtimes = mtimes(&writer.archive().unwrap());
+    assert!(mtimes["ancient"] > UNIX_EPOCH);
+}
@@ -0,0 +1,69 @@
+use cra::*;
+
+/// "zażółć" in ISO 8859-2
+const LATIN2: &[u8] = b"za\xbf\xf3\xb3\xe6";
+
+fn tar() -> Vec<u8> {
+    let mut builder = tar::Builder::new(Vec::new());
+    for name in [LATIN2, b"uwu"] {
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name);
+        header.set_size(3);
+        header.set_mode(0o644);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_mtime(0);
+        header.set_cksum();
+        builder.append(&header, &b"owo"[..]).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+fn ar() -> Vec<u8> {
+    let mut builder = ar::Builder::new(Vec::new());
+    for name in [LATIN2, b"uwu"] {
+        let header = ar::Header::new(name.to_vec(), 3);
+        builder.append(&header, &b"owo"[..]).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+#[test]
+fn test_non_utf8_lossy() {
+    for archive in [tar(), ar()] {
+        let entries = ArcReader::new(&archive).unwrap().entries().clone();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, String::from_utf8_lossy(LATIN2));
+        assert_eq!(entries[0].meta.raw_name.as_deref(), Some(LATIN2));
+        assert_eq!(entries[0].data, b"owo");
+        assert_eq!(entries[1].name, "uwu");
+        assert_eq!(entries[1].meta.raw_name, None);
+    }
+}
+
+#[test]
+fn test_non_utf8_policies() {
+    for archive in [tar(), ar()] {
+        let reader = ArcReader::with_non_utf8_policy(&archive, NonUtf8Policy::Skip).unwrap();
+        let names: Vec<_> = reader.map(|entry| entry.name).collect();
+        assert_eq!(names, ["uwu"]);
+        assert!(matches!(
+            ArcReader::with_non_utf8_policy(&archive, NonUtf8Policy::Reject),
+            Err(ArcError::NonUtf8Name(name)) if name.starts_with("za")
+        ));
+    }
+}
+
+#[test]
+fn test_non_utf8_stream() {
+    let archive = tar();
+    let mut reader = ArcStreamReader::new(&archive[..]).unwrap();
+    let entry = reader.next_entry().unwrap().unwrap();
+    assert_eq!(entry.raw_name(), Some(LATIN2));
+    assert!(entry.name().starts_with("za"));
+    let entry = reader.next_entry().unwrap().unwrap();
+    assert_eq!(entry.raw_name(), None);
+
+    let entries = ArcReader::from_reader(&archive[..]).unwrap();
+    assert_eq!(entries.entries()[0].meta.raw_name.as_deref(), Some(LATIN2));
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "object_store")]
+
+use cra::*;
+use object_store::{memory::InMemory, path::Path, ObjectStore, ObjectStoreExt};
+use std::sync::Arc;
+
+async fn store(archive: &[u8]) -> (Arc<dyn ObjectStore>, Path) {
+    let store = InMemory::new();
+    let location = Path::from("archives/test");
+    store.put(&location, archive.to_vec().into()).await.unwrap();
+    (Arc::new(store), location)
+}
+
+#[tokio::test]
+async fn test_object_store_reader() {
+    let (store, location) = store(include_bytes!("test.tar.gz")).await;
+    let reader = ArcReader::from_object_store(store.as_ref(), &location)
+        .await
+        .unwrap();
+    assert_eq!(reader.format(), ArcFormat::TarGz);
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu/"),
+            ArcEntry::file("uwu/owo", ""),
+        ]
+    );
+
+    assert!(matches!(
+        ArcReader::from_object_store(store.as_ref(), &Path::from("nope")).await,
+        Err(ArcError::ObjectStoreError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_object_store_zip() {
+    let (store, location) = store(include_bytes!("test.zip")).await;
+    let reader = ObjectStoreZipReader::new(store, location).await.unwrap();
+    assert_eq!(reader.names(), vec!["uwu/", "uwu/owo", "hmmm"]);
+    assert_eq!(reader.read_entry("hmmm").await.unwrap(), b"twoja stara\n");
+    assert_eq!(reader.read_entry("uwu/owo").await.unwrap(), b"");
+    assert!(matches!(
+        reader.read_entry("nope").await,
+        Err(ArcError::EntryNotFound(_))
+    ));
+}
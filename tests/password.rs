@@ -0,0 +1,284 @@
+use cra::*;
+
+const ENCRYPTED: &[&[u8]] = &[
+    include_bytes!("test_zipcrypto.zip"),
+    include_bytes!("test_aes.zip"),
+];
+
+#[test]
+fn test_read_with_password() {
+    for archive in ENCRYPTED {
+        let reader = ArcReader::with_password(archive, b"uwu").unwrap();
+        let entries = reader.entries();
+        assert_eq!(entries.len(), 3);
+        let hmmm = entries.iter().find(|entry| entry.name == "hmmm").unwrap();
+        assert_eq!(hmmm.data, b"twoja stara\n");
+    }
+}
+
+#[test]
+fn test_wrong_password() {
+    for archive in ENCRYPTED {
+        assert!(matches!(
+            ArcReader::with_password(archive, b"owo"),
+            Err(ArcError::WrongPassword)
+        ));
+    }
+}
+
+#[test]
+fn test_password_required() {
+    for archive in ENCRYPTED {
+        assert!(matches!(
+            ArcReader::new(archive),
+            Err(ArcError::PasswordRequired)
+        ));
+    }
+}
+
+#[test]
+fn test_password_ignored_for_plain_archives() {
+    let reader = ArcReader::with_password(include_bytes!("test.zip"), b"uwu").unwrap();
+    assert_eq!(reader.entries().len(), 3);
+}
+
+#[test]
+fn test_write_aes() {
+    let data = "twoja stara ".repeat(100);
+    for format in [ArcFormat::Zip, ArcFormat::Jar] {
+        let mut writer = ArcWriter::new(format);
+        writer.zip_password = Some("uwu".into());
+        // so the data would show unless encrypted
+        writer.zip_stored = true;
+        writer.push(ArcEntry::directory("uwu/"));
+        writer.push(ArcEntry::file("uwu/owo", ""));
+        writer.push(ArcEntry::file("hmmm", data.clone()));
+        let archive = writer.archive().unwrap();
+        assert_eq!(find(&archive, data.as_bytes()), None);
+
+        let reader = ArcReader::with_password(&archive, b"uwu").unwrap();
+        assert_eq!(reader.format(), format);
+        let hmmm = reader.entries().iter().find(|entry| entry.name == "hmmm");
+        assert_eq!(hmmm.unwrap().data, data.as_bytes());
+        assert!(matches!(
+            ArcReader::with_password(&archive, b"owo"),
+            Err(ArcError::WrongPassword)
+        ));
+        assert!(matches!(
+            ArcReader::new(&archive),
+            Err(ArcError::PasswordRequired)
+        ));
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[test]
+fn test_sevenz_password() {
+    for (solid, encrypt_header) in [(false, false), (true, false), (false, true)] {
+        let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+        writer.sevenz_password = Some("uwu".into());
+        writer.sevenz_solid = solid;
+        writer.sevenz_encrypt_header = encrypt_header;
+        writer.push(ArcEntry::directory("uwu/"));
+        writer.push(ArcEntry::file("uwu/owo", ""));
+        writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+        writer.push(ArcEntry::file("aaa", "a".repeat(10000)));
+        let archive = writer.archive().unwrap();
+
+        let reader = ArcReader::with_password(&archive, b"uwu").unwrap();
+        let data = |name| {
+            let entry = reader.entries().iter().find(|entry| entry.name == name);
+            entry.unwrap().data.clone()
+        };
+        assert_eq!(reader.entries().len(), 4);
+        assert_eq!(data("hmmm"), b"twoja stara\n");
+        assert_eq!(data("aaa"), "a".repeat(10000).as_bytes());
+        assert!(matches!(
+            ArcReader::with_password(&archive, b"owo"),
+            Err(ArcError::WrongPassword)
+        ));
+        assert!(matches!(
+            ArcReader::new(&archive),
+            Err(ArcError::PasswordRequired)
+        ));
+        // names can only be listed without the password if the header is
+        // left unencrypted
+        assert_eq!(ArcReader::list(&archive).is_ok(), !encrypt_header);
+    }
+}
+
+#[test]
+fn test_secret() {
+    let secret = Secret::from(String::from("uwu"));
+    assert_eq!(secret.expose(), "uwu");
+    assert_eq!(format!("{secret:?}"), "Secret(..)");
+    assert!(!format!("{:?}", Some(secret.clone())).contains("uwu"));
+
+    let reader = ArcReader::with_password(include_bytes!("test_aes.zip"), &secret).unwrap();
+    assert_eq!(reader.entries().len(), 3);
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.sevenz_password = Some(secret.clone());
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let reader = ArcReader::with_password(&writer.archive().unwrap(), secret).unwrap();
+    assert_eq!(reader.entries()[0].data, b"twoja stara\n");
+}
+
+#[test]
+fn test_read_options_combined() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.zip_password = Some("uwu".into());
+    writer.path_policy = PathPolicy::Allow;
+    writer.push(ArcEntry::file("../hmmm", "twoja stara"));
+    let archive = writer.archive().unwrap();
+
+    let options = ReadOptions::new()
+        .password(b"uwu")
+        .path_policy(PathPolicy::Sanitize);
+    let reader = ArcReader::with_options(&archive, &options).unwrap();
+    assert_eq!(reader.entries()[0].name, "hmmm");
+    assert_eq!(reader.entries()[0].data, b"twoja stara");
+    assert!(ArcReader::with_options(&archive, &ReadOptions::new().password(b"uwu")).is_err());
+}
+
+#[test]
+fn test_read_seekable_with_password() {
+    let options = ReadOptions::new().password(b"uwu");
+    for archive in ENCRYPTED {
+        let reader = ArcReader::from_seekable(std::io::Cursor::new(archive), &options).unwrap();
+        assert_eq!(reader.entries().len(), 3);
+        assert!(matches!(
+            ArcReader::from_seekable(std::io::Cursor::new(archive), &ReadOptions::new()),
+            Err(ArcError::PasswordRequired)
+        ));
+    }
+
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.sevenz_password = Some("uwu".into());
+    writer.push(ArcEntry::file("hmmm", "twoja stara"));
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::from_seekable(std::io::Cursor::new(&archive), &options).unwrap();
+    assert_eq!(reader.entries()[0].data, b"twoja stara");
+}
+
+#[test]
+fn test_read_volumes_with_password() {
+    let data: Vec<u8> = (0..200_000u32)
+        .flat_map(|i| (i * 7919).to_le_bytes())
+        .collect();
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.zip_password = Some("uwu".into());
+    writer.zip_stored = true;
+    writer.zip_volume_size = 64 * 1024;
+    writer.push(ArcEntry::file("hmmm", data.clone()));
+    let volumes = writer.archive_volumes().unwrap();
+    assert!(volumes.len() > 1);
+    let volumes: Vec<_> = volumes.iter().map(Vec::as_slice).collect();
+    let options = ReadOptions::new().password(b"uwu");
+    let reader = ArcReader::from_volumes(&volumes, &options).unwrap();
+    assert_eq!(reader.entries()[0].data, data);
+}
+
+#[test]
+fn test_read_entry_with_password() {
+    let options = ReadOptions::new().password(b"uwu");
+    for archive in ENCRYPTED {
+        assert_eq!(
+            ArcReader::read_entry_with(archive, "hmmm", &options).unwrap(),
+            b"twoja stara\n"
+        );
+        assert!(ArcReader::read_entry(archive, "hmmm").is_err());
+
+        let dest = tempfile::NamedTempFile::new().unwrap();
+        let len = ArcReader::extract_entry_with(archive, "hmmm", dest.path(), &options).unwrap();
+        assert_eq!(len, 12);
+        assert_eq!(std::fs::read(dest.path()).unwrap(), b"twoja stara\n");
+    }
+
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.sevenz_password = Some("uwu".into());
+    writer.push(ArcEntry::file("hmmm", "twoja stara"));
+    let archive = writer.archive().unwrap();
+    assert_eq!(
+        ArcReader::read_entry_with(&archive, "hmmm", &options).unwrap(),
+        b"twoja stara"
+    );
+}
+
+#[test]
+fn test_read_entry_with_policies() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.path_policy = PathPolicy::Allow;
+    writer.push(ArcEntry::file("../hmmm", "twoja stara"));
+    let archive = writer.archive().unwrap();
+    let options = ReadOptions::new().path_policy(PathPolicy::Sanitize);
+    assert_eq!(
+        ArcReader::read_entry_with(&archive, "hmmm", &options).unwrap(),
+        b"twoja stara"
+    );
+    assert!(matches!(
+        ArcReader::read_entry(&archive, "hmmm"),
+        Err(ArcError::EntryNotFound(_))
+    ));
+}
+
+#[test]
+fn test_read_borrowed_with_options() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.path_policy = PathPolicy::Allow;
+    writer.push(ArcEntry::file("C:\\hmmm", "twoja stara"));
+    writer.push(ArcEntry::file("uwu", "owo"));
+    let archive = writer.archive().unwrap();
+    assert!(matches!(
+        ArcReader::read_borrowed(&archive),
+        Err(ArcError::UnsafePath(_))
+    ));
+    let options = ReadOptions::new().path_policy(PathPolicy::Sanitize);
+    let entries = ArcReader::read_borrowed_with(&archive, &options).unwrap();
+    assert_eq!(entries[0].name, "hmmm");
+    assert!(matches!(
+        entries[0].data,
+        std::borrow::Cow::Borrowed(b"twoja stara")
+    ));
+    assert!(matches!(
+        ArcReader::read_borrowed_with(&archive, &options.max_entries(1)),
+        Err(ArcError::TooManyEntries(1))
+    ));
+
+    // encrypted data can't be borrowed
+    let options = ReadOptions::new().password(b"uwu");
+    let entries = ArcReader::read_borrowed_with(include_bytes!("test_aes.zip"), &options).unwrap();
+    let hmmm = entries.iter().find(|entry| entry.name == "hmmm").unwrap();
+    assert_eq!(hmmm.data, &b"twoja stara\n"[..]);
+}
+
+#[test]
+fn test_from_reader_with_options() {
+    let options = ReadOptions::new().password(b"uwu");
+    let reader =
+        ArcReader::from_reader_with(&include_bytes!("test_aes.zip")[..], &options).unwrap();
+    assert_eq!(reader.entries().len(), 3);
+    assert!(ArcReader::from_reader(&include_bytes!("test_aes.zip")[..]).is_err());
+
+    let options = ReadOptions::new().max_entries(1);
+    assert!(matches!(
+        ArcReader::from_reader_with(&include_bytes!("test.tar")[..], &options),
+        Err(ArcError::TooManyEntries(1))
+    ));
+}
+
+#[test]
+fn test_recover_with_password() {
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.sevenz_password = Some("uwu".into());
+    writer.push(ArcEntry::file("hmmm", "twoja stara"));
+    let archive = writer.archive().unwrap();
+    let (reader, err) = ArcReader::recover_with(&archive, &ReadOptions::new().password(b"uwu"));
+    assert!(err.is_none());
+    assert_eq!(reader.entries()[0].data, b"twoja stara");
+    assert!(ArcReader::recover(&archive).1.is_some());
+}
@@ -0,0 +1,108 @@
+use cra::*;
+
+fn hostile_zip() -> Vec<u8> {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("uwu/owo", "twoja stara\n"));
+    writer.push(ArcEntry::file("../../etc/passwd", "root::0:0::/:/bin/sh\n"));
+    writer.push(ArcEntry::file("/etc/hosts", "127.0.0.1 uwu\n"));
+    writer.push(ArcEntry::file("C:\\Windows\\win.ini", "[owo]\n"));
+    writer.push(ArcEntry::directory("../"));
+    writer.archive().unwrap()
+}
+
+#[test]
+fn test_safe_paths() {
+    assert!(is_safe_path("uwu/owo"));
+    assert!(is_safe_path("./uwu/"));
+    assert!(is_safe_path("uwu/..owo"));
+    assert!(!is_safe_path("uwu/../../owo"));
+    assert!(!is_safe_path(".."));
+    assert!(!is_safe_path("/etc/passwd"));
+    assert!(!is_safe_path("C:owo"));
+    assert!(!is_safe_path("uwu\\owo"));
+}
+
+#[test]
+fn test_sanitize_path() {
+    assert_eq!(sanitize_path("uwu/owo"), "uwu/owo");
+    assert_eq!(sanitize_path("../../etc/passwd"), "etc/passwd");
+    assert_eq!(sanitize_path("/etc//hosts"), "etc/hosts");
+    assert_eq!(sanitize_path("C:\\Windows\\win.ini"), "Windows/win.ini");
+    assert_eq!(sanitize_path("./uwu/../"), "uwu/");
+    assert_eq!(sanitize_path("../"), "");
+}
+
+#[test]
+fn test_reject_unsafe_paths() {
+    let archive = hostile_zip();
+    assert!(matches!(
+        ArcReader::new(&archive),
+        Err(ArcError::UnsafePath(name)) if name == "../../etc/passwd"
+    ));
+    assert!(matches!(
+        ArcReader::from_seekable(std::io::Cursor::new(&archive), &ReadOptions::new()),
+        Err(ArcError::UnsafePath(_))
+    ));
+    assert!(matches!(
+        ArcReader::read_borrowed(&archive),
+        Err(ArcError::UnsafePath(_))
+    ));
+}
+
+#[test]
+fn test_sanitize_unsafe_paths() {
+    let reader = ArcReader::with_path_policy(&hostile_zip(), PathPolicy::Sanitize).unwrap();
+    let names: Vec<_> = reader.map(|entry| entry.name).collect();
+    assert_eq!(
+        names,
+        ["uwu/owo", "etc/passwd", "etc/hosts", "Windows/win.ini"]
+    );
+}
+
+#[test]
+fn test_allow_unsafe_paths() {
+    let reader = ArcReader::with_path_policy(&hostile_zip(), PathPolicy::Allow).unwrap();
+    assert_eq!(reader.count(), 5);
+}
+
+#[test]
+fn test_write_path_policies() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.path_policy = PathPolicy::Reject;
+    writer.push(ArcEntry::file("uwu/owo", "twoja stara\n"));
+    writer.push(ArcEntry::file("/etc/hosts", "127.0.0.1 uwu\n"));
+    assert!(matches!(
+        writer.archive(),
+        Err(ArcError::UnsafePath(name)) if name == "/etc/hosts"
+    ));
+
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.path_policy = PathPolicy::Sanitize;
+    writer.push(ArcEntry::file("../../etc/passwd", "root::0:0::/:/bin/sh\n"));
+    writer.push(ArcEntry::hard_link("C:\\passwd", "../../etc/passwd"));
+    writer.push(ArcEntry::directory("../"));
+    writer.push_from_reader("/etc/hosts", &b"127.0.0.1 uwu\n"[..], 14);
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    let entries = reader.entries();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].name, "etc/passwd");
+    assert_eq!(entries[1].name, "passwd");
+    assert_eq!(
+        entries[1].meta.kind,
+        EntryKind::HardLink("etc/passwd".into())
+    );
+    assert_eq!(entries[2].name, "etc/hosts");
+    assert_eq!(entries[2].data, b"127.0.0.1 uwu\n");
+}
+
+#[test]
+fn test_stream_write_path_policy() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.path_policy = PathPolicy::Reject;
+    let mut stream = writer.into_stream(Vec::new()).unwrap();
+    stream.push(ArcEntry::file("uwu", "owo")).unwrap();
+    assert!(matches!(
+        stream.push(ArcEntry::file("../uwu", "owo")),
+        Err(ArcError::UnsafePath(_))
+    ));
+}
@@ -0,0 +1,35 @@
+use cra::*;
+
+#[test]
+fn test_glob() {
+    let glob = |glob| Pattern::glob(glob).unwrap();
+    assert!(glob("*.md").matches("README.md"));
+    assert!(!glob("*.md").matches("docs/README.md"));
+    assert!(glob("docs/**/*.md").matches("docs/README.md"));
+    assert!(glob("docs/**/*.md").matches("docs/a/b/c.md"));
+    assert!(!glob("docs/**/*.md").matches("docs.md"));
+    assert!(glob("docs/**").matches("docs/a/b"));
+    assert!(glob("docs").matches("docs/"));
+    assert!(glob("uw?").matches("uwu"));
+    assert!(!glob("uw?").matches("uw/"));
+    assert!(glob("[a-c]wu").matches("bwu"));
+    assert!(glob("[!a-c]wu").matches("uwu"));
+    assert!(!glob("[!a-c]wu").matches("awu"));
+    assert!(glob("(uwu)+.txt").matches("(uwu)+.txt"));
+    assert!(!glob("(uwu)+.txt").matches("uwuuwu.txt"));
+    assert!(matches!(
+        Pattern::glob("[uwu"),
+        Err(ArcError::InvalidPattern(_))
+    ));
+}
+
+#[test]
+fn test_regex() {
+    let pattern = Pattern::regex(r"\.(md|txt)$").unwrap();
+    assert!(pattern.matches("docs/README.md"));
+    assert!(!pattern.matches("src/lib.rs"));
+    assert!(matches!(
+        Pattern::regex("(uwu"),
+        Err(ArcError::InvalidPattern(_))
+    ));
+}
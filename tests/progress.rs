@@ -0,0 +1,111 @@
+use cra::*;
+use std::sync::{Arc, Mutex};
+
+fn entries() -> Vec<ArcEntry> {
+    vec![
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", "twoja stara ".repeat(10_000)),
+        ArcEntry::file("hmmm", "twoj stary\n"),
+    ]
+}
+
+fn archive(format: ArcFormat) -> Vec<u8> {
+    let mut writer = ArcWriter::new(format);
+    writer.extend(&entries());
+    writer.archive().unwrap()
+}
+
+#[test]
+fn test_read_progress() {
+    for format in [
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::Tar,
+        ArcFormat::TarGz,
+        ArcFormat::TarXz,
+        ArcFormat::TarZst,
+        ArcFormat::Cpio,
+    ] {
+        let updates = Mutex::new(Vec::new());
+        let reader = ArcReader::with_progress(&archive(format), |progress| {
+            let update = (progress.name.to_owned(), progress.entries, progress.bytes);
+            updates.lock().unwrap().push(update);
+        })
+        .unwrap();
+        assert_eq!(reader.entries().len(), 3);
+
+        let updates = updates.into_inner().unwrap();
+        assert!(updates
+            .windows(2)
+            .all(|pair| pair[0].1 <= pair[1].1 && pair[0].2 <= pair[1].2));
+        assert_eq!(updates.last().unwrap().1, 3);
+        assert_eq!(updates.last().unwrap().2, 120_011);
+        // data is reported as it's decoded, before the file is done
+        let streamed = updates
+            .iter()
+            .any(|(name, entries, bytes)| name == "uwu/owo" && *entries == 1 && *bytes > 0);
+        assert_eq!(streamed, format != ArcFormat::Cpio, "{format:?}");
+    }
+}
+
+#[test]
+fn test_extract_progress() {
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let recorded = updates.clone();
+    let options = ExtractOptions {
+        progress: Some(Box::new(move |progress: &Progress| {
+            assert_eq!(progress.total_entries, Some(3));
+            assert_eq!(progress.total_bytes, Some(120_011));
+            let update = (progress.name.to_owned(), progress.entries, progress.bytes);
+            recorded.lock().unwrap().push(update);
+        })),
+        ..ExtractOptions::default()
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let reader = ArcReader::new(&archive(ArcFormat::Tar)).unwrap();
+    reader.extract_with(dir.path(), &options).unwrap();
+
+    let updates = updates.lock().unwrap();
+    // the big file is written in a couple of chunks
+    assert!(updates.len() > 4);
+    assert!(updates
+        .windows(2)
+        .all(|pair| pair[0].1 <= pair[1].1 && pair[0].2 <= pair[1].2));
+    assert_eq!(updates.last().unwrap(), &("hmmm".into(), 3, 120_011));
+}
+
+#[test]
+fn test_write_progress() {
+    for format in [
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::Tar,
+        ArcFormat::TarGz,
+        ArcFormat::Cpio,
+        ArcFormat::Ar,
+    ] {
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let recorded = updates.clone();
+        let mut writer = ArcWriter::new(format);
+        writer.progress = Some(Box::new(move |progress: &Progress| {
+            assert_eq!(progress.total_entries, Some(2));
+            assert_eq!(progress.total_bytes, Some(120_011));
+            recorded
+                .lock()
+                .unwrap()
+                .push((progress.entries, progress.bytes));
+        }));
+        if format == ArcFormat::Ar {
+            writer.push(ArcEntry::file("uwu", "twoja stara ".repeat(10_000)));
+        } else {
+            writer.extend(&entries()[..2]);
+        }
+        writer.push_from_reader("hmmm", &b"twoj stary\n"[..], 11);
+        writer.archive().unwrap();
+
+        let updates = updates.lock().unwrap();
+        assert!(updates.len() > 2, "{format:?}");
+        assert!(updates.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(updates.last(), Some(&(2, 120_011)), "{format:?}");
+    }
+}
@@ -0,0 +1,22 @@
+#![cfg(feature = "rar")]
+
+use cra::*;
+
+#[test]
+fn test_rar_reader() {
+    let reader = ArcReader::new(include_bytes!("test.rar"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Rar);
+    assert_eq!(
+        reader.entries(),
+        &vec![ArcEntry::file(".gitignore", "target\nCargo.lock\n")]
+    );
+}
+
+#[test]
+fn test_rar_writer() {
+    let mut writer = ArcWriter::new(ArcFormat::Rar);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    assert!(matches!(writer.archive(), Err(ArcError::ReadOnly)));
+}
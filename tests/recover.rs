@@ -0,0 +1,146 @@
+use cra::*;
+use flate2::{write::DeflateEncoder, Compression, Crc};
+use std::io::Write;
+
+fn entries() -> Vec<ArcEntry> {
+    vec![
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", "twoja stara ".repeat(50)),
+        ArcEntry::file("hmmm", "twoj stary\n"),
+        ArcEntry::file("big", noise(300_000)),
+    ]
+}
+
+/// Returns `len` bytes that don't compress
+fn noise(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn archive(format: ArcFormat) -> Vec<u8> {
+    let mut writer = ArcWriter::new(format);
+    writer.extend(&entries());
+    writer.archive().unwrap()
+}
+
+/// Returns where `needle` first shows up in `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> usize {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .unwrap()
+}
+
+fn names(reader: &ArcReader) -> Vec<&str> {
+    reader
+        .entries()
+        .iter()
+        .map(|entry| entry.name.as_str())
+        .collect()
+}
+
+#[test]
+fn test_recover_intact() {
+    for format in [ArcFormat::Zip, ArcFormat::Tar, ArcFormat::Sevenz] {
+        let (reader, err) = ArcReader::recover(&archive(format));
+        assert!(err.is_none(), "{format:?}");
+        assert_eq!(reader.entries().len(), 4);
+    }
+}
+
+#[test]
+fn test_recover_truncated_zip() {
+    for stored in [true, false] {
+        let mut writer = ArcWriter::new(ArcFormat::Zip);
+        writer.zip_stored = stored;
+        writer.extend(&entries());
+        let archive = writer.archive().unwrap();
+        let cut = &archive[..find(&archive, b"big") + 100];
+        assert!(ArcReader::new(cut).is_err());
+
+        let (reader, err) = ArcReader::recover(cut);
+        assert_eq!(reader.format(), ArcFormat::Zip);
+        assert_eq!(names(&reader), ["uwu/", "uwu/owo", "hmmm"]);
+        assert_eq!(reader.entries()[1], entries()[1].clone());
+        assert_eq!(reader.entries()[2].data, b"twoj stary\n");
+        assert!(err.is_some());
+    }
+}
+
+#[test]
+fn test_recover_zip_with_descriptors() {
+    let mut archive = Vec::new();
+    for (name, data) in [("uwu", "twoja stara ".repeat(50)), ("owo", "hmmm".into())] {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut crc = Crc::new();
+        crc.update(data.as_bytes());
+
+        archive.extend(b"PK\x03\x04\x14\x00\x08\x00\x08\x00");
+        archive.extend([0; 16]);
+        archive.extend((name.len() as u16).to_le_bytes());
+        archive.extend([0; 2]);
+        archive.extend(name.as_bytes());
+        archive.extend(&compressed);
+        archive.extend(b"PK\x07\x08");
+        archive.extend(crc.sum().to_le_bytes());
+        archive.extend((compressed.len() as u32).to_le_bytes());
+        archive.extend((data.len() as u32).to_le_bytes());
+    }
+
+    let (reader, err) = ArcReader::recover(&archive);
+    assert!(err.is_some());
+    assert_eq!(names(&reader), ["uwu", "owo"]);
+    assert_eq!(
+        reader.entries()[0].data,
+        "twoja stara ".repeat(50).as_bytes()
+    );
+    assert_eq!(reader.entries()[1].data, b"hmmm");
+
+    // a corrupted checksum stops at that entry
+    let crc = find(&archive, b"PK\x07\x08") + 4;
+    archive[crc] ^= 0xff;
+    let (reader, err) = ArcReader::recover(&archive);
+    assert!(reader.entries().is_empty());
+    assert!(matches!(err, Some(ArcError::ChecksumMismatch(name)) if name == "uwu"));
+}
+
+#[test]
+fn test_recover_truncated_tarballs() {
+    for format in [ArcFormat::Tar, ArcFormat::TarGz, ArcFormat::TarZst] {
+        let archive = archive(format);
+        let cut = &archive[..archive.len() / 2];
+        assert!(ArcReader::new(cut).is_err());
+
+        let (reader, err) = ArcReader::recover(cut);
+        assert_eq!(reader.format(), format);
+        assert_eq!(names(&reader), ["uwu/", "uwu/owo", "hmmm"], "{format:?}");
+        assert_eq!(reader.entries()[2].data, b"twoj stary\n");
+        assert!(err.is_some());
+    }
+}
+
+#[test]
+fn test_recover_nothing() {
+    let archive = archive(ArcFormat::Sevenz);
+    let (reader, err) = ArcReader::recover(&archive[..archive.len() / 2]);
+    assert_eq!(reader.format(), ArcFormat::Sevenz);
+    assert!(reader.entries().is_empty());
+    assert!(err.is_some());
+
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.push(ArcEntry::symlink("etc", "/etc"));
+    writer.push(ArcEntry::file("etc/passwd", "root::0:0::/:/bin/sh\n"));
+    let archive = writer.archive().unwrap();
+    let (reader, err) = ArcReader::recover(&archive[..archive.len() - 512]);
+    assert!(reader.entries().is_empty());
+    assert!(matches!(err, Some(ArcError::SymlinkEscape(_))));
+}
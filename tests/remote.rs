@@ -0,0 +1,122 @@
+use cra::*;
+use std::{cell::Cell, io};
+
+struct Fetcher {
+    archive: Vec<u8>,
+    fetched: Cell<u64>,
+}
+
+impl RangeFetcher for Fetcher {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.archive.len() as u64)
+    }
+
+    fn fetch(&self, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        self.fetched.set(self.fetched.get() + len);
+        Ok(self.archive[offset as usize..(offset + len) as usize].to_vec())
+    }
+}
+
+#[test]
+fn test_remote_zip() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.zip_stored = true;
+    writer.push(ArcEntry::directory("uwu/"));
+    for i in 0..20 {
+        writer.push(ArcEntry::file(format!("uwu/{i}"), vec![i; 100_000]));
+    }
+    let archive = writer.archive().unwrap();
+
+    let reader = open(archive);
+    let names = reader.names();
+    assert_eq!(names.len(), 21);
+    assert_eq!(names[0], "uwu/");
+    assert_eq!(names[20], "uwu/19");
+    assert_eq!(reader.read_entry("uwu/7").unwrap(), vec![7; 100_000]);
+    assert!(matches!(
+        reader.read_entry("uwu/"),
+        Err(ArcError::EntryNotFound(_))
+    ));
+    assert!(matches!(
+        reader.read_entry("nope"),
+        Err(ArcError::EntryNotFound(_))
+    ));
+
+    let fetcher = reader.into_inner();
+    assert!(fetcher.fetched.get() < fetcher.archive.len() as u64 / 5);
+}
+
+fn open(archive: Vec<u8>) -> RemoteZipReader<Fetcher> {
+    RemoteZipReader::new(Fetcher {
+        archive,
+        fetched: Cell::new(0),
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_remote_zip_deflated() {
+    let reader = open(include_bytes!("test.zip").to_vec());
+    assert_eq!(reader.names(), vec!["uwu/", "uwu/owo", "hmmm"]);
+    assert_eq!(reader.read_entry("hmmm").unwrap(), b"twoja stara\n");
+    assert_eq!(reader.read_entry("uwu/owo").unwrap(), b"");
+
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", "a".repeat(10000)));
+    let reader = open(writer.archive().unwrap());
+    assert_eq!(
+        reader.read_entry("hmmm").unwrap(),
+        "a".repeat(10000).as_bytes()
+    );
+}
+
+#[test]
+fn test_remote_zip_checksum_mismatch() {
+    let mut archive = include_bytes!("test.zip").to_vec();
+    let start = archive
+        .windows(11)
+        .position(|window| window == b"twoja stara")
+        .unwrap();
+    archive[start] ^= 0x01;
+    let reader = open(archive);
+    assert!(matches!(
+        reader.read_entry("hmmm"),
+        Err(ArcError::ChecksumMismatch(name)) if name == "hmmm"
+    ));
+}
+
+#[test]
+fn test_remote_zip_prefixed() {
+    let mut archive = b"some stub in front".to_vec();
+    archive.extend_from_slice(include_bytes!("test.zip"));
+    let reader = open(archive);
+    assert_eq!(reader.read_entry("hmmm").unwrap(), b"twoja stara\n");
+}
+
+#[test]
+fn test_remote_zip64() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    for i in 0..=u16::MAX as u32 + 1 {
+        writer.push(ArcEntry::file(i.to_string(), i.to_string()));
+    }
+    let reader = open(writer.archive().unwrap());
+    assert_eq!(reader.names().len(), u16::MAX as usize + 2);
+    assert_eq!(reader.read_entry("65536").unwrap(), b"65536");
+}
+
+#[test]
+fn test_remote_zip_understated_size() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", "a".repeat(100_000)));
+    let mut archive = writer.archive().unwrap();
+    let central = archive
+        .windows(4)
+        .position(|window| window == b"PK\x01\x02")
+        .unwrap();
+    archive[central + 24..central + 28].copy_from_slice(&10u32.to_le_bytes());
+    let reader = open(archive);
+    assert!(matches!(
+        reader.read_entry("hmmm"),
+        Err(ArcError::IoError(err)) if err.kind() == io::ErrorKind::InvalidData
+    ));
+}
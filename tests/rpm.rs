@@ -0,0 +1,28 @@
+use cra::*;
+
+#[test]
+fn test_rpm_reader() {
+    let reader = ArcReader::new(include_bytes!("test.rpm"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Rpm);
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::directory("./usr/share/uwu"),
+            ArcEntry::file("./usr/share/uwu/hmmm", "twoja stara\n"),
+        ]
+    );
+}
+
+#[test]
+fn test_rpm_truncated() {
+    let buf = include_bytes!("test.rpm");
+    assert!(ArcReader::new(&buf[..120]).is_err());
+}
+
+#[test]
+fn test_rpm_writer() {
+    let writer = ArcWriter::new(ArcFormat::Rpm);
+    assert!(matches!(writer.archive(), Err(ArcError::ReadOnly)));
+}
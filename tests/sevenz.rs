@@ -1,4 +1,5 @@
 use cra::*;
+use std::io::Cursor;
 
 #[test]
 fn test_7z_reader() {
@@ -7,21 +8,180 @@ fn test_7z_reader() {
     let reader = reader.unwrap();
     assert_eq!(reader.format(), ArcFormat::Sevenz);
     assert_eq!(
-        reader.entries(),
-        &vec![
-            ArcEntry::File("hmmm".into(), "twoja stara\n".into()),
-            ArcEntry::Directory("uwu".into()),
-            ArcEntry::File("uwu/owo".into(), vec![]),
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu"),
+            ArcEntry::file("uwu/owo", ""),
         ]
     )
 }
 
+#[test]
+fn test_7z_from_seekable() {
+    let mut source = Cursor::new(include_bytes!("test.7z"));
+    source.set_position(10);
+    let reader = ArcReader::from_seekable(source, &ReadOptions::new()).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Sevenz);
+    assert_eq!(
+        reader.entries(),
+        ArcReader::new(include_bytes!("test.7z")).unwrap().entries()
+    );
+}
+
+#[test]
+fn test_7z_read_entry() {
+    let buf = include_bytes!("test.7z");
+    assert_eq!(
+        ArcReader::read_entry(buf, "hmmm").unwrap(),
+        b"twoja stara\n"
+    );
+    assert_eq!(ArcReader::read_entry(buf, "uwu/owo").unwrap(), b"");
+    for name in ["uwu", "nope"] {
+        assert!(matches!(
+            ArcReader::read_entry(buf, name),
+            Err(ArcError::EntryNotFound(_))
+        ));
+    }
+}
+
+#[test]
+fn test_7z_read_entry_solid() {
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.sevenz_solid = true;
+    for name in ["a", "b", "c"] {
+        writer.push(ArcEntry::file(name, name.repeat(1000)));
+    }
+    let archive = writer.archive().unwrap();
+    for name in ["a", "b", "c"] {
+        assert_eq!(
+            ArcReader::read_entry(&archive, name).unwrap(),
+            name.repeat(1000).as_bytes()
+        );
+    }
+}
+
 #[test]
 fn test_7z_writer() {
     // TODO write an actual test instead of just testing whether it runs at all
     let mut writer = ArcWriter::new(ArcFormat::Sevenz);
-    writer.push(ArcEntry::File("hmmm".into(), "twoja stara\n".into()));
-    writer.push(ArcEntry::Directory("uwu".into()));
-    writer.push(ArcEntry::File("uwu/owo".into(), vec![]));
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.push(ArcEntry::directory("uwu"));
+    writer.push(ArcEntry::file("uwu/owo", ""));
     writer.archive().unwrap();
 }
+
+#[test]
+fn test_7z_writer_codec() {
+    let entries = vec![
+        ArcEntry::file("hmmm", "twoja stara\n".repeat(1000)),
+        ArcEntry::directory("uwu"),
+    ];
+    for codec in [SevenzCodec::Lzma, SevenzCodec::Lzma2] {
+        let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+        writer.sevenz_codec = codec;
+        writer.sevenz_level = 9;
+        writer.sevenz_dict_size = 1 << 16;
+        writer.extend(&entries);
+        let archive = writer.archive().unwrap();
+        let reader = ArcReader::new(&archive).unwrap();
+        assert_eq!(reader.format(), ArcFormat::Sevenz);
+        assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), entries);
+    }
+}
+
+#[test]
+fn test_7z_writer_solid() {
+    // incompressible on its own, but repeated across files
+    let mut seed = 1u32;
+    let data: Vec<u8> = (0..4096)
+        .map(|_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as u8
+        })
+        .collect();
+    let file = |name: &str| ArcEntry::file(name, data.clone());
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.extend(&[file("a.txt"), file("b.bin"), file("c.txt"), file("d.bin")]);
+    let mut archive = |configure: fn(&mut ArcWriter)| {
+        configure(&mut writer);
+        let archive = writer.archive().unwrap();
+        (archive.len(), ArcReader::new(&archive).unwrap())
+    };
+
+    let (separate, reader) = archive(|_| {});
+    assert_eq!(reader.entries().len(), 4);
+    let (solid, reader) = archive(|writer| writer.sevenz_solid = true);
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![file("a.txt"), file("b.bin"), file("c.txt"), file("d.bin")]
+    );
+    let (two_files, _) = archive(|writer| writer.sevenz_solid_files = 2);
+    let (by_extension, reader) = archive(|writer| {
+        writer.sevenz_solid_files = 0;
+        writer.sevenz_solid_by_extension = true;
+    });
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![file("a.txt"), file("c.txt"), file("b.bin"), file("d.bin")]
+    );
+    let (small, _) = archive(|writer| {
+        writer.sevenz_solid_by_extension = false;
+        writer.sevenz_solid_size = 4096;
+    });
+
+    assert!(solid < 2 * data.len());
+    assert!(two_files > 2 * data.len() && two_files < 3 * data.len());
+    assert!(by_extension > 2 * data.len() && by_extension < 3 * data.len());
+    assert!(small > 4 * data.len());
+    assert!(separate > 4 * data.len());
+}
+
+#[test]
+fn test_7z_push_from_reader() {
+    for solid in [false, true] {
+        let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+        writer.sevenz_solid = solid;
+        writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+        writer.push_from_reader("uwu", &b"twoja stara\n"[..], 12);
+        let archive = writer.archive().unwrap();
+        let reader = ArcReader::new(&archive).unwrap();
+        assert_eq!(
+            reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+            vec![
+                ArcEntry::file("hmmm", "twoja stara\n"),
+                ArcEntry::file("uwu", "twoja stara\n"),
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_7z_volumes() {
+    let volumes: Vec<_> = include_bytes!("test.7z").chunks(64).collect();
+    assert!(volumes.len() > 1);
+    let reader = ArcReader::from_volumes(&volumes, &ReadOptions::new()).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Sevenz);
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu"),
+            ArcEntry::file("uwu/owo", ""),
+        ]
+    )
+}
+
+#[test]
+fn test_7z_sfx() {
+    let stub = b"MZ not really an executable";
+    let mut writer = ArcWriter::new(ArcFormat::Sevenz);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let archive = writer.archive_sfx(stub).unwrap();
+    assert!(archive.starts_with(stub));
+    let reader = ArcReader::new(&archive[stub.len()..]).unwrap();
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![ArcEntry::file("hmmm", "twoja stara\n")]
+    );
+}
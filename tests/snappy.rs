@@ -0,0 +1,28 @@
+use cra::*;
+
+#[test]
+fn test_snappy_reader() {
+    let reader = ArcReader::new(include_bytes!("test.sz"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Snappy);
+    assert_eq!(reader.entries(), &vec![ArcEntry::file("", "twoja stara\n")]);
+}
+
+#[test]
+fn test_snappy_writer() {
+    let mut writer = ArcWriter::new(ArcFormat::Snappy);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Snappy);
+    assert_eq!(reader.entries(), &vec![ArcEntry::file("", "twoja stara\n")]);
+}
+
+#[test]
+fn test_snappy_writer_single_file_only() {
+    let mut writer = ArcWriter::new(ArcFormat::Snappy);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.push(ArcEntry::file("uwu", ""));
+    assert!(matches!(writer.archive(), Err(ArcError::SingleFileOnly)));
+}
@@ -0,0 +1,104 @@
+use cra::*;
+
+/// Data of the sparse file in the fixtures, written by GNU tar
+fn sparse_data() -> Vec<u8> {
+    let mut data = vec![0; 65536];
+    data[16384..16389].copy_from_slice(b"hello");
+    data[40000..40005].copy_from_slice(b"world");
+    data
+}
+
+#[test]
+fn test_sparse_read() {
+    for buf in [
+        &include_bytes!("test_sparse_gnu.tar")[..],
+        include_bytes!("test_sparse_pax.tar"),
+        include_bytes!("test_sparse_pax01.tar"),
+    ] {
+        let expected = vec![ArcEntry::file("sparse.img", sparse_data())];
+        assert_eq!(
+            ArcReader::new(buf)
+                .unwrap()
+                .map(ArcEntry::stripped)
+                .collect::<Vec<_>>(),
+            expected
+        );
+        let borrowed: Vec<ArcEntry> = ArcReader::read_borrowed(buf)
+            .unwrap()
+            .into_iter()
+            .map(ArcEntry::from)
+            .map(ArcEntry::stripped)
+            .collect();
+        assert_eq!(borrowed, expected);
+
+        let mut reader = ArcStreamReader::new(buf).unwrap();
+        let entry = reader.next_entry().unwrap().unwrap();
+        assert_eq!(entry.name(), "sparse.img");
+        assert_eq!(entry.size(), 65536);
+        let regions = entry.sparse_regions().unwrap();
+        assert!(regions
+            .iter()
+            .any(|&(offset, len)| offset <= 16384 && len > 0));
+        assert_eq!(regions.last(), Some(&(65536, 0)));
+    }
+}
+
+#[test]
+fn test_sparse_round_trip() {
+    // enough regions for old GNU extension blocks
+    let mut data = vec![0; 100 * 1024];
+    for i in 0..50 {
+        data[i * 2048 + 100] = i as u8 + 1;
+    }
+    let entries = vec![
+        ArcEntry::file("uwu/disk.img", data),
+        ArcEntry::file("sparse.img", sparse_data()),
+        ArcEntry::file("zeros", vec![0; 4096]),
+        ArcEntry::file("hmmm", "twoja stara\n"),
+    ];
+    for tar_format in [TarFormat::Gnu, TarFormat::Pax] {
+        for format in [ArcFormat::Tar, ArcFormat::TarGz] {
+            let mut writer = ArcWriter::new(format);
+            writer.tar_format = tar_format;
+            writer.tar_sparse = true;
+            writer.extend(&entries);
+            let archive = writer.archive().unwrap();
+            let reader = ArcReader::new(&archive).unwrap();
+            assert_eq!(
+                reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+                entries,
+                "{format:?} {tar_format:?}"
+            );
+        }
+    }
+
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.tar_sparse = true;
+    writer.extend(&entries);
+    let archive = writer.archive().unwrap();
+    assert!(archive.len() < 40 * 1024);
+    let mut reader = ArcStreamReader::new(&archive[..]).unwrap();
+    let mut regions = Vec::new();
+    while let Some(entry) = reader.next_entry().unwrap() {
+        regions.push(entry.sparse_regions().map(<[_]>::len));
+    }
+    assert_eq!(regions, [Some(51), Some(3), Some(1), None]);
+}
+
+#[test]
+fn test_sparse_stream_writer() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.tar_format = TarFormat::Pax;
+    writer.tar_sparse = true;
+    let mut stream = writer.into_stream(Vec::new()).unwrap();
+    stream
+        .push(ArcEntry::file("sparse.img", sparse_data()))
+        .unwrap();
+    let archive = stream.finish().unwrap();
+    assert!(archive.len() < 10 * 1024);
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        [ArcEntry::file("sparse.img", sparse_data())]
+    );
+}
@@ -0,0 +1,97 @@
+use cra::*;
+
+fn entries() -> Vec<ArcEntry> {
+    vec![
+        ArcEntry::directory("dev/"),
+        ArcEntry::special("dev/null", SpecialFile::CharDevice(1, 3)),
+        ArcEntry::special("dev/sda", SpecialFile::BlockDevice(8, 0)),
+        ArcEntry::special("fifo", SpecialFile::Fifo),
+        ArcEntry::special(
+            format!("{}/tty", "dev".repeat(50)),
+            SpecialFile::CharDevice(5, 0),
+        ),
+        ArcEntry::file("hmmm", "twoja stara\n"),
+    ]
+}
+
+#[test]
+fn test_special_round_trip() {
+    for tar_format in [TarFormat::Gnu, TarFormat::Pax] {
+        for format in [ArcFormat::Tar, ArcFormat::TarGz] {
+            let mut writer = ArcWriter::new(format);
+            writer.tar_format = tar_format;
+            writer.extend(&entries());
+            let archive = writer.archive().unwrap();
+            let reader = ArcReader::new(&archive).unwrap();
+            assert_eq!(
+                reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+                entries(),
+                "{format:?} {tar_format:?}"
+            );
+            let borrowed: Vec<ArcEntry> = ArcReader::read_borrowed(&archive)
+                .unwrap()
+                .into_iter()
+                .map(ArcEntry::from)
+                .map(ArcEntry::stripped)
+                .collect();
+            assert_eq!(borrowed, entries());
+        }
+    }
+
+    let mut writer = ArcWriter::new(ArcFormat::Cpio);
+    writer.extend(&entries());
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        entries()
+    );
+}
+
+#[test]
+fn test_special_stream() {
+    let mut archive = Vec::new();
+    let mut writer = ArcStreamWriter::new(ArcFormat::Tar, &mut archive).unwrap();
+    for entry in entries() {
+        writer.push(entry).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let mut reader = ArcStreamReader::new(&archive[..]).unwrap();
+    let mut specials = Vec::new();
+    while let Some(entry) = reader.next_entry().unwrap() {
+        specials.push(entry.special());
+    }
+    assert_eq!(
+        specials[1..4],
+        [
+            Some(SpecialFile::CharDevice(1, 3)),
+            Some(SpecialFile::BlockDevice(8, 0)),
+            Some(SpecialFile::Fifo),
+        ]
+    );
+    assert_eq!(specials[5], None);
+
+    let reader = ArcStreamReader::new(&archive[..]).unwrap();
+    assert_eq!(
+        reader
+            .map(|entry| entry.unwrap().stripped())
+            .collect::<Vec<_>>(),
+        entries()
+    );
+
+    // visiting skips them
+    let spilled = ArcReader::from_reader_spilled(&archive[..], 1024).unwrap();
+    assert_eq!(spilled.len(), 2);
+}
+
+#[test]
+fn test_special_unsupported() {
+    for format in [ArcFormat::Zip, ArcFormat::Sevenz, ArcFormat::Ar] {
+        let mut writer = ArcWriter::new(format);
+        writer.push(ArcEntry::special("fifo", SpecialFile::Fifo));
+        assert!(
+            matches!(writer.archive(), Err(ArcError::IoError(_))),
+            "{format:?}"
+        );
+    }
+}
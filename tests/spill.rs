@@ -0,0 +1,89 @@
+use cra::*;
+use std::io::Read;
+
+fn data(entry: &SpilledEntry) -> (bool, Vec<u8>) {
+    let SpilledEntry::File(_, data) = entry else {
+        panic!("not a file");
+    };
+    let mut buf = Vec::new();
+    data.reader().unwrap().read_to_end(&mut buf).unwrap();
+    assert_eq!(data.len(), buf.len() as u64);
+    (data.path().is_some_and(|path| path.exists()), buf)
+}
+
+fn archive(format: ArcFormat) -> Vec<u8> {
+    let mut writer = ArcWriter::new(format);
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("small", "twoja stara\n"));
+    writer.push(ArcEntry::file("big", "a".repeat(10000)));
+    writer.archive().unwrap()
+}
+
+#[test]
+fn test_spilled_reader() {
+    for format in [
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::TarGz,
+        ArcFormat::Cpio,
+    ] {
+        let archive = archive(format);
+        for entries in [
+            ArcReader::from_reader_spilled(&archive[..], 1000).unwrap(),
+            {
+                let path = std::env::temp_dir().join(format!("cra-spill-{format:?}"));
+                std::fs::write(&path, &archive).unwrap();
+                let entries = ArcReader::open_spilled(&path, 1000).unwrap();
+                std::fs::remove_file(&path).unwrap();
+                entries
+            },
+        ] {
+            // 7z lists directories after the files with data
+            let file = |wanted: &str| {
+                entries
+                    .iter()
+                    .find(|entry| matches!(entry, SpilledEntry::File(name, _) if name == wanted))
+                    .unwrap()
+            };
+            assert_eq!(entries.len(), 3);
+            assert!(entries.iter().any(
+                |entry| matches!(entry, SpilledEntry::Directory(name) if name.starts_with("uwu"))
+            ));
+            assert_eq!(data(file("small")), (false, b"twoja stara\n".to_vec()));
+            assert_eq!(data(file("big")), (true, "a".repeat(10000).into()));
+        }
+    }
+}
+
+#[test]
+fn test_spilled_file_removed() {
+    let entries = ArcReader::from_reader_spilled(&archive(ArcFormat::Tar)[..], 1000).unwrap();
+    let path = match &entries[2] {
+        SpilledEntry::File(_, data) => data.path().unwrap().to_owned(),
+        _ => panic!("not a file"),
+    };
+    assert!(path.exists());
+    drop(entries);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_spilling_writer() {
+    for format in [ArcFormat::Zip, ArcFormat::Sevenz, ArcFormat::TarXz] {
+        let mut writer = ArcWriter::new(format);
+        writer.spill_threshold = 1000;
+        writer.push(ArcEntry::file("small", "twoja stara\n"));
+        writer.push(ArcEntry::file("big", "a".repeat(10000)));
+        // spilled files can be archived more than once
+        for _ in 0..2 {
+            let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+            assert_eq!(
+                reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+                vec![
+                    ArcEntry::file("small", "twoja stara\n"),
+                    ArcEntry::file("big", "a".repeat(10000)),
+                ]
+            );
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use cra::*;
+
+fn collect(buf: &[u8]) -> (ArcFormat, Vec<ArcEntry>) {
+    let reader = ArcStreamReader::new(buf).unwrap();
+    let format = reader.format();
+    (format, reader.collect::<ArcResult<_>>().unwrap())
+}
+
+#[test]
+fn test_stream_reader() {
+    for buf in [
+        &include_bytes!("test.tar")[..],
+        include_bytes!("test.tar.gz"),
+        include_bytes!("test.tar.xz"),
+        include_bytes!("test.tar.zst"),
+    ] {
+        let reader = ArcReader::new(buf).unwrap();
+        assert_eq!(collect(buf), (reader.format(), reader.entries().clone()));
+    }
+}
+
+#[test]
+fn test_stream_reader_long_names() {
+    let entries = vec![
+        ArcEntry::directory(format!("{}/", "uwu".repeat(50))),
+        ArcEntry::file(format!("{}/owo", "uwu".repeat(50)), "twoja stara\n"),
+    ];
+    for tar_format in [TarFormat::Gnu, TarFormat::Pax] {
+        let mut writer = ArcWriter::new(ArcFormat::TarBz2);
+        writer.tar_format = tar_format;
+        writer.extend(&entries);
+        let archive = writer.archive().unwrap();
+        let (format, read) = collect(&archive);
+        assert_eq!(format, ArcFormat::TarBz2);
+        assert_eq!(
+            read.into_iter().map(ArcEntry::stripped).collect::<Vec<_>>(),
+            entries
+        );
+    }
+}
+
+#[test]
+fn test_stream_reader_truncated() {
+    let buf = include_bytes!("test.tar");
+    let mut reader = ArcStreamReader::new(&buf[..1100]).unwrap();
+    assert!(reader.next().unwrap().is_ok());
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_stream_reader_unsupported() {
+    assert!(matches!(
+        ArcStreamReader::new(&include_bytes!("test.zip")[..]),
+        Err(ArcError::UnrecognizedFormat)
+    ));
+}
+
+#[test]
+fn test_stream_entry_reader() {
+    use std::io::Read;
+
+    let mut writer = ArcWriter::new(ArcFormat::TarGz);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n".repeat(100)));
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("uwu/owo", "twoja stara\n"));
+    let archive = writer.archive().unwrap();
+    let mut reader = ArcStreamReader::new(&archive[..]).unwrap();
+
+    // only read part of the first entry, the rest is skipped
+    let mut entry = reader.next_entry().unwrap().unwrap();
+    assert_eq!(
+        (entry.name(), entry.is_dir(), entry.size()),
+        ("hmmm", false, 1200)
+    );
+    let mut start = [0; 5];
+    entry.read_exact(&mut start).unwrap();
+    assert_eq!(&start, b"twoja");
+
+    let entry = reader.next_entry().unwrap().unwrap();
+    assert_eq!((entry.name(), entry.is_dir()), ("uwu/", true));
+
+    let mut entry = reader.next_entry().unwrap().unwrap();
+    let mut data = String::new();
+    entry.read_to_string(&mut data).unwrap();
+    assert_eq!((entry.name(), &data[..]), ("uwu/owo", "twoja stara\n"));
+
+    assert!(reader.next_entry().unwrap().is_none());
+}
+
+#[test]
+fn test_stream_writer() {
+    let entries = vec![
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", "twoja stara\n"),
+        ArcEntry::file("hmmm", "a".repeat(10000)),
+    ];
+    for format in [
+        ArcFormat::Tar,
+        ArcFormat::TarGz,
+        ArcFormat::TarXz,
+        ArcFormat::TarBz2,
+        ArcFormat::TarZst,
+        ArcFormat::TarLz4,
+        ArcFormat::TarBr,
+        ArcFormat::TarSz,
+    ] {
+        let mut writer = ArcWriter::new(format);
+        writer.push(entries[0].clone());
+        let mut writer = writer.into_stream(Vec::new()).unwrap();
+        assert_eq!(writer.format(), format);
+        writer.push(entries[1].clone()).unwrap();
+        writer
+            .push_from_reader("hmmm", std::io::repeat(b'a'), 10000)
+            .unwrap();
+        let archive = writer.finish().unwrap();
+
+        let reader = ArcReader::new(&archive).unwrap();
+        assert_eq!(reader.format(), format);
+        assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), entries);
+    }
+}
+
+#[test]
+fn test_stream_writer_incremental() {
+    use std::{cell::RefCell, io::Write, rc::Rc};
+
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let buf = Rc::new(RefCell::new(Vec::new()));
+    let mut writer = ArcStreamWriter::new(ArcFormat::Tar, Shared(buf.clone())).unwrap();
+    writer.push(ArcEntry::file("uwu", "twoja stara\n")).unwrap();
+    assert_eq!(buf.borrow().len(), 1024);
+    writer.push(ArcEntry::directory("owo/")).unwrap();
+    assert_eq!(buf.borrow().len(), 1536);
+    writer.finish().unwrap();
+
+    let reader = ArcReader::new(&buf.borrow()).unwrap();
+    assert_eq!(reader.entries().len(), 2);
+}
+
+#[test]
+fn test_stream_writer_short_reader() {
+    let mut writer = ArcStreamWriter::new(ArcFormat::TarGz, Vec::new()).unwrap();
+    assert!(writer.push_from_reader("uwu", &b"owo"[..], 4).is_err());
+}
+
+#[test]
+fn test_stream_writer_unsupported() {
+    assert!(ArcStreamWriter::new(ArcFormat::Zip, Vec::new()).is_err());
+    assert!(ArcWriter::new(ArcFormat::Gzip)
+        .into_stream(Vec::new())
+        .is_err());
+}
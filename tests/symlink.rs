@@ -0,0 +1,160 @@
+use cra::*;
+
+fn entries() -> Vec<ArcEntry> {
+    vec![
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", "twoja stara\n"),
+        ArcEntry::symlink("link", "uwu/owo"),
+        ArcEntry::symlink("long", format!("{}/owo", "uwu".repeat(50))),
+    ]
+}
+
+#[test]
+fn test_symlink_round_trip() {
+    for format in [
+        ArcFormat::Tar,
+        ArcFormat::TarGz,
+        ArcFormat::Zip,
+        ArcFormat::Sevenz,
+        ArcFormat::Cpio,
+    ] {
+        let mut writer = ArcWriter::new(format);
+        writer.extend(&entries());
+        let archive = writer.archive().unwrap();
+        let read: Vec<_> = ArcReader::new(&archive)
+            .unwrap()
+            .map(ArcEntry::stripped)
+            .collect();
+        for entry in &entries()[1..] {
+            assert!(read.contains(entry), "{format:?} {entry:?}");
+        }
+    }
+}
+
+#[test]
+fn test_symlink_pax() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.tar_format = TarFormat::Pax;
+    writer.extend(&entries());
+    let reader = ArcReader::new(&writer.archive().unwrap()).unwrap();
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        entries()
+    );
+}
+
+#[test]
+fn test_symlink_stream() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.extend(&entries());
+    let archive = writer.archive().unwrap();
+
+    let mut reader = ArcStreamReader::new(&archive[..]).unwrap();
+    let mut targets = Vec::new();
+    while let Some(entry) = reader.next_entry().unwrap() {
+        targets.push(entry.symlink_target().map(str::to_owned));
+    }
+    assert_eq!(targets[..3], [None, None, Some("uwu/owo".into())]);
+
+    let reader = ArcStreamReader::new(&archive[..]).unwrap();
+    assert_eq!(
+        reader
+            .map(|entry| entry.unwrap().stripped())
+            .collect::<Vec<_>>(),
+        entries()
+    );
+}
+
+#[test]
+fn test_symlink_read_borrowed() {
+    for format in [ArcFormat::Tar, ArcFormat::Zip] {
+        let mut writer = ArcWriter::new(format);
+        writer.zip_stored = true;
+        writer.extend(&entries());
+        let archive = writer.archive().unwrap();
+        let entries: Vec<ArcEntry> = ArcReader::read_borrowed(&archive)
+            .unwrap()
+            .into_iter()
+            .map(ArcEntry::from)
+            .map(ArcEntry::stripped)
+            .collect();
+        assert_eq!(entries, self::entries(), "{format:?}");
+    }
+}
+
+#[test]
+fn test_symlink_ar() {
+    let mut writer = ArcWriter::new(ArcFormat::Ar);
+    writer.push(ArcEntry::symlink("link", "owo"));
+    assert!(matches!(writer.archive(), Err(ArcError::IoError(_))));
+}
+
+fn archive(entries: &[ArcEntry]) -> Vec<u8> {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.extend(entries);
+    writer.archive().unwrap()
+}
+
+#[test]
+fn test_symlink_escape() {
+    for (entries, escaping) in [
+        (
+            vec![
+                ArcEntry::symlink("etc", "/etc"),
+                ArcEntry::file("etc/passwd", "root::0:0::/:/bin/sh\n"),
+            ],
+            "etc/passwd",
+        ),
+        (
+            vec![
+                ArcEntry::directory("uwu/"),
+                ArcEntry::symlink("uwu/up", ".."),
+                ArcEntry::symlink("owo", "uwu/up/.."),
+                ArcEntry::file("owo/hmmm", "twoja stara\n"),
+            ],
+            "owo/hmmm",
+        ),
+        (
+            vec![
+                ArcEntry::symlink("uwu", "owo"),
+                ArcEntry::symlink("owo", "uwu"),
+                ArcEntry::file("uwu/hmmm", "twoja stara\n"),
+            ],
+            "uwu/hmmm",
+        ),
+        (
+            vec![
+                ArcEntry::symlink("etc", "/etc"),
+                ArcEntry::hard_link("passwd", "etc/passwd"),
+            ],
+            "passwd",
+        ),
+    ] {
+        let archive = archive(&entries);
+        assert!(matches!(
+            ArcReader::new(&archive),
+            Err(ArcError::SymlinkEscape(name)) if name == escaping
+        ));
+        assert!(matches!(
+            ArcReader::read_borrowed(&archive),
+            Err(ArcError::SymlinkEscape(_))
+        ));
+        let reader = ArcReader::with_symlink_policy(&archive, SymlinkPolicy::Allow).unwrap();
+        assert_eq!(reader.entries().len(), entries.len());
+    }
+}
+
+#[test]
+fn test_symlink_inside() {
+    let archive = archive(&[
+        ArcEntry::directory("uwu/owo/"),
+        ArcEntry::symlink("uwu/up", ".."),
+        ArcEntry::symlink("link", "uwu/./owo"),
+        ArcEntry::file("link/hmmm", "twoja stara\n"),
+        ArcEntry::file("uwu/up/uwu/hmmm", "twoj stary\n"),
+        ArcEntry::file("hmmm", "twoja stara\n"),
+        ArcEntry::symlink("etc", "/etc"),
+    ]);
+    assert_eq!(ArcReader::new(&archive).unwrap().entries().len(), 7);
+    assert!(ArcReader::validate(&archive).is_valid());
+}
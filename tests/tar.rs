@@ -1,4 +1,5 @@
 use cra::*;
+use std::collections::HashMap;
 
 #[test]
 fn test_tar_reader() {
@@ -7,11 +8,11 @@ fn test_tar_reader() {
     let reader = reader.unwrap();
     assert_eq!(reader.format(), ArcFormat::Tar);
     assert_eq!(
-        reader.entries(),
-        &vec![
-            ArcEntry::File("hmmm".into(), "twoja stara\n".into()),
-            ArcEntry::Directory("uwu/".into()),
-            ArcEntry::File("uwu/owo".into(), vec![]),
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu/"),
+            ArcEntry::file("uwu/owo", ""),
         ]
     );
 }
@@ -20,8 +21,203 @@ fn test_tar_reader() {
 fn test_tar_writer() {
     // TODO write an actual test instead of just testing whether it runs at all
     let mut writer = ArcWriter::new(ArcFormat::Tar);
-    writer.push(ArcEntry::File("hmmm".into(), "twoja stara\n".into()));
-    writer.push(ArcEntry::Directory("uwu/".into()));
-    writer.push(ArcEntry::File("uwu/owo".into(), vec![]));
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("uwu/owo", ""));
     writer.archive().unwrap();
 }
+
+#[test]
+fn test_tar_pax_writer() {
+    let long = format!("{}/hmmm", "uwu".repeat(100));
+    let entries = vec![
+        ArcEntry::file("hmmm", "twoja stara\n"),
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file(long, "twoja stara\n"),
+    ];
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.tar_format = TarFormat::Pax;
+    writer.extend(&entries);
+    let archive = writer.archive().unwrap();
+    assert_eq!(&archive[257..263], b"ustar\0");
+    assert!(!archive.windows(13).any(|w| w == b"././@LongLink"));
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Tar);
+    assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), entries);
+}
+
+#[test]
+fn test_tar_push_from_reader() {
+    let data = "twoja stara\n".repeat(100);
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push_from_reader("uwu/owo", std::io::Cursor::new(data.clone()), 1200);
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![ArcEntry::directory("uwu/"), ArcEntry::file("uwu/owo", data),]
+    );
+    // the reader was used up by the first archive
+    assert!(writer.archive().is_err());
+}
+
+#[test]
+fn test_tar_push_from_short_reader() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.push_from_reader("hmmm", &b"twoja stara\n"[..], 100);
+    assert!(matches!(writer.archive(), Err(ArcError::IoError(_))));
+}
+
+#[test]
+fn test_format_from_path() {
+    for (path, format) in [
+        ("a.tar", ArcFormat::Tar),
+        ("dir/a.TAR.GZ", ArcFormat::TarGz),
+        ("a.tgz", ArcFormat::TarGz),
+        ("a.gz", ArcFormat::Gzip),
+        ("a.tar.zst", ArcFormat::TarZst),
+        ("a.zst", ArcFormat::Zstd),
+        ("a.warc.gz", ArcFormat::Warc),
+        ("libfoo.a", ArcFormat::Ar),
+    ] {
+        assert_eq!(ArcFormat::from_path(path).unwrap(), format);
+    }
+    for path in ["a.txt", "tar", ""] {
+        assert!(matches!(
+            ArcFormat::from_path(path),
+            Err(ArcError::UnrecognizedFormat)
+        ));
+    }
+}
+
+#[test]
+fn test_tar_read_borrowed() {
+    use std::borrow::Cow;
+
+    let buf = include_bytes!("test.tar");
+    let entries = ArcReader::read_borrowed(buf).unwrap();
+    assert!(entries
+        .iter()
+        .all(|entry| entry.meta.kind != EntryKind::File || matches!(entry.data, Cow::Borrowed(_))));
+    let entries: Vec<ArcEntry> = entries.into_iter().map(ArcEntry::from).collect();
+    assert_eq!(&entries, ArcReader::new(buf).unwrap().entries());
+}
+
+#[test]
+fn test_tar_buffer_size() {
+    let path = std::env::temp_dir().join("cra-buffer-size.tar");
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.buffer_size = 1;
+    writer.push_from_reader("hmmm", &b"twoja stara\n"[..], 12);
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.write_to_file(&path).unwrap();
+    let reader = ArcReader::open_buffered(&path, 7, &ReadOptions::new()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Tar);
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu/"),
+        ]
+    );
+}
+
+/// Returns the modes of the entries of `archive` that store them, by name
+fn modes(archive: &[u8]) -> HashMap<String, u32> {
+    ArcReader::metas(archive)
+        .unwrap()
+        .into_iter()
+        .filter_map(|(name, meta)| Some((name, meta.mode?)))
+        .collect()
+}
+
+/// Returns the owners of the entries of `archive` that store them, by name
+fn owners(archive: &[u8]) -> HashMap<String, Owner> {
+    ArcReader::metas(archive)
+        .unwrap()
+        .into_iter()
+        .filter_map(|(name, meta)| Some((name, meta.owner?)))
+        .collect()
+}
+
+#[test]
+fn test_tar_read_owners() {
+    let owners = owners(include_bytes!("test.tar.gz"));
+    assert_eq!(owners.len(), 3);
+    assert_eq!(owners["hmmm"].user.as_deref(), Some("ojbyczq"));
+    assert!(self::owners(include_bytes!("test.zip")).is_empty());
+}
+
+#[test]
+fn test_tar_write_owners() {
+    let root = Owner {
+        uid: 0,
+        gid: 0,
+        user: Some("root".into()),
+        group: Some("root".into()),
+    };
+    let nobody = Owner {
+        uid: 65534,
+        gid: 65534,
+        user: None,
+        group: None,
+    };
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.push(ArcEntry::directory("uwu/").with_owner(nobody.clone()));
+    writer.tar_default_owner = Some(root.clone());
+    let owners = owners(&writer.archive().unwrap());
+    assert_eq!(owners["hmmm"], root);
+    assert_eq!(owners["uwu/"], nobody);
+
+    writer.tar_numeric_owner = true;
+    let owners = self::owners(&writer.archive().unwrap());
+    assert_eq!(
+        owners["hmmm"],
+        Owner {
+            user: None,
+            group: None,
+            ..root
+        }
+    );
+}
+
+#[test]
+fn test_tar_header_defaults() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("run", "#!/bin/sh\n").with_mode(0o755));
+    writer.tar_root_defaults();
+    let archive = writer.archive().unwrap();
+    let modes = modes(&archive);
+    assert_eq!(modes["hmmm"], 0o644);
+    assert_eq!(modes["uwu/"], 0o755);
+    assert_eq!(modes["run"], 0o755);
+    let owners = owners(&archive);
+    assert!(owners.values().all(|owner| *owner == Owner::root()));
+
+    writer.tar_file_mode = 0o600;
+    writer.tar_dir_mode = 0o700;
+    writer.tar_default_owner = Some(Owner {
+        uid: 1000,
+        gid: 1000,
+        user: None,
+        group: None,
+    });
+    let archive = writer.archive().unwrap();
+    let modes = self::modes(&archive);
+    assert_eq!(modes["hmmm"], 0o600);
+    assert_eq!(modes["uwu/"], 0o700);
+    assert_eq!(self::owners(&archive)["hmmm"].uid, 1000);
+}
+
+#[test]
+fn test_tar_current_owner() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let owners = owners(&writer.archive().unwrap());
+    assert_eq!(owners["hmmm"], Owner::current());
+}
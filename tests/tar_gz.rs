@@ -0,0 +1,84 @@
+use cra::*;
+use std::io::Cursor;
+
+#[test]
+fn test_tar_gz_reader() {
+    let reader = ArcReader::new(include_bytes!("test.tar.gz"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::TarGz);
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu/"),
+            ArcEntry::file("uwu/owo", ""),
+        ]
+    );
+}
+
+#[test]
+fn test_tar_gz_from_seekable() {
+    let reader = ArcReader::from_seekable(
+        Cursor::new(include_bytes!("test.tar.gz")),
+        &ReadOptions::new(),
+    )
+    .unwrap();
+    assert_eq!(reader.format(), ArcFormat::TarGz);
+    assert_eq!(
+        reader.entries(),
+        ArcReader::new(include_bytes!("test.tar.gz"))
+            .unwrap()
+            .entries()
+    );
+}
+
+#[test]
+fn test_tar_gz_writer() {
+    let entries = vec![
+        ArcEntry::file("hmmm", "twoja stara\n"),
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", ""),
+    ];
+    let mut writer = ArcWriter::new(ArcFormat::TarGz);
+    writer.extend(&entries);
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::TarGz);
+    assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), entries);
+}
+
+#[test]
+fn test_tar_gz_from_reader() {
+    let entries: Vec<_> = (0..100)
+        .map(|i| ArcEntry::file(i.to_string(), i.to_string().repeat(1000)))
+        .collect();
+    let mut writer = ArcWriter::new(ArcFormat::TarGz);
+    writer.extend(&entries);
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::from_reader(&archive[..]).unwrap();
+    assert_eq!(reader.format(), ArcFormat::TarGz);
+    assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), entries);
+}
+
+#[test]
+fn test_tar_gz_read_entry() {
+    let buf = include_bytes!("test.tar.gz");
+    assert_eq!(
+        ArcReader::read_entry(buf, "hmmm").unwrap(),
+        b"twoja stara\n"
+    );
+}
+
+#[test]
+fn test_tar_gz_write_to_mmap() {
+    let path = std::env::temp_dir().join("cra-write-to-mmap.tar.gz");
+    let mut writer = ArcWriter::new(ArcFormat::TarGz);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.write_to_mmap(&path, 1 << 20).unwrap();
+    let reader = ArcReader::open(&path, &ReadOptions::new()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(reader.format(), ArcFormat::TarGz);
+    assert_eq!(reader.entries().len(), 2);
+}
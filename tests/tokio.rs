@@ -0,0 +1,57 @@
+#![cfg(feature = "async")]
+
+use cra::*;
+
+#[tokio::test]
+async fn test_async_reader() {
+    let reader = AsyncArcReader::new(&include_bytes!("test.tar.gz")[..])
+        .await
+        .unwrap();
+    assert_eq!(reader.format(), ArcFormat::TarGz);
+    assert_eq!(
+        reader
+            .into_inner()
+            .map(ArcEntry::stripped)
+            .collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu/"),
+            ArcEntry::file("uwu/owo", ""),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_async_writer() {
+    let entries = vec![
+        ArcEntry::file("hmmm", "twoja stara\n"),
+        ArcEntry::directory("uwu/"),
+    ];
+    let mut writer = AsyncArcWriter::new(ArcFormat::Zip);
+    writer.extend(&entries);
+    let mut archive = Vec::new();
+    writer = writer.archive_to(&mut archive).await.unwrap();
+    assert_eq!(writer.format, ArcFormat::Zip);
+    let reader = AsyncArcReader::new(&archive[..]).await.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zip);
+    assert_eq!(
+        reader
+            .into_inner()
+            .map(ArcEntry::stripped)
+            .collect::<Vec<_>>(),
+        entries
+    );
+}
+
+#[tokio::test]
+async fn test_async_writer_given_back_after_archiving() {
+    let mut writer = AsyncArcWriter::new(ArcFormat::Tar);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let mut first = Vec::new();
+    let writer = writer.archive_to(&mut first).await.unwrap();
+    assert_eq!(writer.format, ArcFormat::Tar);
+    let mut second = Vec::new();
+    writer.archive_to(&mut second).await.unwrap();
+    assert_eq!(first, second);
+    assert_eq!(ArcReader::new(&second).unwrap().entries().len(), 1);
+}
@@ -0,0 +1,81 @@
+use cra::*;
+
+#[test]
+fn test_validate_valid() {
+    for archive in [
+        &include_bytes!("test.zip")[..],
+        include_bytes!("test.tar"),
+        include_bytes!("test.7z"),
+    ] {
+        let report = ArcReader::validate(archive);
+        assert!(report.is_valid(), "{:?}", report.problems);
+        assert_eq!(report.entries, 3);
+    }
+    assert_eq!(
+        ArcReader::validate(include_bytes!("test.tar.gz")).format,
+        ArcFormat::TarGz
+    );
+}
+
+#[test]
+fn test_validate_problems() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.zip_stored = true;
+    writer.push(ArcEntry::file("hmmm", "twoja stara ".repeat(100)));
+    writer.push(ArcEntry::file("../../etc/passwd", "root::0:0::/:/bin/sh\n"));
+    writer.push(ArcEntry::file("uwu", "owo"));
+    let mut archive = writer.archive().unwrap();
+    let start = archive
+        .windows(11)
+        .position(|window| window == b"twoja stara")
+        .unwrap();
+    archive[start] ^= 0x01;
+
+    let report = ArcReader::validate(&archive);
+    assert_eq!(report.format, ArcFormat::Zip);
+    assert_eq!(report.entries, 3);
+    assert!(!report.is_valid());
+    assert!(matches!(
+        &report.problems[..],
+        [Problem::ChecksumMismatch(hmmm), Problem::UnsafePath(passwd)]
+            if hmmm == "hmmm" && passwd == "../../etc/passwd"
+    ));
+
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.push(ArcEntry::file("uwu", "owo"));
+    writer.push(ArcEntry::hard_link("owo", "../uwu"));
+    writer.push(ArcEntry::file("uwu", "owo"));
+    let report = ArcReader::validate(&writer.archive().unwrap());
+    assert!(matches!(
+        &report.problems[..],
+        [Problem::UnsafePath(target), Problem::DuplicateEntry(uwu)]
+            if target == "../uwu" && uwu == "uwu"
+    ));
+}
+
+#[test]
+fn test_validate_unreadable() {
+    let archive = include_bytes!("test.zip");
+    let report = ArcReader::validate(&archive[..archive.len() / 2]);
+    assert_eq!(report.format, ArcFormat::Zip);
+    assert_eq!(report.entries, 0);
+    assert!(matches!(&report.problems[..], [Problem::Unreadable(_)]));
+
+    let report = ArcReader::validate(include_bytes!("test_aes.zip"));
+    assert!(matches!(
+        &report.problems[..],
+        [Problem::Unreadable(ArcError::PasswordRequired)]
+    ));
+}
+
+#[test]
+fn test_validate_symlink_escape() {
+    let mut writer = ArcWriter::new(ArcFormat::Tar);
+    writer.push(ArcEntry::symlink("etc", "/etc"));
+    writer.push(ArcEntry::file("etc/passwd", "root::0:0::/:/bin/sh\n"));
+    let report = ArcReader::validate(&writer.archive().unwrap());
+    assert!(matches!(
+        &report.problems[..],
+        [Problem::SymlinkEscape(name)] if name == "etc/passwd"
+    ));
+}
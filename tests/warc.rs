@@ -0,0 +1,71 @@
+use cra::*;
+
+fn entries() -> Vec<ArcEntry> {
+    vec![
+        ArcEntry::file(
+            "<urn:uuid:00000000-0000-0000-0000-000000000001>",
+            "software: cra\r\n",
+        ),
+        ArcEntry::file(
+            "http://example.com/hmmm",
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n\r\ntwoja stara\n",
+        ),
+    ]
+}
+
+#[test]
+fn test_warc_reader() {
+    let reader = ArcReader::new(include_bytes!("test.warc"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Warc);
+    assert_eq!(reader.entries(), &entries());
+}
+
+#[test]
+fn test_warc_gz_reader() {
+    let reader = ArcReader::new(include_bytes!("test.warc.gz"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Warc);
+    assert_eq!(reader.entries(), &entries());
+}
+
+#[test]
+fn test_warc_headers() {
+    let headers = ArcReader::warc_headers(include_bytes!("test.warc")).unwrap();
+    assert_eq!(
+        headers,
+        ArcReader::warc_headers(include_bytes!("test.warc.gz")).unwrap()
+    );
+    assert_eq!(headers.len(), 2);
+    assert_eq!(
+        headers[1],
+        vec![
+            ("WARC-Type".into(), "response".into()),
+            (
+                "WARC-Record-ID".into(),
+                "<urn:uuid:00000000-0000-0000-0000-000000000002>".into()
+            ),
+            ("WARC-Date".into(), "2024-01-01T00:00:00Z".into()),
+            ("WARC-Target-URI".into(), "http://example.com/hmmm".into()),
+            (
+                "Content-Type".into(),
+                "application/http; msgtype=response".into()
+            ),
+            ("Content-Length".into(), "57".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_warc_truncated() {
+    let buf = include_bytes!("test.warc");
+    assert!(ArcReader::new(&buf[..buf.len() - 10]).is_err());
+}
+
+#[test]
+fn test_warc_writer() {
+    let writer = ArcWriter::new(ArcFormat::Warc);
+    assert!(matches!(writer.archive(), Err(ArcError::ReadOnly)));
+}
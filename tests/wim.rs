@@ -0,0 +1,65 @@
+use cra::*;
+
+#[test]
+fn test_wim_reader() {
+    let reader = ArcReader::new(include_bytes!("test.wim"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Wim);
+    assert_eq!(
+        reader.entries(),
+        &vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu"),
+            ArcEntry::file("uwu/owo", ""),
+        ]
+    );
+}
+
+#[test]
+fn test_wim_image() {
+    let buf = include_bytes!("test.wim");
+    assert_eq!(ArcReader::wim_image_count(buf).unwrap(), 2);
+    let reader = ArcReader::wim_image(buf, 2).unwrap();
+    assert_eq!(
+        reader.entries(),
+        &vec![ArcEntry::file("hmmm", "twoja stara twoja stara\n")]
+    );
+    assert!(matches!(
+        ArcReader::wim_image(buf, 3),
+        Err(ArcError::ImageNotFound(3))
+    ));
+}
+
+#[test]
+fn test_wim_image_with_options() {
+    let buf = include_bytes!("test.wim");
+    let options = ReadOptions::new().max_entries(2);
+    assert!(matches!(
+        ArcReader::wim_image_with(buf, 1, &options),
+        Err(ArcError::TooManyEntries(2))
+    ));
+    let reader = ArcReader::wim_image_with(buf, 2, &options).unwrap();
+    assert_eq!(reader.entries().len(), 1);
+}
+
+#[test]
+fn test_wim_directory_loop() {
+    let mut buf = include_bytes!("test.wim").to_vec();
+    let u64_at = |buf: &[u8], at: usize| u64::from_le_bytes(buf[at..at + 8].try_into().unwrap());
+    // the metadata of the first image is stored uncompressed at 208
+    let security = u32::from_le_bytes(buf[208..212].try_into().unwrap()) as usize;
+    let root = 208 + security.next_multiple_of(8);
+    let children = u64_at(&buf, root + 16);
+    let name: Vec<u8> = "uwu".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let dentry = buf.windows(6).position(|window| window == name).unwrap() - 102;
+    // point uwu at the list it's in
+    buf[dentry + 16..dentry + 24].copy_from_slice(&children.to_le_bytes());
+    assert!(ArcReader::wim_image(&buf, 1).is_err());
+}
+
+#[test]
+fn test_wim_writer() {
+    let writer = ArcWriter::new(ArcFormat::Wim);
+    assert!(matches!(writer.archive(), Err(ArcError::ReadOnly)));
+}
@@ -0,0 +1,30 @@
+use cra::*;
+
+#[test]
+fn test_xar_reader() {
+    let reader = ArcReader::new(include_bytes!("test.xar"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Xar);
+    assert_eq!(
+        reader.entries(),
+        &vec![
+            ArcEntry::file("hmmm", "twoja stara\n"),
+            ArcEntry::directory("uwu"),
+            ArcEntry::file("uwu/owo", "twoja stara twoja stara\n"),
+            ArcEntry::file("uwu/empty", ""),
+        ]
+    );
+}
+
+#[test]
+fn test_xar_truncated() {
+    let buf = include_bytes!("test.xar");
+    assert!(ArcReader::new(&buf[..40]).is_err());
+}
+
+#[test]
+fn test_xar_writer() {
+    let writer = ArcWriter::new(ArcFormat::Xar);
+    assert!(matches!(writer.archive(), Err(ArcError::ReadOnly)));
+}
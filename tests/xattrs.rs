@@ -0,0 +1,95 @@
+use cra::*;
+use std::collections::{BTreeMap, HashMap};
+
+fn xattrs() -> HashMap<String, BTreeMap<String, Vec<u8>>> {
+    let label = b"system_u:object_r:bin_t:s0\0".to_vec();
+    HashMap::from([
+        (
+            "bin/run".to_owned(),
+            BTreeMap::from([
+                ("security.selinux".to_owned(), label),
+                ("user.comment".to_owned(), "uwu".into()),
+            ]),
+        ),
+        (
+            format!("{}/owo", "uwu".repeat(50)),
+            BTreeMap::from([("user.binary".to_owned(), vec![0, 0xff, b'\n'])]),
+        ),
+        (
+            "link".to_owned(),
+            BTreeMap::from([("user.comment".to_owned(), "owo".into())]),
+        ),
+    ])
+}
+
+/// Returns the extended attributes of the entries of `archive` that have
+/// any, by name
+fn read_xattrs(archive: &[u8]) -> HashMap<String, BTreeMap<String, Vec<u8>>> {
+    ArcReader::metas(archive)
+        .unwrap()
+        .into_iter()
+        .filter(|(_, meta)| !meta.xattrs.is_empty())
+        .map(|(name, meta)| (name, meta.xattrs))
+        .collect()
+}
+
+/// Returns `entries` with the attributes from [`xattrs`]
+fn with_xattrs(mut entries: Vec<ArcEntry>) -> Vec<ArcEntry> {
+    let mut xattrs = xattrs();
+    for entry in &mut entries {
+        entry.meta.xattrs = xattrs.remove(&entry.name).unwrap_or_default();
+    }
+    entries
+}
+
+fn entries() -> Vec<ArcEntry> {
+    vec![
+        ArcEntry::directory("bin/"),
+        ArcEntry::file("bin/run", "#!/bin/sh\n"),
+        ArcEntry::file(format!("{}/owo", "uwu".repeat(50)), "owo"),
+        ArcEntry::symlink("link", "bin/run"),
+    ]
+}
+
+#[test]
+fn test_tar_xattrs_round_trip() {
+    for tar_format in [TarFormat::Gnu, TarFormat::Pax] {
+        for format in [ArcFormat::Tar, ArcFormat::TarZst] {
+            let mut writer = ArcWriter::new(format);
+            writer.tar_format = tar_format;
+            writer.extend(&with_xattrs(entries()));
+            let archive = writer.archive().unwrap();
+            assert_eq!(read_xattrs(&archive), xattrs());
+            // the records don't get in the way of reading the entries
+            let reader = ArcReader::new(&archive).unwrap();
+            assert_eq!(
+                reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+                entries(),
+                "{tar_format:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_tar_xattrs_stream() {
+    let writer = ArcWriter::new(ArcFormat::TarGz);
+    let mut stream = writer.into_stream(Vec::new()).unwrap();
+    for entry in with_xattrs(vec![ArcEntry::file("bin/run", "#!/bin/sh\n")]) {
+        stream.push(entry).unwrap();
+    }
+    let archive = stream.finish().unwrap();
+    let xattrs = read_xattrs(&archive);
+    assert_eq!(xattrs.len(), 1);
+    assert_eq!(xattrs["bin/run"], self::xattrs()["bin/run"]);
+
+    let mut reader = ArcStreamReader::new(&archive[..]).unwrap();
+    let entry = reader.next_entry().unwrap().unwrap();
+    assert_eq!(entry.xattrs(), &self::xattrs()["bin/run"]);
+}
+
+#[test]
+fn test_tar_xattrs_other_formats() {
+    assert!(read_xattrs(include_bytes!("test.tar")).is_empty());
+    assert!(read_xattrs(include_bytes!("test.zip")).is_empty());
+}
@@ -1,4 +1,5 @@
 use cra::*;
+use std::{collections::HashMap, fs::File};
 
 #[test]
 fn test_zip_reader() {
@@ -7,21 +8,580 @@ fn test_zip_reader() {
     let reader = reader.unwrap();
     assert_eq!(reader.format(), ArcFormat::Zip);
     assert_eq!(
-        reader.entries(),
-        &vec![
-            ArcEntry::Directory("uwu/".into()),
-            ArcEntry::File("uwu/owo".into(), vec![]),
-            ArcEntry::File("hmmm".into(), "twoja stara\n".into())
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::directory("uwu/"),
+            ArcEntry::file("uwu/owo", ""),
+            ArcEntry::file("hmmm", "twoja stara\n")
         ]
     );
 }
 
+#[test]
+fn test_zip_read_entry() {
+    let buf = include_bytes!("test.zip");
+    assert_eq!(
+        ArcReader::read_entry(buf, "hmmm").unwrap(),
+        b"twoja stara\n"
+    );
+    assert_eq!(ArcReader::read_entry(buf, "uwu/owo").unwrap(), b"");
+    for name in ["uwu/", "nope"] {
+        assert!(matches!(
+            ArcReader::read_entry(buf, name),
+            Err(ArcError::EntryNotFound(_))
+        ));
+    }
+}
+
+#[test]
+fn test_zip_open() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.zip");
+    let reader = ArcReader::open(path, &ReadOptions::new()).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zip);
+    assert_eq!(
+        reader.entries(),
+        ArcReader::new(include_bytes!("test.zip"))
+            .unwrap()
+            .entries()
+    );
+    assert!(matches!(
+        ArcReader::open("tests/missing.zip", &ReadOptions::new()),
+        Err(ArcError::IoError(_))
+    ));
+}
+
+#[test]
+fn test_zip_from_reader() {
+    let reader = ArcReader::from_reader(&include_bytes!("test.zip")[..]).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zip);
+    assert_eq!(
+        reader.entries(),
+        ArcReader::new(include_bytes!("test.zip"))
+            .unwrap()
+            .entries()
+    );
+}
+
+#[test]
+fn test_zip_from_seekable() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.zip");
+    let reader = ArcReader::from_seekable(File::open(path).unwrap(), &ReadOptions::new()).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zip);
+    assert_eq!(
+        reader.entries(),
+        ArcReader::new(include_bytes!("test.zip"))
+            .unwrap()
+            .entries()
+    );
+}
+
 #[test]
 fn test_zip_writer() {
     // TODO write an actual test instead of just testing whether it runs at all
     let mut writer = ArcWriter::new(ArcFormat::Zip);
-    writer.push(ArcEntry::Directory("uwu/".into()));
-    writer.push(ArcEntry::File("uwu/owo".into(), vec![]));
-    writer.push(ArcEntry::File("hmmm".into(), "twoja stara\n".into()));
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("uwu/owo", ""));
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
     writer.archive().unwrap();
 }
+
+#[test]
+fn test_zip_archive_to() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let mut archive = Vec::new();
+    writer.archive_to(&mut archive).unwrap();
+    assert_eq!(
+        ArcReader::new(&archive)
+            .unwrap()
+            .map(ArcEntry::stripped)
+            .collect::<Vec<_>>(),
+        vec![ArcEntry::file("hmmm", "twoja stara\n")]
+    );
+}
+
+#[test]
+fn test_zip_write_to_file() {
+    let path = std::env::temp_dir().join("cra-write-to-file.zip");
+    let mut writer = ArcWriter::new(ArcFormat::from_path(&path).unwrap());
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.write_to_file(&path).unwrap();
+    let reader = ArcReader::open(&path, &ReadOptions::new()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zip);
+    assert_eq!(reader.entries().len(), 2);
+}
+
+#[test]
+fn test_zip_read_borrowed() {
+    use std::borrow::Cow;
+
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("stored", "twoja stara\n").with_method(Method::Stored));
+    writer.push(ArcEntry::file("deflated", "a".repeat(1000)));
+    let archive = writer.archive().unwrap();
+
+    let entries = ArcReader::read_borrowed(&archive).unwrap();
+    assert_eq!(entries[0].name, "uwu/");
+    assert_eq!(entries[0].meta.kind, EntryKind::Directory);
+    assert_eq!(entries[1].name, "stored");
+    assert!(matches!(entries[1].data, Cow::Borrowed(b"twoja stara\n")));
+    assert_eq!(entries[2].name, "deflated");
+    assert!(matches!(&entries[2].data, Cow::Owned(data) if data.len() == 1000));
+}
+
+#[test]
+fn test_zip64_writer() {
+    let entries: Vec<_> = (0..=u16::MAX as u32 + 1)
+        .map(|i| ArcEntry::file(i.to_string(), ""))
+        .collect();
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.extend(&entries);
+    let archive = writer.archive().unwrap();
+    // zip64 end of central directory record
+    assert!(archive.windows(4).any(|w| w == [0x50, 0x4b, 0x06, 0x06]));
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zip);
+    assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), entries);
+}
+
+#[test]
+fn test_zip_stored() {
+    let data = "twoja stara ".repeat(100).into_bytes();
+    let contains = |archive: &[u8]| archive.windows(data.len()).any(|w| w == data);
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", data.clone()));
+    writer.push(ArcEntry::file("uwu", data.clone()));
+    assert!(!contains(&writer.archive().unwrap()));
+
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", data.clone()));
+    writer.push(ArcEntry::file("uwu", data.clone()).with_method(Method::Stored));
+    let archive = writer.archive().unwrap();
+    assert_eq!(
+        archive.windows(data.len()).filter(|w| *w == data).count(),
+        1
+    );
+
+    writer.zip_stored = true;
+    let archive = writer.archive().unwrap();
+    assert_eq!(
+        archive.windows(data.len()).filter(|w| *w == data).count(),
+        2
+    );
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(
+        reader.map(ArcEntry::stripped).collect::<Vec<_>>(),
+        vec![
+            ArcEntry::file("hmmm", data.clone()),
+            ArcEntry::file("uwu", data.clone()),
+        ]
+    );
+}
+
+#[test]
+fn test_zip_volumes() {
+    // incompressible data, so the archive has to span several volumes
+    let mut state = 0x2545f491u32;
+    let data: Vec<u8> = (0..200_000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+    let entries = vec![
+        ArcEntry::directory("uwu/"),
+        ArcEntry::file("uwu/owo", data),
+        ArcEntry::file("hmmm", "twoja stara\n"),
+    ];
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.zip_volume_size = 64 * 1024;
+    writer.extend(&entries);
+    let volumes = writer.archive_volumes().unwrap();
+    assert_eq!(volumes.len(), 4);
+    assert!(volumes[0].starts_with(b"PK\x07\x08"));
+    assert!(volumes.iter().all(|volume| volume.len() <= 64 * 1024));
+
+    let volumes: Vec<_> = volumes.iter().map(|volume| &volume[..]).collect();
+    let reader = ArcReader::from_volumes(&volumes, &ReadOptions::new()).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zip);
+    assert_eq!(reader.map(ArcEntry::stripped).collect::<Vec<_>>(), entries);
+}
+
+#[test]
+fn test_zip_volume_size_too_small() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.zip_volume_size = 1024;
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    assert!(writer.archive_volumes().is_err());
+}
+
+#[test]
+fn test_zip_sfx() {
+    let stub = b"MZ not really an executable";
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let archive = writer.archive_sfx(stub).unwrap();
+    assert!(archive.starts_with(stub));
+    // the central directory points at the local header right after the stub
+    let central = archive.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+    let offset = u32::from_le_bytes(archive[central + 42..central + 46].try_into().unwrap());
+    assert_eq!(offset as usize, stub.len());
+    assert_eq!(&archive[stub.len()..stub.len() + 4], b"PK\x03\x04");
+}
+
+#[test]
+fn test_sfx_unsupported() {
+    let writer = ArcWriter::new(ArcFormat::Tar);
+    assert!(writer.archive_sfx(b"MZ").is_err());
+}
+
+#[test]
+fn test_zip_write_to_mmap() {
+    let path = std::env::temp_dir().join("cra-write-to-mmap.zip");
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::directory("uwu/"));
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let len = writer.write_to_mmap(&path, 1 << 20).unwrap();
+    let archive = std::fs::read(&path).unwrap();
+    assert_eq!(archive.len() as u64, len);
+    assert_eq!(archive, writer.archive().unwrap());
+
+    assert!(matches!(
+        writer.write_to_mmap(&path, 64),
+        Err(ArcError::ZipError(_) | ArcError::IoError(_))
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_zip_comment() {
+    assert!(ArcReader::zip_comment(include_bytes!("test.zip"))
+        .unwrap()
+        .is_empty());
+    for format in [ArcFormat::Zip, ArcFormat::Jar] {
+        let mut writer = ArcWriter::new(format);
+        writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+        writer.zip_comment = b"build 1234\nnot UTF-8: \xff".to_vec();
+        let archive = writer.archive().unwrap();
+        assert_eq!(
+            ArcReader::zip_comment(&archive).unwrap(),
+            writer.zip_comment
+        );
+        assert_eq!(ArcReader::new(&archive).unwrap().format(), format);
+    }
+    assert!(ArcReader::zip_comment(include_bytes!("test.tar"))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_zip_comment_too_long() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.zip_comment = vec![b'a'; 65536];
+    assert!(matches!(writer.archive(), Err(ArcError::IoError(_))));
+    writer.zip_comment.pop();
+    let archive = writer.archive().unwrap();
+    assert_eq!(ArcReader::zip_comment(&archive).unwrap().len(), 65535);
+}
+
+/// Returns the comments of the entries of `archive` that have one, by name
+fn comments(archive: &[u8]) -> HashMap<String, String> {
+    ArcReader::metas(archive)
+        .unwrap()
+        .into_iter()
+        .filter_map(|(name, meta)| Some((name, meta.comment?)))
+        .collect()
+}
+
+#[test]
+fn test_zip_entry_comments() {
+    assert!(comments(include_bytes!("test.zip")).is_empty());
+    let expected = HashMap::from([
+        ("uwu/".to_owned(), "a directory".to_owned()),
+        ("uwu/owo".to_owned(), "zażółć gęślą jaźń".to_owned()),
+    ]);
+    for format in [ArcFormat::Zip, ArcFormat::Jar] {
+        let mut writer = ArcWriter::new(format);
+        writer.push(ArcEntry::directory("uwu/").with_comment("a directory"));
+        writer.push(ArcEntry::file("uwu/owo", "twoja stara\n").with_comment("zażółć gęślą jaźń"));
+        writer.push(ArcEntry::file("hmmm", vec![b'a'; 100_000]));
+        writer.zip_comment = "archive comment".into();
+        let archive = writer.archive().unwrap();
+        assert_eq!(comments(&archive), expected);
+        assert_eq!(
+            ArcReader::zip_comment(&archive).unwrap(),
+            b"archive comment"
+        );
+        let reader = ArcReader::new(&archive).unwrap();
+        let owo = reader
+            .entries()
+            .iter()
+            .find(|entry| entry.name == "uwu/owo");
+        assert_eq!(
+            owo.unwrap().meta.comment.as_deref(),
+            Some("zażółć gęślą jaźń")
+        );
+        assert!(reader
+            .into_iter()
+            .any(|entry| entry.stripped() == ArcEntry::file("hmmm", vec![b'a'; 100_000])));
+    }
+
+    // too long for the central header
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", "").with_comment("a".repeat(65536)));
+    assert!(matches!(writer.archive(), Err(ArcError::IoError(_))));
+}
+
+#[test]
+fn test_zip_entry_comments_round_trip() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n").with_comment("owo"));
+    let archive = writer.archive().unwrap();
+    let mut copy = ArcWriter::new(ArcFormat::Zip);
+    copy.extend(ArcReader::new(&archive).unwrap().entries());
+    let copied = copy.archive().unwrap();
+    assert_eq!(comments(&copied)["hmmm"], "owo");
+}
+
+#[test]
+fn test_zip64_entry_comments() {
+    let entries: Vec<_> = (0..=u16::MAX as u32 + 1)
+        .map(|i| ArcEntry::file(i.to_string(), ""))
+        .collect();
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.extend(&entries);
+    writer.push(ArcEntry::file("commented", "").with_comment("owo"));
+    let archive = writer.archive().unwrap();
+    assert_eq!(
+        comments(&archive),
+        HashMap::from([("commented".into(), "owo".into())])
+    );
+    let mut read: Vec<_> = ArcReader::new(&archive)
+        .unwrap()
+        .map(ArcEntry::stripped)
+        .collect();
+    assert_eq!(read.pop(), Some(ArcEntry::file("commented", "")));
+    assert_eq!(read, entries);
+}
+
+#[test]
+fn test_zip_entry_comments_to_file() {
+    let path = std::env::temp_dir().join("cra-entry-comments.zip");
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n").with_comment("owo"));
+    writer.write_to_file(&path).unwrap();
+    let archive = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(comments(&archive)["hmmm"], "owo");
+}
+
+#[test]
+fn test_zip_extra_fields() {
+    let fields = vec![
+        ZipExtraField {
+            id: 0x7875,
+            data: vec![1, 4, 0xe8, 3, 0, 0, 4, 0xe8, 3, 0, 0],
+        },
+        ZipExtraField {
+            id: 0xcafe,
+            data: b"third-party metadata".to_vec(),
+        },
+    ];
+    let with_fields = |mut entry: ArcEntry| {
+        entry.meta.extra_fields = fields.clone();
+        entry
+    };
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(with_fields(ArcEntry::directory("uwu/")));
+    writer.push(with_fields(ArcEntry::file("uwu/owo", "twoja stara\n")));
+    writer.push(with_fields(ArcEntry::symlink("link", "uwu/owo")));
+    let epoch = std::time::UNIX_EPOCH;
+    writer.push(
+        ArcEntry::file("hmmm", "twoja stara\n")
+            .with_mtime(epoch)
+            .with_atime(epoch),
+    );
+    writer.zip_ntfs_times = true;
+    let archive = writer.archive().unwrap();
+    let read = ArcReader::metas(&archive).unwrap();
+    assert_eq!(read.len(), 4);
+    assert!(read[..3]
+        .iter()
+        .all(|(_, meta)| meta.extra_fields == fields));
+    // timestamps are held by the times
+    assert_eq!(read[3].1.extra_fields, []);
+
+    // copying the entries over keeps them
+    let mut copy = ArcWriter::new(ArcFormat::Zip);
+    copy.extend(ArcReader::new(&archive).unwrap().entries());
+    let copied = copy.archive().unwrap();
+    assert_eq!(ArcReader::metas(&copied).unwrap(), read);
+}
+
+#[test]
+fn test_zip_extra_fields_zip64() {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n").with_extra_field(0x0001, vec![0; 8]));
+    assert!(writer.archive().is_err());
+}
+
+/// Returns a zip with names written in UTF-8 without the UTF-8 flag, and with
+/// `?` replaced by the byte 0x81, "ü" in CP437 and invalid in UTF-8
+fn unflagged_names_zip() -> Vec<u8> {
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::file("gr?n", "twoja stara\n"));
+    writer.push(ArcEntry::directory("zażółć/"));
+    let mut archive = writer.archive().unwrap();
+    let mut pos = 0;
+    while let Some(i) = archive[pos..]
+        .windows(4)
+        .position(|sig| sig == b"PK\x01\x02" || sig == b"PK\x03\x04")
+    {
+        let flags = pos + i + if archive[pos + i + 2] == 1 { 8 } else { 6 };
+        archive[flags + 1] &= !(1 << 3);
+        pos += i + 4;
+    }
+    for byte in archive.iter_mut().filter(|byte| **byte == b'?') {
+        *byte = 0x81;
+    }
+    archive
+}
+
+#[test]
+fn test_zip_name_encoding() {
+    let archive = unflagged_names_zip();
+    let names = |reader: ArcReader| -> Vec<_> {
+        reader
+            .entries()
+            .iter()
+            .map(|entry| entry.name.clone())
+            .collect()
+    };
+    assert_eq!(
+        names(ArcReader::new(&archive).unwrap()),
+        ["grün", "za┼╝├│┼é─ç/"]
+    );
+    assert_eq!(
+        names(ArcReader::with_zip_name_encoding(&archive, ZipNameEncoding::Cp437).unwrap()),
+        ["grün", "za┼╝├│┼é─ç/"]
+    );
+    assert_eq!(
+        names(ArcReader::with_zip_name_encoding(&archive, ZipNameEncoding::Utf8OrCp437).unwrap()),
+        ["grün", "zażółć/"]
+    );
+    let latin1 = |raw: &[u8]| raw.iter().map(|&byte| char::from(byte)).collect();
+    let reader =
+        ArcReader::with_zip_name_encoding(&archive, ZipNameEncoding::Custom(latin1)).unwrap();
+    assert_eq!(names(reader)[0], "gr\u{81}n");
+    assert_eq!(
+        ArcReader::zip_raw_names(&archive).unwrap(),
+        [b"gr\x81n".to_vec(), "zażółć/".into()]
+    );
+
+    // flagged names are left as they are
+    let buf = include_bytes!("test.zip");
+    let reader =
+        ArcReader::with_zip_name_encoding(buf, ZipNameEncoding::Custom(|_| "nope".into())).unwrap();
+    assert_eq!(reader.entries(), ArcReader::new(buf).unwrap().entries());
+    assert!(ArcReader::zip_raw_names(include_bytes!("test.tar"))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_zip_dos_attributes() {
+    let dos_attributes = |archive: &[u8]| -> Vec<_> {
+        ArcReader::metas(archive)
+            .unwrap()
+            .into_iter()
+            .map(|(_, meta)| meta.dos_attributes)
+            .collect()
+    };
+    assert!(dos_attributes(include_bytes!("test.zip"))
+        .iter()
+        .all(Option::is_none));
+    let hidden = DosAttributes {
+        hidden: true,
+        ..Default::default()
+    };
+    let read_only = DosAttributes {
+        read_only: true,
+        system: true,
+        archive: true,
+        ..Default::default()
+    };
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(ArcEntry::directory("uwu/").with_dos_attributes(hidden));
+    writer.push(
+        ArcEntry::file("uwu/owo", "twoja stara\n")
+            .with_mode(0o640)
+            .with_dos_attributes(read_only),
+    );
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n").with_comment("owo"));
+    writer.push(ArcEntry::file("none", "").with_dos_attributes(DosAttributes::default()));
+    let archive = writer.archive().unwrap();
+    assert_eq!(
+        dos_attributes(&archive),
+        [Some(hidden), Some(read_only), None, None]
+    );
+    let metas = ArcReader::metas(&archive).unwrap();
+    assert_eq!(metas[1].0, "uwu/owo");
+    assert_eq!(metas[1].1.mode, Some(0o640));
+    assert_eq!(comments(&archive)["hmmm"], "owo");
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.entries()[1].meta.dos_attributes, Some(read_only));
+    assert_eq!(
+        reader.entries()[0].clone().stripped(),
+        ArcEntry::directory("uwu/")
+    );
+
+    // copying the entries over keeps them
+    let mut copy = ArcWriter::new(ArcFormat::Zip);
+    copy.extend(reader.entries());
+    let copied = copy.archive().unwrap();
+    assert_eq!(dos_attributes(&copied), dos_attributes(&archive));
+}
+
+#[test]
+fn test_zip_ntfs_times() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let mtime = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_700);
+    let atime = UNIX_EPOCH + Duration::new(1_700_000_001, 100);
+    // before the Unix epoch, which NTFS times can hold
+    let ctime = UNIX_EPOCH - Duration::from_secs(86400);
+    let mut writer = ArcWriter::new(ArcFormat::Zip);
+    writer.push(
+        ArcEntry::directory("uwu/")
+            .with_mtime(mtime)
+            .with_atime(atime)
+            .with_ctime(ctime),
+    );
+    writer.push(ArcEntry::file("uwu/owo", "twoja stara\n").with_mtime(mtime));
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.zip_ntfs_times = true;
+    let archive = writer.archive().unwrap();
+    let metas = ArcReader::metas(&archive).unwrap();
+    assert_eq!(
+        (metas[0].1.mtime, metas[0].1.atime, metas[0].1.ctime),
+        (Some(mtime), Some(atime), Some(ctime))
+    );
+    assert_eq!(
+        (metas[1].1.mtime, metas[1].1.atime, metas[1].1.ctime),
+        (Some(mtime), None, None)
+    );
+
+    // without them times are kept to the second
+    writer.zip_ntfs_times = false;
+    let archive = writer.archive().unwrap();
+    let metas = ArcReader::metas(&archive).unwrap();
+    assert_eq!(
+        metas[0].1.mtime,
+        Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+    );
+}
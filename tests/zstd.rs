@@ -0,0 +1,28 @@
+use cra::*;
+
+#[test]
+fn test_zstd_reader() {
+    let reader = ArcReader::new(include_bytes!("test.zst"));
+    assert!(reader.is_ok());
+    let reader = reader.unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zstd);
+    assert_eq!(reader.entries(), &vec![ArcEntry::file("", "twoja stara\n")]);
+}
+
+#[test]
+fn test_zstd_writer() {
+    let mut writer = ArcWriter::new(ArcFormat::Zstd);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    let archive = writer.archive().unwrap();
+    let reader = ArcReader::new(&archive).unwrap();
+    assert_eq!(reader.format(), ArcFormat::Zstd);
+    assert_eq!(reader.entries(), &vec![ArcEntry::file("", "twoja stara\n")]);
+}
+
+#[test]
+fn test_zstd_writer_single_file_only() {
+    let mut writer = ArcWriter::new(ArcFormat::Zstd);
+    writer.push(ArcEntry::file("hmmm", "twoja stara\n"));
+    writer.push(ArcEntry::file("uwu", ""));
+    assert!(matches!(writer.archive(), Err(ArcError::SingleFileOnly)));
+}